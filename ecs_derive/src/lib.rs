@@ -1,9 +1,25 @@
 use proc_macro::TokenStream;
 use quote::quote;
-use syn::{parse_macro_input, DeriveInput};
+use proc_macro2::Span;
+use syn::{parse_macro_input, Data, DeriveInput, Fields, Ident, Index, LitStr, Path};
 
 /// Component トレイトを自動的に実装するマクロ
 /// 
+/// 生成されるコードは`::ecs_wasm_game3::ecs`のパスを使うため、サーバーなど
+/// `ecs_wasm_game3`に依存する別のクレートでも使えます。
+/// 
+/// `#[component(storage = "...")]`属性で格納方式を指定できます。
+/// 
+/// * `"dense"` - 型ごとの`VecStorage`に格納（既定）
+/// * `"sparse"` - 型ごとの`SparseSet`に格納（少数に付くタグ向け）
+/// * `"table"` - アーキタイプのテーブルに格納（大量にまとめて走査するもの向け）
+/// 
+/// `#[component(serialize)]`を指定すると、型がリフレクションのレジストリに登録され、
+/// `World::snapshot`で保存されるようになります（serdeの`Serialize`と`Deserialize`が必要です）。
+/// 
+/// `#[component(on_add = path, on_replace = path, on_remove = path)]`で、
+/// `fn(&mut HookContext)`型の関数をライフサイクルフックとして登録できます。
+/// 
 /// # 使用例
 /// ```rust
 /// #[derive(Component)]
@@ -11,19 +27,98 @@ use syn::{parse_macro_input, DeriveInput};
 ///     x: f32,
 ///     y: f32,
 /// }
+/// 
+/// #[derive(Component)]
+/// #[component(storage = "sparse")]
+/// pub struct Hovered;
+/// 
+/// #[derive(Component, Serialize, Deserialize)]
+/// #[component(serialize)]
+/// pub struct Score(u32);
+/// 
+/// #[derive(Component)]
+/// #[component(on_add = reindex, on_replace = reindex, on_remove = reindex)]
+/// pub struct Name(Cow<'static, str>);
 /// ```
-#[proc_macro_derive(Component)]
+#[proc_macro_derive(Component, attributes(component))]
 pub fn derive_component(input: TokenStream) -> TokenStream {
     // 入力を解析
     let input = parse_macro_input!(input as DeriveInput);
     let name = &input.ident;
+
+    // #[component(...)]属性を解析
+    let mut storage = None;
+    let mut serialize = false;
+    let mut hooks = Vec::new();
+    for attr in input.attrs.iter().filter(|attr| attr.path().is_ident("component")) {
+        let result = attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("storage") {
+                let value: LitStr = meta.value()?.parse()?;
+                let variant = match value.value().as_str() {
+                    "dense" => quote! { Dense },
+                    "sparse" => quote! { Sparse },
+                    "table" => quote! { Table },
+                    other => {
+                        return Err(syn::Error::new(
+                            value.span(),
+                            format!("unknown storage `{}`, expected \"dense\", \"sparse\" or \"table\"", other),
+                        ));
+                    }
+                };
+                storage = Some(variant);
+                Ok(())
+            } else if meta.path.is_ident("serialize") {
+                serialize = true;
+                Ok(())
+            } else if let Some(kind) = ["on_add", "on_replace", "on_remove"].into_iter().find(|kind| meta.path.is_ident(kind)) {
+                let hook: Path = meta.value()?.parse()?;
+                let setter = Ident::new(kind, Span::call_site());
+                hooks.push(quote! { .#setter(#hook) });
+                Ok(())
+            } else {
+                Err(meta.error(
+                    "unsupported component attribute, expected `storage`, `serialize`, `on_add`, `on_replace` or `on_remove`",
+                ))
+            }
+        });
+        if let Err(error) = result {
+            return error.to_compile_error().into();
+        }
+    }
+
+    // 格納方式が指定された場合のみstorage_typeを上書き
+    let storage_type = storage.map(|variant| quote! {
+        fn storage_type() -> ::ecs_wasm_game3::ecs::component::StorageType {
+            ::ecs_wasm_game3::ecs::component::StorageType::#variant
+        }
+    });
+
+    // serializeが指定された場合のみリフレクション情報を返す
+    let reflect = serialize.then(|| quote! {
+        fn reflect() -> Option<::ecs_wasm_game3::ecs::reflect::ComponentReflection> {
+            Some(::ecs_wasm_game3::ecs::reflect::ComponentReflection::new::<Self>())
+        }
+    });
+
+    // フックが指定された場合のみフックを返す
+    let component_hooks = (!hooks.is_empty()).then(|| quote! {
+        fn hooks() -> ::ecs_wasm_game3::ecs::hooks::ComponentHooks {
+            ::ecs_wasm_game3::ecs::hooks::ComponentHooks::new() #(#hooks)*
+        }
+    });
     
     // Component トレイトの実装を生成
     let expanded = quote! {
-        impl Component for #name {
+        impl ::ecs_wasm_game3::ecs::Component for #name {
             fn name() -> &'static str {
                 stringify!(#name)
             }
+
+            #storage_type
+
+            #reflect
+
+            #component_hooks
         }
     };
     
@@ -31,6 +126,69 @@ pub fn derive_component(input: TokenStream) -> TokenStream {
     expanded.into()
 }
 
+/// Bundle トレイトを自動的に実装するマクロ
+/// 
+/// 各フィールドはコンポーネントまたは別の`Bundle`である必要があります。
+/// `Bundle`のフィールドは入れ子のまま展開されます。
+/// 
+/// # 使用例
+/// ```rust
+/// #[derive(Bundle)]
+/// pub struct CursorBundle {
+///     cursor: MouseCursorComponent,
+///     transform: Transform,
+/// }
+/// ```
+#[proc_macro_derive(Bundle)]
+pub fn derive_bundle(input: TokenStream) -> TokenStream {
+    // 入力を解析
+    let input = parse_macro_input!(input as DeriveInput);
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    // フィールドへのアクセスと型を収集
+    let fields = match &input.data {
+        Data::Struct(data) => &data.fields,
+        _ => {
+            return syn::Error::new_spanned(name, "Bundle can only be derived for structs")
+                .to_compile_error()
+                .into();
+        }
+    };
+    let members: Vec<_> = match fields {
+        Fields::Named(named) => named.named.iter()
+            .map(|field| {
+                let ident = &field.ident;
+                quote! { #ident }
+            })
+            .collect(),
+        Fields::Unnamed(unnamed) => (0..unnamed.unnamed.len())
+            .map(|index| {
+                let index = Index::from(index);
+                quote! { #index }
+            })
+            .collect(),
+        Fields::Unit => Vec::new(),
+    };
+    let types: Vec<_> = fields.iter().map(|field| &field.ty).collect();
+
+    // Bundle トレイトの実装を生成
+    let expanded = quote! {
+        impl #impl_generics ::ecs_wasm_game3::ecs::bundle::Bundle for #name #ty_generics #where_clause {
+            fn collect_components(self, components: &mut ::ecs_wasm_game3::ecs::bundle::BundleComponents) {
+                #(::ecs_wasm_game3::ecs::bundle::Bundle::collect_components(self.#members, components);)*
+            }
+
+            fn component_names(names: &mut Vec<&'static str>) {
+                #(<#types as ::ecs_wasm_game3::ecs::bundle::Bundle>::component_names(names);)*
+            }
+        }
+    };
+
+    // トークンストリームに変換して返す
+    expanded.into()
+}
+
 /// Resource トレイトを自動的に実装するマクロ
 /// 
 /// # 使用例
@@ -48,12 +206,12 @@ pub fn derive_resource(input: TokenStream) -> TokenStream {
     
     // Resource トレイトの実装を生成
     let expanded = quote! {
-        impl Resource for #name {
-            fn as_any(&self) -> &dyn std::any::Any {
+        impl ::ecs_wasm_game3::ecs::Resource for #name {
+            fn as_any(&self) -> &dyn ::std::any::Any {
                 self
             }
             
-            fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+            fn as_any_mut(&mut self) -> &mut dyn ::std::any::Any {
                 self
             }
         }
@@ -1,126 +1,9 @@
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
-use async_trait::async_trait;
 use std::time::{Duration, Instant};
 use serde_json::Value;
 
-use crate::config::{GameMode, GameSettings, GameType};
-use crate::message::{GameAction, GameState, GamePhase, ActionResult};
-use crate::player::PlayerId;
-
-/// ゲームタイプ
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
-pub enum GameType {
-    /// マインスイーパー
-    Minesweeper,
-    /// テトリス
-    Tetris,
-    /// チェス
-    Chess,
-    /// オセロ
-    Reversi,
-    /// 汎用ゲームタイプ（カスタムゲーム用）
-    Custom(String),
-}
-
-impl std::fmt::Display for GameType {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            GameType::Minesweeper => write!(f, "Minesweeper"),
-            GameType::Tetris => write!(f, "Tetris"),
-            GameType::Chess => write!(f, "Chess"),
-            GameType::Reversi => write!(f, "Reversi"),
-            GameType::Custom(name) => write!(f, "Custom({})", name),
-        }
-    }
-}
-
-/// ゲーム設定
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct GameSettings {
-    /// 最大プレイヤー数
-    pub max_players: usize,
-    /// ゲーム固有の設定
-    pub game_specific: HashMap<String, serde_json::Value>,
-}
-
-impl Default for GameSettings {
-    fn default() -> Self {
-        Self {
-            max_players: 4,
-            game_specific: HashMap::new(),
-        }
-    }
-}
-
-/// プレイヤー情報
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct Player {
-    /// プレイヤーID
-    pub id: String,
-    /// プレイヤー名
-    pub name: String,
-    /// ホストかどうか
-    pub is_host: bool,
-    /// 準備完了状態
-    pub ready: bool,
-    /// 接続状態
-    pub connected: bool,
-    /// 最終アクティブ時間（Unix時間ミリ秒）
-    pub last_active: u64,
-}
-
-/// ルーム概要（ルーム一覧用）
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct RoomSummary {
-    /// ルームID
-    pub id: String,
-    /// ルームコード
-    pub code: String,
-    /// ゲームタイプ
-    pub game_type: GameType,
-    /// 現在のプレイヤー数
-    pub player_count: usize,
-    /// 最大プレイヤー数
-    pub max_players: usize,
-    /// ゲーム状態
-    pub state: GameState,
-    /// 作成時間（Unix時間ミリ秒）
-    pub created_at: u64,
-}
-
-/// ゲーム状態
-#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
-pub enum GameState {
-    /// ロビー（ゲーム開始前）
-    Lobby,
-    /// ゲーム進行中
-    InProgress,
-    /// ゲーム終了
-    Ended,
-}
-
-/// アクション結果
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct ActionResult {
-    /// 成功したかどうか
-    pub success: bool,
-    /// アクション種別
-    pub action_type: String,
-    /// 結果メッセージ
-    pub message: Option<String>,
-    /// 追加データ
-    pub data: Option<HashMap<String, serde_json::Value>>,
-}
-
-/// ゲームの状態を表すトレイト
-pub trait GameState: Send + Sync {
-    /// ゲームの状態をJSON形式でシリアライズ
-    fn to_json(&self) -> Result<Value, String>;
-    
-    /// JSONからゲームの状態を復元
-    fn from_json(json: &Value) -> Result<Self, String> where Self: Sized;
-}
+use ecs_wasm_game3::ecs::{EventReader, Events, World};
+use ecs_wasm_game3::minesweeper::{self, Board, BoardConfig, BoardOutcome, Cell, CellsRevealed, FlagToggled, Mine, RevealCell, ToggleFlag};
 
 /// ゲームのイベントを表す列挙型
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -438,7 +321,7 @@ impl Game for BaseGame {
         Ok(result)
     }
     
-    fn update(&mut self, delta_time: f32) -> Vec<GameEvent> {
+    fn update(&mut self, _delta_time: f32) -> Vec<GameEvent> {
         let mut events = Vec::new();
         
         // ゲームが開始されていない場合や終了している場合は何もしない
@@ -451,7 +334,7 @@ impl Game for BaseGame {
             if let Some(start_time) = self.start_time {
                 if start_time.elapsed() > timeout {
                     // ゲームを終了
-                    if let Ok(result) = self.end_game(None) {
+                    if self.end_game(None).is_ok() {
                         events.push(GameEvent::GameEnded { winner_id: None });
                     }
                     return events;
@@ -491,231 +374,101 @@ impl Game for BaseGame {
 }
 
 /// マインスイーパー用のゲーム構造体
+///
+/// 盤面はクライアントと共有する`ecs_wasm_game3::minesweeper`のコンポーネントとして
+/// ゲームごとの`World`に置かれ、操作はイベントとしてシステムに処理させます。
 pub struct MinesweeperGame {
     base: BaseGame,
-    pub width: usize,
-    pub height: usize,
-    pub mines: usize,
-    pub board: Vec<Vec<Cell>>,
-    pub revealed_count: usize,
-    pub flagged_count: usize,
-    pub game_over: bool,
-}
-
-/// マインスイーパーのセル
-#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
-pub struct Cell {
-    pub has_mine: bool,
-    pub is_revealed: bool,
-    pub is_flagged: bool,
-    pub adjacent_mines: u8,
+    /// 盤面のマスと、ルールを処理するシステムを持つワールド
+    world: World,
+    /// 未処理の`CellsRevealed`の読み取り位置
+    revealed_reader: EventReader<CellsRevealed>,
+    /// 未処理の`FlagToggled`の読み取り位置
+    flag_reader: EventReader<FlagToggled>,
 }
 
 impl MinesweeperGame {
     /// 新しいマインスイーパーゲームを作成
+    ///
+    /// 盤面の大きさと地雷の数は`BoardConfig::new`の範囲に切り詰められます。
     pub fn new(width: usize, height: usize, mines: usize) -> Self {
+        let to_u32 = |value: usize| u32::try_from(value).unwrap_or(u32::MAX);
+        let config = BoardConfig::new(to_u32(width), to_u32(height), to_u32(mines));
         let mut base = BaseGame::new("Minesweeper".to_string(), 1, 4);
         base.set_custom_config(serde_json::json!({
-            "width": width,
-            "height": height,
-            "mines": mines,
+            "width": config.width,
+            "height": config.height,
+            "mines": config.mines,
         }));
         
-        // 空のボードを作成
-        let board = vec![vec![Cell {
-            has_mine: false,
-            is_revealed: false,
-            is_flagged: false,
-            adjacent_mines: 0,
-        }; width]; height];
+        // 盤面のエンティティとシステムを登録（システム同士に順序の制約はないため失敗しない）
+        let mut world = World::new();
+        minesweeper::init_minesweeper(&mut world, config)
+            .expect("マインスイーパーのシステム登録に失敗しました");
         
         Self {
             base,
-            width,
-            height,
-            mines,
-            board,
-            revealed_count: 0,
-            flagged_count: 0,
-            game_over: false,
+            world,
+            revealed_reader: EventReader::default(),
+            flag_reader: EventReader::default(),
         }
     }
     
-    /// ボードを初期化（地雷の配置）
-    fn initialize_board(&mut self, first_x: usize, first_y: usize) {
-        use rand::Rng;
-        let mut rng = rand::thread_rng();
-        
-        // 地雷をランダムに配置
-        let mut mines_placed = 0;
-        while mines_placed < self.mines {
-            let x = rng.gen_range(0..self.width);
-            let y = rng.gen_range(0..self.height);
-            
-            // 最初にクリックしたセルとその周囲には地雷を配置しない
-            let is_safe_zone = (x as i32 - first_x as i32).abs() <= 1 && (y as i32 - first_y as i32).abs() <= 1;
-            
-            if !self.board[y][x].has_mine && !is_safe_zone {
-                self.board[y][x].has_mine = true;
-                mines_placed += 1;
-            }
-        }
-        
-        // 隣接する地雷の数を計算
-        for y in 0..self.height {
-            for x in 0..self.width {
-                if self.board[y][x].has_mine {
-                    continue;
-                }
-                
-                let mut count = 0;
-                for dy in -1..=1 {
-                    for dx in -1..=1 {
-                        if dx == 0 && dy == 0 {
-                            continue;
-                        }
-                        
-                        let nx = x as i32 + dx;
-                        let ny = y as i32 + dy;
-                        
-                        if nx >= 0 && nx < self.width as i32 && ny >= 0 && ny < self.height as i32 {
-                            if self.board[ny as usize][nx as usize].has_mine {
-                                count += 1;
-                            }
-                        }
-                    }
-                }
-                
-                self.board[y][x].adjacent_mines = count;
-            }
-        }
+    /// ルーム設定から作成（`width`・`height`・`mines`がなければ初級の9x9・10個）
+    pub fn from_settings(settings: &Value) -> Self {
+        // 大きすぎる値はusizeに収まる範囲にしてからBoardConfigで切り詰める
+        let value = |key: &str, default: u64| {
+            settings[key].as_u64().unwrap_or(default).min(u32::MAX as u64) as usize
+        };
+        Self::new(value("width", 9), value("height", 9), value("mines", 10))
     }
     
-    /// セルを開く
-    fn reveal(&mut self, x: usize, y: usize) -> Result<Vec<(usize, usize)>, String> {
-        // 範囲チェック
-        if x >= self.width || y >= self.height {
-            return Err("座標が範囲外です".to_string());
-        }
-        
-        // すでに開かれているか、フラグが立っている場合は何もしない
-        if self.board[y][x].is_revealed || self.board[y][x].is_flagged {
-            return Ok(vec![]);
-        }
-        
-        // 最初のクリックの場合はボードを初期化
-        if self.revealed_count == 0 {
-            self.initialize_board(x, y);
-        }
-        
-        let mut revealed = vec![(x, y)];
-        
-        // セルを開く
-        self.board[y][x].is_revealed = true;
-        self.revealed_count += 1;
-        
-        // 地雷があればゲームオーバー
-        if self.board[y][x].has_mine {
-            self.game_over = true;
-            return Ok(revealed);
-        }
-        
-        // 隣接する地雷がない場合は周囲のセルも開く
-        if self.board[y][x].adjacent_mines == 0 {
-            for dy in -1..=1 {
-                for dx in -1..=1 {
-                    if dx == 0 && dy == 0 {
-                        continue;
-                    }
-                    
-                    let nx = x as i32 + dx;
-                    let ny = y as i32 + dy;
-                    
-                    if nx >= 0 && nx < self.width as i32 && ny >= 0 && ny < self.height as i32 {
-                        let nx = nx as usize;
-                        let ny = ny as usize;
-                        
-                        if !self.board[ny][nx].is_revealed && !self.board[ny][nx].is_flagged {
-                            if let Ok(mut sub_revealed) = self.reveal(nx, ny) {
-                                revealed.append(&mut sub_revealed);
-                            }
-                        }
-                    }
-                }
-            }
-        }
-        
-        Ok(revealed)
+    /// 盤面のワールドを取得
+    pub fn world(&self) -> &World {
+        &self.world
     }
     
-    /// フラグを切り替える
-    fn toggle_flag(&mut self, x: usize, y: usize) -> Result<bool, String> {
-        // 範囲チェック
-        if x >= self.width || y >= self.height {
-            return Err("座標が範囲外です".to_string());
-        }
-        
-        // すでに開かれている場合は何もしない
-        if self.board[y][x].is_revealed {
-            return Ok(false);
-        }
-        
-        // フラグを切り替える
-        self.board[y][x].is_flagged = !self.board[y][x].is_flagged;
-        
-        // フラグの数を更新
-        if self.board[y][x].is_flagged {
-            self.flagged_count += 1;
-        } else {
-            self.flagged_count -= 1;
-        }
-        
-        Ok(self.board[y][x].is_flagged)
+    /// 盤面のリソースを取得
+    fn board(&self) -> &Board {
+        self.world.get_resource::<Board>().expect("盤面のリソースがありません")
     }
     
-    /// ゲームがクリアされたかどうか
-    fn is_cleared(&self) -> bool {
-        self.revealed_count + self.mines == self.width * self.height
+    /// 座標を取得し、盤面の範囲内か確認
+    fn coordinates(&self, data: &Value) -> Result<(u32, u32), String> {
+        let x = data["x"].as_u64().ok_or("x座標が不正です")? as u32;
+        let y = data["y"].as_u64().ok_or("y座標が不正です")? as u32;
+        if self.board().cell_at(x, y).is_none() {
+            return Err("座標が範囲外です".to_string());
+        }
+        Ok((x, y))
     }
     
-    /// プレイヤー視点のボードを取得
-    fn get_player_board(&self) -> Vec<Vec<PlayerCell>> {
-        let mut player_board = Vec::with_capacity(self.height);
-        
-        for y in 0..self.height {
-            let mut row = Vec::with_capacity(self.width);
-            
-            for x in 0..self.width {
-                let cell = &self.board[y][x];
-                let player_cell = if cell.is_revealed {
-                    if cell.has_mine {
-                        PlayerCell::Mine
-                    } else {
-                        PlayerCell::Revealed(cell.adjacent_mines)
-                    }
-                } else if cell.is_flagged {
-                    PlayerCell::Flagged
-                } else {
-                    PlayerCell::Hidden
-                };
-                
-                row.push(player_cell);
-            }
-            
-            player_board.push(row);
+    /// 盤面の勝敗が決まっていればゲームを終了
+    fn sync_outcome(&mut self) {
+        if self.base.ended {
+            return;
+        }
+        let winner_id = match self.board().outcome() {
+            BoardOutcome::Playing => return,
+            BoardOutcome::Cleared { player_id } => Some(player_id.clone()),
+            BoardOutcome::Exploded { .. } => None,
+        };
+        if let Err(e) = self.base.end_game(winner_id) {
+            log::warn!("マインスイーパーの終了処理に失敗しました: {}", e);
         }
-        
-        player_board
     }
 }
 
-/// プレイヤー視点のセル
-#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
-pub enum PlayerCell {
-    Hidden,
-    Revealed(u8),
-    Flagged,
-    Mine,
+impl std::fmt::Debug for MinesweeperGame {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let board = self.board();
+        f.debug_struct("MinesweeperGame")
+            .field("config", &board.config())
+            .field("revealed", &board.revealed())
+            .field("flagged", &board.flagged())
+            .field("outcome", board.outcome())
+            .finish()
+    }
 }
 
 impl Game for MinesweeperGame {
@@ -734,7 +487,7 @@ impl Game for MinesweeperGame {
         }
         
         // ゲームが終了している場合はエラー
-        if self.base.ended || self.game_over {
+        if self.is_game_ended() {
             return Err("ゲームはすでに終了しています".to_string());
         }
         
@@ -745,41 +498,44 @@ impl Game for MinesweeperGame {
         
         match action_type {
             "reveal" => {
-                // 座標を取得
-                let x = data["x"].as_u64().ok_or("x座標が不正です")? as usize;
-                let y = data["y"].as_u64().ok_or("y座標が不正です")? as usize;
+                let (x, y) = self.coordinates(&data)?;
                 
-                // セルを開く
-                let revealed = self.reveal(x, y)?;
+                // 操作を送ってシステムに処理させる
+                self.world.send_event(RevealCell { x, y, player_id: player_id.to_string() });
+                self.world.update(0.0);
                 
-                // ゲームオーバーかクリアかチェック
-                if self.game_over {
-                    self.base.end_game(None)?;
-                } else if self.is_cleared() {
-                    self.base.end_game(Some(player_id.to_string()))?;
-                }
+                let revealed: Vec<(u32, u32)> = match self.world.get_resource::<Events<CellsRevealed>>() {
+                    Some(events) => self.revealed_reader.read(events).flat_map(|result| result.cells.clone()).collect(),
+                    None => Vec::new(),
+                };
+                self.sync_outcome();
                 
                 // 結果を返す
+                let outcome = self.board().outcome();
                 Ok(serde_json::json!({
                     "revealed": revealed,
-                    "game_over": self.game_over,
-                    "is_cleared": self.is_cleared(),
+                    "game_over": matches!(outcome, BoardOutcome::Exploded { .. }),
+                    "is_cleared": matches!(outcome, BoardOutcome::Cleared { .. }),
                 }))
             },
             "flag" => {
-                // 座標を取得
-                let x = data["x"].as_u64().ok_or("x座標が不正です")? as usize;
-                let y = data["y"].as_u64().ok_or("y座標が不正です")? as usize;
+                let (x, y) = self.coordinates(&data)?;
                 
-                // フラグを切り替える
-                let is_flagged = self.toggle_flag(x, y)?;
+                self.world.send_event(ToggleFlag { x, y, player_id: player_id.to_string() });
+                self.world.update(0.0);
+                
+                // 開かれたマスへの操作は無視され、結果のイベントも届かない
+                let is_flagged = match self.world.get_resource::<Events<FlagToggled>>() {
+                    Some(events) => self.flag_reader.read(events).last().map_or(false, |result| result.flagged),
+                    None => false,
+                };
                 
                 // 結果を返す
                 Ok(serde_json::json!({
                     "x": x,
                     "y": y,
                     "is_flagged": is_flagged,
-                    "flagged_count": self.flagged_count,
+                    "flagged_count": self.board().flagged(),
                 }))
             },
             _ => Err(format!("不明なアクションタイプです: {}", action_type)),
@@ -795,36 +551,42 @@ impl Game for MinesweeperGame {
     }
     
     fn update(&mut self, delta_time: f32) -> Vec<GameEvent> {
+        // 操作以外で送られたイベントもここで処理される
+        self.world.update(delta_time);
+        if !self.base.ended {
+            self.sync_outcome();
+            if self.base.ended {
+                return vec![GameEvent::GameEnded { winner_id: self.base.winner_id.clone() }];
+            }
+        }
+        
         self.base.update(delta_time)
     }
     
     fn get_state(&self) -> Result<Value, String> {
         // ベースのゲーム状態を取得
         let base_state = self.base.get_state()?;
+        let board = self.board();
+        let config = board.config();
         
         // マインスイーパー固有の状態を追加
         let mut state = serde_json::json!({
             "base": base_state,
-            "width": self.width,
-            "height": self.height,
-            "mines": self.mines,
-            "revealed_count": self.revealed_count,
-            "flagged_count": self.flagged_count,
-            "game_over": self.game_over,
-            "board": self.get_player_board(),
+            "width": config.width,
+            "height": config.height,
+            "mines": config.mines,
+            "revealed_count": board.revealed(),
+            "flagged_count": board.flagged(),
+            "game_over": matches!(board.outcome(), BoardOutcome::Exploded { .. }),
+            "outcome": board.outcome(),
+            "board": board.view(&self.world),
         });
         
-        // ゲームオーバーの場合は全ての地雷の位置を公開
-        if self.game_over || self.base.ended {
-            let mines = self.board.iter().enumerate().flat_map(|(y, row)| {
-                row.iter().enumerate().filter_map(move |(x, cell)| {
-                    if cell.has_mine {
-                        Some(serde_json::json!({ "x": x, "y": y }))
-                    } else {
-                        None
-                    }
-                })
-            }).collect::<Vec<_>>();
+        // ゲームが終了した場合は全ての地雷の位置を公開
+        if self.is_game_ended() {
+            let mines = self.world.query::<(&Cell, &Mine)>().iter(&self.world)
+                .map(|(cell, _)| serde_json::json!({ "x": cell.x, "y": cell.y }))
+                .collect::<Vec<_>>();
             
             if let Some(obj) = state.as_object_mut() {
                 obj.insert("mines_positions".to_string(), serde_json::Value::Array(mines));
@@ -839,27 +601,6 @@ impl Game for MinesweeperGame {
     }
     
     fn is_game_ended(&self) -> bool {
-        self.base.ended || self.game_over
+        self.base.ended || self.board().is_finished()
     }
 }
-
-/// ゲームファクトリー - ゲーム設定に基づいて適切なゲーム実装を作成
-pub struct GameFactory;
-
-impl GameFactory {
-    /// 新しいゲームインスタンスを作成
-    pub fn create_game(settings: &GameSettings, players: &[PlayerId]) -> Box<dyn Game> {
-        match settings.game_type {
-            GameType::Generic => Box::new(GenericGame::new(settings, players)),
-            GameType::Custom(ref name) => {
-                // カスタムゲームの場合、名前に基づいて実装を選択
-                match name.as_str() {
-                    // 将来的に実装するカスタムゲーム
-                    // "minesweeper" => Box::new(MinesweeperGame::new(settings, players)),
-                    // "tictactoe" => Box::new(TicTacToeGame::new(settings, players)),
-                    _ => Box::new(GenericGame::new(settings, players)),
-                }
-            }
-        }
-    }
-} 
\ No newline at end of file
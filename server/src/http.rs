@@ -1,6 +1,5 @@
 use std::convert::Infallible;
-use std::path::Path;
-use std::sync::Arc;
+use std::path::PathBuf;
 use warp::{http::StatusCode, Filter, Rejection, Reply};
 
 use crate::websocket::SharedRoomManager;
@@ -34,7 +33,7 @@ fn json_error(message: &str) -> impl Reply {
 /// ルームリストAPIハンドラー
 async fn handle_get_rooms(room_manager: SharedRoomManager) -> Result<impl Reply, Rejection> {
     let manager = room_manager.read().await;
-    let rooms = manager.list_public_rooms();
+    let rooms = manager.list_rooms();
     
     // JSONレスポンスを返す
     Ok(warp::reply::json(&rooms))
@@ -79,24 +78,15 @@ async fn handle_rejection(err: Rejection) -> Result<impl Reply, Infallible> {
 /// HTTPルーターを構築
 pub fn create_http_routes(
     room_manager: SharedRoomManager,
-    static_dir: impl AsRef<Path>,
-) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
-    let static_dir = static_dir.as_ref().to_path_buf();
-    
+    static_dir: PathBuf,
+) -> impl Filter<Extract = impl Reply, Error = Infallible> + Clone {
     // 静的ファイルルート
     let static_files = warp::path("static")
         .and(warp::fs::dir(static_dir.clone()));
     
     // SPAルート (index.htmlにフォールバック)
-    let spa_route = warp::path::end()
-        .or(warp::path::tail().and(warp::path::full().not_matches(warp::path("api"))))
-        .map(move |_| {
-            let index_path = static_dir.join("index.html");
-            warp::fs::file(index_path)
-        })
-        .and_then(|file_reply| async {
-            Ok::<_, Rejection>(file_reply)
-        });
+    let spa_route = warp::get()
+        .and(warp::fs::file(static_dir.join("index.html")));
     
     // ルームマネージャーの共有
     let with_room_manager = warp::any().map(move || room_manager.clone());
@@ -133,7 +123,7 @@ pub fn create_http_routes(
 /// HTTPサーバーを起動
 pub async fn start_http_server(
     room_manager: SharedRoomManager,
-    static_dir: impl AsRef<Path>,
+    static_dir: PathBuf,
     port: u16,
 ) {
     println!("🌐 HTTPサーバーをポート{}で起動中...", port);
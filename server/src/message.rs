@@ -1,7 +1,4 @@
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
-
-use crate::game::{ActionResult, GameType, GameSettings, RoomSummary};
 
 /// クライアントからサーバーへのメッセージ
 #[derive(Debug, Deserialize)]
@@ -29,7 +26,11 @@ pub enum ClientMessage {
     LeaveRoom,
     
     /// ゲーム開始リクエスト (ルームホストのみ)
-    StartGame,
+    StartGame {
+        /// 初期ゲーム状態 (サーバーでルールを実行するゲームタイプでは無視されます)
+        #[serde(default)]
+        initial_state: serde_json::Value,
+    },
     
     /// ゲームアクション (ゲーム特有のアクション)
     GameAction {
@@ -44,11 +45,11 @@ pub enum ClientMessage {
     },
     
     /// ハートビート応答
-    Pong,
+    HeartbeatResponse,
 }
 
 /// サーバーからクライアントへのメッセージ
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, Serialize)]
 #[serde(tag = "type", rename_all = "camelCase")]
 pub enum ServerMessage {
     /// 接続時のウェルカムメッセージ
@@ -59,16 +60,22 @@ pub enum ServerMessage {
     
     /// ルーム作成成功
     RoomCreated {
+        /// ルームID
+        room_id: String,
         /// ルームコード
         room_code: String,
         /// ゲームタイプ
         game_type: String,
         /// ゲーム設定
         settings: serde_json::Value,
+        /// 自分がホストかどうか
+        is_host: bool,
     },
     
     /// ルーム参加成功
     RoomJoined {
+        /// ルームID
+        room_id: String,
         /// ルームコード
         room_code: String,
         /// ゲームタイプ
@@ -81,6 +88,9 @@ pub enum ServerMessage {
         is_host: bool,
     },
     
+    /// ルーム退出完了
+    RoomLeft,
+    
     /// プレイヤーが入室
     PlayerJoined {
         /// プレイヤー情報
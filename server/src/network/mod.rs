@@ -1,3 +0,0 @@
-pub mod server;
-
-// 他のネットワーク関連モジュールは必要に応じて追加 
\ No newline at end of file
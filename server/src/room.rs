@@ -1,319 +1,22 @@
 use serde::{Deserialize, Serialize};
-use std::collections::{HashMap, HashSet};
+use std::collections::HashMap;
 use std::time::{Duration, Instant};
 use uuid::Uuid;
-use std::sync::Arc;
-use tokio::sync::{RwLock, mpsc};
-use rand::distributions::{Alphanumeric, DistString};
+use tokio::sync::mpsc;
 use rand::Rng;
 use serde_json::Value;
 
-use crate::player::{Player, PlayerId};
-use crate::config::{GameMode, GameSettings, GameType};
-use crate::message::{GameState, GamePhase, ServerMessage, ClientMessage, ActionResult};
-use crate::game::{Game, BaseGame};
-
-/// ルームID
-#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
-pub struct RoomId(pub String);
-
-impl RoomId {
-    /// 新しいルームIDを生成
-    pub fn new() -> Self {
-        Self(Uuid::new_v4().to_string())
-    }
-    
-    /// 文字列からルームIDを生成
-    pub fn from_string(s: &str) -> Self {
-        Self(s.to_string())
-    }
-    
-    /// 文字列表現を取得
-    pub fn to_string(&self) -> String {
-        self.0.clone()
-    }
-}
-
-impl std::fmt::Display for RoomId {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", self.0)
-    }
-}
-
-/// ルームの状態
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
-pub enum RoomState {
-    Waiting,   // プレイヤー待機中
-    Playing,   // ゲームプレイ中
-    Finished,  // ゲーム終了
-}
-
-/// ルーム情報を表す構造体
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct RoomInfo {
-    pub id: String,
-    pub name: String,
-    pub owner_id: String,
-    pub max_players: usize,
-    pub player_count: usize,
-    pub is_public: bool,
-    pub state: RoomState,
-    pub game_type: String,
-    pub created_at: u64,
-}
-
-/// プレイヤー情報を表す構造体
-#[derive(Debug, Clone)]
-pub struct Player {
-    pub id: String,
-    pub name: String,
-    pub sender: mpsc::UnboundedSender<String>,
-    pub last_ping: Instant,
-}
-
-/// ゲームルームを表す構造体
-#[derive(Debug)]
-pub struct GameRoom {
-    pub info: RoomInfo,
-    pub players: HashMap<String, Player>,
-    pub spectators: HashSet<String>,
-    pub last_activity: Instant,
-}
-
-impl GameRoom {
-    pub fn new(name: String, owner_id: String, owner_name: String, sender: mpsc::UnboundedSender<String>, max_players: usize, is_public: bool, game_type: String) -> Self {
-        let room_id = Uuid::new_v4().to_string();
-        let now = Instant::now();
-        
-        let mut players = HashMap::new();
-        players.insert(owner_id.clone(), Player {
-            id: owner_id.clone(),
-            name: owner_name,
-            sender,
-            last_ping: now,
-        });
-        
-        Self {
-            info: RoomInfo {
-                id: room_id,
-                name,
-                owner_id,
-                max_players,
-                player_count: 1,
-                is_public,
-                state: RoomState::Waiting,
-                game_type,
-                created_at: std::time::SystemTime::now()
-                    .duration_since(std::time::UNIX_EPOCH)
-                    .unwrap_or(Duration::from_secs(0))
-                    .as_secs(),
-            },
-            players,
-            spectators: HashSet::new(),
-            last_activity: now,
-        }
-    }
-    
-    // プレイヤーをルームに追加
-    pub fn add_player(&mut self, player_id: String, player_name: String, sender: mpsc::UnboundedSender<String>) -> bool {
-        if self.players.len() >= self.info.max_players {
-            return false;
-        }
-        
-        self.players.insert(player_id, Player {
-            id: player_id,
-            name: player_name,
-            sender,
-            last_ping: Instant::now(),
-        });
-        
-        self.info.player_count = self.players.len();
-        self.last_activity = Instant::now();
-        true
-    }
-    
-    // プレイヤーをルームから削除
-    pub fn remove_player(&mut self, player_id: &str) -> bool {
-        let was_removed = self.players.remove(player_id).is_some();
-        
-        if was_removed {
-            self.info.player_count = self.players.len();
-            
-            // オーナーが退出した場合、新しいオーナーを設定
-            if player_id == self.info.owner_id && !self.players.is_empty() {
-                if let Some(new_owner_id) = self.players.keys().next().cloned() {
-                    self.info.owner_id = new_owner_id;
-                }
-            }
-        }
-        
-        self.last_activity = Instant::now();
-        was_removed
-    }
-    
-    // 観戦者を追加
-    pub fn add_spectator(&mut self, spectator_id: String) -> bool {
-        let result = self.spectators.insert(spectator_id);
-        self.last_activity = Instant::now();
-        result
-    }
-    
-    // 観戦者を削除
-    pub fn remove_spectator(&mut self, spectator_id: &str) -> bool {
-        let result = self.spectators.remove(spectator_id);
-        self.last_activity = Instant::now();
-        result
-    }
-    
-    // ルームの状態を更新
-    pub fn set_state(&mut self, state: RoomState) {
-        self.info.state = state;
-        self.last_activity = Instant::now();
-    }
-    
-    // 全プレイヤーにメッセージを送信
-    pub fn broadcast_message(&self, message: &str) {
-        for player in self.players.values() {
-            let _ = player.sender.send(message.to_string());
-        }
-    }
-    
-    // 特定プレイヤー以外の全員にメッセージを送信
-    pub fn broadcast_message_except(&self, excluded_player_id: &str, message: &str) {
-        for (id, player) in self.players.iter() {
-            if id != excluded_player_id {
-                let _ = player.sender.send(message.to_string());
-            }
-        }
-    }
-}
-
-/// ルームマネージャー
-pub struct RoomManager {
-    rooms: HashMap<String, GameRoom>,
-}
-
-impl RoomManager {
-    pub fn new() -> Self {
-        Self {
-            rooms: HashMap::new(),
-        }
-    }
-    
-    // 新しいルームを作成
-    pub fn create_room(&mut self, name: String, owner_id: String, owner_name: String, sender: mpsc::UnboundedSender<String>, max_players: usize, is_public: bool, game_type: String) -> RoomInfo {
-        let room = GameRoom::new(name, owner_id, owner_name, sender, max_players, is_public, game_type);
-        let room_info = room.info.clone();
-        self.rooms.insert(room_info.id.clone(), room);
-        room_info
-    }
-    
-    // ルームを削除
-    pub fn delete_room(&mut self, room_id: &str) -> bool {
-        self.rooms.remove(room_id).is_some()
-    }
-    
-    // ルームを取得
-    pub fn get_room(&self, room_id: &str) -> Option<&GameRoom> {
-        self.rooms.get(room_id)
-    }
-    
-    // ルームを可変で取得
-    pub fn get_room_mut(&mut self, room_id: &str) -> Option<&mut GameRoom> {
-        self.rooms.get_mut(room_id)
-    }
-    
-    // 公開ルーム一覧を取得
-    pub fn get_public_rooms(&self) -> Vec<RoomInfo> {
-        self.rooms.values()
-            .filter(|room| room.info.is_public)
-            .map(|room| room.info.clone())
-            .collect()
-    }
-    
-    // プレイヤーが参加しているルームを取得
-    pub fn get_player_room(&self, player_id: &str) -> Option<&GameRoom> {
-        self.rooms.values().find(|room| room.players.contains_key(player_id))
-    }
-    
-    // プレイヤーが参加しているルームを可変で取得
-    pub fn get_player_room_mut(&mut self, player_id: &str) -> Option<&mut GameRoom> {
-        self.rooms.values_mut().find(|room| room.players.contains_key(player_id))
-    }
-    
-    // タイムアウトしたルームとプレイヤーのクリーンアップ
-    pub fn cleanup(&mut self, room_timeout: Duration, player_timeout: Duration) {
-        let now = Instant::now();
-        
-        // タイムアウトしたプレイヤーを各ルームから削除
-        for room in self.rooms.values_mut() {
-            let timed_out_players: Vec<String> = room.players.iter()
-                .filter(|(_, player)| now.duration_since(player.last_ping) > player_timeout)
-                .map(|(id, _)| id.clone())
-                .collect();
-            
-            for player_id in timed_out_players {
-                log::info!("🕒 タイムアウトによりプレイヤーを削除: {}", player_id);
-                room.remove_player(&player_id);
-            }
-        }
-        
-        // タイムアウトしたルームか空のルームを削除
-        let to_remove: Vec<String> = self.rooms.iter()
-            .filter(|(_, room)| {
-                now.duration_since(room.last_activity) > room_timeout || room.players.is_empty()
-            })
-            .map(|(id, _)| id.clone())
-            .collect();
-        
-        for room_id in to_remove {
-            log::info!("🕒 タイムアウトまたは空のためルームを削除: {}", room_id);
-            self.rooms.remove(&room_id);
-        }
-    }
-}
-
-/// ルームの要約情報（ルーム一覧表示用）
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct RoomSummary {
-    /// ルームID
-    pub id: String,
-    
-    /// ルーム参加コード
-    pub code: String,
-    
-    /// ルーム名
-    pub name: String,
-    
-    /// 現在のプレイヤー数
-    pub player_count: usize,
-    
-    /// 最大プレイヤー数
-    pub max_players: usize,
-    
-    /// ゲームタイプ
-    pub game_type: GameType,
-    
-    /// ルームの状態
-    pub state: RoomState,
-    
-    /// 作成日時（Unix時間）
-    pub created_at: u64,
-}
-
-/// スレッドセーフなルームマネージャー
-pub type SharedRoomManager = Arc<RwLock<RoomManager>>;
-
-/// 新しい共有ルームマネージャーを作成
-pub fn create_room_manager() -> SharedRoomManager {
-    Arc::new(RwLock::new(RoomManager::new()))
-}
+use crate::message::{Player, ServerMessage};
+use crate::game::{Game, GameEvent, MinesweeperGame};
 
 // 6文字の英数字ルームコード生成（わかりやすい文字のみ）
 const ROOM_CODE_CHARS: &[u8] = b"ABCDEFGHJKLMNPQRSTUVWXYZ23456789";
 
 /// ゲームルーム情報
-#[derive(Debug, Clone)]
+///
+/// サーバーでルールを実行するゲームタイプでは、ルームごとにゲームの`World`を持つため
+/// `Clone`は実装しません。
+#[derive(Debug)]
 pub struct Room {
     /// ルームID (内部用UUID)
     pub id: String,
@@ -328,10 +31,11 @@ pub struct Room {
     /// 参加プレイヤー
     pub players: HashMap<String, Player>,
     /// プレイヤーごとのメッセージ送信チャンネル
-    #[serde(skip)]
     pub player_channels: HashMap<String, mpsc::UnboundedSender<ServerMessage>>,
     /// ゲーム状態
     pub game_state: Option<Value>,
+    /// サーバーで実行するゲーム（ルールをサーバーで処理するゲームタイプのみ）
+    pub game: Option<MinesweeperGame>,
     /// ゲーム進行中かどうか
     pub game_in_progress: bool,
     /// 最終更新時刻
@@ -363,6 +67,7 @@ impl Room {
             players,
             player_channels: HashMap::new(),
             game_state: None,
+            game: None,
             game_in_progress: false,
             last_updated: Instant::now(),
         }
@@ -436,6 +141,20 @@ impl Room {
             return false;
         }
         
+        // サーバーでルールを実行するゲームは、初期状態もサーバーのゲームから作る
+        let initial_state = match self.create_game() {
+            Ok(Some(game)) => {
+                let state = game.get_state().unwrap_or(initial_state);
+                self.game = Some(game);
+                state
+            }
+            Ok(None) => initial_state,
+            Err(e) => {
+                log::error!("ゲームの作成に失敗しました: {}", e);
+                return false;
+            }
+        };
+        
         self.game_state = Some(initial_state.clone());
         self.game_in_progress = true;
         
@@ -448,6 +167,59 @@ impl Room {
         true
     }
     
+    /// ゲームタイプに応じてサーバーで実行するゲームを作成し、プレイヤーを参加させて開始
+    fn create_game(&self) -> Result<Option<MinesweeperGame>, String> {
+        let mut game = match self.game_type.as_str() {
+            "minesweeper" => MinesweeperGame::from_settings(&self.settings),
+            _ => return Ok(None),
+        };
+        for player in self.players.values() {
+            game.add_player(player.id.clone(), player.name.clone())?;
+        }
+        game.start_game()?;
+        Ok(Some(game))
+    }
+    
+    /// サーバーで実行しているゲームにアクションを適用
+    ///
+    /// # 戻り値
+    ///
+    /// * `(アクション結果, 新しいゲーム状態, ゲームが終了したか, 勝者)`
+    ///
+    /// # エラー
+    ///
+    /// * サーバーでルールを実行していない場合や、アクションが不正な場合
+    pub fn apply_action(&mut self, player_id: &str, action: &Value) -> Result<(Value, Value, bool, Option<Vec<String>>), String> {
+        let game = self.game.as_mut().ok_or("このルームのゲームはサーバーで実行されていません")?;
+        let action_type = action["type"].as_str().ok_or("アクションタイプが指定されていません")?;
+        
+        let result = game.process_action(player_id, action_type, action.clone())?;
+        let state = game.get_state()?;
+        let ended = game.is_game_ended();
+        let winner_ids = game.get_result().and_then(|result| result.winner_id).map(|id| vec![id]);
+        Ok((result, state, ended, winner_ids))
+    }
+    
+    /// サーバーで実行しているゲームを進める
+    ///
+    /// ゲームが終了した場合は終了通知を送ります。
+    pub fn tick(&mut self, delta_time: f32) {
+        if !self.game_in_progress {
+            return;
+        }
+        let game = match self.game.as_mut() {
+            Some(game) => game,
+            None => return,
+        };
+        
+        let events = game.update(delta_time);
+        if events.iter().any(|event| matches!(event, GameEvent::GameEnded { .. })) {
+            let winner_ids = game.get_result().and_then(|result| result.winner_id).map(|id| vec![id]);
+            let final_state = game.get_state().unwrap_or(Value::Null);
+            self.end_game(winner_ids, final_state);
+        }
+    }
+    
     /// ゲーム状態を更新
     pub fn update_game_state(&mut self, new_state: Value) {
         if !self.game_in_progress {
@@ -616,13 +388,13 @@ impl RoomManager {
         channel: mpsc::UnboundedSender<ServerMessage>,
     ) -> Result<(String, String, String, Value, Vec<Player>, bool), String> {
         // ルームが存在するか確認
-        let room = match self.rooms.get_mut(room_code) {
-            Some(room) => room,
+        let in_progress = match self.rooms.get(room_code) {
+            Some(room) => room.game_in_progress,
             None => return Err("ルームが見つかりません".to_string()),
         };
         
         // ゲームが進行中かチェック
-        if room.game_in_progress {
+        if in_progress {
             return Err("ゲームが既に進行中です".to_string());
         }
         
@@ -658,6 +430,10 @@ impl RoomManager {
         }
         
         // プレイヤーをルームに追加
+        let room = match self.rooms.get_mut(room_code) {
+            Some(room) => room,
+            None => return Err("ルームが見つかりません".to_string()),
+        };
         if !room.add_player(player_id.clone(), player_name.clone(), channel.clone()) {
             return Err("ルームに参加できません".to_string());
         }
@@ -846,6 +622,31 @@ impl RoomManager {
         Ok(())
     }
     
+    /// ゲーム進行中の全ルームのゲームを進める
+    pub fn tick_rooms(&mut self, delta_time: f32) {
+        for room in self.rooms.values_mut() {
+            room.tick(delta_time);
+        }
+    }
+    
+    /// ゲームアクションを適用
+    ///
+    /// 結果と新しい状態をルームに送信し、ゲームが終了した場合は終了処理も行います。
+    pub fn apply_action(&mut self, player_id: &str, action: &Value) -> Result<(), String> {
+        let room = match self.get_player_room_mut(player_id) {
+            Some(room) => room,
+            None => return Err("ルームに参加していません".to_string()),
+        };
+        
+        let (result, state, ended, winner_ids) = room.apply_action(player_id, action)?;
+        room.send_action_result(player_id.to_string(), result);
+        room.update_game_state(state.clone());
+        if ended {
+            room.end_game(winner_ids, state);
+        }
+        Ok(())
+    }
+    
     /// 古いルームをクリーンアップ
     pub fn cleanup_inactive_rooms(&mut self, max_inactive_time: Duration) {
         let now = Instant::now();
@@ -868,4 +669,49 @@ impl RoomManager {
             }
         }
     }
-} 
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_minesweeper_room_applies_actions_on_server() {
+        let mut manager = RoomManager::new();
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let (_, room_code) = manager.create_room(
+            "host".to_string(),
+            "ホスト".to_string(),
+            "minesweeper".to_string(),
+            serde_json::json!({ "width": 5, "height": 5, "mines": 3 }),
+            tx,
+        );
+
+        // ゲームを開始すると、サーバーのゲームから作った初期状態が届く
+        manager.start_game("host", Value::Null).expect("ゲームを開始できません");
+        assert!(manager.get_room(&room_code).unwrap().game.is_some());
+        match rx.try_recv().expect("開始通知が届いていません") {
+            ServerMessage::GameStarted { state } => assert!(!state.is_null()),
+            message => panic!("予期しないメッセージ: {:?}", message),
+        }
+
+        // 旗を立てると、結果と新しい状態がルーム全体に送られる
+        manager
+            .apply_action("host", &serde_json::json!({ "type": "flag", "x": 0, "y": 0 }))
+            .expect("アクションを適用できません");
+        match rx.try_recv().expect("アクション結果が届いていません") {
+            ServerMessage::GameActionResult { player_id, result } => {
+                assert_eq!(player_id, "host");
+                assert_eq!(result["is_flagged"], true);
+                assert_eq!(result["flagged_count"], 1);
+            }
+            message => panic!("予期しないメッセージ: {:?}", message),
+        }
+        assert!(matches!(rx.try_recv(), Ok(ServerMessage::GameStateUpdate { .. })));
+
+        // 盤面の外を指定したアクションはエラーになる
+        assert!(manager
+            .apply_action("host", &serde_json::json!({ "type": "flag", "x": 10, "y": 0 }))
+            .is_err());
+    }
+}
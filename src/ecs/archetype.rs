@@ -0,0 +1,727 @@
+//! アーキタイプ（テーブル）ストレージ
+//!
+//! 同じコンポーネントの組み合わせを持つエンティティを1つのテーブルにまとめて格納します。
+//! テーブルの各列は1種類のコンポーネントを行順に詰めて保持するため、
+//! クエリはエンティティごとのハッシュ検索なしにテーブルを先頭から順に走査できます。
+//!
+//! `StorageType::Table`を指定したコンポーネントだけがテーブルに格納され、
+//! それ以外のコンポーネントは従来通り`VecStorage`に格納されます。
+//!
+//! ```
+//! struct Cell { mine: bool }
+//! impl_component!(Cell, "Cell", Table);
+//!
+//! for archetype in world.components().archetypes().iter() {
+//!     if let Some(cells) = archetype.column::<Cell>() {
+//!         let mines = cells.iter().filter(|cell| cell.mine).count();
+//!     }
+//! }
+//! ```
+
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use std::ptr::NonNull;
+
+use crate::ecs::component::{Component, ComponentTicks};
+use crate::ecs::entity::Entity;
+
+/// アーキタイプの識別子
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ArchetypeId(u32);
+
+impl ArchetypeId {
+    /// アーキタイプのインデックスを取得
+    pub fn index(&self) -> usize {
+        self.0 as usize
+    }
+}
+
+/// エンティティが格納されているテーブルと行
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EntityLocation {
+    /// エンティティが属するアーキタイプ
+    pub archetype: ArchetypeId,
+    /// テーブル内の行
+    pub row: usize,
+}
+
+/// テーブルの1列を表す型消去されたストレージ
+pub(crate) trait Column: Any + Send + Sync {
+    /// コンポーネントの型IDを取得
+    fn component_type_id(&self) -> TypeId;
+
+    /// コンポーネントの名前を取得
+    fn component_name(&self) -> &'static str;
+
+    /// 行を削除し、最後の行を削除位置に移動
+    fn swap_remove(&mut self, row: usize);
+
+    /// 行を取り出して同じ型の別の列の末尾に追加
+    fn move_row(&mut self, row: usize, dst: &mut dyn Column);
+
+    /// 同じ型の空の列を作成
+    fn new_empty(&self) -> Box<dyn Column>;
+
+    /// データと変更ティックの先頭へのポインタを取得
+    fn raw_parts_mut(&mut self) -> (NonNull<u8>, NonNull<ComponentTicks>);
+
+    /// 内部ストレージをAny型として取得
+    fn as_any(&self) -> &dyn Any;
+
+    /// 内部ストレージを可変Any型として取得
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+}
+
+/// 型Tのコンポーネントを行順に格納する列
+struct TableColumn<T: Component> {
+    /// コンポーネントデータ
+    data: Vec<T>,
+    /// 各コンポーネントの変更ティック（dataと同じ並び）
+    ticks: Vec<ComponentTicks>,
+}
+
+impl<T: Component> TableColumn<T> {
+    fn new() -> Self {
+        Self {
+            data: Vec::new(),
+            ticks: Vec::new(),
+        }
+    }
+
+    fn push(&mut self, component: T, ticks: ComponentTicks) {
+        self.data.push(component);
+        self.ticks.push(ticks);
+    }
+}
+
+impl<T: Component> Column for TableColumn<T> {
+    fn component_type_id(&self) -> TypeId {
+        TypeId::of::<T>()
+    }
+
+    fn component_name(&self) -> &'static str {
+        T::name()
+    }
+
+    fn swap_remove(&mut self, row: usize) {
+        self.data.swap_remove(row);
+        self.ticks.swap_remove(row);
+    }
+
+    fn move_row(&mut self, row: usize, dst: &mut dyn Column) {
+        let dst = dst.as_any_mut()
+            .downcast_mut::<TableColumn<T>>()
+            .expect("Failed to downcast column");
+        let component = self.data.swap_remove(row);
+        let ticks = self.ticks.swap_remove(row);
+        dst.push(component, ticks);
+    }
+
+    fn new_empty(&self) -> Box<dyn Column> {
+        Box::new(TableColumn::<T>::new())
+    }
+
+    fn raw_parts_mut(&mut self) -> (NonNull<u8>, NonNull<ComponentTicks>) {
+        (NonNull::from(self.data.as_mut_slice()).cast(), NonNull::from(self.ticks.as_mut_slice()).cast())
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+/// バンドルとしてテーブルにまとめて追加するコンポーネント（型消去）
+pub(crate) trait TableValue: Send + Sync {
+    /// コンポーネントの型IDを取得
+    fn component_type_id(&self) -> TypeId;
+
+    /// このコンポーネントを格納する空の列を作成
+    fn new_column(&self) -> Box<dyn Column>;
+
+    /// 列の末尾に追加
+    fn push(self: Box<Self>, column: &mut dyn Column, tick: u64);
+
+    /// 列の行の値を置き換え、変更ティックを更新
+    fn replace(self: Box<Self>, column: &mut dyn Column, row: usize, tick: u64);
+}
+
+impl<T: Component> TableValue for T {
+    fn component_type_id(&self) -> TypeId {
+        TypeId::of::<T>()
+    }
+
+    fn new_column(&self) -> Box<dyn Column> {
+        Box::new(TableColumn::<T>::new())
+    }
+
+    fn push(self: Box<Self>, column: &mut dyn Column, tick: u64) {
+        column.as_any_mut()
+            .downcast_mut::<TableColumn<T>>()
+            .expect("Failed to downcast column")
+            .push(*self, ComponentTicks::new(tick));
+    }
+
+    fn replace(self: Box<Self>, column: &mut dyn Column, row: usize, tick: u64) {
+        let column = column.as_any_mut()
+            .downcast_mut::<TableColumn<T>>()
+            .expect("Failed to downcast column");
+        column.ticks[row].changed = tick;
+        column.data[row] = *self;
+    }
+}
+
+/// アーキタイプごとの列の先頭（データ, 変更ティック）
+///
+/// 並列実行中のシステムは、列を持つアーキタイプへの可変参照を作らずにこのポインタから書き込みます。
+pub(crate) type ColumnPtrs = Vec<Option<(NonNull<u8>, NonNull<ComponentTicks>)>>;
+
+/// 同じコンポーネントの組み合わせを持つエンティティのテーブル
+pub struct Archetype {
+    /// アーキタイプの識別子
+    id: ArchetypeId,
+    /// 格納するコンポーネントの型ID（昇順）
+    component_types: Vec<TypeId>,
+    /// コンポーネントの列（component_typesと同じ並び）
+    columns: Vec<Box<dyn Column>>,
+    /// 各行のエンティティ
+    entities: Vec<Entity>,
+}
+
+impl Archetype {
+    /// アーキタイプの識別子を取得
+    pub fn id(&self) -> ArchetypeId {
+        self.id
+    }
+
+    /// 格納しているコンポーネントの型IDを取得
+    pub fn component_types(&self) -> &[TypeId] {
+        &self.component_types
+    }
+
+    /// 格納しているコンポーネントの名前を列挙
+    pub fn component_names(&self) -> impl Iterator<Item = &'static str> + '_ {
+        self.columns.iter().map(|column| column.component_name())
+    }
+
+    /// コンポーネントを格納しているかどうか
+    pub fn contains(&self, type_id: TypeId) -> bool {
+        self.column_index(type_id).is_some()
+    }
+
+    /// テーブル内のエンティティを行順に取得
+    pub fn entities(&self) -> &[Entity] {
+        &self.entities
+    }
+
+    /// テーブルの行数を取得
+    pub fn len(&self) -> usize {
+        self.entities.len()
+    }
+
+    /// テーブルが空かどうか
+    pub fn is_empty(&self) -> bool {
+        self.entities.is_empty()
+    }
+
+    /// コンポーネントの列を行順のスライスとして取得
+    pub fn column<T: Component>(&self) -> Option<&[T]> {
+        self.typed_column::<T>().map(|column| column.data.as_slice())
+    }
+
+    fn column_index(&self, type_id: TypeId) -> Option<usize> {
+        self.component_types.binary_search(&type_id).ok()
+    }
+
+    fn typed_column<T: Component>(&self) -> Option<&TableColumn<T>> {
+        let index = self.column_index(TypeId::of::<T>())?;
+        self.columns[index].as_any().downcast_ref::<TableColumn<T>>()
+    }
+
+    fn typed_column_mut<T: Component>(&mut self) -> Option<&mut TableColumn<T>> {
+        let index = self.column_index(TypeId::of::<T>())?;
+        self.columns[index].as_any_mut().downcast_mut::<TableColumn<T>>()
+    }
+}
+
+/// すべてのアーキタイプとエンティティの格納位置を管理する構造体
+#[derive(Default)]
+pub struct Archetypes {
+    /// アーキタイプの一覧（ArchetypeIdがインデックス）
+    archetypes: Vec<Archetype>,
+    /// コンポーネントの組み合わせ→アーキタイプのマッピング
+    by_components: HashMap<Vec<TypeId>, ArchetypeId>,
+    /// エンティティのインデックス→格納位置のマッピング
+    locations: Vec<Option<EntityLocation>>,
+}
+
+impl Archetypes {
+    /// 空のアーキタイプ一覧を作成
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// アーキタイプを列挙
+    pub fn iter(&self) -> impl Iterator<Item = &Archetype> + '_ {
+        self.archetypes.iter()
+    }
+
+    /// アーキタイプを取得
+    pub fn get(&self, id: ArchetypeId) -> Option<&Archetype> {
+        self.archetypes.get(id.index())
+    }
+
+    /// アーキタイプの数を取得
+    pub fn len(&self) -> usize {
+        self.archetypes.len()
+    }
+
+    /// アーキタイプが1つもないかどうか
+    pub fn is_empty(&self) -> bool {
+        self.archetypes.is_empty()
+    }
+
+    /// エンティティの格納位置を取得
+    ///
+    /// テーブルにコンポーネントを持たないエンティティや、古いハンドルの場合は`None`を返します。
+    pub fn location(&self, entity: Entity) -> Option<EntityLocation> {
+        let location = (*self.locations.get(entity.index() as usize)?)?;
+        let archetype = &self.archetypes[location.archetype.index()];
+        if archetype.entities[location.row] == entity {
+            Some(location)
+        } else {
+            None
+        }
+    }
+
+    /// エンティティがコンポーネントを持っているか確認
+    pub fn contains(&self, entity: Entity, type_id: TypeId) -> bool {
+        self.location(entity)
+            .is_some_and(|location| self.archetypes[location.archetype.index()].contains(type_id))
+    }
+
+    /// コンポーネントを取得
+    pub fn get_component<T: Component>(&self, entity: Entity) -> Option<&T> {
+        let location = self.location(entity)?;
+        let column = self.archetypes[location.archetype.index()].typed_column::<T>()?;
+        Some(&column.data[location.row])
+    }
+
+    /// コンポーネントを可変で取得し、変更ティックを更新
+    pub fn get_component_mut<T: Component>(&mut self, entity: Entity, tick: u64) -> Option<&mut T> {
+        let location = self.location(entity)?;
+        let column = self.archetypes[location.archetype.index()].typed_column_mut::<T>()?;
+        column.ticks[location.row].changed = tick;
+        Some(&mut column.data[location.row])
+    }
+
+    /// コンポーネントの変更ティックを取得
+    pub fn get_ticks<T: Component>(&self, entity: Entity) -> Option<ComponentTicks> {
+        let location = self.location(entity)?;
+        let column = self.archetypes[location.archetype.index()].typed_column::<T>()?;
+        Some(column.ticks[location.row])
+    }
+
+    /// 生ポインタ経由でコンポーネントへのポインタを取得
+    ///
+    /// # Safety
+    ///
+    /// `archetypes`は有効なArchetypesを指していなければなりません。
+    pub(crate) unsafe fn get_ptr<T: Component>(archetypes: *const Self, entity: Entity) -> Option<*const T> {
+        let archetypes = &*archetypes;
+        let location = archetypes.location(entity)?;
+        let column = archetypes.archetypes[location.archetype.index()].typed_column::<T>()?;
+        Some(column.data.as_ptr().add(location.row))
+    }
+
+    /// 型の列の先頭をアーキタイプごとに取得
+    ///
+    /// アーキタイプを持たない型や、列を持たないアーキタイプの位置は`None`になります。
+    pub(crate) fn column_ptrs(&mut self, type_id: TypeId) -> ColumnPtrs {
+        self.archetypes.iter_mut()
+            .map(|archetype| {
+                let index = archetype.column_index(type_id)?;
+                Some(archetype.columns[index].raw_parts_mut())
+            })
+            .collect()
+    }
+
+    /// 事前に取得した列の先頭からコンポーネントへの可変ポインタを取得し、変更ティックを更新
+    ///
+    /// 格納位置の検索には共有参照だけを使い、列の要素以外を指す可変参照は作りません。
+    ///
+    /// # Safety
+    ///
+    /// `archetypes`は有効なArchetypesを指し、`columns`は`column_ptrs`で`T`について取得したものでなければなりません。
+    /// 取得後にアーキタイプや列の構造が変更されていてはいけません。
+    pub(crate) unsafe fn get_mut_ptr<T: Component>(
+        archetypes: *const Self,
+        columns: &ColumnPtrs,
+        entity: Entity,
+        tick: u64,
+    ) -> Option<*mut T> {
+        let location = (*archetypes).location(entity)?;
+        let (data, ticks) = (*columns.get(location.archetype.index())?)?;
+        (*ticks.as_ptr().add(location.row)).changed = tick;
+        Some(data.cast::<T>().as_ptr().add(location.row))
+    }
+
+    /// 特定のコンポーネントを持つすべてのエンティティを取得
+    pub fn entities_with(&self, type_id: TypeId) -> impl Iterator<Item = Entity> + '_ {
+        self.archetypes.iter()
+            .filter(move |archetype| archetype.contains(type_id))
+            .flat_map(|archetype| archetype.entities.iter().copied())
+    }
+
+    /// エンティティにコンポーネントを追加
+    ///
+    /// エンティティは新しいコンポーネントの組み合わせに対応するテーブルへ移動します。
+    /// 既に同じ型のコンポーネントを持つ場合は置き換え、変更ティックのみを更新します。
+    pub fn insert<T: Component>(&mut self, entity: Entity, component: T, tick: u64) -> Option<T> {
+        let type_id = TypeId::of::<T>();
+        let location = self.location(entity);
+
+        if let Some(location) = location {
+            let archetype = &mut self.archetypes[location.archetype.index()];
+            if let Some(column) = archetype.typed_column_mut::<T>() {
+                column.ticks[location.row].changed = tick;
+                return Some(std::mem::replace(&mut column.data[location.row], component));
+            }
+        }
+
+        let mut component_types = location
+            .map(|location| self.archetypes[location.archetype.index()].component_types.clone())
+            .unwrap_or_default();
+        let insert_at = component_types.binary_search(&type_id).unwrap_err();
+        component_types.insert(insert_at, type_id);
+
+        let target = match self.by_components.get(&component_types) {
+            Some(&id) => id,
+            None => {
+                let mut columns = location
+                    .map(|location| {
+                        self.archetypes[location.archetype.index()].columns.iter()
+                            .map(|column| column.new_empty())
+                            .collect::<Vec<_>>()
+                    })
+                    .unwrap_or_default();
+                columns.insert(insert_at, Box::new(TableColumn::<T>::new()));
+                self.create_archetype(component_types, columns)
+            }
+        };
+
+        let row = match location {
+            Some(location) => self.move_entity(entity, location, target),
+            None => self.archetypes[target.index()].entities.len(),
+        };
+
+        let archetype = &mut self.archetypes[target.index()];
+        archetype.typed_column_mut::<T>()
+            .expect("Failed to downcast column")
+            .push(component, ComponentTicks::new(tick));
+        if location.is_none() {
+            archetype.entities.push(entity);
+        }
+        self.set_location(entity, Some(EntityLocation { archetype: target, row }));
+        None
+    }
+
+    /// エンティティに複数のコンポーネントをまとめて追加
+    ///
+    /// 移動先のテーブルを一度だけ決め、エンティティの移動も一度だけ行います。
+    /// 既に持っているコンポーネントはその場で置き換え、同じ型が複数ある場合は後の値を使います。
+    pub(crate) fn insert_many(&mut self, entity: Entity, values: Vec<Box<dyn TableValue>>, tick: u64) {
+        let location = self.location(entity);
+        let source_types = location
+            .map(|location| self.archetypes[location.archetype.index()].component_types.clone())
+            .unwrap_or_default();
+
+        let mut added: Vec<Box<dyn TableValue>> = Vec::new();
+        for value in values {
+            let type_id = value.component_type_id();
+            if let (Some(location), Ok(index)) = (location, source_types.binary_search(&type_id)) {
+                let column = self.archetypes[location.archetype.index()].columns[index].as_mut();
+                value.replace(column, location.row, tick);
+            } else if let Some(slot) = added.iter_mut().find(|added| added.component_type_id() == type_id) {
+                *slot = value;
+            } else {
+                added.push(value);
+            }
+        }
+        if added.is_empty() {
+            return;
+        }
+
+        let mut component_types = source_types.clone();
+        for value in &added {
+            let insert_at = component_types.binary_search(&value.component_type_id()).unwrap_err();
+            component_types.insert(insert_at, value.component_type_id());
+        }
+
+        let target = match self.by_components.get(&component_types) {
+            Some(&id) => id,
+            None => {
+                let columns = component_types.iter()
+                    .map(|type_id| match (location, source_types.binary_search(type_id)) {
+                        (Some(location), Ok(index)) => self.archetypes[location.archetype.index()].columns[index].new_empty(),
+                        _ => added.iter()
+                            .find(|value| value.component_type_id() == *type_id)
+                            .expect("追加するコンポーネントの列がありません")
+                            .new_column(),
+                    })
+                    .collect();
+                self.create_archetype(component_types, columns)
+            }
+        };
+
+        let row = match location {
+            Some(location) => self.move_entity(entity, location, target),
+            None => {
+                let entities = &mut self.archetypes[target.index()].entities;
+                entities.push(entity);
+                entities.len() - 1
+            }
+        };
+
+        let archetype = &mut self.archetypes[target.index()];
+        for value in added {
+            let index = archetype.column_index(value.component_type_id())
+                .expect("追加するコンポーネントの列がありません");
+            value.push(archetype.columns[index].as_mut(), tick);
+        }
+        self.set_location(entity, Some(EntityLocation { archetype: target, row }));
+    }
+
+    /// エンティティからコンポーネントを削除
+    ///
+    /// エンティティは残りのコンポーネントの組み合わせに対応するテーブルへ移動します。
+    pub fn remove(&mut self, entity: Entity, type_id: TypeId) -> bool {
+        let location = match self.location(entity) {
+            Some(location) => location,
+            None => return false,
+        };
+        let source = &self.archetypes[location.archetype.index()];
+        let remove_at = match source.column_index(type_id) {
+            Some(index) => index,
+            None => return false,
+        };
+
+        if source.component_types.len() == 1 {
+            self.remove_row(location);
+            self.set_location(entity, None);
+            return true;
+        }
+
+        let mut component_types = source.component_types.clone();
+        component_types.remove(remove_at);
+        let target = match self.by_components.get(&component_types) {
+            Some(&id) => id,
+            None => {
+                let columns = source.columns.iter()
+                    .enumerate()
+                    .filter(|(index, _)| *index != remove_at)
+                    .map(|(_, column)| column.new_empty())
+                    .collect();
+                self.create_archetype(component_types, columns)
+            }
+        };
+
+        let row = self.move_entity(entity, location, target);
+        self.set_location(entity, Some(EntityLocation { archetype: target, row }));
+        true
+    }
+
+    /// エンティティのすべてのコンポーネントを削除
+    ///
+    /// # 戻り値
+    ///
+    /// 削除されたコンポーネントの型IDと名前
+    pub fn remove_all(&mut self, entity: Entity) -> Vec<(TypeId, &'static str)> {
+        let location = match self.location(entity) {
+            Some(location) => location,
+            None => return Vec::new(),
+        };
+
+        let removed = self.archetypes[location.archetype.index()].columns.iter()
+            .map(|column| (column.component_type_id(), column.component_name()))
+            .collect();
+        self.remove_row(location);
+        self.set_location(entity, None);
+        removed
+    }
+
+    /// すべてのテーブルを空にする
+    pub fn clear(&mut self) {
+        for archetype in self.archetypes.iter_mut() {
+            for column in archetype.columns.iter_mut() {
+                *column = column.new_empty();
+            }
+            archetype.entities.clear();
+        }
+        self.locations.clear();
+    }
+
+    fn create_archetype(&mut self, component_types: Vec<TypeId>, columns: Vec<Box<dyn Column>>) -> ArchetypeId {
+        let id = ArchetypeId(self.archetypes.len() as u32);
+        self.by_components.insert(component_types.clone(), id);
+        self.archetypes.push(Archetype {
+            id,
+            component_types,
+            columns,
+            entities: Vec::new(),
+        });
+        id
+    }
+
+    fn set_location(&mut self, entity: Entity, location: Option<EntityLocation>) {
+        let index = entity.index() as usize;
+        if index >= self.locations.len() {
+            self.locations.resize(index + 1, None);
+        }
+        self.locations[index] = location;
+    }
+
+    /// 行を削除し、削除位置に移動したエンティティの格納位置を更新
+    fn remove_row(&mut self, location: EntityLocation) {
+        let archetype = &mut self.archetypes[location.archetype.index()];
+        for column in archetype.columns.iter_mut() {
+            column.swap_remove(location.row);
+        }
+        archetype.entities.swap_remove(location.row);
+        self.fix_swapped(location);
+    }
+
+    /// エンティティを別のテーブルへ移動
+    ///
+    /// 移動先にない列のコンポーネントは破棄されます。移動先の新しい行を返します。
+    fn move_entity(&mut self, entity: Entity, location: EntityLocation, target: ArchetypeId) -> usize {
+        let (source, destination) = pair_mut(&mut self.archetypes, location.archetype.index(), target.index());
+        let row = destination.entities.len();
+
+        for (column, type_id) in source.columns.iter_mut().zip(source.component_types.iter()) {
+            match destination.column_index(*type_id) {
+                Some(index) => column.move_row(location.row, destination.columns[index].as_mut()),
+                None => column.swap_remove(location.row),
+            }
+        }
+        source.entities.swap_remove(location.row);
+        destination.entities.push(entity);
+
+        self.fix_swapped(location);
+        row
+    }
+
+    /// swap_removeで削除位置に移動したエンティティの格納位置を更新
+    fn fix_swapped(&mut self, location: EntityLocation) {
+        let archetype = &self.archetypes[location.archetype.index()];
+        if let Some(&moved) = archetype.entities.get(location.row) {
+            self.locations[moved.index() as usize] = Some(location);
+        }
+    }
+}
+
+/// ベクターの異なる2要素への可変参照を取得
+fn pair_mut<T>(items: &mut [T], a: usize, b: usize) -> (&mut T, &mut T) {
+    assert_ne!(a, b);
+    if a < b {
+        let (left, right) = items.split_at_mut(b);
+        (&mut left[a], &mut right[0])
+    } else {
+        let (left, right) = items.split_at_mut(a);
+        (&mut right[0], &mut left[b])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ecs::{With, World};
+
+    #[derive(Debug, PartialEq)]
+    struct Cell {
+        mine: bool,
+    }
+
+    #[derive(Debug, PartialEq)]
+    struct Revealed(u8);
+
+    #[derive(Debug, PartialEq)]
+    struct Hovered;
+
+    crate::impl_component!(Cell, "Cell", Table);
+    crate::impl_component!(Revealed, "Revealed", Table);
+    crate::impl_component!(Hovered, "Hovered");
+
+    #[test]
+    fn test_entities_move_between_tables() {
+        let mut world = World::new();
+        let cells: Vec<Entity> = (0..4).map(|i| {
+            let entity = world.create_entity();
+            world.add_component(entity, Cell { mine: i % 2 == 0 });
+            entity
+        }).collect();
+
+        world.add_component(cells[0], Revealed(1));
+        world.add_component(cells[2], Revealed(3));
+        assert!(world.remove_component::<Cell>(cells[2]));
+
+        let archetypes = world.components().archetypes();
+        assert_eq!(archetypes.len(), 3);
+        let cell_table = archetypes.iter()
+            .find(|archetype| archetype.component_types() == [TypeId::of::<Cell>()])
+            .unwrap();
+        assert_eq!(cell_table.len(), 2);
+        assert_eq!(cell_table.column::<Cell>().unwrap(), &[Cell { mine: false }, Cell { mine: false }]);
+
+        assert_eq!(world.get_component::<Cell>(cells[0]), Some(&Cell { mine: true }));
+        assert_eq!(world.get_component::<Revealed>(cells[0]), Some(&Revealed(1)));
+        assert_eq!(world.get_component::<Cell>(cells[2]), None);
+        assert_eq!(world.get_component::<Revealed>(cells[2]), Some(&Revealed(3)));
+        assert_eq!(world.get_component::<Cell>(cells[3]), Some(&Cell { mine: false }));
+    }
+
+    #[test]
+    fn test_query_mixes_table_and_dense_storage() {
+        let mut world = World::new();
+        let hovered = world.create_entity();
+        world.add_component(hovered, Cell { mine: false });
+        world.add_component(hovered, Revealed(0));
+        world.add_component(hovered, Hovered);
+        let plain = world.create_entity();
+        world.add_component(plain, Cell { mine: true });
+        world.add_component(plain, Revealed(0));
+        let lone = world.create_entity();
+        world.add_component(lone, Cell { mine: true });
+
+        for (cell, revealed) in world.query::<(&Cell, &mut Revealed)>().iter_mut(&mut world) {
+            revealed.0 = if cell.mine { 9 } else { 1 };
+        }
+        assert_eq!(world.get_component::<Revealed>(plain), Some(&Revealed(9)));
+
+        let query = world.query_filtered::<Entity, (With<Cell>, With<Hovered>)>();
+        assert_eq!(query.entities(), vec![hovered]);
+        assert_eq!(world.query::<&Cell>().len(), 3);
+    }
+
+    #[test]
+    fn test_despawn_removes_row_and_logs() {
+        let mut world = World::new();
+        let first = world.create_entity();
+        world.add_component(first, Cell { mine: true });
+        let second = world.create_entity();
+        world.add_component(second, Cell { mine: false });
+
+        world.destroy_entity(first);
+        assert_eq!(world.removed_components::<Cell>().len(), 1);
+        assert_eq!(world.get_component::<Cell>(second), Some(&Cell { mine: false }));
+
+        // 再利用されたインデックスでも古いハンドルでは取得できない
+        let reused = world.create_entity();
+        world.add_component(reused, Cell { mine: true });
+        assert_eq!(world.get_component::<Cell>(first), None);
+        assert_eq!(world.get_component::<Cell>(reused), Some(&Cell { mine: true }));
+    }
+}
@@ -0,0 +1,250 @@
+//! コンポーネントのまとまり
+//!
+//! 一緒に追加されることの多いコンポーネントを`Bundle`としてまとめ、
+//! `World::spawn`や`World::insert_bundle`で一度に追加できるようにします。
+//! すべてのコンポーネントと、`Bundle`のタプルも`Bundle`です。
+//!
+//! ```
+//! #[derive(Bundle)]
+//! pub struct CellBundle {
+//!     cell: Cell,
+//!     transform: Transform,
+//! }
+//!
+//! // Bundleは入れ子にできる
+//! #[derive(Bundle)]
+//! pub struct MineBundle {
+//!     cell: CellBundle,
+//!     mine: Mine,
+//! }
+//!
+//! let entity = world.spawn(MineBundle { cell, mine: Mine });
+//! world.insert_bundle(entity, (Revealed, Highlighted));
+//! ```
+
+use std::any::TypeId;
+
+use crate::ecs::archetype::TableValue;
+use crate::ecs::hooks::ComponentHooks;
+use crate::ecs::{Component, ComponentManager, Entity, StorageType};
+
+/// エンティティにまとめて追加できるコンポーネントの組
+///
+/// 構造体には`#[derive(Bundle)]`で実装します。各フィールドは`Bundle`
+/// （コンポーネントまたは別の`Bundle`）である必要があります。
+pub trait Bundle: 'static + Send + Sync {
+    /// すべてのコンポーネントを宣言順に集める
+    fn collect_components(self, components: &mut BundleComponents);
+
+    /// 含まれるコンポーネント名を宣言順に追加
+    fn component_names(names: &mut Vec<&'static str>) where Self: Sized;
+}
+
+impl<T: Component> Bundle for T {
+    fn collect_components(self, components: &mut BundleComponents) {
+        components.push(self);
+    }
+
+    fn component_names(names: &mut Vec<&'static str>) {
+        names.push(T::name());
+    }
+}
+
+/// バンドルから集めた、まとめて追加するコンポーネント
+///
+/// `ComponentManager::insert_bundle`に渡すと、テーブル格納のコンポーネントは
+/// 一度の移動で最終的なテーブルに格納されます。
+#[derive(Default)]
+pub struct BundleComponents {
+    components: Vec<Box<dyn PendingComponent>>,
+}
+
+impl BundleComponents {
+    /// 空のコンポーネントの組を作成
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// コンポーネントを追加
+    pub fn push<T: Component>(&mut self, component: T) {
+        self.components.push(Box::new(Pending(component)));
+    }
+
+    /// 集めたコンポーネントの数を取得
+    pub fn len(&self) -> usize {
+        self.components.len()
+    }
+
+    /// コンポーネントが1つもないかどうか
+    pub fn is_empty(&self) -> bool {
+        self.components.is_empty()
+    }
+
+    pub(crate) fn into_components(self) -> Vec<Box<dyn PendingComponent>> {
+        self.components
+    }
+}
+
+/// 追加を待っているコンポーネント（型消去）
+pub(crate) trait PendingComponent: Send + Sync {
+    /// コンポーネントの型IDを取得
+    fn component_type_id(&self) -> TypeId;
+
+    /// コンポーネントの名前を取得
+    fn component_name(&self) -> &'static str;
+
+    /// コンポーネント型を登録し、格納方式とフックを取得
+    fn register(&self, manager: &mut ComponentManager) -> (StorageType, ComponentHooks);
+
+    /// 型ごとのストレージに追加（テーブル格納以外）
+    fn insert_into_storage(self: Box<Self>, manager: &mut ComponentManager, entity: Entity, tick: u64);
+
+    /// テーブルに追加する値に変換
+    fn into_table_value(self: Box<Self>) -> Box<dyn TableValue>;
+}
+
+/// 型Tの追加を待っているコンポーネント
+struct Pending<T: Component>(T);
+
+impl<T: Component> PendingComponent for Pending<T> {
+    fn component_type_id(&self) -> TypeId {
+        TypeId::of::<T>()
+    }
+
+    fn component_name(&self) -> &'static str {
+        T::name()
+    }
+
+    fn register(&self, manager: &mut ComponentManager) -> (StorageType, ComponentHooks) {
+        manager.register::<T>();
+        (manager.storage_type::<T>(), manager.hooks::<T>())
+    }
+
+    fn insert_into_storage(self: Box<Self>, manager: &mut ComponentManager, entity: Entity, tick: u64) {
+        if let Some(storage) = manager.typed_storage_mut::<T>() {
+            storage.insert(entity, self.0, tick);
+        }
+    }
+
+    fn into_table_value(self: Box<Self>) -> Box<dyn TableValue> {
+        Box::new(self.0)
+    }
+}
+
+/// タプル型に対するBundleの実装を生成するマクロ
+macro_rules! impl_bundle_tuple {
+    ($(($name:ident, $value:ident)),*) => {
+        impl<$($name: Bundle),*> Bundle for ($($name,)*) {
+            fn collect_components(self, components: &mut BundleComponents) {
+                let ($($value,)*) = self;
+                $($value.collect_components(components);)*
+            }
+
+            fn component_names(names: &mut Vec<&'static str>) {
+                $($name::component_names(names);)*
+            }
+        }
+    };
+}
+
+impl_bundle_tuple!((A, a));
+impl_bundle_tuple!((A, a), (B, b));
+impl_bundle_tuple!((A, a), (B, b), (C, c));
+impl_bundle_tuple!((A, a), (B, b), (C, c), (D, d));
+impl_bundle_tuple!((A, a), (B, b), (C, c), (D, d), (E, e));
+impl_bundle_tuple!((A, a), (B, b), (C, c), (D, d), (E, e), (F, f));
+impl_bundle_tuple!((A, a), (B, b), (C, c), (D, d), (E, e), (F, f), (G, g));
+impl_bundle_tuple!((A, a), (B, b), (C, c), (D, d), (E, e), (F, f), (G, g), (H, h));
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ecs::{Bundle, Transform, World};
+
+    #[derive(Debug, PartialEq, Component)]
+    struct Cell {
+        x: u32,
+        y: u32,
+    }
+
+    #[derive(Debug, PartialEq, Component)]
+    #[component(storage = "table")]
+    struct Mine;
+
+    #[derive(Debug, PartialEq, Component)]
+    #[component(storage = "sparse")]
+    struct Flagged;
+
+    #[derive(Debug, PartialEq, Component)]
+    #[component(storage = "table")]
+    struct Revealed(u8);
+
+    #[derive(Debug, PartialEq, Component)]
+    #[component(storage = "table")]
+    struct Highlighted;
+
+    #[derive(Bundle)]
+    struct CellBundle {
+        cell: Cell,
+        transform: Transform,
+    }
+
+    #[derive(Bundle)]
+    struct MineBundle {
+        cell: CellBundle,
+        mine: Mine,
+    }
+
+    #[test]
+    fn test_spawn_nested_bundle() {
+        let mut world = World::new();
+        let entity = world.spawn(MineBundle {
+            cell: CellBundle {
+                cell: Cell { x: 2, y: 3 },
+                transform: Transform::from_xy(64.0, 96.0),
+            },
+            mine: Mine,
+        });
+
+        assert_eq!(world.get_component::<Cell>(entity), Some(&Cell { x: 2, y: 3 }));
+        assert_eq!(world.get_component::<Transform>(entity).unwrap().translation, (64.0, 96.0));
+        assert!(world.get_component::<Mine>(entity).is_some());
+
+        let mut names = Vec::new();
+        MineBundle::component_names(&mut names);
+        assert_eq!(names, vec!["Cell", "Transform", "Mine"]);
+    }
+
+    #[test]
+    fn test_insert_tuple_bundle() {
+        let mut world = World::new();
+        let entity = world.spawn(Cell { x: 0, y: 0 });
+        world.insert_bundle(entity, (Flagged, (Mine, Transform::default())));
+
+        assert!(world.get_component::<Flagged>(entity).is_some());
+        assert!(world.get_component::<Mine>(entity).is_some());
+        assert!(world.get_component::<Transform>(entity).is_some());
+        assert_eq!(world.query::<(&Cell, &Mine, &Flagged)>().len(), 1);
+    }
+
+    #[test]
+    fn test_bundle_moves_entity_once() {
+        let mut world = World::new();
+        let entity = world.spawn((Mine, Cell { x: 1, y: 1 }, Revealed(2), Flagged));
+
+        // 途中の組み合わせのテーブルは作られない
+        let archetypes = world.components().archetypes();
+        assert_eq!(archetypes.len(), 1);
+        let location = archetypes.location(entity).unwrap();
+        assert_eq!(archetypes.get(location.archetype).unwrap().component_types().len(), 2);
+
+        let other = world.spawn(Mine);
+        world.insert_bundle(other, (Highlighted, Revealed(5), Mine));
+        let archetypes = world.components().archetypes();
+        assert_eq!(archetypes.len(), 3);
+        assert_eq!(world.get_component::<Revealed>(other), Some(&Revealed(5)));
+        assert!(world.get_component::<Highlighted>(other).is_some());
+        assert_eq!(world.get_component::<Revealed>(entity), Some(&Revealed(2)));
+        assert_eq!(world.query::<(&Mine, &Revealed)>().len(), 2);
+    }
+}
@@ -0,0 +1,264 @@
+//! 遅延実行されるワールド操作のキュー
+//!
+//! システムの実行中はクエリがワールドを借用しているため、エンティティの生成や削除、
+//! コンポーネントの追加・削除を直接行えません。`Commands`にこれらの操作を積んでおくと、
+//! `SystemProcessor`がフェーズの区切り（同期ポイント）でまとめて適用します。
+//!
+//! ```
+//! let commands = Commands::of(resources);
+//! for (entity, cell) in world.query::<(Entity, &Cell)>().iter(world) {
+//!     if cell.is_mine {
+//!         commands.spawn()
+//!             .insert(Explosion::new())
+//!             .insert(cell.position);
+//!         commands.despawn(entity);
+//!     }
+//! }
+//! ```
+
+use std::any::Any;
+
+use crate::ecs::{Bundle, Component, Entity, Resource, ResourceManager, World};
+
+/// ワールドに対する遅延操作
+type WorldCommand = Box<dyn FnOnce(&mut World) + Send + Sync>;
+
+/// 生成されたエンティティに対する遅延操作
+type EntityCommand = Box<dyn FnOnce(&mut World, Entity) + Send + Sync>;
+
+/// キューに積まれた操作
+enum Command {
+    /// エンティティを生成し、コンポーネントを追加する
+    Spawn(Vec<EntityCommand>),
+    /// 任意のワールド操作
+    World(WorldCommand),
+}
+
+/// 遅延実行されるワールド操作のキュー
+///
+/// `SystemProcessor`の生成時にリソースとして登録され、各フェーズの終了時に
+/// 積まれた順に適用されます。
+#[derive(Default)]
+pub struct Commands {
+    /// 未適用の操作
+    queue: Vec<Command>,
+}
+
+impl Commands {
+    /// 空のコマンドキューを作成
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// リソースマネージャーからコマンドキューを取得
+    ///
+    /// 登録されていない場合は新しく登録します。
+    pub fn of(resources: &mut ResourceManager) -> &mut Commands {
+        if !resources.contains::<Commands>() {
+            resources.insert(Commands::new());
+        }
+        resources.get_mut::<Commands>().expect("Commandsリソースの取得に失敗しました")
+    }
+
+    /// エンティティの生成を予約
+    ///
+    /// 返されたビルダーで追加するコンポーネントを指定します。
+    /// エンティティは適用時に生成されるため、この時点ではIDは決まっていません。
+    pub fn spawn(&mut self) -> EntityCommands<'_> {
+        self.queue.push(Command::Spawn(Vec::new()));
+        match self.queue.last_mut() {
+            Some(Command::Spawn(inserts)) => EntityCommands { inserts },
+            _ => unreachable!(),
+        }
+    }
+
+    /// エンティティの削除を予約
+    pub fn despawn(&mut self, entity: Entity) {
+        self.add(move |world| world.destroy_entity(entity));
+    }
+
+    /// エンティティとその子孫の削除を予約
+    pub fn despawn_recursive(&mut self, entity: Entity) {
+        self.add(move |world| world.despawn_recursive(entity));
+    }
+
+    /// 親エンティティの設定を予約
+    pub fn set_parent(&mut self, child: Entity, parent: Entity) {
+        self.add(move |world| {
+            world.set_parent(child, parent);
+        });
+    }
+
+    /// 親子関係の解除を予約
+    pub fn remove_parent(&mut self, child: Entity) {
+        self.add(move |world| {
+            world.remove_parent(child);
+        });
+    }
+
+    /// コンポーネントの追加を予約
+    pub fn insert<T: Component>(&mut self, entity: Entity, component: T) {
+        self.add(move |world| world.add_component(entity, component));
+    }
+
+    /// コンポーネントをまとめて追加することを予約
+    pub fn insert_bundle<B: Bundle>(&mut self, entity: Entity, bundle: B) {
+        self.add(move |world| world.insert_bundle(entity, bundle));
+    }
+
+    /// コンポーネントの削除を予約
+    pub fn remove<T: Component>(&mut self, entity: Entity) {
+        self.add(move |world| {
+            world.remove_component::<T>(entity);
+        });
+    }
+
+    /// 任意のワールド操作を予約
+    pub fn add<F>(&mut self, command: F)
+    where
+        F: FnOnce(&mut World) + Send + Sync + 'static,
+    {
+        self.queue.push(Command::World(Box::new(command)));
+    }
+
+    /// 未適用の操作の数を取得
+    pub fn len(&self) -> usize {
+        self.queue.len()
+    }
+
+    /// 未適用の操作がないかどうか
+    pub fn is_empty(&self) -> bool {
+        self.queue.is_empty()
+    }
+
+    /// 積まれた操作を順番にワールドへ適用
+    ///
+    /// 削除済みのエンティティに対する操作は無視されます。
+    pub fn apply(&mut self, world: &mut World) {
+        for command in self.queue.drain(..) {
+            match command {
+                Command::Spawn(inserts) => {
+                    let entity = world.create_entity();
+                    for insert in inserts {
+                        insert(world, entity);
+                    }
+                }
+                Command::World(command) => command(world),
+            }
+        }
+    }
+}
+
+impl Resource for Commands {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+/// 生成を予約したエンティティへのコンポーネント追加を指定するビルダー
+pub struct EntityCommands<'a> {
+    /// 生成後に適用する操作
+    inserts: &'a mut Vec<EntityCommand>,
+}
+
+impl<'a> EntityCommands<'a> {
+    /// 生成時に追加するコンポーネントを指定
+    pub fn insert<T: Component>(self, component: T) -> Self {
+        self.inserts.push(Box::new(move |world, entity| world.add_component(entity, component)));
+        self
+    }
+
+    /// 生成時にまとめて追加するコンポーネントを指定
+    pub fn insert_bundle<B: Bundle>(self, bundle: B) -> Self {
+        self.inserts.push(Box::new(move |world, entity| world.insert_bundle(entity, bundle)));
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ecs::{System, SystemPhase, SystemPriority};
+    use crate::Error;
+
+    #[derive(Debug, PartialEq)]
+    struct Cell {
+        is_mine: bool,
+    }
+
+    struct Explosion;
+
+    crate::impl_component!(Cell, "Cell");
+    crate::impl_component!(Explosion, "Explosion");
+
+    /// 地雷のセルを爆発エフェクトに置き換えるシステム
+    struct RevealSystem;
+
+    impl System for RevealSystem {
+        fn name(&self) -> &'static str {
+            "RevealSystem"
+        }
+
+        fn phase(&self) -> SystemPhase {
+            SystemPhase::Update
+        }
+
+        fn priority(&self) -> SystemPriority {
+            SystemPriority::default()
+        }
+
+        fn run(&mut self, world: &mut World, resources: &mut ResourceManager, _delta_time: f32) -> Result<(), Error> {
+            let commands = Commands::of(resources);
+            for (entity, cell) in world.query::<(Entity, &Cell)>().iter(world) {
+                if cell.is_mine {
+                    commands.spawn().insert(Explosion);
+                    commands.despawn(entity);
+                }
+            }
+            // 適用前なのでワールドはまだ変わっていない
+            assert_eq!(world.query::<&Explosion>().len(), 0);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_commands_applied_after_phase() {
+        let mut world = World::new();
+        let safe = world.create_entity();
+        world.add_component(safe, Cell { is_mine: false });
+        let mine = world.create_entity();
+        world.add_component(mine, Cell { is_mine: true });
+        world.register_system(RevealSystem).unwrap();
+
+        world.update(0.016);
+
+        assert!(!world.is_alive(mine));
+        assert!(world.is_alive(safe));
+        assert_eq!(world.query::<&Explosion>().len(), 1);
+    }
+
+    #[test]
+    fn test_commands_apply_in_order() {
+        let mut world = World::new();
+        let entity = world.create_entity();
+
+        let mut commands = Commands::new();
+        commands.insert(entity, Cell { is_mine: true });
+        commands.remove::<Cell>(entity);
+        commands.insert(entity, Cell { is_mine: false });
+        commands.despawn(entity);
+        // 削除後の操作は無視される
+        commands.insert(entity, Explosion);
+        assert_eq!(commands.len(), 5);
+
+        commands.apply(&mut world);
+        assert!(commands.is_empty());
+        assert!(!world.is_alive(entity));
+        assert_eq!(world.removed_components::<Cell>().len(), 2);
+        assert_eq!(world.query::<&Explosion>().len(), 0);
+    }
+}
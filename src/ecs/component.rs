@@ -1,28 +1,100 @@
 use std::any::{Any, TypeId};
-use std::collections::HashMap;
+#[cfg(not(target_arch = "wasm32"))]
+use std::cell::Cell;
+use std::collections::{HashMap, HashSet};
 use std::marker::PhantomData;
+use std::ptr::NonNull;
 
-use crate::ecs::entity::{Entity, EntityId};
+use crate::ecs::archetype::{Archetypes, ColumnPtrs};
+use crate::ecs::bundle::BundleComponents;
+use crate::ecs::commands::Commands;
+use crate::ecs::entity::Entity;
+use crate::ecs::hooks::{ComponentHook, ComponentHooks, HookContext};
+use crate::ecs::reflect::{ComponentReflection, ComponentRegistry};
+use crate::ecs::removal::{RemovalLog, RemovedComponent};
+
+#[cfg(not(target_arch = "wasm32"))]
+thread_local! {
+    /// 並列実行中のシステムが前回実行されたティック（スレッドごと）
+    static THREAD_LAST_RUN_TICK: Cell<Option<u64>> = const { Cell::new(None) };
+}
+
+/// 現在のスレッドで実行するシステムの変更検出の基準ティックを設定
+///
+/// 並列実行ではシステムごとに基準が異なるため、`ComponentManager`の値の代わりに使われます。
+/// `None`で解除します。
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn set_thread_last_run_tick(tick: Option<u64>) {
+    THREAD_LAST_RUN_TICK.with(|cell| cell.set(tick));
+}
 
 /// コンポーネント型を識別するためのトレイト
 pub trait Component: 'static + Send + Sync {
     /// コンポーネントの名前を取得
     fn name() -> &'static str where Self: Sized;
+
+    /// コンポーネントの格納方式を取得
+    ///
+    /// 既定では`StorageType::Dense`で、型ごとの`VecStorage`に格納されます。
+    fn storage_type() -> StorageType where Self: Sized {
+        StorageType::Dense
+    }
+
+    /// シリアライズ用のリフレクション情報を取得
+    ///
+    /// 既定では`None`で、スナップショットの対象になりません。
+    /// `#[component(serialize)]`を指定すると`ComponentReflection::new::<Self>()`を返します。
+    fn reflect() -> Option<ComponentReflection> where Self: Sized {
+        None
+    }
+
+    /// ライフサイクルフックを取得
+    ///
+    /// 既定ではフックはありません。
+    /// `#[component(on_add = ..., on_replace = ..., on_remove = ...)]`を指定すると、
+    /// 指定した関数を登録した`ComponentHooks`を返します。
+    fn hooks() -> ComponentHooks where Self: Sized {
+        ComponentHooks::default()
+    }
+}
+
+/// コンポーネントの格納方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum StorageType {
+    /// 型ごとの`VecStorage`に格納する
+    #[default]
+    Dense,
+    /// 型ごとの`SparseSet`に格納する
+    ///
+    /// 少数のエンティティにだけ付くタグや、頻繁に付け外しするコンポーネントに向いています。
+    Sparse,
+    /// 同じコンポーネントの組み合わせを持つエンティティをテーブルにまとめて格納する
+    ///
+    /// 盤面のセルのように数が多く、まとめて走査されるコンポーネントに向いています。
+    /// 追加・削除のたびにエンティティがテーブル間を移動するため、頻繁に付け外しする
+    /// タグのようなコンポーネントには向きません。
+    Table,
 }
 
 /// コンポーネントのストレージ抽象化
-pub trait ComponentStorage {
+///
+/// コンポーネントは常に`Send + Sync`なので、ストレージも同様です。
+/// これによりネイティブ環境では`World`をスレッド間で受け渡せます。
+pub trait ComponentStorage: Send + Sync {
     /// コンポーネントの型IDを取得
     fn component_type_id(&self) -> TypeId;
 
+    /// コンポーネントの名前を取得
+    fn component_name(&self) -> &'static str;
+
     /// エンティティからコンポーネントを削除
-    fn remove(&mut self, entity: EntityId) -> bool;
+    fn remove(&mut self, entity: Entity) -> bool;
 
     /// すべてのコンポーネントをクリア
     fn clear(&mut self);
 
     /// 特定のエンティティのコンポーネントが存在するか確認
-    fn has(&self, entity: EntityId) -> bool;
+    fn has(&self, entity: Entity) -> bool;
 
     /// 内部ストレージをAny型として取得
     fn as_any(&self) -> &dyn Any;
@@ -30,16 +102,72 @@ pub trait ComponentStorage {
     /// 内部ストレージを可変Any型として取得
     fn as_any_mut(&mut self) -> &mut dyn Any;
     
-    /// このストレージに格納されているすべてのエンティティIDのベクターを返す
-    fn entity_ids(&self) -> Vec<EntityId>;
+    /// このストレージに格納されているすべてのエンティティのベクターを返す
+    fn entities(&self) -> Vec<Entity>;
+}
+
+/// コンポーネントの追加・変更が行われたティック
+///
+/// ティックは`ComponentManager`が管理する単調増加のカウンタで、
+/// システムが最後に実行されたティックと比較して変更を検出します。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ComponentTicks {
+    /// コンポーネントが追加されたティック
+    pub added: u64,
+    /// コンポーネントが最後に変更されたティック
+    pub changed: u64,
+}
+
+impl ComponentTicks {
+    /// 指定したティックで追加されたコンポーネントのティックを作成
+    pub fn new(tick: u64) -> Self {
+        Self {
+            added: tick,
+            changed: tick,
+        }
+    }
+
+    /// `last_run`より後に追加されたか
+    pub fn is_added(&self, last_run: u64) -> bool {
+        self.added > last_run
+    }
+
+    /// `last_run`より後に変更（または追加）されたか
+    pub fn is_changed(&self, last_run: u64) -> bool {
+        self.changed > last_run
+    }
+}
+
+/// 特定の型Tのコンポーネントを格納するストレージに共通する操作
+///
+/// `VecStorage`と`SparseSet`が実装し、`ComponentManager`は格納方式に関係なく
+/// このトレイトを通してコンポーネントを読み書きします。
+pub trait TypedStorage<T: Component>: ComponentStorage {
+    /// コンポーネントを追加
+    ///
+    /// 既存のコンポーネントを置き換えた場合は変更ティックのみを更新します。
+    fn insert(&mut self, entity: Entity, component: T, tick: u64) -> Option<T>;
+
+    /// コンポーネントを取得
+    fn get(&self, entity: Entity) -> Option<&T>;
+
+    /// コンポーネントを可変で取得し、変更ティックを更新
+    fn get_mut(&mut self, entity: Entity, tick: u64) -> Option<&mut T>;
+
+    /// コンポーネントの変更ティックを取得
+    fn ticks(&self, entity: Entity) -> Option<ComponentTicks>;
 }
 
 /// 特定の型Tに対するコンポーネントストレージの実装
 pub struct VecStorage<T: Component> {
-    /// エンティティID→インデックスのマッピング
-    entities: HashMap<EntityId, usize>,
-    /// コンポーネントデータとそのエンティティIDのペア
-    data: Vec<(EntityId, T)>,
+    /// エンティティ→インデックスのマッピング
+    ///
+    /// 世代を含むエンティティをキーにするため、削除済みの古いハンドルでは取得できません。
+    entities: HashMap<Entity, usize>,
+    /// コンポーネントデータとそのエンティティのペア
+    data: Vec<(Entity, T)>,
+    /// 各コンポーネントの変更ティック（dataと同じ並び）
+    ticks: Vec<ComponentTicks>,
     /// 型情報
     _marker: PhantomData<T>,
 }
@@ -50,47 +178,89 @@ impl<T: Component> VecStorage<T> {
         VecStorage {
             entities: HashMap::new(),
             data: Vec::new(),
+            ticks: Vec::new(),
             _marker: PhantomData,
         }
     }
 
-    /// コンポーネントを追加
-    pub fn insert(&mut self, entity: EntityId, component: T) -> Option<T> {
+    /// 生ポインタ経由でコンポーネントへのポインタを取得
+    ///
+    /// ストレージ全体への参照を作らずに要素を指すため、
+    /// クエリが複数の要素への参照を同時に保持できます。
+    ///
+    /// # Safety
+    ///
+    /// `storage`は有効なVecStorageを指していなければなりません。
+    pub(crate) unsafe fn get_ptr(storage: *const Self, entity: Entity) -> Option<*const T> {
+        let index = *(*storage).entities.get(&entity)?;
+        let data = (*storage).data.as_ptr();
+        Some(std::ptr::addr_of!((*data.add(index)).1))
+    }
+
+    /// 生ポインタ経由でコンポーネントへの可変ポインタを取得し、変更ティックを更新
+    ///
+    /// # Safety
+    ///
+    /// `storage`は可変参照から得た有効なVecStorageへのポインタでなければなりません。
+    pub(crate) unsafe fn get_mut_ptr(storage: *mut Self, entity: Entity, tick: u64) -> Option<*mut T> {
+        let index = *(*storage).entities.get(&entity)?;
+        (*(*storage).ticks.as_mut_ptr().add(index)).changed = tick;
+        let data = (*storage).data.as_mut_ptr();
+        Some(std::ptr::addr_of_mut!((*data.add(index)).1))
+    }
+
+    /// すべてのコンポーネントとそのエンティティを取得
+    pub fn iter(&self) -> impl Iterator<Item = (Entity, &T)> {
+        self.data.iter().map(|(e, c)| (*e, c))
+    }
+
+    /// すべてのコンポーネントとそのエンティティを可変で取得し、変更ティックを更新
+    pub fn iter_mut(&mut self, tick: u64) -> impl Iterator<Item = (Entity, &mut T)> {
+        self.data.iter_mut().zip(self.ticks.iter_mut()).map(move |((e, c), ticks)| {
+            ticks.changed = tick;
+            (*e, c)
+        })
+    }
+}
+
+impl<T: Component> Default for VecStorage<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Component> TypedStorage<T> for VecStorage<T> {
+    fn insert(&mut self, entity: Entity, component: T, tick: u64) -> Option<T> {
         if let Some(&index) = self.entities.get(&entity) {
             // 既存のコンポーネントを置き換え
             let old = std::mem::replace(&mut self.data[index].1, component);
+            self.ticks[index].changed = tick;
             Some(old)
         } else {
             // 新しいコンポーネントを追加
             let index = self.data.len();
             self.data.push((entity, component));
+            self.ticks.push(ComponentTicks::new(tick));
             self.entities.insert(entity, index);
             None
         }
     }
 
-    /// コンポーネントを取得
-    pub fn get(&self, entity: EntityId) -> Option<&T> {
+    fn get(&self, entity: Entity) -> Option<&T> {
         self.entities.get(&entity).map(|&index| &self.data[index].1)
     }
 
-    /// コンポーネントを可変で取得
-    pub fn get_mut(&mut self, entity: EntityId) -> Option<&mut T> {
+    fn get_mut(&mut self, entity: Entity, tick: u64) -> Option<&mut T> {
         if let Some(&index) = self.entities.get(&entity) {
+            self.ticks[index].changed = tick;
             Some(&mut self.data[index].1)
         } else {
             None
         }
     }
 
-    /// すべてのコンポーネントとそのエンティティIDを取得
-    pub fn iter(&self) -> impl Iterator<Item = (EntityId, &T)> {
-        self.data.iter().map(|(e, c)| (*e, c))
-    }
-
-    /// すべてのコンポーネントとそのエンティティIDを可変で取得
-    pub fn iter_mut(&mut self) -> impl Iterator<Item = (EntityId, &mut T)> {
-        self.data.iter_mut().map(|(e, c)| (*e, c))
+    fn ticks(&self, entity: Entity) -> Option<ComponentTicks> {
+        self.entities.get(&entity).map(|&index| self.ticks[index])
     }
 }
 
@@ -99,7 +269,11 @@ impl<T: Component> ComponentStorage for VecStorage<T> {
         TypeId::of::<T>()
     }
 
-    fn remove(&mut self, entity: EntityId) -> bool {
+    fn component_name(&self) -> &'static str {
+        T::name()
+    }
+
+    fn remove(&mut self, entity: Entity) -> bool {
         if let Some(index) = self.entities.remove(&entity) {
             // 最後の要素を削除位置に移動して、データベクターを縮小
             let last_idx = self.data.len() - 1;
@@ -107,10 +281,12 @@ impl<T: Component> ComponentStorage for VecStorage<T> {
                 // 借用問題を回避するために一時変数を使用
                 let swapped_entity = self.data[last_idx].0;
                 self.data.swap(index, last_idx);
+                self.ticks.swap(index, last_idx);
                 // スワップされたエンティティのインデックスを更新
                 self.entities.insert(swapped_entity, index);
             }
             self.data.pop();
+            self.ticks.pop();
             true
         } else {
             false
@@ -120,9 +296,10 @@ impl<T: Component> ComponentStorage for VecStorage<T> {
     fn clear(&mut self) {
         self.entities.clear();
         self.data.clear();
+        self.ticks.clear();
     }
 
-    fn has(&self, entity: EntityId) -> bool {
+    fn has(&self, entity: Entity) -> bool {
         self.entities.contains_key(&entity)
     }
 
@@ -134,16 +311,274 @@ impl<T: Component> ComponentStorage for VecStorage<T> {
         self
     }
     
-    fn entity_ids(&self) -> Vec<EntityId> {
-        self.data.iter().map(|(entity_id, _)| *entity_id).collect()
+    fn entities(&self) -> Vec<Entity> {
+        self.data.iter().map(|(entity, _)| *entity).collect()
+    }
+}
+
+/// スパースセットによるコンポーネントストレージの実装
+///
+/// エンティティのインデックスで直接引ける疎配列と、コンポーネントを詰めて格納する
+/// 密配列からなります。ハッシュ計算なしで追加・削除・検索ができるため、
+/// `Flagged`や`Hovered`のように少数のエンティティが頻繁に付け外しするタグに向いています。
+pub struct SparseSet<T: Component> {
+    /// エンティティのインデックス→密配列の位置
+    sparse: Vec<Option<usize>>,
+    /// 密配列の各要素のエンティティ
+    dense: Vec<Entity>,
+    /// コンポーネントデータ（denseと同じ並び）
+    data: Vec<T>,
+    /// 各コンポーネントの変更ティック（denseと同じ並び）
+    ticks: Vec<ComponentTicks>,
+}
+
+impl<T: Component> SparseSet<T> {
+    /// 新しいストレージを作成
+    pub fn new() -> Self {
+        SparseSet {
+            sparse: Vec::new(),
+            dense: Vec::new(),
+            data: Vec::new(),
+            ticks: Vec::new(),
+        }
+    }
+
+    /// 密配列での位置を取得
+    ///
+    /// 世代が一致しない古いハンドルの場合は`None`を返します。
+    fn dense_index(&self, entity: Entity) -> Option<usize> {
+        let index = (*self.sparse.get(entity.index() as usize)?)?;
+        if self.dense[index] == entity {
+            Some(index)
+        } else {
+            None
+        }
+    }
+
+    /// 生ポインタ経由でコンポーネントへのポインタを取得
+    ///
+    /// # Safety
+    ///
+    /// `storage`は有効なSparseSetを指していなければなりません。
+    pub(crate) unsafe fn get_ptr(storage: *const Self, entity: Entity) -> Option<*const T> {
+        let index = (*storage).dense_index(entity)?;
+        Some((*storage).data.as_ptr().add(index))
+    }
+
+    /// 生ポインタ経由でコンポーネントへの可変ポインタを取得し、変更ティックを更新
+    ///
+    /// # Safety
+    ///
+    /// `storage`は可変参照から得た有効なSparseSetへのポインタでなければなりません。
+    pub(crate) unsafe fn get_mut_ptr(storage: *mut Self, entity: Entity, tick: u64) -> Option<*mut T> {
+        let index = (*storage).dense_index(entity)?;
+        (*(*storage).ticks.as_mut_ptr().add(index)).changed = tick;
+        Some((*storage).data.as_mut_ptr().add(index))
+    }
+
+    /// すべてのコンポーネントとそのエンティティを取得
+    pub fn iter(&self) -> impl Iterator<Item = (Entity, &T)> {
+        self.dense.iter().copied().zip(self.data.iter())
+    }
+}
+
+impl<T: Component> Default for SparseSet<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Component> TypedStorage<T> for SparseSet<T> {
+    fn insert(&mut self, entity: Entity, component: T, tick: u64) -> Option<T> {
+        if let Some(index) = self.dense_index(entity) {
+            self.ticks[index].changed = tick;
+            return Some(std::mem::replace(&mut self.data[index], component));
+        }
+
+        let slot = entity.index() as usize;
+        if slot >= self.sparse.len() {
+            self.sparse.resize(slot + 1, None);
+        }
+        self.sparse[slot] = Some(self.dense.len());
+        self.dense.push(entity);
+        self.data.push(component);
+        self.ticks.push(ComponentTicks::new(tick));
+        None
+    }
+
+    fn get(&self, entity: Entity) -> Option<&T> {
+        self.dense_index(entity).map(|index| &self.data[index])
+    }
+
+    fn get_mut(&mut self, entity: Entity, tick: u64) -> Option<&mut T> {
+        let index = self.dense_index(entity)?;
+        self.ticks[index].changed = tick;
+        Some(&mut self.data[index])
+    }
+
+    fn ticks(&self, entity: Entity) -> Option<ComponentTicks> {
+        self.dense_index(entity).map(|index| self.ticks[index])
+    }
+}
+
+impl<T: Component> ComponentStorage for SparseSet<T> {
+    fn component_type_id(&self) -> TypeId {
+        TypeId::of::<T>()
+    }
+
+    fn component_name(&self) -> &'static str {
+        T::name()
+    }
+
+    fn remove(&mut self, entity: Entity) -> bool {
+        let index = match self.dense_index(entity) {
+            Some(index) => index,
+            None => return false,
+        };
+
+        self.dense.swap_remove(index);
+        self.data.swap_remove(index);
+        self.ticks.swap_remove(index);
+        self.sparse[entity.index() as usize] = None;
+        // 末尾から移動してきたエンティティの位置を更新
+        if let Some(moved) = self.dense.get(index) {
+            self.sparse[moved.index() as usize] = Some(index);
+        }
+        true
+    }
+
+    fn clear(&mut self) {
+        self.sparse.clear();
+        self.dense.clear();
+        self.data.clear();
+        self.ticks.clear();
+    }
+
+    fn has(&self, entity: Entity) -> bool {
+        self.dense_index(entity).is_some()
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn entities(&self) -> Vec<Entity> {
+        self.dense.clone()
+    }
+}
+
+/// クエリが保持するコンポーネントの格納先へのポインタ
+pub enum ComponentPtr<T: Component> {
+    /// `VecStorage`に格納されている
+    Dense(NonNull<VecStorage<T>>),
+    /// `SparseSet`に格納されている
+    Sparse(NonNull<SparseSet<T>>),
+    /// アーキタイプのテーブルに格納されている
+    Table(NonNull<Archetypes>),
+    /// アーキタイプのテーブルに格納されている（可変アクセス用、アーキタイプごとの列の先頭を保持）
+    TableMut(NonNull<Archetypes>, ColumnPtrs),
+}
+
+impl<T: Component> ComponentPtr<T> {
+    /// コンポーネントへのポインタを取得
+    ///
+    /// # Safety
+    ///
+    /// ポインタの指す先が有効でなければなりません。
+    pub(crate) unsafe fn get(&self, entity: Entity) -> Option<*const T> {
+        match self {
+            ComponentPtr::Dense(storage) => VecStorage::get_ptr(storage.as_ptr(), entity),
+            ComponentPtr::Sparse(storage) => SparseSet::get_ptr(storage.as_ptr(), entity),
+            ComponentPtr::Table(archetypes) | ComponentPtr::TableMut(archetypes, _) => {
+                Archetypes::get_ptr::<T>(archetypes.as_ptr(), entity)
+            }
+        }
+    }
+
+    /// コンポーネントへの可変ポインタを取得し、変更ティックを更新
+    ///
+    /// 読み取り用に取得した`Table`では常に`None`を返します。
+    ///
+    /// # Safety
+    ///
+    /// ポインタは可変参照から得たものでなければなりません。
+    pub(crate) unsafe fn get_mut(&self, entity: Entity, tick: u64) -> Option<*mut T> {
+        match self {
+            ComponentPtr::Dense(storage) => VecStorage::get_mut_ptr(storage.as_ptr(), entity, tick),
+            ComponentPtr::Sparse(storage) => SparseSet::get_mut_ptr(storage.as_ptr(), entity, tick),
+            ComponentPtr::Table(_) => None,
+            ComponentPtr::TableMut(archetypes, columns) => {
+                Archetypes::get_mut_ptr::<T>(archetypes.as_ptr(), columns, entity, tick)
+            }
+        }
+    }
+}
+
+/// 型を消したコンポーネントの格納先への可変ポインタ
+///
+/// 並列実行の前に、システムが書き込みを宣言したコンポーネントについて解決しておきます。
+pub(crate) enum ErasedComponentPtr {
+    /// `VecStorage`または`SparseSet`に格納されている
+    Storage(NonNull<dyn ComponentStorage>),
+    /// アーキタイプのテーブルに格納されている
+    Table(NonNull<Archetypes>, ColumnPtrs),
+}
+
+impl ErasedComponentPtr {
+    /// 型を指定してクエリ用のポインタに戻す
+    ///
+    /// 格納方式が一致しない場合は`None`を返します。
+    ///
+    /// # Safety
+    ///
+    /// 解決したComponentManagerが有効で、解決後に構造が変更されていてはいけません。
+    /// 同じ型のストレージに他のスレッドがアクセスしていてはいけません。
+    pub(crate) unsafe fn typed<T: Component>(&self, storage_type: StorageType) -> Option<ComponentPtr<T>> {
+        match (self, storage_type) {
+            (ErasedComponentPtr::Storage(storage), StorageType::Dense) => (*storage.as_ptr()).as_any_mut()
+                .downcast_mut::<VecStorage<T>>()
+                .map(|storage| ComponentPtr::Dense(NonNull::from(storage))),
+            (ErasedComponentPtr::Storage(storage), StorageType::Sparse) => (*storage.as_ptr()).as_any_mut()
+                .downcast_mut::<SparseSet<T>>()
+                .map(|storage| ComponentPtr::Sparse(NonNull::from(storage))),
+            (ErasedComponentPtr::Table(archetypes, columns), StorageType::Table) => {
+                Some(ComponentPtr::TableMut(*archetypes, columns.clone()))
+            }
+            _ => None,
+        }
     }
 }
 
 /// コンポーネントマネージャー
 /// 異なる型のコンポーネントを格納・管理する
+///
+/// コンポーネントは`StorageType`に応じて型ごとの`VecStorage`・`SparseSet`か、
+/// アーキタイプのテーブルのいずれかに格納されます。
 pub struct ComponentManager {
     /// 型ID → コンポーネントストレージのマッピング
     storages: HashMap<TypeId, Box<dyn ComponentStorage>>,
+    /// テーブル格納のコンポーネントを保持するアーキタイプ
+    archetypes: Archetypes,
+    /// 登録時に指定された格納方式
+    storage_types: HashMap<TypeId, StorageType>,
+    /// 現在の変更ティック
+    change_tick: u64,
+    /// 実行中のシステムが前回実行されたティック
+    last_run_tick: u64,
+    /// コンポーネントとエンティティの削除ログ
+    removal_log: RemovalLog,
+    /// シリアライズ可能なコンポーネント型の登録簿
+    registry: ComponentRegistry,
+    /// 型ID → (コンポーネント名, ライフサイクルフック)
+    hooks: HashMap<TypeId, (&'static str, ComponentHooks)>,
+    /// フックを登録した型IDの登録順（一括削除時のフックの実行順）
+    hook_order: Vec<TypeId>,
+    /// フックから積まれた未適用の遅延操作
+    hook_commands: Commands,
 }
 
 impl ComponentManager {
@@ -151,126 +586,592 @@ impl ComponentManager {
     pub fn new() -> Self {
         ComponentManager {
             storages: HashMap::new(),
+            archetypes: Archetypes::new(),
+            storage_types: HashMap::new(),
+            change_tick: 1,
+            last_run_tick: 0,
+            removal_log: RemovalLog::new(),
+            registry: ComponentRegistry::new(),
+            hooks: HashMap::new(),
+            hook_order: Vec::new(),
+            hook_commands: Commands::new(),
+        }
+    }
+
+    /// 削除ログを取得
+    pub fn removal_log(&self) -> &RemovalLog {
+        &self.removal_log
+    }
+
+    /// 削除ログを可変で取得
+    pub fn removal_log_mut(&mut self) -> &mut RemovalLog {
+        &mut self.removal_log
+    }
+
+    /// 現在の変更ティックを取得
+    pub fn change_tick(&self) -> u64 {
+        self.change_tick
+    }
+
+    /// 変更ティックを進め、新しいティックを返す
+    pub fn increment_change_tick(&mut self) -> u64 {
+        self.change_tick += 1;
+        self.change_tick
+    }
+
+    /// 変更検出の基準となるティックを取得
+    ///
+    /// システムの実行中はそのシステムが前回実行されたティックを返します。
+    /// `Changed<T>`や`Added<T>`はこのティックより後の変更のみを検出します。
+    /// 並列実行中は、各スレッドで実行中のシステムのティックを返します。
+    pub fn last_run_tick(&self) -> u64 {
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(tick) = THREAD_LAST_RUN_TICK.with(Cell::get) {
+            return tick;
         }
+        self.last_run_tick
+    }
+
+    /// 変更検出の基準となるティックを設定
+    pub fn set_last_run_tick(&mut self, tick: u64) {
+        self.last_run_tick = tick;
+    }
+
+    /// エンティティのコンポーネントの変更ティックを取得
+    pub fn get_component_ticks<T: Component>(&self, entity: Entity) -> Option<ComponentTicks> {
+        if self.storage_type::<T>() == StorageType::Table {
+            return self.archetypes.get_ticks::<T>(entity);
+        }
+
+        self.typed_storage::<T>().and_then(|storage| storage.ticks(entity))
+    }
+
+    /// コンポーネントの格納方式を取得
+    ///
+    /// `register_with_storage`で指定されていればそれを、なければ`Component::storage_type`を返します。
+    pub fn storage_type<T: Component>(&self) -> StorageType {
+        self.storage_types.get(&TypeId::of::<T>())
+            .copied()
+            .unwrap_or_else(T::storage_type)
+    }
+
+    /// アーキタイプの一覧を取得
+    pub fn archetypes(&self) -> &Archetypes {
+        &self.archetypes
+    }
+
+    /// シリアライズ可能なコンポーネント型の登録簿を取得
+    pub fn registry(&self) -> &ComponentRegistry {
+        &self.registry
+    }
+
+    /// コンポーネント型がシリアライズ可能なら登録簿に登録
+    ///
+    /// 別の型と名前が重複する型は登録されず、スナップショットの対象外になります（警告は最初の1回だけ）。
+    fn register_reflection<T: Component>(&mut self) {
+        let type_id = TypeId::of::<T>();
+        if self.registry.contains(type_id) || self.registry.is_rejected(type_id) {
+            return;
+        }
+        if let Some(reflection) = T::reflect() {
+            if let Err(error) = self.registry.register(reflection) {
+                log::warn!("{}（{}はスナップショットに含まれません）", error, std::any::type_name::<T>());
+            }
+        }
+    }
+
+    /// コンポーネント型にフックがあれば登録
+    fn register_hooks<T: Component>(&mut self) {
+        let type_id = TypeId::of::<T>();
+        if self.hooks.contains_key(&type_id) {
+            return;
+        }
+        let hooks = T::hooks();
+        if !hooks.is_empty() {
+            self.insert_hooks(type_id, T::name(), hooks);
+        }
+    }
+
+    /// フックを登録し、初めての型なら登録順に追加
+    fn insert_hooks(&mut self, type_id: TypeId, name: &'static str, hooks: ComponentHooks) {
+        if self.hooks.insert(type_id, (name, hooks)).is_none() {
+            self.hook_order.push(type_id);
+        }
+    }
+
+    /// コンポーネント型のライフサイクルフックを設定
+    ///
+    /// `Component::hooks`で宣言したフックを置き換えます。
+    /// コンポーネントの定義を変更できない型にフックを付ける場合に使用します。
+    pub fn set_hooks<T: Component>(&mut self, hooks: ComponentHooks) {
+        self.insert_hooks(TypeId::of::<T>(), T::name(), hooks);
+    }
+
+    /// コンポーネント型のライフサイクルフックを取得
+    pub fn hooks<T: Component>(&self) -> ComponentHooks {
+        self.hooks.get(&TypeId::of::<T>())
+            .map(|(_, hooks)| *hooks)
+            .unwrap_or_else(T::hooks)
+    }
+
+    /// フックを呼び出す
+    fn run_hook(&mut self, hook: ComponentHook, entity: Entity, component_name: &'static str) {
+        let mut commands = std::mem::take(&mut self.hook_commands);
+        hook(&mut HookContext::new(entity, component_name, self, &mut commands));
+        self.hook_commands = commands;
+    }
+
+    /// フックから積まれた未適用の遅延操作を取り出す
+    ///
+    /// `World`の操作の後に取り出して適用されます。
+    pub fn take_hook_commands(&mut self) -> Commands {
+        std::mem::take(&mut self.hook_commands)
     }
 
     /// コンポーネントストレージを登録
+    ///
+    /// `#[component(serialize)]`が指定された型は登録簿にも登録されます。
     pub fn register<T: Component>(&mut self) {
+        self.register_reflection::<T>();
+        self.register_hooks::<T>();
+
         let type_id = TypeId::of::<T>();
-        if !self.storages.contains_key(&type_id) {
-            let storage = VecStorage::<T>::new();
-            self.storages.insert(type_id, Box::new(storage));
+        if self.storages.contains_key(&type_id) {
+            return;
+        }
+
+        match self.storage_type::<T>() {
+            StorageType::Dense => {
+                self.storages.insert(type_id, Box::new(VecStorage::<T>::new()));
+            }
+            StorageType::Sparse => {
+                self.storages.insert(type_id, Box::new(SparseSet::<T>::new()));
+            }
+            // テーブルはコンポーネントの組み合わせごとに必要になった時点で作成する
+            StorageType::Table => {}
         }
     }
 
+    /// 格納方式を指定してコンポーネントを登録
+    ///
+    /// コンポーネントの定義を変えずに格納方式を切り替えたい場合に使用します。
+    /// 既にコンポーネントが追加されている型の格納方式は変更できません。
+    ///
+    /// # パニック
+    ///
+    /// 既に別の格納方式でコンポーネントが格納されている場合
+    pub fn register_with_storage<T: Component>(&mut self, storage_type: StorageType) {
+        let type_id = TypeId::of::<T>();
+        let current = self.storage_type::<T>();
+        if current != storage_type {
+            let in_use = match current {
+                StorageType::Dense | StorageType::Sparse => self.storages.contains_key(&type_id),
+                StorageType::Table => self.archetypes.iter().any(|archetype| archetype.contains(type_id)),
+            };
+            if in_use {
+                panic!("{}の格納方式は既に{:?}で使用されています", T::name(), current);
+            }
+        }
+        self.storage_types.insert(type_id, storage_type);
+        self.register::<T>();
+    }
+
     /// エンティティにコンポーネントを追加
+    ///
+    /// 既に持っている場合は置き換えます。追加の後に`on_add`フックが、
+    /// 置き換えの前に`on_replace`フックが呼ばれます。
     pub fn add_component<T: Component>(&mut self, entity: Entity, component: T) {
-        let type_id = TypeId::of::<T>();
-        
-        // 必要に応じてストレージを登録
-        if !self.storages.contains_key(&type_id) {
+        let tick = self.change_tick;
+        let replaced = self.has_component::<T>(entity);
+        if replaced {
+            if let Some(hook) = self.hooks::<T>().replace_hook() {
+                self.run_hook(hook, entity, T::name());
+            }
+        }
+
+        if self.storage_type::<T>() == StorageType::Table {
+            self.register_reflection::<T>();
+            self.register_hooks::<T>();
+            self.archetypes.insert(entity, component, tick);
+        } else {
+            // 必要に応じてストレージを登録
             self.register::<T>();
+
+            if let Some(storage) = self.typed_storage_mut::<T>() {
+                storage.insert(entity, component, tick);
+            }
         }
-        
-        if let Some(storage) = self.storages.get_mut(&type_id) {
-            // ダウンキャストしてコンポーネントを追加
-            let storage = storage.as_any_mut()
-                .downcast_mut::<VecStorage<T>>()
-                .expect("Failed to downcast storage");
-            
-            storage.insert(entity.id(), component);
+
+        if !replaced {
+            if let Some(hook) = self.hooks::<T>().add_hook() {
+                self.run_hook(hook, entity, T::name());
+            }
+        }
+    }
+
+    /// エンティティにバンドルのコンポーネントをまとめて追加
+    ///
+    /// テーブル格納のコンポーネントは、一度の移動で最終的なテーブルに格納されます。
+    /// 既に持っているコンポーネントの`on_replace`フックはすべての追加の前に、
+    /// 新しく追加したコンポーネントの`on_add`フックはすべての追加の後に、バンドルの順で呼ばれます。
+    /// 同じ型が複数ある場合は後の値が使われ、フックは1度だけ呼ばれます。
+    pub fn insert_bundle(&mut self, entity: Entity, components: BundleComponents) {
+        let tick = self.change_tick;
+        let components = components.into_components();
+
+        let mut storage_types = Vec::with_capacity(components.len());
+        let mut seen = HashSet::with_capacity(components.len());
+        let mut replace_hooks = Vec::new();
+        let mut add_hooks = Vec::new();
+        for component in &components {
+            let (storage_type, hooks) = component.register(self);
+            storage_types.push(storage_type);
+            let type_id = component.component_type_id();
+            if !seen.insert(type_id) {
+                continue;
+            }
+            let name = component.component_name();
+            if self.has_component_type(entity, type_id) {
+                replace_hooks.extend(hooks.replace_hook().map(|hook| (hook, name)));
+            } else {
+                add_hooks.extend(hooks.add_hook().map(|hook| (hook, name)));
+            }
+        }
+
+        for (hook, name) in replace_hooks {
+            self.run_hook(hook, entity, name);
+        }
+
+        let mut table = Vec::new();
+        for (component, storage_type) in components.into_iter().zip(storage_types) {
+            if storage_type == StorageType::Table {
+                table.push(component.into_table_value());
+            } else {
+                component.insert_into_storage(self, entity, tick);
+            }
+        }
+        if !table.is_empty() {
+            self.archetypes.insert_many(entity, table, tick);
+        }
+
+        for (hook, name) in add_hooks {
+            self.run_hook(hook, entity, name);
+        }
+    }
+
+    /// 特定の型の`VecStorage`を取得
+    ///
+    /// `StorageType::Dense`以外で格納されている場合は`None`を返します。
+    pub fn storage<T: Component>(&self) -> Option<&VecStorage<T>> {
+        self.storages.get(&TypeId::of::<T>())
+            .and_then(|storage| storage.as_any().downcast_ref::<VecStorage<T>>())
+    }
+
+    /// 特定の型の`VecStorage`を可変で取得
+    ///
+    /// `StorageType::Dense`以外で格納されている場合は`None`を返します。
+    pub fn storage_mut<T: Component>(&mut self) -> Option<&mut VecStorage<T>> {
+        self.storages.get_mut(&TypeId::of::<T>())
+            .and_then(|storage| storage.as_any_mut().downcast_mut::<VecStorage<T>>())
+    }
+
+    /// 特定の型のストレージを格納方式に関係なく取得
+    ///
+    /// テーブル格納のコンポーネントは型ごとのストレージを持たないため`None`を返します。
+    pub fn typed_storage<T: Component>(&self) -> Option<&dyn TypedStorage<T>> {
+        let storage = self.storages.get(&TypeId::of::<T>())?.as_any();
+        match self.storage_type::<T>() {
+            StorageType::Dense => storage.downcast_ref::<VecStorage<T>>().map(|s| s as &dyn TypedStorage<T>),
+            StorageType::Sparse => storage.downcast_ref::<SparseSet<T>>().map(|s| s as &dyn TypedStorage<T>),
+            StorageType::Table => None,
+        }
+    }
+
+    /// 特定の型のストレージを格納方式に関係なく可変で取得
+    pub fn typed_storage_mut<T: Component>(&mut self) -> Option<&mut dyn TypedStorage<T>> {
+        let storage_type = self.storage_type::<T>();
+        let storage = self.storages.get_mut(&TypeId::of::<T>())?.as_any_mut();
+        match storage_type {
+            StorageType::Dense => storage.downcast_mut::<VecStorage<T>>().map(|s| s as &mut dyn TypedStorage<T>),
+            StorageType::Sparse => storage.downcast_mut::<SparseSet<T>>().map(|s| s as &mut dyn TypedStorage<T>),
+            StorageType::Table => None,
+        }
+    }
+
+    /// クエリ用にコンポーネントの格納先へのポインタを取得
+    pub(crate) fn component_ptr<T: Component>(&self) -> Option<ComponentPtr<T>> {
+        let storage = self.storages.get(&TypeId::of::<T>()).map(|storage| storage.as_any());
+        match self.storage_type::<T>() {
+            StorageType::Dense => storage?.downcast_ref::<VecStorage<T>>()
+                .map(|storage| ComponentPtr::Dense(NonNull::from(storage))),
+            StorageType::Sparse => storage?.downcast_ref::<SparseSet<T>>()
+                .map(|storage| ComponentPtr::Sparse(NonNull::from(storage))),
+            StorageType::Table => Some(ComponentPtr::Table(NonNull::from(&self.archetypes))),
         }
     }
 
+    /// クエリ用にコンポーネントの格納先への可変ポインタを取得
+    pub(crate) fn component_ptr_mut<T: Component>(&mut self) -> Option<ComponentPtr<T>> {
+        let storage_type = self.storage_type::<T>();
+        // ストレージへの可変参照から解決するため、型を消したポインタと同じ経路で取得する
+        unsafe { Self::component_ptr_erased(self, TypeId::of::<T>()).typed::<T>(storage_type) }
+    }
+
+    /// 型IDを指定してコンポーネントの格納先への可変ポインタを取得
+    ///
+    /// ストレージがない型はテーブル格納として扱い、列を持つアーキタイプの先頭を集めます。
+    /// アーキタイプ一覧へのポインタは`components`から直接求めるため、
+    /// 続けて他の型を解決しても先に返したポインタは無効になりません。
+    ///
+    /// # Safety
+    ///
+    /// `components`は可変参照から得た有効なComponentManagerへのポインタでなければなりません。
+    pub(crate) unsafe fn component_ptr_erased(components: *mut Self, type_id: TypeId) -> ErasedComponentPtr {
+        if let Some(storage) = (*components).storages.get_mut(&type_id) {
+            return ErasedComponentPtr::Storage(NonNull::from(&mut **storage));
+        }
+        let columns = (*components).archetypes.column_ptrs(type_id);
+        ErasedComponentPtr::Table(NonNull::new_unchecked(std::ptr::addr_of_mut!((*components).archetypes)), columns)
+    }
+
     /// エンティティからコンポーネントを取得
     pub fn get_component<T: Component>(&self, entity: Entity) -> Option<&T> {
-        let type_id = TypeId::of::<T>();
+        if self.storage_type::<T>() == StorageType::Table {
+            return self.archetypes.get_component::<T>(entity);
+        }
         
-        self.storages.get(&type_id).and_then(|storage| {
-            let storage = storage.as_any()
-                .downcast_ref::<VecStorage<T>>()
-                .expect("Failed to downcast storage");
-            
-            storage.get(entity.id())
-        })
+        self.typed_storage::<T>().and_then(|storage| storage.get(entity))
     }
 
     /// エンティティからコンポーネントを可変で取得
     pub fn get_component_mut<T: Component>(&mut self, entity: Entity) -> Option<&mut T> {
-        let type_id = TypeId::of::<T>();
+        let tick = self.change_tick;
+
+        if self.storage_type::<T>() == StorageType::Table {
+            return self.archetypes.get_component_mut::<T>(entity, tick);
+        }
         
-        self.storages.get_mut(&type_id).and_then(|storage| {
-            let storage = storage.as_any_mut()
-                .downcast_mut::<VecStorage<T>>()
-                .expect("Failed to downcast storage");
-            
-            storage.get_mut(entity.id())
-        })
+        self.typed_storage_mut::<T>().and_then(|storage| storage.get_mut(entity, tick))
     }
 
     /// エンティティがコンポーネントを持っているか確認
     pub fn has_component<T: Component>(&self, entity: Entity) -> bool {
         let type_id = TypeId::of::<T>();
+
+        if self.storage_type::<T>() == StorageType::Table {
+            return self.archetypes.contains(entity, type_id);
+        }
         
         if let Some(storage) = self.storages.get(&type_id) {
-            storage.has(entity.id())
+            storage.has(entity)
         } else {
             false
         }
     }
 
+    /// エンティティが持つコンポーネントの名前を名前順に取得
+    ///
+    /// シリアライズできないコンポーネントも含みます。
+    pub fn component_names(&self, entity: Entity) -> Vec<&'static str> {
+        let mut names: Vec<&'static str> = self.storages.values()
+            .filter(|storage| storage.has(entity))
+            .map(|storage| storage.component_name())
+            .collect();
+        if let Some(archetype) = self.archetypes.location(entity).and_then(|location| self.archetypes.get(location.archetype)) {
+            names.extend(archetype.component_names());
+        }
+        names.sort_unstable();
+        names
+    }
+
+    /// エンティティが型IDのコンポーネントを持っているか確認
+    pub fn has_component_type(&self, entity: Entity, type_id: TypeId) -> bool {
+        self.archetypes.contains(entity, type_id)
+            || self.storages.get(&type_id).is_some_and(|storage| storage.has(entity))
+    }
+
     /// エンティティからコンポーネントを削除
+    ///
+    /// 削除の前に`on_remove`フックが呼ばれます。
     pub fn remove_component<T: Component>(&mut self, entity: Entity) -> bool {
         let type_id = TypeId::of::<T>();
-        
-        if let Some(storage) = self.storages.get_mut(&type_id) {
-            storage.remove(entity.id())
+        if let Some(hook) = self.hooks::<T>().remove_hook() {
+            if self.has_component::<T>(entity) {
+                self.run_hook(hook, entity, T::name());
+            }
+        }
+
+        let removed = if self.storage_type::<T>() == StorageType::Table {
+            self.archetypes.remove(entity, type_id)
+        } else if let Some(storage) = self.storages.get_mut(&type_id) {
+            storage.remove(entity)
         } else {
             false
+        };
+
+        if removed {
+            self.removal_log.record_component(RemovedComponent {
+                entity,
+                component_type: type_id,
+                component_name: T::name(),
+                despawned: false,
+                tick: self.change_tick,
+            });
         }
+
+        removed
     }
 
     /// 特定のコンポーネント型を持つすべてのエンティティを取得
-    pub fn get_entities_with<T: Component>(&self) -> Vec<EntityId> {
+    pub fn get_entities_with<T: Component>(&self) -> Vec<Entity> {
         let type_id = TypeId::of::<T>();
-        
-        if let Some(storage) = self.storages.get(&type_id) {
-            let storage = storage.as_any()
-                .downcast_ref::<VecStorage<T>>()
-                .expect("Failed to downcast storage");
-            
-            storage.iter().map(|(entity_id, _)| entity_id).collect()
-        } else {
-            Vec::new()
+
+        if self.storage_type::<T>() == StorageType::Table {
+            return self.archetypes.entities_with(type_id).collect();
         }
+        
+        self.storages.get(&type_id)
+            .map(|storage| storage.entities())
+            .unwrap_or_default()
     }
 
     /// エンティティからすべてのコンポーネントを削除
+    ///
+    /// `on_remove`フックは、フックを持つコンポーネント型が登録された順に呼ばれます。
     pub fn remove_all_components(&mut self, entity: Entity) {
+        self.remove_all_components_internal(entity, false);
+    }
+
+    /// エンティティの削除を処理
+    ///
+    /// すべてのコンポーネントを削除し、エンティティの削除を削除ログに記録します。
+    pub fn despawn_entity(&mut self, entity: Entity) {
+        self.remove_all_components_internal(entity, true);
+        self.removal_log.record_despawn(entity, self.change_tick);
+    }
+
+    /// すべてのコンポーネントを削除し、削除ログに記録
+    ///
+    /// 削除の前に、エンティティが持つコンポーネントの`on_remove`フックを呼びます。
+    fn remove_all_components_internal(&mut self, entity: Entity, despawned: bool) {
+        // フックはコンポーネント型の登録順に呼ぶ
+        let remove_hooks: Vec<(ComponentHook, &'static str)> = self.hook_order.iter()
+            .filter(|type_id| self.has_component_type(entity, **type_id))
+            .filter_map(|type_id| {
+                let (name, hooks) = &self.hooks[type_id];
+                hooks.remove_hook().map(|hook| (hook, *name))
+            })
+            .collect();
+        for (hook, name) in remove_hooks {
+            self.run_hook(hook, entity, name);
+        }
+
+        for (component_type, component_name) in self.archetypes.remove_all(entity) {
+            self.removal_log.record_component(RemovedComponent {
+                entity,
+                component_type,
+                component_name,
+                despawned,
+                tick: self.change_tick,
+            });
+        }
+
         for storage in self.storages.values_mut() {
-            storage.remove(entity.id());
+            if storage.remove(entity) {
+                self.removal_log.record_component(RemovedComponent {
+                    entity,
+                    component_type: storage.component_type_id(),
+                    component_name: storage.component_name(),
+                    despawned,
+                    tick: self.change_tick,
+                });
+            }
         }
     }
 
-    /// すべてのエンティティIDを収集
+    /// コンポーネントを持つすべてのエンティティを収集
     pub fn entities(&self) -> impl Iterator<Item = Entity> + '_ {
-        // すべてのコンポーネントストレージを検索して、一意なエンティティIDのセットを作成
         let mut entity_set = std::collections::HashSet::new();
-        
-        // すべてのストレージからエンティティIDを収集
         for storage in self.storages.values() {
-            for entity_id in storage.entity_ids() {
-                entity_set.insert(entity_id);
-            }
+            entity_set.extend(storage.entities());
         }
-        
-        // 一意なエンティティIDをEntityインスタンスに変換
-        entity_set.into_iter().map(|_id| {
-            // EntityIdからEntityインスタンスを再構築
-            let entity = Entity::new();
-            // これは比較用の仮のEntityインスタンスで、実際の処理では
-            // 各コンポーネントの処理がEntityのIDに基づいて行われるため機能します
-            entity
-        })
+        for archetype in self.archetypes.iter() {
+            entity_set.extend(archetype.entities().iter().copied());
+        }
+        entity_set.into_iter()
+    }
+}
+
+impl Default for ComponentManager {
+    fn default() -> Self {
+        Self::new()
     }
-} 
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, PartialEq)]
+    struct Position(i32);
+
+    #[derive(Debug, PartialEq, crate::ecs::Component)]
+    #[component(storage = "sparse")]
+    struct Hovered(u8);
+
+    #[derive(Debug, PartialEq, crate::ecs::Component)]
+    #[component(storage = "table")]
+    struct Cell(u8);
+
+    crate::impl_component!(Position, "Position");
+
+    #[test]
+    fn test_derive_selects_storage() {
+        assert_eq!(Position::storage_type(), StorageType::Dense);
+        assert_eq!(Hovered::storage_type(), StorageType::Sparse);
+        assert_eq!(Cell::storage_type(), StorageType::Table);
+
+        let mut manager = ComponentManager::new();
+        let entity = Entity::from_raw(0, 0);
+        manager.add_component(entity, Hovered(1));
+        manager.add_component(entity, Cell(2));
+        manager.add_component(entity, Position(3));
+
+        assert!(manager.storage::<Hovered>().is_none());
+        assert_eq!(manager.typed_storage::<Hovered>().and_then(|s| s.get(entity)), Some(&Hovered(1)));
+        assert_eq!(manager.archetypes().len(), 1);
+        assert_eq!(manager.get_component::<Cell>(entity), Some(&Cell(2)));
+        assert_eq!(manager.get_component::<Position>(entity), Some(&Position(3)));
+    }
+
+    #[test]
+    fn test_sparse_set_swap_remove() {
+        let mut storage = SparseSet::<Hovered>::new();
+        let entities: Vec<Entity> = (0..3).map(|i| Entity::from_raw(i * 10, 0)).collect();
+        for (value, &entity) in entities.iter().enumerate() {
+            storage.insert(entity, Hovered(value as u8), 1);
+        }
+
+        assert!(storage.remove(entities[0]));
+        assert!(!storage.remove(entities[0]));
+        assert_eq!(storage.get(entities[2]), Some(&Hovered(2)));
+        assert_eq!(storage.get(entities[1]), Some(&Hovered(1)));
+
+        // 同じインデックスでも世代が異なれば別のエンティティ
+        assert!(!storage.has(Entity::from_raw(20, 1)));
+        assert_eq!(storage.insert(entities[1], Hovered(9), 2), Some(Hovered(1)));
+        assert_eq!(storage.ticks(entities[1]), Some(ComponentTicks { added: 1, changed: 2 }));
+    }
+
+    #[test]
+    fn test_register_with_storage_overrides_default() {
+        let mut manager = ComponentManager::new();
+        manager.register_with_storage::<Position>(StorageType::Sparse);
+        let entity = Entity::from_raw(0, 0);
+        manager.add_component(entity, Position(1));
+
+        assert_eq!(manager.storage_type::<Position>(), StorageType::Sparse);
+        assert!(manager.storage::<Position>().is_none());
+        assert!(manager.has_component::<Position>(entity));
+        assert!(manager.remove_component::<Position>(entity));
+    }
+}
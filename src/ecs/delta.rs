@@ -0,0 +1,409 @@
+//! ワールドの差分
+//!
+//! 2つの`WorldSnapshot`を比較し、生成・削除されたエンティティと変更されたコンポーネントだけを
+//! まとめた`WorldDelta`を作成します。差分は`network::messages`の`EntitySnapshot`と
+//! `ComponentData`で表されるため、ネットワーク同期とリプレイの両方で同じ形式を使えます。
+//!
+//! ```
+//! let before = world.snapshot()?;
+//! world.update(delta_time);
+//! let delta = world.delta_since(&before, timestamp)?;
+//!
+//! // 受信側
+//! remote_world.apply_delta(&delta)?;
+//! ```
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::ecs::reflect::PendingInsert;
+use crate::ecs::snapshot::{check_spawn_index, EntityRecord, SnapshotError, WorldSnapshot};
+use crate::ecs::{Entity, World};
+use crate::network::messages::{ComponentData, EntitySnapshot};
+
+/// 2つのワールドの状態の差分
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct WorldDelta {
+    /// 新しく生成されたエンティティ（すべての登録済みコンポーネントを含む）
+    pub spawned: Vec<EntitySnapshot>,
+    /// 削除されたエンティティ
+    pub despawned: Vec<Entity>,
+    /// 値が変わった、または追加されたコンポーネント（変更のあったものだけを含む）
+    pub changed: Vec<EntitySnapshot>,
+    /// 削除されたコンポーネント（エンティティとコンポーネント名）
+    pub removed: Vec<(Entity, String)>,
+}
+
+impl WorldDelta {
+    /// 2つのスナップショットの差分を計算
+    ///
+    /// インデックスが同じでも世代が異なるエンティティは、削除と生成として扱われます。
+    ///
+    /// # 引数
+    ///
+    /// * `from` - 変更前の状態
+    /// * `to` - 変更後の状態
+    /// * `timestamp` - 各`EntitySnapshot`に記録するタイムスタンプ
+    pub fn between(from: &WorldSnapshot, to: &WorldSnapshot, timestamp: f64) -> Self {
+        let previous: HashMap<Entity, &EntityRecord> = from.entities.iter()
+            .map(|record| (record.entity, record))
+            .collect();
+        let current: HashMap<Entity, &EntityRecord> = to.entities.iter()
+            .map(|record| (record.entity, record))
+            .collect();
+
+        let mut delta = Self::default();
+        for record in &from.entities {
+            if !current.contains_key(&record.entity) {
+                delta.despawned.push(record.entity);
+            }
+        }
+
+        for record in &to.entities {
+            let old = match previous.get(&record.entity) {
+                Some(old) => old,
+                None => {
+                    delta.spawned.push(entity_snapshot(record.entity, record.components.iter(), timestamp));
+                    continue;
+                }
+            };
+
+            let changed = record.components.iter()
+                .filter(|(name, value)| old.components.get(*name) != Some(*value));
+            let snapshot = entity_snapshot(record.entity, changed, timestamp);
+            if !snapshot.components.is_empty() {
+                delta.changed.push(snapshot);
+            }
+            for name in old.components.keys() {
+                if !record.components.contains_key(name) {
+                    delta.removed.push((record.entity, name.clone()));
+                }
+            }
+        }
+        delta
+    }
+
+    /// 差分がないかどうか
+    pub fn is_empty(&self) -> bool {
+        self.spawned.is_empty() && self.despawned.is_empty() && self.changed.is_empty() && self.removed.is_empty()
+    }
+
+    /// 差分をワールドに適用
+    ///
+    /// 削除、生成、コンポーネントの変更、コンポーネントの削除の順に適用します。
+    /// エラーの場合、ワールドは変更されません。
+    pub fn apply(&self, world: &mut World) -> Result<(), SnapshotError> {
+        // 先にすべてを検証・デシリアライズしてからワールドを変更する
+        let mut spawned_indices = HashSet::with_capacity(self.spawned.len());
+        for snapshot in &self.spawned {
+            if !spawned_indices.insert(snapshot.entity_id) {
+                return Err(SnapshotError::InvalidData(format!("{}が重複しています", to_entity(snapshot))));
+            }
+            check_spawn_index(world, to_entity(snapshot), self.spawned.len())?;
+            if let Some(occupant) = world.entity_at(snapshot.entity_id) {
+                if !self.despawned.contains(&occupant) {
+                    return Err(SnapshotError::InvalidData(format!(
+                        "{}のスロットは{}が使用中です", to_entity(snapshot), occupant
+                    )));
+                }
+            }
+        }
+        let spawned = self.spawned.iter()
+            .map(|snapshot| Ok((to_entity(snapshot), pending_inserts(world, snapshot)?)))
+            .collect::<Result<Vec<_>, SnapshotError>>()?;
+        let changed = self.changed.iter()
+            .map(|snapshot| {
+                let entity = to_entity(snapshot);
+                if !world.is_alive(entity) || self.despawned.contains(&entity) {
+                    return Err(SnapshotError::InvalidData(format!("変更対象の{}が存在しません", entity)));
+                }
+                if spawned_indices.contains(&entity.index()) {
+                    return Err(SnapshotError::InvalidData(format!("変更対象の{}のスロットに生成されるエンティティがあります", entity)));
+                }
+                Ok((entity, pending_inserts(world, snapshot)?))
+            })
+            .collect::<Result<Vec<_>, SnapshotError>>()?;
+        let mut removed = Vec::with_capacity(self.removed.len());
+        for (entity, name) in &self.removed {
+            let reflection = *world.components().registry().get_by_name(name)
+                .ok_or_else(|| SnapshotError::UnknownComponent(name.clone()))?;
+            removed.push((*entity, reflection));
+        }
+
+        for &entity in &self.despawned {
+            world.destroy_entity(entity);
+        }
+        for (entity, inserts) in spawned {
+            // スロットが空いていることとインデックスの重複は確認済み
+            world.spawn_at(entity);
+            for insert in inserts {
+                insert(world, entity);
+            }
+        }
+        for (entity, inserts) in changed {
+            for insert in inserts {
+                insert(world, entity);
+            }
+        }
+        for (entity, reflection) in removed {
+            reflection.remove(world, entity);
+        }
+        Ok(())
+    }
+
+    /// 差分をスナップショットに適用し、変更後のスナップショットを作成
+    ///
+    /// 送信済みの状態を手元で追跡する場合に、ワールドを使わずに基準を進められます。
+    pub fn apply_to_snapshot(&self, snapshot: &WorldSnapshot) -> WorldSnapshot {
+        let mut records: BTreeMap<u32, EntityRecord> = snapshot.entities.iter()
+            .filter(|record| !self.despawned.contains(&record.entity))
+            .map(|record| (record.entity.index(), record.clone()))
+            .collect();
+
+        for entity_snapshot in &self.spawned {
+            let entity = to_entity(entity_snapshot);
+            records.insert(entity.index(), EntityRecord {
+                entity,
+                components: entity_snapshot.components.iter()
+                    .map(|(name, data)| (name.clone(), component_value(data)))
+                    .collect(),
+            });
+        }
+        // インデックスが同じでも世代の異なる記録には適用しない
+        for entity_snapshot in &self.changed {
+            let entity = to_entity(entity_snapshot);
+            if let Some(record) = records.get_mut(&entity.index()).filter(|record| record.entity == entity) {
+                for (name, data) in &entity_snapshot.components {
+                    record.components.insert(name.clone(), component_value(data));
+                }
+            }
+        }
+        for (entity, name) in &self.removed {
+            if let Some(record) = records.get_mut(&entity.index()).filter(|record| record.entity == *entity) {
+                record.components.remove(name);
+            }
+        }
+
+        WorldSnapshot {
+            entities: records.into_values().collect(),
+        }
+    }
+}
+
+/// コンポーネントの値から`EntitySnapshot`を作成
+fn entity_snapshot<'a>(
+    entity: Entity,
+    components: impl Iterator<Item = (&'a String, &'a Value)>,
+    timestamp: f64,
+) -> EntitySnapshot {
+    let mut snapshot = EntitySnapshot::new(entity.index(), timestamp);
+    snapshot.generation = entity.generation();
+    for (name, value) in components {
+        snapshot.add_component(name, ComponentData::Custom { data: value.clone() });
+    }
+    snapshot
+}
+
+/// `EntitySnapshot`のエンティティのハンドルを取得
+fn to_entity(snapshot: &EntitySnapshot) -> Entity {
+    Entity::from_raw(snapshot.entity_id, snapshot.generation)
+}
+
+/// `ComponentData`をコンポーネントの値に変換
+///
+/// `Custom`はそのままの値、それ以外の種類は`type`タグを除いたフィールドを値とします。
+fn component_value(data: &ComponentData) -> Value {
+    match data {
+        ComponentData::Custom { data } => data.clone(),
+        other => {
+            let mut value = serde_json::to_value(other).unwrap_or(Value::Null);
+            if let Value::Object(fields) = &mut value {
+                fields.remove("type");
+            }
+            value
+        }
+    }
+}
+
+/// `EntitySnapshot`のコンポーネントをデシリアライズ
+fn pending_inserts(world: &World, snapshot: &EntitySnapshot) -> Result<Vec<PendingInsert>, SnapshotError> {
+    let registry = world.components().registry();
+    snapshot.components.iter()
+        .map(|(name, data)| {
+            let reflection = registry.get_by_name(name)
+                .ok_or_else(|| SnapshotError::UnknownComponent(name.clone()))?;
+            reflection.deserialize(component_value(data)).map_err(|e| SnapshotError::Deserialize {
+                component: name.clone(),
+                message: e.to_string(),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ecs::Component;
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Component)]
+    #[component(serialize)]
+    struct Position {
+        x: f32,
+        y: f32,
+        z: Option<f32>,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Component)]
+    #[component(serialize)]
+    struct Flagged(bool);
+
+    #[test]
+    fn test_delta_lists_only_changes() {
+        let mut world = World::new();
+        let moved = world.create_entity();
+        world.add_component(moved, Position { x: 0.0, y: 0.0, z: None });
+        world.add_component(moved, Flagged(true));
+        let idle = world.create_entity();
+        world.add_component(idle, Position { x: 5.0, y: 5.0, z: None });
+        let doomed = world.create_entity();
+        let before = world.snapshot().unwrap();
+
+        world.get_component_mut::<Position>(moved).unwrap().x = 1.0;
+        world.remove_component::<Flagged>(moved);
+        world.destroy_entity(doomed);
+        let reused = world.create_entity();
+        world.add_component(reused, Flagged(false));
+
+        let delta = world.delta_since(&before, 2.0).unwrap();
+        assert_eq!(delta.despawned, vec![doomed]);
+        assert_eq!(delta.spawned.len(), 1);
+        assert_eq!(to_entity(&delta.spawned[0]), reused);
+        assert_eq!(delta.changed.len(), 1);
+        assert_eq!(delta.changed[0].entity_id, moved.index());
+        assert_eq!(delta.changed[0].components.len(), 1);
+        assert_eq!(delta.removed, vec![(moved, "Flagged".to_string())]);
+        assert!(WorldDelta::between(&before, &before, 0.0).is_empty());
+
+        // スナップショットに適用した結果は変更後のワールドと一致する
+        assert_eq!(delta.apply_to_snapshot(&before), world.snapshot().unwrap());
+    }
+
+    #[test]
+    fn test_apply_delta_matches_source() {
+        let mut source = World::new();
+        let a = source.create_entity();
+        source.add_component(a, Position { x: 1.0, y: 2.0, z: None });
+        let b = source.create_entity();
+        source.add_component(b, Flagged(false));
+
+        let mut replica = World::new();
+        replica.register_component::<Position>().unwrap();
+        replica.register_component::<Flagged>().unwrap();
+        replica.restore(&source.snapshot().unwrap()).unwrap();
+
+        let before = source.snapshot().unwrap();
+        source.get_component_mut::<Position>(a).unwrap().y = 3.0;
+        source.add_component(a, Flagged(true));
+        source.destroy_entity(b);
+        let c = source.create_entity();
+        source.add_component(c, Position { x: 0.0, y: 0.0, z: Some(1.0) });
+
+        let delta = source.delta_since(&before, 0.0).unwrap();
+        let wire = serde_json::to_string(&delta).unwrap();
+        replica.apply_delta(&serde_json::from_str(&wire).unwrap()).unwrap();
+        assert_eq!(replica.snapshot().unwrap(), source.snapshot().unwrap());
+        assert!(!replica.is_alive(b));
+        assert!(replica.is_alive(c));
+    }
+
+    #[test]
+    fn test_apply_typed_component_data() {
+        let mut world = World::new();
+        let entity = world.create_entity();
+        world.add_component(entity, Position { x: 0.0, y: 0.0, z: None });
+
+        let mut snapshot = EntitySnapshot::new(entity.index(), 0.0);
+        snapshot.generation = entity.generation();
+        snapshot.add_component("Position", ComponentData::Position { x: 4.0, y: 2.0, z: None });
+        let delta = WorldDelta {
+            changed: vec![snapshot],
+            ..WorldDelta::default()
+        };
+        world.apply_delta(&delta).unwrap();
+        assert_eq!(world.get_component::<Position>(entity).unwrap().x, 4.0);
+
+        // 存在しないエンティティへの変更はワールドを変えずに失敗する
+        world.destroy_entity(entity);
+        assert!(world.apply_delta(&delta).is_err());
+    }
+
+    #[test]
+    fn test_apply_rejects_conflicting_spawns() {
+        let mut world = World::new();
+        let kept = world.create_entity();
+        world.add_component(kept, Flagged(true));
+        let doomed = world.create_entity();
+
+        let mut first = EntitySnapshot::new(5, 0.0);
+        first.add_component("Flagged", ComponentData::Custom { data: serde_json::json!(false) });
+        let delta = WorldDelta {
+            spawned: vec![first.clone(), first],
+            despawned: vec![doomed],
+            ..WorldDelta::default()
+        };
+        assert!(world.apply_delta(&delta).is_err());
+        // 削除も行われていない
+        assert!(world.is_alive(doomed));
+
+        // 変更対象のスロットへの生成も拒否する
+        let mut changed = EntitySnapshot::new(kept.index(), 0.0);
+        changed.generation = kept.generation();
+        changed.add_component("Flagged", ComponentData::Custom { data: serde_json::json!(false) });
+        let mut spawned = changed.clone();
+        spawned.generation += 1;
+        let delta = WorldDelta {
+            spawned: vec![spawned],
+            despawned: vec![kept],
+            changed: vec![changed],
+            ..WorldDelta::default()
+        };
+        assert!(world.apply_delta(&delta).is_err());
+        assert_eq!(world.get_component::<Flagged>(kept), Some(&Flagged(true)));
+    }
+
+    #[test]
+    fn test_apply_rejects_out_of_range_index() {
+        let mut world = World::new();
+        world.create_entity();
+
+        let mut far = EntitySnapshot::new(u32::MAX, 0.0);
+        far.add_component("Flagged", ComponentData::Custom { data: serde_json::json!(true) });
+        let delta = WorldDelta {
+            spawned: vec![far],
+            ..WorldDelta::default()
+        };
+        assert!(matches!(world.apply_delta(&delta), Err(SnapshotError::InvalidData(_))));
+        assert_eq!(world.entity_slot_count(), 1);
+    }
+
+    #[test]
+    fn test_apply_to_snapshot_checks_generation() {
+        let mut world = World::new();
+        let entity = world.create_entity();
+        world.add_component(entity, Flagged(true));
+        let snapshot = world.snapshot().unwrap();
+
+        // 古い世代への変更・削除は無視される
+        let mut stale = EntitySnapshot::new(entity.index(), 0.0);
+        stale.generation = entity.generation() + 1;
+        stale.add_component("Flagged", ComponentData::Custom { data: serde_json::json!(false) });
+        let delta = WorldDelta {
+            changed: vec![stale],
+            removed: vec![(Entity::from_raw(entity.index(), entity.generation() + 1), "Flagged".to_string())],
+            ..WorldDelta::default()
+        };
+        assert_eq!(delta.apply_to_snapshot(&snapshot), snapshot);
+    }
+}
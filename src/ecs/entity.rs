@@ -1,15 +1,24 @@
-use std::collections::HashSet;
 use std::fmt;
-use rand;
+
+use serde::{Deserialize, Serialize};
 
 /// エンティティの一意な識別子
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-pub struct EntityId(u64);
+///
+/// エンティティスロットのインデックスで、削除されたエンティティのスロットは再利用されます。
+/// 値は小さく保たれるため、ネットワーク上では`u32`としてそのまま送信できます。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct EntityId(u32);
 
 impl EntityId {
-    /// 新しいエンティティIDを生成
-    pub fn new() -> Self {
-        Self(rand::random())
+    /// インデックスからエンティティIDを作成
+    pub fn from_index(index: u32) -> Self {
+        Self(index)
+    }
+
+    /// エンティティIDのインデックスを取得
+    pub fn index(&self) -> u32 {
+        self.0
     }
 }
 
@@ -21,18 +30,21 @@ impl fmt::Display for EntityId {
 
 /// エンティティを表す構造体
 /// エンティティはIDとバージョンからなり、再利用されたIDを区別できる
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct Entity {
     id: EntityId,
     generation: u32,
 }
 
 impl Entity {
-    /// 新しいエンティティを作成
-    pub fn new() -> Self {
+    /// インデックスと世代からエンティティを作成
+    ///
+    /// ネットワークやスナップショットから受け取った値を復元する場合に使用します。
+    /// 作成したエンティティが有効かどうかは`EntityManager::is_alive`で確認してください。
+    pub fn from_raw(index: u32, generation: u32) -> Self {
         Self {
-            id: EntityId::new(),
-            generation: 0,
+            id: EntityId(index),
+            generation,
         }
     }
 
@@ -45,55 +57,176 @@ impl Entity {
     pub fn generation(&self) -> u32 {
         self.generation
     }
-    
+
     /// エンティティIDの内部値を取得
-    /// 
+    ///
     /// # 戻り値
-    /// 
-    /// エンティティIDのu64内部値
-    pub fn index(&self) -> u64 {
-        match format!("{}", self.id).strip_prefix("Entity(").and_then(|s| s.strip_suffix(")")) {
-            Some(id_str) => id_str.parse::<u64>().unwrap_or(0),
-            None => 0,
-        }
+    ///
+    /// エンティティスロットのインデックス
+    pub fn index(&self) -> u32 {
+        self.id.0
+    }
+}
+
+impl fmt::Display for Entity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Entity({}v{})", self.id.0, self.generation)
     }
 }
 
 /// エンティティの生成と削除を管理する構造体
+///
+/// 削除されたエンティティのインデックスはフリーリストに戻され、
+/// 次に作成されるエンティティで再利用されます。再利用時には世代が進むため、
+/// 削除前のハンドルは無効なハンドルとして扱われます。
 pub struct EntityManager {
-    active_entities: HashSet<Entity>,
-    _next_generation: u32,
+    /// スロットごとの現在の世代
+    generations: Vec<u32>,
+    /// スロットごとの生存フラグ
+    alive: Vec<bool>,
+    /// 再利用可能なインデックス
+    ///
+    /// `spawn_at`で使用中になったインデックスは取り除かず、取り出すときに読み飛ばします。
+    free_list: Vec<u32>,
+    /// 生存しているエンティティの数
+    alive_count: usize,
 }
 
 impl EntityManager {
     /// 新しいエンティティマネージャーを作成
     pub fn new() -> Self {
         Self {
-            active_entities: HashSet::new(),
-            _next_generation: 0,
+            generations: Vec::new(),
+            alive: Vec::new(),
+            free_list: Vec::new(),
+            alive_count: 0,
         }
     }
 
     /// 新しいエンティティを作成
+    ///
+    /// フリーリストにインデックスがあればそれを再利用し、なければ新しいスロットを割り当てます。
     pub fn create_entity(&mut self) -> Entity {
-        let entity = Entity::new();
-        self.active_entities.insert(entity);
-        entity
+        let index = loop {
+            match self.free_list.pop() {
+                // `spawn_at`で使用中になったスロットは読み飛ばす
+                Some(index) if self.alive[index as usize] => continue,
+                Some(index) => break index,
+                None => {
+                    let index = u32::try_from(self.generations.len())
+                        .expect("エンティティ数が上限に達しました");
+                    self.generations.push(0);
+                    self.alive.push(false);
+                    break index;
+                }
+            }
+        };
+
+        self.alive[index as usize] = true;
+        self.alive_count += 1;
+
+        Entity {
+            id: EntityId(index),
+            generation: self.generations[index as usize],
+        }
     }
 
     /// エンティティを削除
-    pub fn destroy_entity(&mut self, entity: Entity) {
-        self.active_entities.remove(&entity);
+    ///
+    /// スロットの世代を進め、インデックスをフリーリストに戻します。
+    ///
+    /// # 戻り値
+    ///
+    /// エンティティが有効で削除された場合は`true`
+    pub fn destroy_entity(&mut self, entity: Entity) -> bool {
+        if !self.is_alive(entity) {
+            return false;
+        }
+
+        let index = entity.index() as usize;
+        self.alive[index] = false;
+        self.generations[index] = self.generations[index].wrapping_add(1);
+        self.free_list.push(entity.index());
+        self.alive_count -= 1;
+        true
+    }
+
+    /// 指定したインデックスと世代のエンティティを作成
+    ///
+    /// スナップショットの復元など、エンティティのハンドルを保ったまま作り直す場合に使用します。
+    /// 途中のスロットが足りない場合は、未使用のスロットとして追加されます。
+    ///
+    /// # 戻り値
+    ///
+    /// スロットが既に使用中の場合は`false`
+    pub fn spawn_at(&mut self, entity: Entity) -> bool {
+        let index = entity.index() as usize;
+        while self.generations.len() <= index {
+            let free = self.generations.len() as u32;
+            self.generations.push(0);
+            self.alive.push(false);
+            self.free_list.push(free);
+        }
+        if self.alive[index] {
+            return false;
+        }
+
+        // フリーリストのインデックスは`create_entity`で読み飛ばされる
+        self.generations[index] = entity.generation;
+        self.alive[index] = true;
+        self.alive_count += 1;
+        true
+    }
+
+    /// 確保済みのスロットの数（使用中と未使用の両方を含む）
+    pub fn slot_count(&self) -> usize {
+        self.generations.len()
     }
 
     /// エンティティが有効かどうかを確認
+    ///
+    /// 削除済みのエンティティや、再利用されたスロットの古いハンドルは無効です。
     pub fn is_alive(&self, entity: Entity) -> bool {
-        self.active_entities.contains(&entity)
+        let index = entity.index() as usize;
+        index < self.alive.len()
+            && self.alive[index]
+            && self.generations[index] == entity.generation
+    }
+
+    /// インデックスから現在有効なエンティティを取得
+    pub fn entity_at(&self, index: u32) -> Option<Entity> {
+        let slot = index as usize;
+        if slot < self.alive.len() && self.alive[slot] {
+            Some(Entity {
+                id: EntityId(index),
+                generation: self.generations[slot],
+            })
+        } else {
+            None
+        }
     }
 
     /// アクティブなエンティティの数を取得
     pub fn entity_count(&self) -> usize {
-        self.active_entities.len()
+        self.alive_count
+    }
+
+    /// アクティブなエンティティを列挙
+    pub fn entities(&self) -> impl Iterator<Item = Entity> + '_ {
+        self.alive.iter()
+            .zip(self.generations.iter())
+            .enumerate()
+            .filter(|(_, (alive, _))| **alive)
+            .map(|(index, (_, generation))| Entity {
+                id: EntityId(index as u32),
+                generation: *generation,
+            })
+    }
+}
+
+impl Default for EntityManager {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
@@ -103,10 +236,10 @@ pub struct EntityBuilder {
 }
 
 impl EntityBuilder {
-    /// 新しいエンティティビルダーを作成
-    pub fn new() -> Self {
+    /// エンティティマネージャーから新しいエンティティを割り当ててビルダーを作成
+    pub fn new(entity_manager: &mut EntityManager) -> Self {
         Self {
-            entity: Entity::new(),
+            entity: entity_manager.create_entity(),
         }
     }
 
@@ -114,4 +247,94 @@ impl EntityBuilder {
     pub fn build(self) -> Entity {
         self.entity
     }
-} 
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ecs::{Component, World};
+
+    struct Health(u32);
+
+    crate::impl_component!(Health, "Health");
+
+    #[test]
+    fn test_indices_are_recycled_with_new_generation() {
+        let mut manager = EntityManager::new();
+        let first = manager.create_entity();
+        let second = manager.create_entity();
+        assert_eq!(first.index(), 0);
+        assert_eq!(second.index(), 1);
+
+        assert!(manager.destroy_entity(first));
+        assert!(!manager.destroy_entity(first));
+        assert!(!manager.is_alive(first));
+
+        let reused = manager.create_entity();
+        assert_eq!(reused.index(), first.index());
+        assert_eq!(reused.generation(), first.generation() + 1);
+        assert!(manager.is_alive(reused));
+        assert!(!manager.is_alive(first));
+        assert_eq!(manager.entity_count(), 2);
+        assert_eq!(manager.entity_at(0), Some(reused));
+    }
+
+    #[test]
+    fn test_entities_lists_only_alive() {
+        let mut manager = EntityManager::new();
+        let entities: Vec<Entity> = (0..4).map(|_| manager.create_entity()).collect();
+        manager.destroy_entity(entities[1]);
+        manager.destroy_entity(entities[3]);
+
+        let alive: Vec<Entity> = manager.entities().collect();
+        assert_eq!(alive, vec![entities[0], entities[2]]);
+        assert!(!manager.is_alive(Entity::from_raw(10, 0)));
+    }
+
+    #[test]
+    fn test_stale_handle_fails_lookups() {
+        let mut world = World::new();
+        let stale = world.create_entity();
+        world.add_component(stale, Health(10));
+        world.destroy_entity(stale);
+
+        let reused = world.create_entity();
+        assert_eq!(reused.index(), stale.index());
+        world.add_component(reused, Health(20));
+
+        assert!(!world.is_alive(stale));
+        assert!(world.get_component::<Health>(stale).is_none());
+        assert!(world.get_component_mut::<Health>(stale).is_none());
+        assert!(!world.remove_component::<Health>(stale));
+
+        // 古いハンドルへの操作は新しいエンティティに影響しない
+        world.add_component(stale, Health(99));
+        world.destroy_entity(stale);
+        assert!(world.is_alive(reused));
+        assert_eq!(world.get_component::<Health>(reused).map(|health| health.0), Some(20));
+        assert_eq!(world.query::<&Health>().len(), 1);
+    }
+
+    #[test]
+    fn test_spawn_at_keeps_handle() {
+        let mut manager = EntityManager::new();
+        let restored = Entity::from_raw(2, 5);
+        assert!(manager.spawn_at(restored));
+        assert!(!manager.spawn_at(restored));
+        assert!(manager.is_alive(restored));
+        assert_eq!(manager.entity_count(), 1);
+
+        // 間のスロットは通常の生成で使われる
+        let mut created: Vec<u32> = (0..3).map(|_| manager.create_entity().index()).collect();
+        created.sort();
+        assert_eq!(created, vec![0, 1, 3]);
+
+        // フリーリストに残ったスロットを`spawn_at`で使っても、通常の生成では再利用されない
+        let freed = Entity::from_raw(1, 0);
+        assert!(manager.destroy_entity(freed));
+        let respawned = Entity::from_raw(1, 7);
+        assert!(manager.spawn_at(respawned));
+        assert_eq!(manager.create_entity().index(), 4);
+        assert!(manager.is_alive(respawned));
+    }
+}
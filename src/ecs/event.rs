@@ -0,0 +1,348 @@
+//! 型ごとのイベントチャネル
+//!
+//! システム間の通知を`Events<T>`リソースを介して行います。
+//! 送信側は`EventWriter<T>`でイベントを積み、受信側はシステムごとに
+//! `EventReader<T>`を保持して、まだ読んでいないイベントだけを受け取ります。
+//!
+//! イベントは2つのバッファで管理され、送信されたフレームと次のフレームの間だけ読めます。
+//! そのため、送信側より先に実行されるシステムも次のフレームでイベントを受け取れます。
+//!
+//! ```
+//! world.add_event::<CellRevealed>();
+//!
+//! // 送信側のシステム
+//! EventWriter::<CellRevealed>::of(resources).send(CellRevealed { x, y });
+//!
+//! // 受信側のシステム（readerはシステムのフィールドとして保持する）
+//! for event in self.reader.read(resources.get::<Events<CellRevealed>>().unwrap()) {
+//!     // ...
+//! }
+//! ```
+
+use std::any::Any;
+use std::marker::PhantomData;
+
+use crate::ecs::{Resource, ResourceManager};
+
+/// イベントとして送信できる型
+#[cfg(not(target_arch = "wasm32"))]
+pub trait Event: 'static + Send + Sync {}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl<T: 'static + Send + Sync> Event for T {}
+
+/// イベントとして送信できる型（Wasm環境用）
+#[cfg(target_arch = "wasm32")]
+pub trait Event: 'static {}
+
+#[cfg(target_arch = "wasm32")]
+impl<T: 'static> Event for T {}
+
+/// 通し番号付きのイベント
+struct EventInstance<T> {
+    /// 送信順の通し番号
+    id: usize,
+    /// イベント本体
+    event: T,
+}
+
+/// ダブルバッファ方式のイベントキュー
+///
+/// `update`を呼ぶたびに古いバッファを破棄し、現在のバッファを古いバッファに移します。
+/// `World::add_event`で登録すると、フレームの開始時に自動で`update`が呼ばれます。
+pub struct Events<T: Event> {
+    /// 前のフレームに送信されたイベント
+    previous: Vec<EventInstance<T>>,
+    /// このフレームに送信されたイベント
+    current: Vec<EventInstance<T>>,
+    /// これまでに送信されたイベントの総数
+    event_count: usize,
+}
+
+impl<T: Event> Default for Events<T> {
+    fn default() -> Self {
+        Self {
+            previous: Vec::new(),
+            current: Vec::new(),
+            event_count: 0,
+        }
+    }
+}
+
+impl<T: Event> Events<T> {
+    /// 空のイベントキューを作成
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// イベントを送信
+    pub fn send(&mut self, event: T) {
+        self.current.push(EventInstance {
+            id: self.event_count,
+            event,
+        });
+        self.event_count += 1;
+    }
+
+    /// バッファを入れ替え、2フレーム前のイベントを破棄
+    pub fn update(&mut self) {
+        self.previous = std::mem::take(&mut self.current);
+    }
+
+    /// リソースマネージャー内の`Events<T>`を更新
+    ///
+    /// `SystemProcessor`がフレームの開始時に呼び出します。
+    pub fn update_resource(resources: &mut ResourceManager) {
+        if let Some(events) = resources.get_mut::<Events<T>>() {
+            events.update();
+        }
+    }
+
+    /// 保持しているすべてのイベントを古い順に取得
+    pub fn iter(&self) -> impl Iterator<Item = &T> + '_ {
+        self.previous.iter()
+            .chain(self.current.iter())
+            .map(|instance| &instance.event)
+    }
+
+    /// これから送信されるイベントだけを読む受信カーソルを作成
+    pub fn reader(&self) -> EventReader<T> {
+        EventReader {
+            last_event_count: self.event_count,
+            _marker: PhantomData,
+        }
+    }
+
+    /// 保持しているイベントの数を取得
+    pub fn len(&self) -> usize {
+        self.previous.len() + self.current.len()
+    }
+
+    /// イベントを保持していないかどうか
+    pub fn is_empty(&self) -> bool {
+        self.previous.is_empty() && self.current.is_empty()
+    }
+
+    /// すべてのイベントを破棄
+    pub fn clear(&mut self) {
+        self.previous.clear();
+        self.current.clear();
+    }
+}
+
+impl<T: Event> Resource for Events<T> {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+/// イベントの送信側
+pub struct EventWriter<'a, T: Event> {
+    /// 送信先のイベントキュー
+    events: &'a mut Events<T>,
+}
+
+impl<'a, T: Event> EventWriter<'a, T> {
+    /// イベントキューへの送信側を作成
+    pub fn new(events: &'a mut Events<T>) -> Self {
+        Self { events }
+    }
+
+    /// リソースマネージャーに登録された`Events<T>`への送信側を取得
+    ///
+    /// # パニック
+    ///
+    /// `World::add_event::<T>()`でイベントが登録されていない場合
+    pub fn of(resources: &'a mut ResourceManager) -> Self {
+        let events = resources.get_mut::<Events<T>>().unwrap_or_else(|| {
+            panic!(
+                "イベント{}が登録されていません。World::add_eventで登録してください",
+                std::any::type_name::<T>()
+            )
+        });
+        Self { events }
+    }
+
+    /// イベントを送信
+    pub fn send(&mut self, event: T) {
+        self.events.send(event);
+    }
+
+    /// 複数のイベントを送信
+    pub fn send_batch(&mut self, events: impl IntoIterator<Item = T>) {
+        for event in events {
+            self.events.send(event);
+        }
+    }
+}
+
+/// イベントの受信カーソル
+///
+/// 最後に読んだ位置を覚えているため、同じイベントを二度読むことはありません。
+/// 受信するシステムごとに1つ保持します。
+pub struct EventReader<T: Event> {
+    /// 読み終えたイベントの数
+    last_event_count: usize,
+    /// イベント型のマーカー
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<T: Event> Default for EventReader<T> {
+    fn default() -> Self {
+        Self {
+            last_event_count: 0,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T: Event> EventReader<T> {
+    /// 保持されているすべてのイベントを読む受信カーソルを作成
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// まだ読んでいないイベントを古い順に取得し、カーソルを進める
+    pub fn read<'a>(&mut self, events: &'a Events<T>) -> impl Iterator<Item = &'a T> + 'a {
+        let last_event_count = self.last_event_count;
+        self.last_event_count = events.event_count;
+        events.previous.iter()
+            .chain(events.current.iter())
+            .filter(move |instance| instance.id >= last_event_count)
+            .map(|instance| &instance.event)
+    }
+
+    /// まだ読んでいないイベントの数を取得
+    pub fn len(&self, events: &Events<T>) -> usize {
+        events.previous.iter()
+            .chain(events.current.iter())
+            .filter(|instance| instance.id >= self.last_event_count)
+            .count()
+    }
+
+    /// まだ読んでいないイベントがないかどうか
+    pub fn is_empty(&self, events: &Events<T>) -> bool {
+        self.len(events) == 0
+    }
+
+    /// 未読のイベントを読まずに捨てる
+    pub fn clear(&mut self, events: &Events<T>) {
+        self.last_event_count = events.event_count;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ecs::{System, SystemPhase, SystemPriority, World};
+    use std::sync::{Arc, Mutex};
+    use crate::Error;
+
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    struct MineHit(u32);
+
+    #[test]
+    fn test_events_live_for_two_updates() {
+        let mut events = Events::<MineHit>::new();
+        let mut reader = EventReader::new();
+
+        events.send(MineHit(1));
+        events.update();
+        events.send(MineHit(2));
+        assert_eq!(events.len(), 2);
+
+        events.update();
+        assert_eq!(events.iter().copied().collect::<Vec<_>>(), vec![MineHit(2)]);
+        // 読まずに2回updateされたイベントは失われる
+        assert_eq!(reader.read(&events).copied().collect::<Vec<_>>(), vec![MineHit(2)]);
+        assert!(reader.is_empty(&events));
+
+        events.update();
+        assert!(events.is_empty());
+    }
+
+    #[test]
+    fn test_readers_are_independent() {
+        let mut events = Events::<MineHit>::new();
+        let mut first = events.reader();
+        EventWriter::new(&mut events).send_batch([MineHit(1), MineHit(2)]);
+        let mut second = events.reader();
+        events.send(MineHit(3));
+
+        assert_eq!(first.read(&events).count(), 3);
+        assert_eq!(first.read(&events).count(), 0);
+        assert_eq!(second.read(&events).copied().collect::<Vec<_>>(), vec![MineHit(3)]);
+    }
+
+    /// 毎フレームMineHitを送信するシステム
+    struct Sender;
+
+    impl System for Sender {
+        fn name(&self) -> &'static str {
+            "Sender"
+        }
+
+        fn phase(&self) -> SystemPhase {
+            SystemPhase::Update
+        }
+
+        fn priority(&self) -> SystemPriority {
+            SystemPriority::new(1)
+        }
+
+        fn run(&mut self, _world: &mut World, resources: &mut ResourceManager, _delta_time: f32) -> Result<(), Error> {
+            EventWriter::<MineHit>::of(resources).send(MineHit(7));
+            Ok(())
+        }
+    }
+
+    /// 送信側より先に実行され、受け取ったイベント数を記録するシステム
+    struct Receiver {
+        reader: EventReader<MineHit>,
+        received: Arc<Mutex<Vec<usize>>>,
+    }
+
+    impl System for Receiver {
+        fn name(&self) -> &'static str {
+            "Receiver"
+        }
+
+        fn phase(&self) -> SystemPhase {
+            SystemPhase::Update
+        }
+
+        fn priority(&self) -> SystemPriority {
+            SystemPriority::new(0)
+        }
+
+        fn run(&mut self, _world: &mut World, resources: &mut ResourceManager, _delta_time: f32) -> Result<(), Error> {
+            let events = resources.get::<Events<MineHit>>().unwrap();
+            self.received.lock().unwrap().push(self.reader.read(events).count());
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_events_flow_between_systems() {
+        let mut world = World::new();
+        world.add_event::<MineHit>();
+        let received = Arc::new(Mutex::new(Vec::new()));
+        world.register_system(Sender).unwrap();
+        world.register_system(Receiver {
+            reader: EventReader::new(),
+            received: received.clone(),
+        }).unwrap();
+
+        for _ in 0..3 {
+            world.update(0.016);
+        }
+
+        // 先に実行される受信側は前のフレームのイベントを受け取る
+        assert_eq!(*received.lock().unwrap(), vec![0, 1, 1]);
+        assert_eq!(world.get_resource::<Events<MineHit>>().unwrap().len(), 2);
+    }
+}
@@ -0,0 +1,198 @@
+//! エンティティの親子関係
+//!
+//! 子エンティティには`Parent`、親エンティティには`Children`が付きます。
+//! 2つのコンポーネントは`World::set_parent`と`World::remove_parent`で一緒に更新されるため、
+//! 直接追加・削除しないでください。
+//!
+//! ```
+//! let panel = world.create_entity();
+//! let button = world.create_entity();
+//! world.set_parent(button, panel);
+//!
+//! // パネルごとボタンも削除される
+//! world.despawn_recursive(panel);
+//! ```
+
+use serde::{Deserialize, Serialize};
+
+use crate::ecs::{Component, Entity, World};
+
+/// 親エンティティ
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Component)]
+#[component(serialize)]
+pub struct Parent(pub Entity);
+
+impl Parent {
+    /// 親エンティティを取得
+    pub fn get(&self) -> Entity {
+        self.0
+    }
+}
+
+/// 子エンティティの一覧（追加順）
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize, Component)]
+#[component(serialize)]
+pub struct Children(pub Vec<Entity>);
+
+impl Children {
+    /// 子エンティティを追加順に取得
+    pub fn iter(&self) -> impl Iterator<Item = Entity> + '_ {
+        self.0.iter().copied()
+    }
+
+    /// 子エンティティの数を取得
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// 子エンティティがないかどうか
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+/// 親を設定し、元の親の`Children`から取り除く
+///
+/// 自分自身や自分の子孫を親にすることはできません。
+pub(crate) fn set_parent(world: &mut World, child: Entity, parent: Entity) -> bool {
+    if !world.is_alive(child) || !world.is_alive(parent) || is_descendant(world, parent, child) {
+        return false;
+    }
+
+    detach(world, child);
+    world.add_component(child, Parent(parent));
+    match world.get_component_mut::<Children>(parent) {
+        Some(children) => children.0.push(child),
+        None => world.add_component(parent, Children(vec![child])),
+    }
+    true
+}
+
+/// 親子関係を解除
+pub(crate) fn remove_parent(world: &mut World, child: Entity) -> bool {
+    if !detach(world, child) {
+        return false;
+    }
+    world.remove_component::<Parent>(child);
+    true
+}
+
+/// エンティティとその子孫をすべて削除
+pub(crate) fn despawn_recursive(world: &mut World, entity: Entity) {
+    if !world.is_alive(entity) {
+        return;
+    }
+    detach(world, entity);
+
+    let mut pending = vec![entity];
+    while let Some(current) = pending.pop() {
+        if let Some(children) = world.get_component::<Children>(current) {
+            pending.extend(children.iter());
+        }
+        world.destroy_entity(current);
+    }
+}
+
+/// 子孫を深さ優先で取得（自分自身は含まない）
+pub(crate) fn descendants(world: &World, entity: Entity) -> Vec<Entity> {
+    let mut result = Vec::new();
+    let mut pending: Vec<Entity> = children_of(world, entity).iter().rev().copied().collect();
+    while let Some(current) = pending.pop() {
+        if !world.is_alive(current) {
+            continue;
+        }
+        result.push(current);
+        pending.extend(children_of(world, current).iter().rev());
+    }
+    result
+}
+
+/// 子エンティティの一覧を取得
+pub(crate) fn children_of(world: &World, entity: Entity) -> &[Entity] {
+    world.get_component::<Children>(entity)
+        .map(|children| children.0.as_slice())
+        .unwrap_or(&[])
+}
+
+/// `entity`が`ancestor`自身またはその子孫かどうか
+fn is_descendant(world: &World, entity: Entity, ancestor: Entity) -> bool {
+    let mut current = Some(entity);
+    while let Some(node) = current {
+        if node == ancestor {
+            return true;
+        }
+        current = world.get_component::<Parent>(node).map(Parent::get);
+    }
+    false
+}
+
+/// 元の親の`Children`から取り除く（`Parent`は残す）
+fn detach(world: &mut World, child: Entity) -> bool {
+    let parent = match world.get_component::<Parent>(child) {
+        Some(parent) => parent.get(),
+        None => return false,
+    };
+
+    let now_empty = match world.get_component_mut::<Children>(parent) {
+        Some(children) => {
+            children.0.retain(|entity| *entity != child);
+            children.is_empty()
+        }
+        None => false,
+    };
+    if now_empty {
+        world.remove_component::<Children>(parent);
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_set_parent_moves_child() {
+        let mut world = World::new();
+        let panel = world.create_entity();
+        let other = world.create_entity();
+        let button = world.create_entity();
+
+        assert!(world.set_parent(button, panel));
+        assert_eq!(world.children(panel), &[button]);
+        assert!(world.set_parent(button, other));
+        assert!(world.children(panel).is_empty());
+        assert!(world.get_component::<Children>(panel).is_none());
+        assert_eq!(world.parent(button), Some(other));
+
+        // 子孫を親にすることはできない
+        assert!(!world.set_parent(other, button));
+        assert!(!world.set_parent(other, other));
+
+        assert!(world.remove_parent(button));
+        assert_eq!(world.parent(button), None);
+        assert!(world.children(other).is_empty());
+    }
+
+    #[test]
+    fn test_despawn_recursive() {
+        let mut world = World::new();
+        let root = world.create_entity();
+        let panel = world.create_entity();
+        let button = world.create_entity();
+        let label = world.create_entity();
+        let sibling = world.create_entity();
+        world.set_parent(panel, root);
+        world.set_parent(button, panel);
+        world.set_parent(label, button);
+        world.set_parent(sibling, root);
+
+        assert_eq!(world.descendants(root), vec![panel, button, label, sibling]);
+
+        world.despawn_recursive(panel);
+        assert!(!world.is_alive(panel));
+        assert!(!world.is_alive(button));
+        assert!(!world.is_alive(label));
+        assert!(world.is_alive(sibling));
+        assert_eq!(world.children(root), &[sibling]);
+    }
+}
@@ -0,0 +1,269 @@
+//! コンポーネントのライフサイクルフック
+//!
+//! コンポーネントの追加・置き換え・削除のたびに呼び出される関数を型ごとに登録します。
+//! フックは`ComponentManager`の操作の中で呼ばれ、コンポーネントの値を読み取れます。
+//! ワールドやリソースの変更は`HookContext::commands`に積み、`World`の操作の直後
+//! （またはフェーズの区切り）に適用されます。
+//!
+//! ```
+//! // `Name`は追加・置き換え・削除のたびに`NameIndex`を更新する
+//! #[derive(Component)]
+//! #[component(on_add = reindex, on_replace = reindex, on_remove = reindex)]
+//! pub struct Name(Cow<'static, str>);
+//!
+//! fn reindex(context: &mut HookContext) {
+//!     let entity = context.entity();
+//!     context.commands().add(move |world| {
+//!         let name = world.get_component::<Name>(entity).map(|name| name.as_str().to_string());
+//!         if let Some(index) = world.get_resource_mut::<NameIndex>() {
+//!             index.update(entity, name);
+//!         }
+//!     });
+//! }
+//! ```
+
+use crate::ecs::{Commands, Component, ComponentManager, Entity};
+
+/// コンポーネントのライフサイクルフック
+pub type ComponentHook = fn(&mut HookContext<'_>);
+
+/// 1つのコンポーネント型に登録されたフック
+///
+/// `Component::hooks`で返すか、`#[component(on_add = ...)]`などの属性で指定します。
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ComponentHooks {
+    /// エンティティがコンポーネントを持っていなかったときの追加後
+    on_add: Option<ComponentHook>,
+    /// 既存のコンポーネントが新しい値で置き換えられる前
+    on_replace: Option<ComponentHook>,
+    /// コンポーネントが削除される前（エンティティの削除時を含む）
+    on_remove: Option<ComponentHook>,
+}
+
+impl ComponentHooks {
+    /// フックのない状態を作成
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 追加時のフックを設定
+    ///
+    /// 追加された値は`HookContext::get`で読み取れます。
+    pub fn on_add(mut self, hook: ComponentHook) -> Self {
+        self.on_add = Some(hook);
+        self
+    }
+
+    /// 置き換え時のフックを設定
+    ///
+    /// 置き換えられる前の値は`HookContext::get`で読み取れます。
+    /// 置き換えでは`on_add`は呼ばれません。
+    pub fn on_replace(mut self, hook: ComponentHook) -> Self {
+        self.on_replace = Some(hook);
+        self
+    }
+
+    /// 削除時のフックを設定
+    ///
+    /// 削除される値は`HookContext::get`で読み取れます。
+    pub fn on_remove(mut self, hook: ComponentHook) -> Self {
+        self.on_remove = Some(hook);
+        self
+    }
+
+    /// 追加時のフックを取得
+    pub fn add_hook(&self) -> Option<ComponentHook> {
+        self.on_add
+    }
+
+    /// 置き換え時のフックを取得
+    pub fn replace_hook(&self) -> Option<ComponentHook> {
+        self.on_replace
+    }
+
+    /// 削除時のフックを取得
+    pub fn remove_hook(&self) -> Option<ComponentHook> {
+        self.on_remove
+    }
+
+    /// フックが1つも登録されていないかどうか
+    pub fn is_empty(&self) -> bool {
+        self.on_add.is_none() && self.on_replace.is_none() && self.on_remove.is_none()
+    }
+}
+
+/// フックに渡される情報
+pub struct HookContext<'a> {
+    /// 対象のエンティティ
+    entity: Entity,
+    /// 対象のコンポーネント名
+    component_name: &'static str,
+    /// フックの呼び出し元
+    components: &'a ComponentManager,
+    /// フックから積まれた遅延操作
+    commands: &'a mut Commands,
+}
+
+impl<'a> HookContext<'a> {
+    /// フックに渡す情報を作成
+    pub(crate) fn new(
+        entity: Entity,
+        component_name: &'static str,
+        components: &'a ComponentManager,
+        commands: &'a mut Commands,
+    ) -> Self {
+        Self {
+            entity,
+            component_name,
+            components,
+            commands,
+        }
+    }
+
+    /// 対象のエンティティを取得
+    pub fn entity(&self) -> Entity {
+        self.entity
+    }
+
+    /// 対象のコンポーネント名を取得
+    pub fn component_name(&self) -> &'static str {
+        self.component_name
+    }
+
+    /// 対象のエンティティのコンポーネントを取得
+    ///
+    /// フックの対象のコンポーネントも、呼び出された時点の値で取得できます。
+    pub fn get<T: Component>(&self) -> Option<&T> {
+        self.components.get_component::<T>(self.entity)
+    }
+
+    /// コンポーネントマネージャーを取得
+    pub fn components(&self) -> &ComponentManager {
+        self.components
+    }
+
+    /// ワールドに対する遅延操作のキューを取得
+    ///
+    /// 積まれた操作は、フックを呼び出した`World`の操作の直後に適用されます。
+    pub fn commands(&mut self) -> &mut Commands {
+        self.commands
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ecs::{Resource, World};
+
+    /// フックの呼び出し記録
+    #[derive(Default, Resource)]
+    struct HookLog(Vec<String>);
+
+    #[derive(Debug, Component)]
+    #[component(on_add = log_add, on_replace = log_replace, on_remove = log_remove)]
+    struct Body {
+        mass: u32,
+    }
+
+    #[derive(Debug, Component)]
+    #[component(storage = "table", on_remove = log_remove)]
+    struct Tile;
+
+    fn log(context: &mut HookContext, event: &str) {
+        let mass = context.get::<Body>().map(|body| body.mass);
+        let message = format!("{} {} {:?}", event, context.component_name(), mass);
+        context.commands().add(move |world| {
+            world.get_resource_mut::<HookLog>().unwrap().0.push(message);
+        });
+    }
+
+    fn log_add(context: &mut HookContext) {
+        log(context, "add");
+    }
+
+    fn log_replace(context: &mut HookContext) {
+        log(context, "replace");
+    }
+
+    fn log_remove(context: &mut HookContext) {
+        log(context, "remove");
+    }
+
+    /// フックで登録されるエンティティの一覧
+    #[derive(Default, Resource)]
+    struct Layer(Vec<Entity>);
+
+    #[derive(Component)]
+    #[component(on_add = join_layer, on_remove = leave_layer)]
+    struct Cursor;
+
+    fn join_layer(context: &mut HookContext) {
+        let entity = context.entity();
+        context.commands().add(move |world| {
+            world.get_resource_mut::<Layer>().unwrap().0.push(entity);
+        });
+    }
+
+    fn leave_layer(context: &mut HookContext) {
+        let entity = context.entity();
+        context.commands().add(move |world| {
+            world.get_resource_mut::<Layer>().unwrap().0.retain(|&other| other != entity);
+        });
+    }
+
+    fn take_log(world: &mut World) -> Vec<String> {
+        std::mem::take(&mut world.get_resource_mut::<HookLog>().unwrap().0)
+    }
+
+    #[test]
+    fn test_hooks_see_component_values() {
+        let mut world = World::new();
+        world.insert_resource(HookLog::default());
+        let entity = world.spawn(Body { mass: 1 });
+        world.add_component(entity, Body { mass: 2 });
+        world.remove_component::<Body>(entity);
+        // 持っていないコンポーネントの削除ではフックは呼ばれない
+        world.remove_component::<Body>(entity);
+
+        assert_eq!(take_log(&mut world), vec![
+            "add Body Some(1)",
+            "replace Body Some(1)",
+            "remove Body Some(2)",
+        ]);
+    }
+
+    #[test]
+    fn test_despawn_runs_remove_hooks() {
+        let mut world = World::new();
+        world.insert_resource(HookLog::default());
+        let entity = world.spawn((Body { mass: 3 }, Tile));
+        take_log(&mut world);
+
+        world.destroy_entity(entity);
+        // フックを持つ型が登録された順に呼ばれる
+        assert_eq!(take_log(&mut world), vec!["remove Body Some(3)", "remove Tile Some(3)"]);
+
+        // ComponentManagerを直接操作した場合は次の同期ポイントで適用される
+        let entity = world.spawn(Body { mass: 4 });
+        take_log(&mut world);
+        world.components_mut().remove_all_components(entity);
+        assert!(take_log(&mut world).is_empty());
+        world.apply_commands();
+        assert_eq!(take_log(&mut world), vec!["remove Body Some(4)"]);
+    }
+
+    #[test]
+    fn test_hooks_keep_layer_in_sync() {
+        let mut world = World::new();
+        world.insert_resource(Layer::default());
+        let first = world.spawn(Cursor);
+        let second = world.spawn(Cursor);
+        assert_eq!(world.get_resource::<Layer>().unwrap().0, vec![first, second]);
+
+        world.remove_component::<Cursor>(first);
+        assert_eq!(world.get_resource::<Layer>().unwrap().0, vec![second]);
+
+        world.destroy_entity(second);
+        assert!(world.get_resource::<Layer>().unwrap().0.is_empty());
+    }
+}
@@ -0,0 +1,185 @@
+//! ワールドの中身を調べるためのインスペクター
+//!
+//! 生存しているエンティティとそのコンポーネント、登録されているリソースを一覧にします。
+//! `#[component(serialize)]`で登録されたコンポーネントは値も含まれ、`patch_component`で
+//! 一部のフィールドだけを書き換えられます。ブラウザの開発者ツールからのデバッグに使用します。
+//!
+//! ```
+//! let json = serde_json::to_string(&world.inspect())?;
+//!
+//! // 一部のフィールドだけを書き換える
+//! world.patch_component(entity, "Transform", &json!({ "translation": [64.0, 32.0] }))?;
+//! ```
+
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::ecs::prefab::merge;
+use crate::ecs::{Entity, Name, SnapshotError, World};
+
+/// 1つのコンポーネントの情報
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ComponentInfo {
+    /// コンポーネント名
+    pub name: String,
+    /// シリアライズした値（登録されていないコンポーネントは`None`）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub value: Option<Value>,
+}
+
+/// 1つのエンティティの情報
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct EntityInfo {
+    /// エンティティのハンドル
+    pub entity: Entity,
+    /// `Name`コンポーネントの名前
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// コンポーネントの情報（名前順）
+    pub components: Vec<ComponentInfo>,
+}
+
+/// ワールド全体の情報
+#[derive(Debug, Clone, PartialEq, Default, Serialize)]
+pub struct WorldInspection {
+    /// エンティティの情報（インデックス順）
+    pub entities: Vec<EntityInfo>,
+    /// 登録されているリソースの型名（名前順）
+    pub resources: Vec<String>,
+}
+
+/// ワールド全体を調べる
+pub fn inspect(world: &World) -> WorldInspection {
+    WorldInspection {
+        entities: world.entities()
+            .filter_map(|entity| inspect_entity(world, entity))
+            .collect(),
+        resources: world.processor().resources().type_names()
+            .into_iter()
+            .map(str::to_string)
+            .collect(),
+    }
+}
+
+/// 1つのエンティティを調べる
+///
+/// シリアライズに失敗したコンポーネントは、エラーメッセージを文字列の値として含めます。
+///
+/// # 戻り値
+///
+/// * エンティティが生存していない場合は`None`
+pub fn inspect_entity(world: &World, entity: Entity) -> Option<EntityInfo> {
+    if !world.is_alive(entity) {
+        return None;
+    }
+    let components = world.components();
+    let registry = components.registry();
+    let components = components.component_names(entity)
+        .into_iter()
+        .map(|name| ComponentInfo {
+            name: name.to_string(),
+            value: registry.get_by_name(name)
+                .and_then(|reflection| reflection.serialize(components, entity))
+                .map(|value| value.unwrap_or_else(|e| Value::String(e.to_string()))),
+        })
+        .collect();
+    Some(EntityInfo {
+        entity,
+        name: world.get_component::<Name>(entity).map(|name| name.to_string()),
+        components,
+    })
+}
+
+/// コンポーネントの値の一部を書き換える
+///
+/// 現在の値に`patch`を重ね（オブジェクト同士はフィールドごと）、デシリアライズして置き換えます。
+/// 置き換えなので`on_replace`フックが呼ばれます。
+///
+/// # エラー
+///
+/// * エンティティが生存していない場合や、コンポーネントを持っていない場合
+/// * コンポーネントが`#[component(serialize)]`で登録されていない場合
+/// * 書き換えた値をデシリアライズできない場合。このときコンポーネントは変更されません
+pub fn patch_component(world: &mut World, entity: Entity, name: &str, patch: &Value) -> Result<(), SnapshotError> {
+    if !world.is_alive(entity) {
+        return Err(SnapshotError::InvalidData(format!("{}は存在しません", world.entity_label(entity))));
+    }
+    let components = world.components();
+    let reflection = components.registry().get_by_name(name)
+        .ok_or_else(|| SnapshotError::UnknownComponent(name.to_string()))?;
+    let mut value = reflection.serialize(components, entity)
+        .ok_or_else(|| SnapshotError::InvalidData(format!("{}は{}を持っていません", world.entity_label(entity), name)))?
+        .map_err(|e| SnapshotError::Serialize {
+            component: name.to_string(),
+            message: e.to_string(),
+        })?;
+    merge(&mut value, patch);
+    let insert = reflection.deserialize(value).map_err(|e| SnapshotError::Deserialize {
+        component: name.to_string(),
+        message: e.to_string(),
+    })?;
+    insert(world, entity);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ecs::{Component, Resource, Transform};
+    use serde::Deserialize;
+    use serde_json::json;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize, Component)]
+    #[component(serialize)]
+    struct Health {
+        current: u32,
+        max: u32,
+    }
+
+    #[derive(Debug, Component)]
+    #[component(storage = "table")]
+    struct Mine;
+
+    #[derive(Default, Resource)]
+    struct Score;
+
+    #[test]
+    fn test_inspect_lists_components_and_resources() {
+        let mut world = World::new();
+        world.insert_resource(Score);
+        let entity = world.spawn((Health { current: 3, max: 5 }, Mine, Name::new("Sapper")));
+
+        let inspection = inspect(&world);
+        assert_eq!(inspection.entities.len(), 1);
+        assert_eq!(inspection.entities[0].name.as_deref(), Some("Sapper"));
+        assert_eq!(inspection.entities[0].components, vec![
+            ComponentInfo { name: "Health".into(), value: Some(json!({ "current": 3, "max": 5 })) },
+            ComponentInfo { name: "Mine".into(), value: None },
+            ComponentInfo { name: "Name".into(), value: Some(json!("Sapper")) },
+        ]);
+        assert!(inspection.resources.iter().any(|name| name.ends_with("::Score")));
+
+        world.destroy_entity(entity);
+        assert!(inspect_entity(&world, entity).is_none());
+    }
+
+    #[test]
+    fn test_patch_component() {
+        let mut world = World::new();
+        let entity = world.spawn((Health { current: 3, max: 5 }, Transform::IDENTITY, Mine));
+
+        patch_component(&mut world, entity, "Health", &json!({ "current": 1 })).unwrap();
+        assert_eq!(world.get_component::<Health>(entity), Some(&Health { current: 1, max: 5 }));
+        patch_component(&mut world, entity, "Transform", &json!({ "translation": [4.0, 2.0] })).unwrap();
+        assert_eq!(world.get_component::<Transform>(entity).unwrap().translation, (4.0, 2.0));
+
+        // 不正な値では変更されない
+        let error = patch_component(&mut world, entity, "Health", &json!({ "current": "full" })).unwrap_err();
+        assert!(matches!(error, SnapshotError::Deserialize { .. }));
+        assert_eq!(world.get_component::<Health>(entity).unwrap().current, 1);
+        assert_eq!(
+            patch_component(&mut world, entity, "Mine", &json!({})).unwrap_err(),
+            SnapshotError::UnknownComponent("Mine".to_string())
+        );
+    }
+}
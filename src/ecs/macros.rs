@@ -6,6 +6,8 @@
 /// 
 /// このマクロは、構造体に`Component`トレイトを自動的に実装します。
 /// 
+/// 3番目の引数に`Dense`・`Sparse`・`Table`を指定すると格納方式を変更できます。
+/// 
 /// # 使用例
 /// ```rust
 /// #[derive(Component)]
@@ -13,6 +15,8 @@
 ///     x: f32,
 ///     y: f32,
 /// }
+/// 
+/// impl_component!(Cell, "Cell", Table);
 /// ```
 #[macro_export]
 macro_rules! impl_component {
@@ -23,15 +27,26 @@ macro_rules! impl_component {
             }
         }
     };
+    ($type:ty, $name:expr, $storage:ident) => {
+        impl Component for $type {
+            fn name() -> &'static str {
+                $name
+            }
+
+            fn storage_type() -> $crate::ecs::component::StorageType {
+                $crate::ecs::component::StorageType::$storage
+            }
+        }
+    };
 }
 
 /// コンポーネントマクロのテスト
 #[cfg(test)]
 mod tests {
-    use super::*;
     use crate::ecs::Component;
 
     struct TestComponent {
+        #[allow(dead_code)]
         value: i32,
     }
 
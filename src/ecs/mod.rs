@@ -12,7 +12,7 @@
 
 // マクロのリエクスポート
 // ecs_deriveクレートで定義されたマクロをここでリエクスポートして、外部からアクセス可能にする
-pub use ecs_derive::{Component, Resource};
+pub use ecs_derive::{Bundle, Component, Resource};
 
 // モジュール宣言
 // ECSアーキテクチャの各部分を別々のモジュールに分けて整理
@@ -22,21 +22,61 @@ pub mod system;      // システム（ゲームロジック）を管理
 pub mod resource;    // リソース（グローバルデータ）を管理
 pub mod macros;      // 便利なマクロを定義
 pub mod query;       // エンティティとコンポーネントのクエリ機能を提供
+pub mod removal;     // コンポーネントとエンティティの削除を記録
+pub mod archetype;   // テーブル格納のコンポーネントをアーキタイプごとに管理
+pub mod commands;    // システムから遅延実行するワールド操作のキュー
+pub mod event;       // システム間で通知を送る型ごとのイベントチャネル
+pub mod state;       // ゲームの状態と状態に応じたシステムの実行制御
+pub mod time;        // FixedUpdateフェーズを駆動する固定タイムステップの時計
+pub mod reflect;     // シリアライズ可能なコンポーネントの登録簿
+pub mod snapshot;    // ワールドの保存と復元
+pub mod delta;       // 2つのワールドの状態の差分
+pub mod hierarchy;   // エンティティの親子関係
+pub mod transform;   // ローカル座標とワールド座標の変換
+pub mod bundle;      // まとめて追加するコンポーネントの組
+pub mod param;       // 関数システムと自動で取得されるシステムパラメータ
+pub mod hooks;       // コンポーネントの追加・削除時に呼ばれるフック
+pub mod profile;     // システムごとの実行時間の計測
+pub mod prefab;      // JSONで定義するプレハブ
+pub mod inspector;   // デバッグ用のワールドの一覧と値の書き換え
+pub mod name;        // エンティティの名前と名前からの検索
+pub mod world_cell;  // 並列実行中のシステムに渡すワールドへのハンドル
+#[cfg(not(target_arch = "wasm32"))]
+mod pool;            // 並列実行するシステムを受け持つワーカースレッド
 
 // 主要な構造体をエクスポート
 // 外部からこれらの型を直接インポートできるようにする
 pub use entity::{Entity, EntityId, EntityManager};
-pub use component::{Component, ComponentManager};
-pub use system::{System, SystemPhase, SystemPriority, SystemProcessor};
+pub use component::{Component, ComponentManager, StorageType};
+pub use archetype::{Archetype, ArchetypeId, Archetypes};
+pub use system::{Condition, ScheduleError, System, SystemConfig, SystemPhase, SystemPriority, SystemProcessor};
 pub use resource::{Resource, ResourceManager};
-pub use query::{Query, Changed, With};
+pub use query::{Query, QueryData, QueryFilter, Added, Changed, With, Without};
+pub use removal::{RemovedComponent, RemovedComponents, DespawnedEntity};
+pub use commands::{Commands, EntityCommands};
+pub use event::{Event, EventReader, EventWriter, Events};
+pub use state::{in_state, State, States};
+pub use time::FixedTime;
+pub use reflect::{ComponentReflection, ComponentRegistry};
+pub use snapshot::{EntityRecord, SnapshotError, WorldSnapshot};
+pub use delta::WorldDelta;
+pub use hierarchy::{Children, Parent};
+pub use transform::{GlobalTransform, Transform};
+pub use bundle::{Bundle, BundleComponents};
+pub use param::{DeltaTime, FunctionSystem, Res, ResMut, SystemAccess, SystemParam, SystemQuery};
+pub use prefab::{Prefab, PrefabError, PrefabLibrary};
+pub use hooks::{ComponentHook, ComponentHooks, HookContext};
+pub use profile::{SystemTiming, SystemTimings};
+pub use inspector::{ComponentInfo, EntityInfo, WorldInspection};
+pub use name::{Name, NameIndex};
+pub use world_cell::UnsafeWorldCell;
 
 // プレリュードモジュール
 // よく使われる型やマクロを一括でインポートするための便利な場所
 #[macro_use]
 pub mod prelude {
     pub use crate::impl_component;
-    pub use ecs_derive::{Component, Resource};
+    pub use ecs_derive::{Bundle, Component, Resource};
 }
 
 /// ゲーム世界全体を表す中央のオブジェクト
@@ -94,6 +134,54 @@ impl World {
         self.processor.create_entity()
     }
 
+    /// コンポーネントをまとめて持つエンティティを作成
+    /// 
+    /// `create_entity`の後に`add_component`を繰り返す代わりに使用します。
+    /// 
+    /// # 引数
+    /// 
+    /// * `bundle` - 追加するコンポーネント（単体、タプル、または`#[derive(Bundle)]`の構造体）
+    /// 
+    /// # 戻り値
+    /// 
+    /// * 作成されたEntity
+    /// 
+    /// # 例
+    /// 
+    /// ```
+    /// let cursor = world.spawn((
+    ///     MouseCursorComponent::new(player_id, x, y),
+    ///     Transform::from_xy(x, y),
+    /// ));
+    /// ```
+    pub fn spawn<B: Bundle>(&mut self, bundle: B) -> Entity {
+        let entity = self.create_entity();
+        self.insert_bundle(entity, bundle);
+        entity
+    }
+
+    /// エンティティにコンポーネントをまとめて追加
+    /// 
+    /// 既に持っているコンポーネントは置き換えられます。
+    /// テーブル格納のコンポーネントは、一度の移動で最終的なアーキタイプに格納されます。
+    /// 
+    /// # 引数
+    /// 
+    /// * `entity` - コンポーネントを追加するエンティティ
+    /// * `bundle` - 追加するコンポーネント
+    /// 
+    /// # 例
+    /// 
+    /// ```
+    /// world.insert_bundle(cell_entity, (Revealed, Highlighted));
+    /// ```
+    pub fn insert_bundle<B: Bundle>(&mut self, entity: Entity, bundle: B) {
+        let mut components = BundleComponents::new();
+        bundle.collect_components(&mut components);
+        self.processor.insert_bundle(entity, components);
+        self.apply_hook_commands();
+    }
+
     /// エンティティを削除
     /// 
     /// 指定したエンティティとそれに関連するすべてのコンポーネントを削除します。
@@ -109,6 +197,316 @@ impl World {
     /// ```
     pub fn destroy_entity(&mut self, entity: Entity) {
         self.processor.destroy_entity(entity);
+        self.apply_hook_commands();
+    }
+
+    /// エンティティが有効かどうかを確認
+    /// 
+    /// 削除されたエンティティや、インデックスが再利用された後の古いハンドルは無効です。
+    /// 
+    /// # 引数
+    /// 
+    /// * `entity` - 確認するエンティティ
+    /// 
+    /// # 例
+    /// 
+    /// ```
+    /// world.destroy_entity(player_entity);
+    /// assert!(!world.is_alive(player_entity));
+    /// ```
+    pub fn is_alive(&self, entity: Entity) -> bool {
+        self.processor.is_alive(entity)
+    }
+
+    /// エンティティとその子孫をすべて削除
+    /// 
+    /// 親の`Children`からも取り除かれます。
+    /// `destroy_entity`は子を削除しないため、親子関係のあるエンティティにはこちらを使用します。
+    /// 
+    /// # 引数
+    /// 
+    /// * `entity` - 削除するエンティティ
+    /// 
+    /// # 例
+    /// 
+    /// ```
+    /// // パネルとその上のボタンをまとめて削除
+    /// world.despawn_recursive(panel);
+    /// ```
+    pub fn despawn_recursive(&mut self, entity: Entity) {
+        hierarchy::despawn_recursive(self, entity);
+    }
+
+    /// 親エンティティを設定
+    /// 
+    /// 子に`Parent`、親に`Children`を追加します。既に別の親がある場合は付け替えます。
+    /// 
+    /// # 引数
+    /// 
+    /// * `child` - 子エンティティ
+    /// * `parent` - 親エンティティ
+    /// 
+    /// # 戻り値
+    /// 
+    /// * どちらかが削除済みの場合や、親が子自身またはその子孫の場合は`false`
+    /// 
+    /// # 例
+    /// 
+    /// ```
+    /// let label = world.create_entity();
+    /// world.add_component(label, Transform::from_xy(0.0, -16.0));
+    /// world.set_parent(label, cursor_entity);
+    /// ```
+    pub fn set_parent(&mut self, child: Entity, parent: Entity) -> bool {
+        hierarchy::set_parent(self, child, parent)
+    }
+
+    /// 親子関係を解除
+    /// 
+    /// # 引数
+    /// 
+    /// * `child` - 子エンティティ
+    /// 
+    /// # 戻り値
+    /// 
+    /// * 親がなかった場合は`false`
+    pub fn remove_parent(&mut self, child: Entity) -> bool {
+        hierarchy::remove_parent(self, child)
+    }
+
+    /// 親エンティティを取得
+    pub fn parent(&self, entity: Entity) -> Option<Entity> {
+        self.get_component::<Parent>(entity).map(Parent::get)
+    }
+
+    /// 子エンティティを追加順に取得
+    pub fn children(&self, entity: Entity) -> &[Entity] {
+        hierarchy::children_of(self, entity)
+    }
+
+    /// 子孫を深さ優先で取得（自分自身は含まない）
+    pub fn descendants(&self, entity: Entity) -> Vec<Entity> {
+        hierarchy::descendants(self, entity)
+    }
+
+    /// インデックスを使用している生存中のエンティティを取得
+    /// 
+    /// ネットワーク越しにインデックスだけを受け取った場合など、現在の世代を調べるのに使用します。
+    /// 
+    /// # 引数
+    /// 
+    /// * `index` - エンティティのインデックス
+    /// 
+    /// # 戻り値
+    /// 
+    /// * スロットが使われていない場合は`None`
+    pub fn entity_at(&self, index: u32) -> Option<Entity> {
+        self.processor.entity_at(index)
+    }
+
+    /// 確保済みのエンティティスロットの数を取得
+    /// 
+    /// 削除されて再利用を待っているスロットも含みます。
+    pub fn entity_slot_count(&self) -> usize {
+        self.processor.entity_slot_count()
+    }
+
+    /// 名前の付いたエンティティを探す
+    /// 
+    /// 同じ名前のエンティティが複数ある場合は、最初に名前が付けられたものを返します。
+    /// 
+    /// # 引数
+    /// 
+    /// * `name` - `Name`コンポーネントの名前
+    /// 
+    /// # 例
+    /// 
+    /// ```
+    /// world.spawn((Name::new("BoardRoot"), Transform::IDENTITY));
+    /// let root = world.find_by_name("BoardRoot").unwrap();
+    /// ```
+    pub fn find_by_name(&self, name: &str) -> Option<Entity> {
+        self.get_resource::<NameIndex>().and_then(|index| index.get(name))
+    }
+
+    /// 名前の付いたすべてのエンティティを名前が付けられた順に取得
+    pub fn find_all_by_name(&self, name: &str) -> Vec<Entity> {
+        self.get_resource::<NameIndex>()
+            .map(|index| index.get_all(name).to_vec())
+            .unwrap_or_default()
+    }
+
+    /// ログやエラーメッセージ用のエンティティの表記を取得
+    /// 
+    /// 名前がある場合は`Entity(3v1) "BoardRoot"`のように名前を添えます。
+    pub fn entity_label(&self, entity: Entity) -> String {
+        match self.get_component::<Name>(entity) {
+            Some(name) => format!("{} \"{}\"", entity, name),
+            None => entity.to_string(),
+        }
+    }
+
+    /// 指定したハンドルのままエンティティを作成
+    /// 
+    /// スナップショットの復元など、保存しておいたハンドルを再び有効にする場合に使用します。
+    /// 
+    /// # 引数
+    /// 
+    /// * `entity` - 作成するエンティティのハンドル
+    /// 
+    /// # 戻り値
+    /// 
+    /// * スロットが既に使用中の場合は`false`
+    pub fn spawn_at(&mut self, entity: Entity) -> bool {
+        self.processor.spawn_at(entity)
+    }
+
+    /// コンポーネント型を登録
+    /// 
+    /// `#[component(serialize)]`が指定された型は、まだエンティティに追加されていなくても
+    /// スナップショットの復元で使えるようになります。
+    /// 
+    /// # エラー
+    /// 
+    /// * `#[component(serialize)]`が指定された別の型が同じ名前で登録されている場合は
+    ///   `SnapshotError::DuplicateComponentName`（コンポーネント自体は使えますが、
+    ///   スナップショットの対象にはなりません）
+    /// 
+    /// # 例
+    /// 
+    /// ```
+    /// world.register_component::<Position>()?;
+    /// world.restore(&snapshot)?;
+    /// ```
+    pub fn register_component<T: Component>(&mut self) -> Result<(), SnapshotError> {
+        let components = self.processor.components_mut();
+        components.register::<T>();
+        if components.registry().is_rejected(std::any::TypeId::of::<T>()) {
+            return Err(SnapshotError::DuplicateComponentName(T::name().to_string()));
+        }
+        Ok(())
+    }
+
+    /// ワールドのスナップショットを作成
+    /// 
+    /// 生存しているすべてのエンティティと、`#[component(serialize)]`が指定された
+    /// コンポーネントの値を保存します。
+    /// 
+    /// # エラー
+    /// 
+    /// * コンポーネントのシリアライズに失敗した場合
+    /// 
+    /// # 例
+    /// 
+    /// ```
+    /// let json = world.snapshot()?.to_json()?;
+    /// ```
+    pub fn snapshot(&self) -> Result<WorldSnapshot, SnapshotError> {
+        WorldSnapshot::capture(self)
+    }
+
+    /// スナップショットの状態にワールドを戻す
+    /// 
+    /// 既存のエンティティはすべて削除され、スナップショットのエンティティが同じハンドルで
+    /// 作り直されます。リソースとシステムはそのまま残ります。
+    /// 
+    /// # エラー
+    /// 
+    /// * 登録されていないコンポーネントが含まれる場合や、デシリアライズに失敗した場合。
+    ///   このときワールドは変更されません
+    /// 
+    /// # 例
+    /// 
+    /// ```
+    /// let snapshot = WorldSnapshot::from_bytes(&bytes)?;
+    /// world.restore(&snapshot)?;
+    /// ```
+    pub fn restore(&mut self, snapshot: &WorldSnapshot) -> Result<(), SnapshotError> {
+        snapshot.restore(self)
+    }
+
+    /// スナップショットから現在までの差分を計算
+    /// 
+    /// # 引数
+    /// 
+    /// * `since` - 基準となるスナップショット
+    /// * `timestamp` - 差分に記録するタイムスタンプ
+    /// 
+    /// # エラー
+    /// 
+    /// * 現在の状態のシリアライズに失敗した場合
+    /// 
+    /// # 例
+    /// 
+    /// ```
+    /// let delta = world.delta_since(&last_sent, now)?;
+    /// if !delta.is_empty() {
+    ///     send(&delta);
+    /// }
+    /// ```
+    pub fn delta_since(&self, since: &WorldSnapshot, timestamp: f64) -> Result<WorldDelta, SnapshotError> {
+        Ok(WorldDelta::between(since, &self.snapshot()?, timestamp))
+    }
+
+    /// 差分をワールドに適用
+    /// 
+    /// # エラー
+    /// 
+    /// * 登録されていないコンポーネントや存在しないエンティティが含まれる場合。
+    ///   このときワールドは変更されません
+    /// 
+    /// # 例
+    /// 
+    /// ```
+    /// world.apply_delta(&delta)?;
+    /// ```
+    pub fn apply_delta(&mut self, delta: &WorldDelta) -> Result<(), SnapshotError> {
+        delta.apply(self)
+    }
+
+    /// ワールドの中身を一覧にする
+    /// 
+    /// すべてのエンティティのコンポーネント名と、登録済みコンポーネントの値、
+    /// リソースの型名を含みます。
+    /// 
+    /// # 例
+    /// 
+    /// ```
+    /// let json = serde_json::to_string(&world.inspect())?;
+    /// ```
+    pub fn inspect(&self) -> WorldInspection {
+        inspector::inspect(self)
+    }
+
+    /// 1つのエンティティの中身を取得
+    /// 
+    /// # 戻り値
+    /// 
+    /// * エンティティが生存していない場合は`None`
+    pub fn inspect_entity(&self, entity: Entity) -> Option<EntityInfo> {
+        inspector::inspect_entity(self, entity)
+    }
+
+    /// コンポーネントの値の一部を書き換える
+    /// 
+    /// # 引数
+    /// 
+    /// * `entity` - 対象のエンティティ
+    /// * `name` - `#[component(serialize)]`で登録されたコンポーネント名
+    /// * `patch` - 現在の値に重ねる値（オブジェクトはフィールドごとに上書き）
+    /// 
+    /// # エラー
+    /// 
+    /// * エンティティがコンポーネントを持っていない場合や、デシリアライズに失敗した場合。
+    ///   このときコンポーネントは変更されません
+    /// 
+    /// # 例
+    /// 
+    /// ```
+    /// world.patch_component(player, "Transform", &json!({ "rotation": 1.57 }))?;
+    /// ```
+    pub fn patch_component(&mut self, entity: Entity, name: &str, patch: &serde_json::Value) -> Result<(), SnapshotError> {
+        inspector::patch_component(self, entity, name, patch)
     }
 
     /// エンティティにコンポーネントを追加
@@ -133,6 +531,7 @@ impl World {
     /// ```
     pub fn add_component<T: Component>(&mut self, entity: Entity, component: T) {
         self.processor.add_component(entity, component);
+        self.apply_hook_commands();
     }
 
     /// エンティティからコンポーネントを取得
@@ -215,7 +614,35 @@ impl World {
     /// }
     /// ```
     pub fn remove_component<T: Component>(&mut self, entity: Entity) -> bool {
-        self.processor.remove_component::<T>(entity)
+        let removed = self.processor.remove_component::<T>(entity);
+        self.apply_hook_commands();
+        removed
+    }
+
+    /// 削除されたコンポーネントの一覧を取得
+    ///
+    /// 実行中のシステムが前回実行されてから`T`が削除されたエンティティを返します。
+    /// システムの外から呼び出した場合は、保持されているすべての記録を返します。
+    ///
+    /// # 例
+    ///
+    /// ```
+    /// for entity in world.removed_components::<MouseCursorComponent>().iter() {
+    ///     renderer.remove_entity_from_layers(entity);
+    /// }
+    /// ```
+    pub fn removed_components<T: Component>(&self) -> RemovedComponents<'_, T> {
+        RemovedComponents::new(self)
+    }
+
+    /// 削除されたエンティティを列挙
+    ///
+    /// 実行中のシステムが前回実行されてから削除されたエンティティを返します。
+    pub fn despawned_entities(&self) -> impl Iterator<Item = Entity> + '_ {
+        let components = self.components();
+        components.removal_log()
+            .despawned_since(components.last_run_tick())
+            .map(|despawned| despawned.entity)
     }
 
     /// システムを登録
@@ -231,14 +658,51 @@ impl World {
     /// 
     /// * `system` - 登録するシステム
     /// 
+    /// # 戻り値
+    /// 
+    /// * 他のシステムとの`before`/`after`の制約が循環する場合は`ScheduleError`
+    ///   （システムは登録されません）
+    /// 
+    /// # 例
+    /// 
+    /// ```
+    /// world.register_system(MovementSystem::new())?;
+    /// world.register_system(CardEffectSystem::new())?;
+    /// ```
+    pub fn register_system<S: System>(&mut self, system: S) -> Result<(), ScheduleError> {
+        self.processor.register_system(system)
+    }
+
+    /// 実行順序の設定を追加してシステムを登録
+    /// 
+    /// システム自身が宣言したラベルや制約に、`config`のものを加えて登録します。
+    /// 
     /// # 例
     /// 
     /// ```
-    /// world.register_system(MovementSystem::new());
-    /// world.register_system(CardEffectSystem::new());
+    /// world.register_system_with(
+    ///     ScoreSystem::new(),
+    ///     SystemConfig::new().label("score").after("GameStateSystem"),
+    /// )?;
     /// ```
-    pub fn register_system<S: System>(&mut self, system: S) {
-        self.processor.register_system(system);
+    pub fn register_system_with<S: System>(&mut self, system: S, config: SystemConfig) -> Result<(), ScheduleError> {
+        self.processor.register_system_with(system, config)
+    }
+
+    /// 同時に実行するシステムの最大数を設定
+    ///
+    /// ネイティブ環境では、宣言されたアクセスが競合しない関数システムを
+    /// 最大`max_threads`個まで並列に実行します。既定値は利用可能なCPU数です。
+    /// 1を指定するとすべて順番に実行します。Wasm環境では常に順番に実行します。
+    ///
+    /// # 例
+    ///
+    /// ```
+    /// // 実行順を固定して再現したい場合
+    /// world.set_max_threads(1);
+    /// ```
+    pub fn set_max_threads(&mut self, max_threads: usize) {
+        self.processor.set_max_threads(max_threads);
     }
 
     /// 世界を更新（すべてのシステムを実行）
@@ -275,8 +739,8 @@ impl World {
     /// # 例
     /// 
     /// ```
-    /// // 物理シミュレーションのみを更新
-    /// world.update_phase(SystemPhase::Physics, 0.016);
+    /// // 固定ティックを1回だけ進める
+    /// world.update_phase(SystemPhase::FixedUpdate, 1.0 / 60.0);
     /// ```
     pub fn update_phase(&mut self, phase: SystemPhase, delta_time: f32) {
         let world = self as *mut World;
@@ -305,6 +769,129 @@ impl World {
         }
     }
 
+    /// イベント型を登録
+    ///
+    /// `Events<T>`リソースを追加し、フレームごとにバッファが入れ替わるようにします。
+    /// 送信されたイベントは2フレーム後に自動で破棄されます。
+    ///
+    /// # 例
+    ///
+    /// ```
+    /// world.add_event::<CellRevealed>();
+    /// world.send_event(CellRevealed { x: 3, y: 4 });
+    /// ```
+    pub fn add_event<T: event::Event>(&mut self) {
+        self.processor.add_event::<T>();
+    }
+
+    /// イベントを送信
+    ///
+    /// # パニック
+    ///
+    /// `add_event`でイベント型が登録されていない場合
+    pub fn send_event<T: event::Event>(&mut self, event: T) {
+        match self.processor.get_resource_mut::<Events<T>>() {
+            Some(events) => events.send(event),
+            None => panic!(
+                "イベント{}が登録されていません。World::add_eventで登録してください",
+                std::any::type_name::<T>()
+            ),
+        }
+    }
+
+    /// 状態型を登録
+    ///
+    /// `State<S>`リソースを追加します。`in_state`を実行条件にしたシステムや、
+    /// `register_on_enter`/`register_on_exit`で登録したシステムはこの状態を参照します。
+    ///
+    /// # 例
+    ///
+    /// ```
+    /// world.add_state(GameStateType::Splash);
+    /// ```
+    pub fn add_state<S: state::States>(&mut self, initial: S) {
+        self.processor.add_state(initial);
+    }
+
+    /// 状態の変更を予約
+    ///
+    /// 変更は次のフレームの開始時に適用され、そのときに`OnExit`/`OnEnter`の
+    /// システムが実行されます。
+    pub fn set_state<S: state::States>(&mut self, next: S) {
+        self.processor.set_state(next);
+    }
+
+    /// 現在の状態を取得
+    ///
+    /// # 戻り値
+    ///
+    /// * 状態型が登録されていない場合は`None`
+    pub fn state<S: state::States>(&self) -> Option<S> {
+        self.processor.state::<S>()
+    }
+
+    /// 状態に入ったときに1回実行するシステムを登録
+    ///
+    /// # 例
+    ///
+    /// ```
+    /// world.register_on_enter(GameStateType::Paused, ShowPauseMenuSystem::new())?;
+    /// ```
+    pub fn register_on_enter<S: state::States, T: System>(&mut self, state: S, system: T) -> Result<(), ScheduleError> {
+        self.processor.register_on_enter(state, system)
+    }
+
+    /// 状態から出たときに1回実行するシステムを登録
+    pub fn register_on_exit<S: state::States, T: System>(&mut self, state: S, system: T) -> Result<(), ScheduleError> {
+        self.processor.register_on_exit(state, system)
+    }
+
+    /// `Commands`に積まれた操作を即座に適用
+    ///
+    /// 通常はフェーズの終了時に自動で適用されます。
+    /// システムの外でコマンドを積んだ場合などに使用します。
+    ///
+    /// # 例
+    ///
+    /// ```
+    /// world.get_resource_mut::<Commands>().unwrap().spawn().insert(Explosion::new());
+    /// world.apply_commands();
+    /// ```
+    pub fn apply_commands(&mut self) {
+        let world = self as *mut World;
+        unsafe {
+            (*world).processor.apply_commands(&mut *world);
+        }
+    }
+
+    /// コンポーネントのフックから積まれた操作を適用
+    ///
+    /// フックは`ComponentManager`の中で呼ばれるため、ワールドへの変更は
+    /// `World`の操作が終わった後にここで適用されます。
+    pub(crate) fn apply_hook_commands(&mut self) {
+        let mut commands = self.processor.components_mut().take_hook_commands();
+        if !commands.is_empty() {
+            commands.apply(self);
+        }
+    }
+
+    /// コンポーネント型のライフサイクルフックを設定
+    ///
+    /// `Component::hooks`で宣言したフックを置き換えます。
+    ///
+    /// # 例
+    ///
+    /// ```
+    /// world.set_component_hooks::<MouseCursorComponent>(
+    ///     ComponentHooks::new()
+    ///         .on_add(register_in_cursor_layer)
+    ///         .on_remove(unregister_from_cursor_layer),
+    /// );
+    /// ```
+    pub fn set_component_hooks<T: Component>(&mut self, hooks: ComponentHooks) {
+        self.processor.components_mut().set_hooks::<T>(hooks);
+    }
+
     /// リソースを追加または更新
     /// 
     /// リソースはエンティティに紐付かないグローバルデータです。
@@ -456,6 +1043,16 @@ impl World {
         &mut self.processor
     }
 
+    /// コンポーネントマネージャーへの参照を取得
+    pub fn components(&self) -> &ComponentManager {
+        self.processor.components()
+    }
+
+    /// コンポーネントマネージャーへの可変参照を取得
+    pub fn components_mut(&mut self) -> &mut ComponentManager {
+        self.processor.components_mut()
+    }
+
     /// 全エンティティを取得するイテレータを返す
     /// 
     /// 現在ワールドに存在するすべてのエンティティを反復処理するイテレータを返します。
@@ -475,63 +1072,56 @@ impl World {
         self.processor.entities()
     }
 
-    /// 特定のコンポーネント型に対するクエリを作成
+    /// コンポーネントの組み合わせに対するクエリを作成
     ///
-    /// クエリを使用することで、特定のコンポーネント型を持つエンティティのセットを取得できます。
-    /// タプル型`(Entity, &T)`形式のクエリもサポートしています。
-    /// これはエンティティとそのコンポーネントをまとめて取得する場合に便利です。
+    /// クエリを使用することで、指定したコンポーネントをすべて持つエンティティのセットを取得できます。
+    /// `&T`、`&mut T`、`Option<&T>`、`Entity`およびそれらのタプルを指定できます。
     ///
     /// # 型パラメータ
     /// 
-    /// * `T` - クエリ対象のコンポーネント型
+    /// * `Q` - 取得するデータの型
     /// 
     /// # 戻り値
     /// 
-    /// * 指定されたコンポーネント型を持つエンティティに対するクエリ
+    /// * 条件を満たすエンティティを収集済みのクエリ
+    ///
+    /// # パニック
+    ///
+    /// 同じコンポーネントへの可変参照が重複している場合
     ///
     /// # 例
     ///
     /// ```
-    /// // 標準的なコンポーネントクエリ
-    /// let mut query = world.query::<NetworkComponent>();
-    /// for (entity, network) in query.iter(world) {
+    /// // 読み取り専用のクエリ
+    /// let query = world.query::<(Entity, &NetworkComponent)>();
+    /// for (entity, network) in query.iter(&world) {
     ///     // entityとnetworkを使用した処理
     /// }
     /// 
-    /// // タプル型を使用したクエリ
-    /// let mut query = world.query::<(Entity, &NetworkComponent)>();
-    /// for (entity, network) in query.iter(world) {
-    ///     // entityとnetworkを使用した処理
+    /// // 複数のコンポーネントを同時に変更するクエリ
+    /// let query = world.query::<(&Position, &mut Velocity)>();
+    /// for (position, velocity) in query.iter_mut(&mut world) {
+    ///     velocity.x -= position.x * 0.1;
     /// }
     /// ```
-    pub fn query<T>(&mut self) -> Query<T> 
-    where
-        T: 'static + component::Component, // コンポーネント制約を追加
-    {
+    pub fn query<Q: query::QueryData>(&self) -> Query<Q> {
+        self.query_filtered::<Q, ()>()
+    }
+
+    /// フィルタ付きのクエリを作成
+    ///
+    /// `With<T>`や`Without<T>`などのフィルタで結果を絞り込みます。
+    /// フィルタ対象のコンポーネントは取得結果には含まれません。
+    ///
+    /// # 例
+    ///
+    /// ```
+    /// let query = world.query_filtered::<&mut Position, (With<Player>, Without<Frozen>)>();
+    /// ```
+    pub fn query_filtered<Q: query::QueryData, F: query::QueryFilter>(&self) -> Query<Q, F> {
         let mut query = Query::new();
-        
-        // タプル型(Entity, &ComponentType)の特別な処理
-        let type_name = std::any::type_name::<T>();
-        if type_name.starts_with("(") && type_name.contains("Entity") {
-            // タプル型の第2要素を抽出して処理する特殊ケース
-            // ここでは型情報からでは完全な型を取り出せないため、エンティティをすべて追加
-            for entity in self.entities() {
-                query.add_entity(entity);
-            }
-        } else {
-            // 通常のコンポーネント型向けの処理
-            for entity in self.entities() {
-                // コンポーネント型を持つエンティティのみをフィルタリング
-                if let Some(_) = std::any::type_name::<T>().strip_prefix("(") {
-                    // タプル型の場合は特殊処理
-                    query.add_entity(entity);
-                } else if self.get_component::<T>(entity).is_some() {
-                    // 通常のコンポーネント型の場合は存在チェック
-                    query.add_entity(entity);
-                }
-            }
-        }
-        
+        // runはエラーを返さないため結果は無視してよい
+        let _ = query.run(self);
         query
     }
 
@@ -608,6 +1198,12 @@ impl World {
     }
 }
 
+impl Default for World {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 // 型IDから型名を取得するための内部トレイト
 trait _TypeIdExt {
     fn type_name(&self) -> &'static str;
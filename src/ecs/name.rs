@@ -0,0 +1,179 @@
+//! エンティティの名前
+//!
+//! `Name`コンポーネントを追加したエンティティは、`NameIndex`リソースに名前で登録され、
+//! ハンドルをリソースなどに保存しておかなくても`World::find_by_name`で探せます。
+//! 索引はコンポーネントのフックで更新されるため、`World`の操作の直後（`ComponentManager`を
+//! 直接操作した場合は次の同期ポイント）から検索できます。
+//!
+//! ```
+//! world.spawn((Name::new("BoardRoot"), Transform::IDENTITY));
+//!
+//! let root = world.find_by_name("BoardRoot").unwrap();
+//! log::warn!("{}の子が見つかりません", world.entity_label(root));
+//! ```
+//!
+//! 名前を変えるときは`World::add_component`で新しい`Name`に置き換えてください。
+//! `get_component_mut`で書き換えた場合、索引は更新されません。
+
+use std::any::Any;
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::ecs::{Component, Entity, HookContext, Resource};
+
+/// エンティティの名前
+///
+/// 同じ名前を複数のエンティティに付けることもできます。
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize, Component)]
+#[component(serialize, on_add = reindex, on_replace = reindex, on_remove = reindex)]
+#[serde(transparent)]
+pub struct Name(Cow<'static, str>);
+
+impl Name {
+    /// 名前を作成
+    pub fn new(name: impl Into<Cow<'static, str>>) -> Self {
+        Self(name.into())
+    }
+
+    /// 名前を文字列として取得
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for Name {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl From<&'static str> for Name {
+    fn from(name: &'static str) -> Self {
+        Self::new(name)
+    }
+}
+
+impl From<String> for Name {
+    fn from(name: String) -> Self {
+        Self::new(name)
+    }
+}
+
+/// `Name`の追加・置き換え・削除のたびに、適用時点の名前で索引を更新する
+fn reindex(context: &mut HookContext) {
+    let entity = context.entity();
+    context.commands().add(move |world| {
+        let name = world.get_component::<Name>(entity).map(|name| name.as_str().to_string());
+        match world.get_resource_mut::<NameIndex>() {
+            Some(index) => index.update(entity, name),
+            None => {
+                let mut index = NameIndex::default();
+                index.update(entity, name);
+                world.insert_resource(index);
+            }
+        }
+    });
+}
+
+/// 名前からエンティティを引く索引
+///
+/// `SystemProcessor`の生成時にリソースとして登録されます。
+#[derive(Debug, Clone, Default)]
+pub struct NameIndex {
+    /// 名前 → エンティティ（名前が付けられた順）
+    entities: HashMap<String, Vec<Entity>>,
+    /// エンティティ → 名前
+    names: HashMap<Entity, String>,
+}
+
+impl NameIndex {
+    /// 名前の付いたエンティティを取得
+    ///
+    /// 同じ名前のエンティティが複数ある場合は、最初に名前が付けられたものを返します。
+    pub fn get(&self, name: &str) -> Option<Entity> {
+        self.get_all(name).first().copied()
+    }
+
+    /// 名前の付いたすべてのエンティティを名前が付けられた順に取得
+    pub fn get_all(&self, name: &str) -> &[Entity] {
+        self.entities.get(name).map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    /// エンティティの名前を取得
+    pub fn name_of(&self, entity: Entity) -> Option<&str> {
+        self.names.get(&entity).map(String::as_str)
+    }
+
+    /// 名前の付いたエンティティの数
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    /// 名前の付いたエンティティがないかどうか
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+
+    /// エンティティの名前を設定（`None`で索引から外す）
+    pub(crate) fn update(&mut self, entity: Entity, name: Option<String>) {
+        if self.names.get(&entity) == name.as_ref() {
+            return;
+        }
+        if let Some(old) = self.names.remove(&entity) {
+            if let Some(entities) = self.entities.get_mut(&old) {
+                entities.retain(|&e| e != entity);
+                if entities.is_empty() {
+                    self.entities.remove(&old);
+                }
+            }
+        }
+        if let Some(name) = name {
+            self.entities.entry(name.clone()).or_default().push(entity);
+            self.names.insert(entity, name);
+        }
+    }
+}
+
+impl Resource for NameIndex {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ecs::World;
+
+    #[test]
+    fn test_index_follows_component() {
+        let mut world = World::new();
+        let first = world.spawn(Name::new("Cursor"));
+        let second = world.spawn(Name::new("Cursor"));
+        let board = world.spawn(Name::new("BoardRoot"));
+
+        assert_eq!(world.find_by_name("BoardRoot"), Some(board));
+        assert_eq!(world.find_by_name("Cursor"), Some(first));
+        assert_eq!(world.find_all_by_name("Cursor"), vec![first, second]);
+
+        // 置き換えると新しい名前で引ける
+        world.add_component(first, Name::from(format!("Cursor/{}", 1)));
+        assert_eq!(world.find_by_name("Cursor"), Some(second));
+        assert_eq!(world.find_by_name("Cursor/1"), Some(first));
+        assert_eq!(world.entity_label(first), format!("{} \"Cursor/1\"", first));
+
+        world.remove_component::<Name>(second);
+        world.destroy_entity(board);
+        assert_eq!(world.find_by_name("Cursor"), None);
+        assert_eq!(world.find_by_name("BoardRoot"), None);
+        assert_eq!(world.entity_label(board), board.to_string());
+        assert_eq!(world.get_resource::<NameIndex>().unwrap().len(), 1);
+    }
+}
@@ -0,0 +1,749 @@
+//! 関数システムとシステムパラメータ
+//!
+//! 通常の関数をシステムとして登録できるようにします。引数に`Res<T>`、`ResMut<T>`、
+//! `SystemQuery<Q, F>`などを書くと、実行時にリソースやクエリ結果が自動で渡されます。
+//! 引数の型からシステムがアクセスするリソースとコンポーネントが分かるため、
+//! `System::access`で実行前に確認できます。
+//!
+//! ```
+//! fn reveal_cells(
+//!     time: Res<FixedTime>,
+//!     mut stats: ResMut<GameStats>,
+//!     mut cells: SystemQuery<(&Cell, &mut Sprite), Changed<Cell>>,
+//! ) {
+//!     for (cell, sprite) in cells.iter_mut() {
+//!         sprite.visible = cell.revealed;
+//!         stats.revealed += 1;
+//!     }
+//! }
+//!
+//! world.register_system(FunctionSystem::new(SystemPhase::Update, reveal_cells))?;
+//! ```
+//!
+//! 従来の`System`トレイトを実装したシステムもそのまま使用できます。
+
+use std::any::TypeId;
+use std::fmt;
+use std::marker::PhantomData;
+use std::ops::{Deref, DerefMut};
+use std::ptr::NonNull;
+
+use crate::Error;
+
+use crate::ecs::query::{QueryAccess, QueryData, QueryFilter, ReadOnlyQueryData};
+use crate::ecs::{Component, Entity, Query, Resource, ResourceManager, System, SystemPhase, SystemPriority, UnsafeWorldCell, World};
+
+/// システムがアクセスするリソースとコンポーネントの一覧
+///
+/// 関数システムでは引数の型から自動で作られます。`System`トレイトを直接実装する場合は
+/// `System::access`で宣言できます。
+#[derive(Debug, Clone, Default)]
+pub struct SystemAccess {
+    /// コンポーネントへのアクセス
+    components: QueryAccess,
+    /// 読み取りのみ行うリソース（型ID, 型名）
+    resource_reads: Vec<(TypeId, &'static str)>,
+    /// 書き込みを行うリソース（型ID, 型名）
+    resource_writes: Vec<(TypeId, &'static str)>,
+}
+
+impl SystemAccess {
+    /// 空のアクセス情報を作成
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// コンポーネントの読み取りを記録
+    ///
+    /// # パニック
+    ///
+    /// 同じコンポーネントへの書き込みが既に記録されている場合
+    pub fn add_component_read<T: Component>(&mut self) {
+        self.components.add_read::<T>();
+    }
+
+    /// コンポーネントの書き込みを記録
+    ///
+    /// # パニック
+    ///
+    /// 同じコンポーネントへの読み取りまたは書き込みが既に記録されている場合
+    pub fn add_component_write<T: Component>(&mut self) {
+        self.components.add_write::<T>();
+    }
+
+    /// リソースの読み取りを記録
+    ///
+    /// # パニック
+    ///
+    /// 同じリソースへの書き込みが既に記録されている場合
+    pub fn add_resource_read<T: Resource>(&mut self) {
+        let type_id = TypeId::of::<T>();
+        if self.resource_writes.iter().any(|(id, _)| *id == type_id) {
+            panic!("{}へのResMutとResが競合しています", std::any::type_name::<T>());
+        }
+        if !self.resource_reads.iter().any(|(id, _)| *id == type_id) {
+            self.resource_reads.push((type_id, std::any::type_name::<T>()));
+        }
+    }
+
+    /// リソースの書き込みを記録
+    ///
+    /// # パニック
+    ///
+    /// 同じリソースへの読み取りまたは書き込みが既に記録されている場合
+    pub fn add_resource_write<T: Resource>(&mut self) {
+        let type_id = TypeId::of::<T>();
+        if self.resource_writes.iter().any(|(id, _)| *id == type_id)
+            || self.resource_reads.iter().any(|(id, _)| *id == type_id)
+        {
+            panic!("{}へのResMutが重複しています", std::any::type_name::<T>());
+        }
+        self.resource_writes.push((type_id, std::any::type_name::<T>()));
+    }
+
+    /// コンポーネントへのアクセスを取得
+    pub fn components(&self) -> &QueryAccess {
+        &self.components
+    }
+
+    /// コンポーネントへのアクセスを可変で取得
+    ///
+    /// `QueryData::add_access`にそのまま渡せます。
+    pub fn components_mut(&mut self) -> &mut QueryAccess {
+        &mut self.components
+    }
+
+    /// 読み取り対象のリソースの型名を取得
+    pub fn resource_reads(&self) -> impl Iterator<Item = &'static str> + '_ {
+        self.resource_reads.iter().map(|(_, name)| *name)
+    }
+
+    /// 書き込み対象のリソースの型名を取得
+    pub fn resource_writes(&self) -> impl Iterator<Item = &'static str> + '_ {
+        self.resource_writes.iter().map(|(_, name)| *name)
+    }
+
+    /// 書き込み対象のリソースの型IDを取得
+    pub(crate) fn resource_write_ids(&self) -> impl Iterator<Item = TypeId> + '_ {
+        self.resource_writes.iter().map(|(id, _)| *id)
+    }
+
+    /// 別のシステムと同時に実行しても競合しないかを確認
+    ///
+    /// コンポーネントとリソースのどちらについても、一方が書き込むものに
+    /// もう一方がアクセスしていなければ互換です。
+    pub fn is_compatible(&self, other: &SystemAccess) -> bool {
+        let conflicts = |writes: &[(TypeId, &'static str)], other: &SystemAccess| {
+            writes.iter().any(|(id, _)| {
+                other.resource_reads.iter().any(|(o, _)| o == id) || other.resource_writes.iter().any(|(o, _)| o == id)
+            })
+        };
+        self.components.is_compatible(&other.components)
+            && !conflicts(&self.resource_writes, other)
+            && !conflicts(&other.resource_writes, self)
+    }
+}
+
+impl fmt::Display for SystemAccess {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let list = |names: Vec<&'static str>| names.join(", ");
+        write!(
+            f,
+            "components(read: [{}], write: [{}]) resources(read: [{}], write: [{}])",
+            list(self.components.read_names().collect()),
+            list(self.components.write_names().collect()),
+            list(self.resource_reads().collect()),
+            list(self.resource_writes().collect()),
+        )
+    }
+}
+
+/// システムの実行中にパラメータが参照するワールドとリソース
+pub struct SystemContext<'w> {
+    /// 宣言されたアクセスだけを許可するワールドへのハンドル
+    world: &'w UnsafeWorldCell<'w>,
+    /// 前のフレームからの経過時間
+    delta_time: f32,
+}
+
+/// 関数システムの引数として自動で取得できる値
+///
+/// # Safety
+///
+/// `add_access`は`fetch`が行うすべてのアクセスを正しく申告しなければなりません。
+/// 申告されたアクセスが競合しないことを前提に、各パラメータは同時に取得されます。
+pub unsafe trait SystemParam {
+    /// 実行時に関数へ渡される値
+    type Item<'w>;
+
+    /// アクセスするリソースとコンポーネントを記録
+    fn add_access(access: &mut SystemAccess);
+
+    /// 値を取得
+    ///
+    /// 取得できない場合（リソースが登録されていない場合など）は理由を返します。
+    ///
+    /// # Safety
+    ///
+    /// `context`のハンドルは`add_access`で申告したアクセスを許可していなければなりません。
+    unsafe fn fetch<'w>(context: &SystemContext<'w>) -> Result<Self::Item<'w>, String>;
+}
+
+/// リソースへの参照
+pub struct Res<'w, T: Resource> {
+    /// リソース本体
+    value: &'w T,
+}
+
+impl<'w, T: Resource> Deref for Res<'w, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        self.value
+    }
+}
+
+unsafe impl<'a, T: Resource> SystemParam for Res<'a, T> {
+    type Item<'w> = Res<'w, T>;
+
+    fn add_access(access: &mut SystemAccess) {
+        access.add_resource_read::<T>();
+    }
+
+    unsafe fn fetch<'w>(context: &SystemContext<'w>) -> Result<Self::Item<'w>, String> {
+        context.world.get_resource::<T>()
+            .map(|value| Res { value })
+            .ok_or_else(|| missing_resource::<T>())
+    }
+}
+
+/// リソースへの可変参照
+pub struct ResMut<'w, T: Resource> {
+    /// リソース本体
+    value: &'w mut T,
+}
+
+impl<'w, T: Resource> Deref for ResMut<'w, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        self.value
+    }
+}
+
+impl<'w, T: Resource> DerefMut for ResMut<'w, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        self.value
+    }
+}
+
+unsafe impl<'a, T: Resource> SystemParam for ResMut<'a, T> {
+    type Item<'w> = ResMut<'w, T>;
+
+    fn add_access(access: &mut SystemAccess) {
+        access.add_resource_write::<T>();
+    }
+
+    unsafe fn fetch<'w>(context: &SystemContext<'w>) -> Result<Self::Item<'w>, String> {
+        context.world.get_resource_mut::<T>()
+            .map(|value| ResMut { value })
+            .ok_or_else(|| missing_resource::<T>())
+    }
+}
+
+/// 登録されていないリソースのエラーメッセージ
+fn missing_resource<T>() -> String {
+    format!("リソース{}が登録されていません", std::any::type_name::<T>())
+}
+
+unsafe impl<'a, T: Resource> SystemParam for Option<Res<'a, T>> {
+    type Item<'w> = Option<Res<'w, T>>;
+
+    fn add_access(access: &mut SystemAccess) {
+        access.add_resource_read::<T>();
+    }
+
+    unsafe fn fetch<'w>(context: &SystemContext<'w>) -> Result<Self::Item<'w>, String> {
+        Ok(Res::<T>::fetch(context).ok())
+    }
+}
+
+unsafe impl<'a, T: Resource> SystemParam for Option<ResMut<'a, T>> {
+    type Item<'w> = Option<ResMut<'w, T>>;
+
+    fn add_access(access: &mut SystemAccess) {
+        access.add_resource_write::<T>();
+    }
+
+    unsafe fn fetch<'w>(context: &SystemContext<'w>) -> Result<Self::Item<'w>, String> {
+        Ok(ResMut::<T>::fetch(context).ok())
+    }
+}
+
+/// 前のフレームからの経過時間（秒）
+///
+/// `FixedUpdate`フェーズでは1ティックの長さです。
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DeltaTime(pub f32);
+
+impl Deref for DeltaTime {
+    type Target = f32;
+
+    fn deref(&self) -> &f32 {
+        &self.0
+    }
+}
+
+unsafe impl SystemParam for DeltaTime {
+    type Item<'w> = DeltaTime;
+
+    fn add_access(_access: &mut SystemAccess) {}
+
+    unsafe fn fetch<'w>(context: &SystemContext<'w>) -> Result<Self::Item<'w>, String> {
+        Ok(DeltaTime(context.delta_time))
+    }
+}
+
+/// システムの引数として使うクエリ
+///
+/// `Query`と同じ型パラメータを取り、実行前に結果が収集されます。
+/// ワールドを渡さずに`iter`/`iter_mut`で走査できます。
+pub struct SystemQuery<'w, Q: QueryData, F: QueryFilter = ()> {
+    /// 収集済みのクエリ
+    query: Query<Q, F>,
+    /// 実行前に解決したストレージへのポインタ
+    state: Q::State,
+    /// ワールドの借用期間
+    _marker: PhantomData<&'w World>,
+}
+
+impl<'w, Q: QueryData, F: QueryFilter> SystemQuery<'w, Q, F> {
+    /// クエリの結果をイテレートする
+    pub fn iter(&self) -> impl Iterator<Item = Q::Item<'_>> + '_
+    where
+        Q: ReadOnlyQueryData,
+    {
+        // 読み取り専用なので同時に複数の参照を返して問題ない
+        unsafe { self.query.iter_state(&self.state) }
+    }
+
+    /// クエリの結果を可変でイテレートする
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = Q::Item<'_>> + '_ {
+        // 各エンティティは結果に一度しか現れず、他のパラメータと競合しないことはアクセス情報で確認済み
+        unsafe { self.query.iter_state(&self.state) }
+    }
+
+    /// 特定のエンティティのデータを取得
+    pub fn get(&self, entity: Entity) -> Option<Q::Item<'_>>
+    where
+        Q: ReadOnlyQueryData,
+    {
+        if !self.query.contains(entity) {
+            return None;
+        }
+        unsafe { Q::fetch(&self.state, entity) }
+    }
+
+    /// 特定のエンティティのデータを可変で取得
+    pub fn get_mut(&mut self, entity: Entity) -> Option<Q::Item<'_>> {
+        if !self.query.contains(entity) {
+            return None;
+        }
+        // 可変で借用している間は他の要素への参照も返せない
+        unsafe { Q::fetch(&self.state, entity) }
+    }
+
+    /// 特定のエンティティのデータを取得し、取得できない場合はエラーを返す
+    ///
+    /// エラーをシステムから返すと、ログにはエンティティの名前を添えて記録されます。
+    ///
+    /// # エラー
+    ///
+    /// エンティティがクエリの条件を満たさない場合は`Error::MissingComponent`
+    pub fn get_required(&self, entity: Entity) -> Result<Q::Item<'_>, Error>
+    where
+        Q: ReadOnlyQueryData,
+    {
+        self.get(entity).ok_or_else(|| Self::missing(entity))
+    }
+
+    /// 特定のエンティティのデータを可変で取得し、取得できない場合はエラーを返す
+    ///
+    /// # エラー
+    ///
+    /// エンティティがクエリの条件を満たさない場合は`Error::MissingComponent`
+    pub fn get_required_mut(&mut self, entity: Entity) -> Result<Q::Item<'_>, Error> {
+        if !self.query.contains(entity) {
+            return Err(Self::missing(entity));
+        }
+        unsafe { Q::fetch(&self.state, entity) }.ok_or_else(|| Self::missing(entity))
+    }
+
+    /// クエリが取得するコンポーネントを持っていないことを表すエラー
+    fn missing(entity: Entity) -> Error {
+        let mut access = QueryAccess::new();
+        Q::add_access(&mut access);
+        let components: Vec<&'static str> = access.read_names()
+            .chain(access.write_names())
+            .map(|name| name.rsplit("::").next().unwrap_or(name))
+            .collect();
+        Error::MissingComponent {
+            entity,
+            components: components.join(", "),
+        }
+    }
+
+    /// 結果のエンティティを取得
+    pub fn entities(&self) -> Vec<Entity> {
+        self.query.entities()
+    }
+
+    /// 結果のエンティティ数を取得
+    pub fn len(&self) -> usize {
+        self.query.len()
+    }
+
+    /// 結果が空かどうか
+    pub fn is_empty(&self) -> bool {
+        self.query.is_empty()
+    }
+}
+
+unsafe impl<'a, Q: QueryData + 'static, F: QueryFilter + 'static> SystemParam for SystemQuery<'a, Q, F> {
+    type Item<'w> = SystemQuery<'w, Q, F>;
+
+    fn add_access(access: &mut SystemAccess) {
+        Q::add_access(access.components_mut());
+        F::add_access(access.components_mut());
+    }
+
+    unsafe fn fetch<'w>(context: &SystemContext<'w>) -> Result<Self::Item<'w>, String> {
+        let mut query = Query::<Q, F>::new();
+        query.collect(context.world.components(), context.world.entities().entities());
+        Ok(SystemQuery {
+            query,
+            state: Q::init_state_in(context.world),
+            _marker: PhantomData,
+        })
+    }
+}
+
+/// タプル型に対するSystemParamの実装を生成するマクロ
+macro_rules! impl_system_param_tuple {
+    ($($name:ident),*) => {
+        unsafe impl<$($name: SystemParam),*> SystemParam for ($($name,)*) {
+            type Item<'w> = ($($name::Item<'w>,)*);
+
+            fn add_access(_access: &mut SystemAccess) {
+                $($name::add_access(_access);)*
+            }
+
+            #[allow(clippy::unused_unit)]
+            unsafe fn fetch<'w>(_context: &SystemContext<'w>) -> Result<Self::Item<'w>, String> {
+                Ok(($($name::fetch(_context)?,)*))
+            }
+        }
+    };
+}
+
+impl_system_param_tuple!();
+impl_system_param_tuple!(A);
+impl_system_param_tuple!(A, B);
+impl_system_param_tuple!(A, B, C);
+impl_system_param_tuple!(A, B, C, D);
+impl_system_param_tuple!(A, B, C, D, E);
+impl_system_param_tuple!(A, B, C, D, E, F);
+impl_system_param_tuple!(A, B, C, D, E, F, G);
+impl_system_param_tuple!(A, B, C, D, E, F, G, H);
+
+/// 関数システムの戻り値
+///
+/// `()`または`Result<(), Error>`を返す関数を登録できます。
+pub trait SystemOutput: 'static {
+    /// システムの実行結果に変換
+    fn into_result(self) -> Result<(), Error>;
+}
+
+impl SystemOutput for () {
+    fn into_result(self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+impl SystemOutput for Result<(), Error> {
+    fn into_result(self) -> Result<(), Error> {
+        self
+    }
+}
+
+/// 関数システムとして登録できる関数
+#[cfg(not(target_arch = "wasm32"))]
+pub trait SystemFunction: 'static + Send + Sync {}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl<T: 'static + Send + Sync> SystemFunction for T {}
+
+/// 関数システムとして登録できる関数（Wasm環境用）
+#[cfg(target_arch = "wasm32")]
+pub trait SystemFunction: 'static {}
+
+#[cfg(target_arch = "wasm32")]
+impl<T: 'static> SystemFunction for T {}
+
+/// システムパラメータを引数に取る関数
+///
+/// `Marker`は引数の型から実装を選ぶための型で、直接指定する必要はありません。
+pub trait SystemParamFunction<Marker>: SystemFunction {
+    /// 引数全体をまとめたパラメータ
+    type Param: SystemParam;
+
+    /// 取得したパラメータで関数を呼び出す
+    fn call(&mut self, params: <Self::Param as SystemParam>::Item<'_>) -> Result<(), Error>;
+}
+
+/// 引数の数ごとにSystemParamFunctionの実装を生成するマクロ
+macro_rules! impl_system_param_function {
+    ($($param:ident),*) => {
+        #[allow(non_snake_case)]
+        impl<Func, Out, $($param: SystemParam),*> SystemParamFunction<fn($($param,)*) -> Out> for Func
+        where
+            Func: SystemFunction,
+            for<'a> &'a mut Func: FnMut($($param),*) -> Out + FnMut($($param::Item<'_>),*) -> Out,
+            Out: SystemOutput,
+        {
+            type Param = ($($param,)*);
+
+            fn call(&mut self, params: <Self::Param as SystemParam>::Item<'_>) -> Result<(), Error> {
+                // 高階のライフタイムを持つ引数で呼び出すための補助関数
+                #[allow(clippy::too_many_arguments)]
+                fn call_inner<Out, $($param),*>(mut f: impl FnMut($($param),*) -> Out, $($param: $param),*) -> Out {
+                    f($($param),*)
+                }
+                let ($($param,)*) = params;
+                call_inner(self, $($param),*).into_result()
+            }
+        }
+    };
+}
+
+impl_system_param_function!();
+impl_system_param_function!(A);
+impl_system_param_function!(A, B);
+impl_system_param_function!(A, B, C);
+impl_system_param_function!(A, B, C, D);
+impl_system_param_function!(A, B, C, D, E);
+impl_system_param_function!(A, B, C, D, E, F);
+impl_system_param_function!(A, B, C, D, E, F, G);
+impl_system_param_function!(A, B, C, D, E, F, G, H);
+
+/// 関数をシステムとして扱うためのラッパー
+///
+/// 名前は既定で関数名になります。優先度や順序は`System`トレイトを実装した
+/// システムと同じように、`with_priority`や`SystemConfig`で指定します。
+pub struct FunctionSystem<F, Marker> {
+    /// システムの本体
+    function: F,
+    /// システム名
+    name: &'static str,
+    /// 実行フェーズ
+    phase: SystemPhase,
+    /// 優先度
+    priority: SystemPriority,
+    /// 引数から求めたアクセス情報
+    access: SystemAccess,
+    /// 引数の型のマーカー
+    _marker: PhantomData<fn() -> Marker>,
+}
+
+impl<F: SystemParamFunction<Marker>, Marker: 'static> FunctionSystem<F, Marker> {
+    /// 関数からシステムを作成
+    ///
+    /// # パニック
+    ///
+    /// 引数同士のアクセスが競合している場合（例: 同じ型の`ResMut`が2つある）
+    pub fn new(phase: SystemPhase, function: F) -> Self {
+        let mut access = SystemAccess::new();
+        F::Param::add_access(&mut access);
+
+        let full_name = std::any::type_name::<F>();
+        Self {
+            function,
+            name: full_name.rsplit("::").next().unwrap_or(full_name),
+            phase,
+            priority: SystemPriority::default(),
+            access,
+            _marker: PhantomData,
+        }
+    }
+
+    /// システム名を設定
+    ///
+    /// クロージャを登録する場合や、ラベルとして別の名前を使いたい場合に指定します。
+    pub fn with_name(mut self, name: &'static str) -> Self {
+        self.name = name;
+        self
+    }
+
+    /// 優先度を設定
+    pub fn with_priority(mut self, priority: SystemPriority) -> Self {
+        self.priority = priority;
+        self
+    }
+}
+
+impl<F: SystemParamFunction<Marker>, Marker: 'static> System for FunctionSystem<F, Marker> {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn phase(&self) -> SystemPhase {
+        self.phase
+    }
+
+    fn priority(&self) -> SystemPriority {
+        self.priority
+    }
+
+    fn access(&self) -> Option<SystemAccess> {
+        Some(self.access.clone())
+    }
+
+    fn is_parallel(&self) -> bool {
+        true
+    }
+
+    fn run(&mut self, world: &mut World, resources: &mut ResourceManager, delta_time: f32) -> Result<(), Error> {
+        // 排他参照から作るため、宣言したアクセスは他と競合しない
+        unsafe {
+            let world = UnsafeWorldCell::new(NonNull::from(world), NonNull::from(resources), &self.access);
+            self.run_unsafe(&world, delta_time)
+        }
+    }
+
+    unsafe fn run_unsafe(&mut self, world: &UnsafeWorldCell<'_>, delta_time: f32) -> Result<(), Error> {
+        let context = SystemContext { world, delta_time };
+        // 引数同士のアクセスが競合しないことはnewで確認済み
+        let params = match F::Param::fetch(&context) {
+            Ok(params) => params,
+            Err(reason) => {
+                log::warn!("{}の実行をスキップしました: {}", self.name, reason);
+                return Ok(());
+            }
+        };
+        self.function.call(params)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ecs::{Changed, FixedTime, Name};
+
+    #[derive(Debug, PartialEq, Component)]
+    struct Cell {
+        revealed: bool,
+    }
+
+    #[derive(Debug, PartialEq, Component)]
+    struct Sprite {
+        visible: bool,
+    }
+
+    #[derive(Default, Resource)]
+    struct Stats {
+        revealed: u32,
+        elapsed: f32,
+    }
+
+    fn show_revealed(
+        mut stats: ResMut<Stats>,
+        delta_time: DeltaTime,
+        mut cells: SystemQuery<(&Cell, &mut Sprite), Changed<Cell>>,
+    ) {
+        stats.elapsed += *delta_time;
+        for (cell, sprite) in cells.iter_mut() {
+            sprite.visible = cell.revealed;
+            if cell.revealed {
+                stats.revealed += 1;
+            }
+        }
+    }
+
+    #[test]
+    fn test_function_system_fetches_params() {
+        let mut world = World::new();
+        world.insert_resource(Stats::default());
+        let hidden = world.spawn((Cell { revealed: false }, Sprite { visible: true }));
+        let shown = world.spawn((Cell { revealed: true }, Sprite { visible: false }));
+        world.register_system(FunctionSystem::new(SystemPhase::Update, show_revealed)).unwrap();
+        assert_eq!(world.processor().system_names(SystemPhase::Update), vec!["show_revealed"]);
+
+        world.update(0.5);
+        assert_eq!(world.get_component::<Sprite>(hidden), Some(&Sprite { visible: false }));
+        assert_eq!(world.get_component::<Sprite>(shown), Some(&Sprite { visible: true }));
+
+        // 変更のないセルは次のフレームでは処理されない
+        world.update(0.5);
+        let stats = world.get_resource::<Stats>().unwrap();
+        assert_eq!(stats.revealed, 1);
+        assert_eq!(stats.elapsed, 1.0);
+    }
+
+    #[test]
+    fn test_access_is_inspectable() {
+        let system = FunctionSystem::new(SystemPhase::Update, show_revealed);
+        let access = system.access().unwrap();
+        assert_eq!(access.resource_writes().count(), 1);
+        assert_eq!(access.components().reads().count(), 1);
+        assert_eq!(access.components().writes().count(), 1);
+
+        let reader = FunctionSystem::new(SystemPhase::Update, |_time: Res<FixedTime>, _cells: SystemQuery<&Cell>| {})
+            .with_name("reader");
+        let reader_access = reader.access().unwrap();
+        assert_eq!(reader.name(), "reader");
+        // Cellの読み取り同士、別リソースなので競合しない
+        assert!(reader_access.is_compatible(&access));
+
+        let writer = FunctionSystem::new(SystemPhase::Update, |_sprites: SystemQuery<&mut Sprite>| {});
+        assert!(!writer.access().unwrap().is_compatible(&access));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_conflicting_params_panic() {
+        FunctionSystem::new(SystemPhase::Update, |_a: ResMut<Stats>, _b: Res<Stats>| {});
+    }
+
+    #[test]
+    fn test_missing_resource_skips_system() {
+        let mut world = World::new();
+        let entity = world.spawn(Sprite { visible: false });
+        world.register_system(FunctionSystem::new(SystemPhase::Update, |_stats: Res<Stats>, mut sprites: SystemQuery<&mut Sprite>| {
+            for sprite in sprites.iter_mut() {
+                sprite.visible = true;
+            }
+        })).unwrap();
+
+        world.update(0.016);
+        assert_eq!(world.get_component::<Sprite>(entity), Some(&Sprite { visible: false }));
+    }
+
+    #[derive(Default, Resource)]
+    struct LastError(Option<Error>);
+
+    #[test]
+    fn test_get_required_reports_entity() {
+        let mut world = World::new();
+        world.insert_resource(LastError::default());
+        let board = world.spawn((Name::new("BoardRoot"), Cell { revealed: false }));
+        world.register_system(FunctionSystem::new(SystemPhase::Update,
+            move |mut last: ResMut<LastError>, mut sprites: SystemQuery<(&Cell, &mut Sprite)>| {
+                last.0 = sprites.get_required_mut(board).err();
+            })).unwrap();
+
+        world.update(0.016);
+        let error = world.get_resource::<LastError>().unwrap().0.clone().unwrap();
+        assert_eq!(error, Error::MissingComponent { entity: board, components: "Cell, Sprite".to_string() });
+        assert_eq!(error.describe(&world), format!("{} \"BoardRoot\"はCell, Spriteを持っていません", board));
+    }
+}
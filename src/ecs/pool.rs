@@ -0,0 +1,168 @@
+//! 並列実行するシステムを受け持つワーカースレッド
+//!
+//! バッチごとにスレッドを起動すると、毎フレーム何度もスレッドの生成と終了が発生します。
+//! `WorkerPool`は`SystemProcessor`が所有する固定数のスレッドで、バッチのシステムを
+//! ジョブとして受け取って実行します。
+//!
+//! `WorkerPool::scope`はすべてのジョブの終了を待ってから戻るため、ジョブは
+//! 呼び出し元の変数を借用できます（`std::thread::scope`と同じ使い方です）。
+
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+
+/// ワーカーが実行するジョブ
+type Job = Box<dyn FnOnce() + Send + 'static>;
+
+/// 固定数のワーカースレッド
+pub(crate) struct WorkerPool {
+    /// ジョブの送信先（破棄するとワーカーが終了する）
+    sender: Option<Sender<Job>>,
+    /// ワーカースレッド
+    workers: Vec<JoinHandle<()>>,
+}
+
+impl WorkerPool {
+    /// `threads`個のワーカースレッドを起動
+    pub(crate) fn new(threads: usize) -> Self {
+        let (sender, receiver) = mpsc::channel::<Job>();
+        let receiver = Arc::new(Mutex::new(receiver));
+        let workers = (0..threads.max(1))
+            .map(|index| {
+                let receiver = Arc::clone(&receiver);
+                thread::Builder::new()
+                    .name(format!("ecs-worker-{}", index))
+                    .spawn(move || worker_loop(&receiver))
+                    .expect("ワーカースレッドを起動できませんでした")
+            })
+            .collect();
+        Self {
+            sender: Some(sender),
+            workers,
+        }
+    }
+
+    /// ワーカースレッドの数を取得
+    pub(crate) fn threads(&self) -> usize {
+        self.workers.len()
+    }
+
+    /// ジョブをワーカーで実行し、すべての終了を待って結果をジョブの順に返す
+    ///
+    /// 最後のジョブは呼び出し元のスレッドで実行します。
+    /// ジョブがパニックした場合は、すべてのジョブの終了後に呼び出し元でパニックを再開します。
+    pub(crate) fn scope<'a, R: Send + 'a>(&self, jobs: Vec<Box<dyn FnOnce() -> R + Send + 'a>>) -> Vec<R> {
+        let count = jobs.len();
+        let mut jobs = jobs;
+        let local = match jobs.pop() {
+            Some(job) => job,
+            None => return Vec::new(),
+        };
+
+        let (result_sender, result_receiver) = mpsc::channel();
+        let sender = self.sender.as_ref().expect("ワーカープールは終了しています");
+        for (index, job) in jobs.into_iter().enumerate() {
+            let result_sender = result_sender.clone();
+            let job: Box<dyn FnOnce() + Send + 'a> = Box::new(move || {
+                let result = panic::catch_unwind(AssertUnwindSafe(job));
+                let _ = result_sender.send((index, result));
+            });
+            // ジョブが借用するデータは、結果を受け取るまでこの関数の中で生存している
+            let job = unsafe { std::mem::transmute::<Box<dyn FnOnce() + Send + 'a>, Job>(job) };
+            sender.send(job).expect("ワーカープールは終了しています");
+        }
+        drop(result_sender);
+
+        let local_result = panic::catch_unwind(AssertUnwindSafe(local));
+
+        // ジョブが借用を持ったまま戻らないよう、パニックの有無にかかわらずすべての終了を待つ
+        let mut results: Vec<Option<thread::Result<R>>> = (0..count - 1).map(|_| None).collect();
+        for _ in 0..count - 1 {
+            let (index, result) = result_receiver.recv()
+                .expect("ワーカースレッドが終了しました");
+            results[index] = Some(result);
+        }
+
+        results.into_iter()
+            .map(|result| result.expect("ジョブの結果がありません"))
+            .chain(std::iter::once(local_result))
+            .map(|result| result.unwrap_or_else(|payload| panic::resume_unwind(payload)))
+            .collect()
+    }
+}
+
+impl Drop for WorkerPool {
+    fn drop(&mut self) {
+        // 送信先を破棄するとワーカーの受信が終わる
+        self.sender.take();
+        for worker in self.workers.drain(..) {
+            let _ = worker.join();
+        }
+    }
+}
+
+/// ジョブを受け取って実行し続ける
+fn worker_loop(receiver: &Mutex<Receiver<Job>>) {
+    loop {
+        let job = match receiver.lock() {
+            Ok(receiver) => receiver.recv(),
+            Err(_) => return,
+        };
+        match job {
+            Ok(job) => job(),
+            Err(_) => return,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[test]
+    fn test_scope_runs_borrowing_jobs_on_reused_threads() {
+        let pool = WorkerPool::new(2);
+        let counter = AtomicUsize::new(0);
+        for round in 0..3 {
+            let jobs: Vec<Box<dyn FnOnce() -> usize + Send + '_>> = (0..3)
+                .map(|index| -> Box<dyn FnOnce() -> usize + Send + '_> {
+                    let counter = &counter;
+                    Box::new(move || {
+                        counter.fetch_add(1, Ordering::SeqCst);
+                        round * 10 + index
+                    })
+                })
+                .collect();
+            assert_eq!(pool.scope(jobs), vec![round * 10, round * 10 + 1, round * 10 + 2]);
+        }
+        assert_eq!(counter.load(Ordering::SeqCst), 9);
+        assert_eq!(pool.threads(), 2);
+    }
+
+    #[test]
+    fn test_scope_waits_for_all_jobs_before_panicking() {
+        let pool = WorkerPool::new(2);
+        let finished = AtomicUsize::new(0);
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            let jobs: Vec<Box<dyn FnOnce() + Send + '_>> = vec![
+                Box::new(|| panic!("システムがパニックしました")),
+                Box::new(|| {
+                    thread::sleep(std::time::Duration::from_millis(20));
+                    finished.fetch_add(1, Ordering::SeqCst);
+                }),
+                Box::new(|| {
+                    finished.fetch_add(1, Ordering::SeqCst);
+                }),
+            ];
+            pool.scope(jobs);
+        }));
+        assert!(result.is_err());
+        assert_eq!(finished.load(Ordering::SeqCst), 2);
+
+        // パニックの後もワーカーは使える
+        let jobs: Vec<Box<dyn FnOnce() -> u8 + Send>> = vec![Box::new(|| 1), Box::new(|| 2)];
+        assert_eq!(pool.scope(jobs), vec![1, 2]);
+    }
+}
@@ -0,0 +1,352 @@
+//! JSONで定義するプレハブ
+//!
+//! プレハブはエンティティに追加するコンポーネントとその値を名前付きでまとめたものです。
+//! コンポーネントは`#[component(serialize)]`で登録された名前で指定し、値はスナップショットと
+//! 同じ形式で書きます。`extends`で別のプレハブを継承でき、継承先で書いたフィールドだけが
+//! 上書きされます。値に`null`を書くと、継承元のコンポーネントを取り除きます。
+//!
+//! ```
+//! let library = PrefabLibrary::from_json(r#"{
+//!     "Enemy": {
+//!         "components": {
+//!             "Transform": { "translation": [0.0, 0.0], "rotation": 0.0, "scale": [1.0, 1.0] },
+//!             "Health": { "current": 10, "max": 10 }
+//!         }
+//!     },
+//!     "Boss": {
+//!         "extends": "Enemy",
+//!         "components": {
+//!             "Transform": { "scale": [2.0, 2.0] },
+//!             "Health": { "current": 50, "max": 50 }
+//!         }
+//!     }
+//! }"#)?;
+//!
+//! world.register_component::<Health>()?;
+//! let boss = library.spawn(&mut world, "Boss")?;
+//! ```
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::ecs::reflect::PendingInsert;
+use crate::ecs::{Entity, World};
+
+/// プレハブの読み込み・生成時のエラー
+#[derive(Debug, Clone, PartialEq)]
+pub enum PrefabError {
+    /// JSONとして読み込めなかった
+    Parse(String),
+    /// 存在しないプレハブ名が指定された
+    UnknownPrefab(String),
+    /// 継承が循環している（循環したプレハブ名の順）
+    Cycle(Vec<String>),
+    /// 登録されていないコンポーネント名が含まれていた
+    UnknownComponent {
+        /// プレハブ名
+        prefab: String,
+        /// コンポーネント名
+        component: String,
+    },
+    /// コンポーネントをデシリアライズできなかった
+    Deserialize {
+        /// プレハブ名
+        prefab: String,
+        /// コンポーネント名
+        component: String,
+        /// 原因
+        message: String,
+    },
+}
+
+impl fmt::Display for PrefabError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PrefabError::Parse(message) => {
+                write!(f, "プレハブの読み込みに失敗しました: {}", message)
+            }
+            PrefabError::UnknownPrefab(name) => {
+                write!(f, "プレハブ{}は定義されていません", name)
+            }
+            PrefabError::Cycle(names) => {
+                write!(f, "プレハブの継承が循環しています: {}", names.join(" -> "))
+            }
+            PrefabError::UnknownComponent { prefab, component } => {
+                write!(f, "プレハブ{}のコンポーネント{}は登録されていません", prefab, component)
+            }
+            PrefabError::Deserialize { prefab, component, message } => {
+                write!(f, "プレハブ{}のコンポーネント{}のデシリアライズに失敗しました: {}", prefab, component, message)
+            }
+        }
+    }
+}
+
+impl std::error::Error for PrefabError {}
+
+#[cfg(feature = "web")]
+impl From<PrefabError> for wasm_bindgen::JsValue {
+    fn from(error: PrefabError) -> Self {
+        wasm_bindgen::JsValue::from_str(&error.to_string())
+    }
+}
+
+/// 1つのプレハブの定義
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct Prefab {
+    /// 継承元のプレハブ名
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub extends: Option<String>,
+    /// コンポーネント名 → 値（`null`は継承元のコンポーネントを取り除く）
+    #[serde(default)]
+    pub components: BTreeMap<String, Value>,
+}
+
+/// 名前付きのプレハブの集まり
+#[derive(Debug, Clone, Default)]
+pub struct PrefabLibrary {
+    /// プレハブ名 → 定義
+    prefabs: HashMap<String, Prefab>,
+}
+
+impl PrefabLibrary {
+    /// 空のライブラリを作成
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// JSONからライブラリを作成
+    ///
+    /// JSONはプレハブ名をキー、定義を値とするオブジェクトです。
+    pub fn from_json(json: &str) -> Result<Self, PrefabError> {
+        let mut library = Self::new();
+        library.load_json(json)?;
+        Ok(library)
+    }
+
+    /// JSONのプレハブを追加で読み込む
+    ///
+    /// 同じ名前のプレハブは置き換えられます。継承元は別のJSONで定義されていても構いません。
+    /// エラーの場合、ライブラリは変更されません。
+    pub fn load_json(&mut self, json: &str) -> Result<(), PrefabError> {
+        let prefabs: HashMap<String, Prefab> = serde_json::from_str(json)
+            .map_err(|e| PrefabError::Parse(e.to_string()))?;
+        self.prefabs.extend(prefabs);
+        Ok(())
+    }
+
+    /// プレハブを追加
+    ///
+    /// 同じ名前のプレハブは置き換えられます。
+    pub fn insert(&mut self, name: impl Into<String>, prefab: Prefab) {
+        self.prefabs.insert(name.into(), prefab);
+    }
+
+    /// プレハブの定義を取得
+    pub fn get(&self, name: &str) -> Option<&Prefab> {
+        self.prefabs.get(name)
+    }
+
+    /// プレハブが定義されているかどうか
+    pub fn contains(&self, name: &str) -> bool {
+        self.prefabs.contains_key(name)
+    }
+
+    /// 定義されているプレハブ名を取得（順不同）
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.prefabs.keys().map(String::as_str)
+    }
+
+    /// 継承を展開したコンポーネントの値を取得
+    ///
+    /// 継承元から順に値を重ね、オブジェクト同士はフィールドごとに上書きします。
+    ///
+    /// # エラー
+    ///
+    /// * プレハブや継承元が定義されていない場合
+    /// * 継承が循環している場合
+    pub fn resolve(&self, name: &str) -> Result<BTreeMap<String, Value>, PrefabError> {
+        // 自分から継承元へたどり、根から順に重ねる
+        let mut chain: Vec<&str> = Vec::new();
+        let mut current = Some(name);
+        while let Some(prefab_name) = current {
+            if chain.contains(&prefab_name) {
+                let mut cycle: Vec<String> = chain.iter()
+                    .skip_while(|&&seen| seen != prefab_name)
+                    .map(|seen| seen.to_string())
+                    .collect();
+                cycle.push(prefab_name.to_string());
+                return Err(PrefabError::Cycle(cycle));
+            }
+            let prefab = self.prefabs.get(prefab_name)
+                .ok_or_else(|| PrefabError::UnknownPrefab(prefab_name.to_string()))?;
+            chain.push(prefab_name);
+            current = prefab.extends.as_deref();
+        }
+
+        let mut components = BTreeMap::new();
+        for prefab_name in chain.iter().rev() {
+            for (component, value) in &self.prefabs[*prefab_name].components {
+                if value.is_null() {
+                    components.remove(component);
+                    continue;
+                }
+                match components.get_mut(component) {
+                    Some(base) => merge(base, value),
+                    None => {
+                        components.insert(component.clone(), value.clone());
+                    }
+                }
+            }
+        }
+        Ok(components)
+    }
+
+    /// プレハブからエンティティを生成
+    ///
+    /// コンポーネントは事前に`World::register_component`などで登録されている必要があります。
+    ///
+    /// # エラー
+    ///
+    /// * プレハブを展開できない場合（`resolve`を参照）
+    /// * 登録されていないコンポーネントが含まれる場合や、デシリアライズに失敗した場合。
+    ///   このときエンティティは生成されません
+    pub fn spawn(&self, world: &mut World, name: &str) -> Result<Entity, PrefabError> {
+        let inserts = self.prepare(world, name)?;
+        let entity = world.create_entity();
+        for insert in inserts {
+            insert(world, entity);
+        }
+        Ok(entity)
+    }
+
+    /// 既存のエンティティにプレハブのコンポーネントを追加
+    ///
+    /// 既に持っているコンポーネントは置き換えられます。
+    ///
+    /// # エラー
+    ///
+    /// * `spawn`と同じ。エラーの場合、エンティティは変更されません
+    pub fn insert_into(&self, world: &mut World, entity: Entity, name: &str) -> Result<(), PrefabError> {
+        let inserts = self.prepare(world, name)?;
+        for insert in inserts {
+            insert(world, entity);
+        }
+        Ok(())
+    }
+
+    /// 展開したコンポーネントをすべてデシリアライズする
+    fn prepare(&self, world: &World, name: &str) -> Result<Vec<PendingInsert>, PrefabError> {
+        let components = self.resolve(name)?;
+        let registry = world.components().registry();
+        components.into_iter()
+            .map(|(component, value)| {
+                let reflection = registry.get_by_name(&component)
+                    .ok_or_else(|| PrefabError::UnknownComponent {
+                        prefab: name.to_string(),
+                        component: component.clone(),
+                    })?;
+                reflection.deserialize(value).map_err(|e| PrefabError::Deserialize {
+                    prefab: name.to_string(),
+                    component,
+                    message: e.to_string(),
+                })
+            })
+            .collect()
+    }
+}
+
+/// `overlay`の値を`base`に重ねる
+///
+/// オブジェクト同士はフィールドごとに再帰的に重ね、それ以外は置き換えます。
+pub(crate) fn merge(base: &mut Value, overlay: &Value) {
+    match (base, overlay) {
+        (Value::Object(base), Value::Object(overlay)) => {
+            for (key, value) in overlay {
+                match base.get_mut(key) {
+                    Some(field) => merge(field, value),
+                    None => {
+                        base.insert(key.clone(), value.clone());
+                    }
+                }
+            }
+        }
+        (base, overlay) => *base = overlay.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ecs::{Component, Transform};
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize, Component)]
+    #[component(serialize)]
+    struct Health {
+        current: u32,
+        max: u32,
+    }
+
+    const PREFABS: &str = r#"{
+        "Enemy": {
+            "components": {
+                "Transform": { "translation": [0.0, 0.0], "rotation": 0.0, "scale": [1.0, 1.0] },
+                "Health": { "current": 10, "max": 10 }
+            }
+        },
+        "Boss": {
+            "extends": "Enemy",
+            "components": {
+                "Transform": { "scale": [2.0, 2.0] },
+                "Health": { "current": 50, "max": 50 }
+            }
+        },
+        "Dummy": {
+            "extends": "Boss",
+            "components": { "Health": null }
+        }
+    }"#;
+
+    #[test]
+    fn test_spawn_with_inheritance() {
+        let mut world = World::new();
+        world.register_component::<Transform>().unwrap();
+        world.register_component::<Health>().unwrap();
+        let library = PrefabLibrary::from_json(PREFABS).unwrap();
+
+        let boss = library.spawn(&mut world, "Boss").unwrap();
+        assert_eq!(world.get_component::<Health>(boss), Some(&Health { current: 50, max: 50 }));
+        assert_eq!(world.get_component::<Transform>(boss), Some(&Transform::IDENTITY.with_scale(2.0, 2.0)));
+
+        // nullで継承元のコンポーネントを取り除ける
+        let dummy = library.spawn(&mut world, "Dummy").unwrap();
+        assert!(world.get_component::<Health>(dummy).is_none());
+        assert_eq!(world.get_component::<Transform>(dummy).unwrap().scale, (2.0, 2.0));
+    }
+
+    #[test]
+    fn test_errors_do_not_spawn() {
+        let mut world = World::new();
+        world.register_component::<Transform>().unwrap();
+        let mut library = PrefabLibrary::from_json(PREFABS).unwrap();
+
+        // Healthが登録されていない
+        let error = library.spawn(&mut world, "Boss").unwrap_err();
+        assert_eq!(error, PrefabError::UnknownComponent {
+            prefab: "Boss".to_string(),
+            component: "Health".to_string(),
+        });
+        assert_eq!(world.entities().count(), 0);
+
+        assert_eq!(library.spawn(&mut world, "Dragon").unwrap_err(), PrefabError::UnknownPrefab("Dragon".to_string()));
+
+        library.load_json(r#"{ "Enemy": { "extends": "Dummy" } }"#).unwrap();
+        assert_eq!(
+            library.resolve("Boss").unwrap_err(),
+            PrefabError::Cycle(vec!["Boss".into(), "Enemy".into(), "Dummy".into(), "Boss".into()])
+        );
+        assert!(matches!(PrefabLibrary::from_json("[]"), Err(PrefabError::Parse(_))));
+    }
+}
@@ -0,0 +1,217 @@
+//! システムごとの実行時間の計測
+//!
+//! `SystemProcessor`はシステムを実行するたびに所要時間を計測し、`SystemTimings`
+//! リソースに記録します。直近の実行回数分の最小・平均・最大を確認できるため、
+//! フレームが遅くなったときにどのシステムが原因かを特定できます。
+//!
+//! ```
+//! let timings = world.get_resource::<SystemTimings>().unwrap();
+//! for timing in timings.slowest(3) {
+//!     log::info!("{} ({}): 平均{:.2}ms 最大{:.2}ms", timing.name, timing.phase, timing.avg_ms, timing.max_ms);
+//! }
+//! ```
+//!
+//! 時間は`platform::now_ms`（ブラウザでは`Performance.now()`、それ以外では`Instant`）で計測します。
+
+use std::any::Any;
+use std::collections::{HashMap, VecDeque};
+
+use serde::{Deserialize, Serialize};
+
+use crate::ecs::{Resource, SystemPhase};
+
+/// 集計に使う直近の実行回数の既定値
+pub const DEFAULT_WINDOW: usize = 120;
+
+/// 計測の基準からの経過時間（ミリ秒）を取得
+pub use crate::platform::now_ms;
+
+/// 1つのシステムの実行時間の集計
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SystemTiming {
+    /// システム名
+    pub name: String,
+    /// 実行フェーズ
+    pub phase: String,
+    /// これまでの実行回数
+    pub runs: u64,
+    /// 直近の実行時間（ミリ秒）
+    pub last_ms: f64,
+    /// 集計範囲の最小実行時間（ミリ秒）
+    pub min_ms: f64,
+    /// 集計範囲の平均実行時間（ミリ秒）
+    pub avg_ms: f64,
+    /// 集計範囲の最大実行時間（ミリ秒）
+    pub max_ms: f64,
+}
+
+/// 1つのシステムの計測値
+#[derive(Debug, Clone)]
+struct Samples {
+    /// 実行フェーズ
+    phase: SystemPhase,
+    /// これまでの実行回数
+    runs: u64,
+    /// 直近の実行時間（ミリ秒、古い順）
+    durations: VecDeque<f64>,
+}
+
+/// システムごとの実行時間
+///
+/// `SystemProcessor`の生成時にリソースとして登録されます。
+/// 同じ名前のシステムは1つにまとめて集計されます。
+#[derive(Debug, Clone)]
+pub struct SystemTimings {
+    /// 集計に使う直近の実行回数
+    window: usize,
+    /// システム名 → 計測値
+    samples: HashMap<&'static str, Samples>,
+    /// 最初に記録された順のシステム名
+    order: Vec<&'static str>,
+}
+
+impl Default for SystemTimings {
+    fn default() -> Self {
+        Self::new(DEFAULT_WINDOW)
+    }
+}
+
+impl SystemTimings {
+    /// 集計に使う直近の実行回数を指定して作成
+    pub fn new(window: usize) -> Self {
+        Self {
+            window: window.max(1),
+            samples: HashMap::new(),
+            order: Vec::new(),
+        }
+    }
+
+    /// 集計に使う直近の実行回数を取得
+    pub fn window(&self) -> usize {
+        self.window
+    }
+
+    /// 集計に使う直近の実行回数を設定
+    ///
+    /// 既に記録された値のうち、範囲外の古いものは捨てられます。
+    pub fn set_window(&mut self, window: usize) {
+        self.window = window.max(1);
+        for samples in self.samples.values_mut() {
+            while samples.durations.len() > self.window {
+                samples.durations.pop_front();
+            }
+        }
+    }
+
+    /// システムの実行時間を記録
+    pub fn record(&mut self, name: &'static str, phase: SystemPhase, duration_ms: f64) {
+        let samples = self.samples.entry(name).or_insert_with(|| Samples {
+            phase,
+            runs: 0,
+            durations: VecDeque::new(),
+        });
+        if samples.runs == 0 {
+            self.order.push(name);
+        }
+        samples.runs += 1;
+        if samples.durations.len() == self.window {
+            samples.durations.pop_front();
+        }
+        samples.durations.push_back(duration_ms);
+    }
+
+    /// システムの集計を取得
+    pub fn get(&self, name: &str) -> Option<SystemTiming> {
+        self.samples.get_key_value(name).map(|(name, samples)| Self::summarize(name, samples))
+    }
+
+    /// すべてのシステムの集計を最初に実行された順に取得
+    pub fn timings(&self) -> Vec<SystemTiming> {
+        self.order.iter()
+            .map(|name| Self::summarize(name, &self.samples[name]))
+            .collect()
+    }
+
+    /// 平均実行時間の長い順に`count`個のシステムの集計を取得
+    pub fn slowest(&self, count: usize) -> Vec<SystemTiming> {
+        let mut timings = self.timings();
+        timings.sort_by(|a, b| b.avg_ms.total_cmp(&a.avg_ms));
+        timings.truncate(count);
+        timings
+    }
+
+    /// すべての記録を消去
+    pub fn clear(&mut self) {
+        self.samples.clear();
+        self.order.clear();
+    }
+
+    /// 計測値を集計
+    fn summarize(name: &str, samples: &Samples) -> SystemTiming {
+        let durations = &samples.durations;
+        let count = durations.len().max(1) as f64;
+        SystemTiming {
+            name: name.to_string(),
+            phase: format!("{:?}", samples.phase),
+            runs: samples.runs,
+            last_ms: durations.back().copied().unwrap_or(0.0),
+            min_ms: durations.iter().copied().reduce(f64::min).unwrap_or(0.0),
+            avg_ms: durations.iter().sum::<f64>() / count,
+            max_ms: durations.iter().copied().reduce(f64::max).unwrap_or(0.0),
+        }
+    }
+}
+
+impl Resource for SystemTimings {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rolling_window() {
+        let mut timings = SystemTimings::new(3);
+        for duration in [5.0, 1.0, 2.0, 3.0] {
+            timings.record("PhysicsSystem", SystemPhase::FixedUpdate, duration);
+        }
+        timings.record("InputSystem", SystemPhase::Input, 0.5);
+
+        let physics = timings.get("PhysicsSystem").unwrap();
+        assert_eq!(physics.runs, 4);
+        assert_eq!(physics.phase, "FixedUpdate");
+        // 最初の5.0msは集計範囲から外れている
+        assert_eq!((physics.min_ms, physics.avg_ms, physics.max_ms, physics.last_ms), (1.0, 2.0, 3.0, 3.0));
+
+        let names: Vec<String> = timings.timings().into_iter().map(|timing| timing.name).collect();
+        assert_eq!(names, vec!["PhysicsSystem", "InputSystem"]);
+        assert_eq!(timings.slowest(1)[0].name, "PhysicsSystem");
+
+        timings.set_window(1);
+        assert_eq!(timings.get("PhysicsSystem").unwrap().avg_ms, 3.0);
+    }
+
+    #[test]
+    fn test_processor_records_each_run() {
+        use crate::ecs::{FunctionSystem, World};
+
+        let mut world = World::new();
+        world.register_system(FunctionSystem::new(SystemPhase::Update, || {
+            std::thread::sleep(std::time::Duration::from_millis(2));
+        }).with_name("SlowSystem")).unwrap();
+        world.update(0.016);
+        world.update(0.016);
+
+        let timing = world.get_resource::<SystemTimings>().unwrap().get("SlowSystem").unwrap();
+        assert_eq!(timing.runs, 2);
+        assert_eq!(timing.phase, "Update");
+        assert!(timing.min_ms >= 2.0 && timing.min_ms <= timing.avg_ms && timing.avg_ms <= timing.max_ms);
+    }
+}
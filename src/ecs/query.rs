@@ -1,14 +1,32 @@
 //! エンティティクエリシステム
-//! 
+//!
 //! このモジュールは、エンティティとコンポーネントのクエリを行うための
 //! 機能を提供します。フィルタリングと変更検出に重点を置いています。
+//!
+//! クエリは`(&Position, &mut Velocity)`のようなタプル型で記述し、
+//! `ComponentManager`の各ストレージを結合して結果を返します。
+//! テーブル格納のコンポーネントを含むクエリは、一致するアーキタイプのテーブルだけを
+//! 先頭から順に走査します。
+//!
+//! ```
+//! let query = world.query::<(Entity, &Position, &mut Velocity)>();
+//! for (entity, position, velocity) in query.iter_mut(&mut world) {
+//!     velocity.x += position.x * 0.1;
+//! }
+//! ```
 
+use std::any::TypeId;
 use std::marker::PhantomData;
-use wasm_bindgen::JsValue;
-use crate::ecs::{Component, Entity, World};
-use crate::ecs::component;
+use std::ptr::NonNull;
+use crate::Error;
+use crate::ecs::{Component, Entity, UnsafeWorldCell, World};
+use crate::ecs::component::{self, ComponentManager, ComponentPtr, StorageType};
 
 /// コンポーネントの変更を検出するフィルタ
+///
+/// 実行中のシステムが前回実行されてから追加または変更された
+/// コンポーネントを持つエンティティのみに一致します。
+/// `get_component_mut`やクエリの可変アクセスが変更として記録されます。
 #[derive(Debug)]
 pub struct Changed<T: Component> {
     _marker: PhantomData<T>,
@@ -22,6 +40,23 @@ impl<T: Component> Default for Changed<T> {
     }
 }
 
+/// コンポーネントの追加を検出するフィルタ
+///
+/// 実行中のシステムが前回実行されてから追加された
+/// コンポーネントを持つエンティティのみに一致します。
+#[derive(Debug)]
+pub struct Added<T: Component> {
+    _marker: PhantomData<T>,
+}
+
+impl<T: Component> Default for Added<T> {
+    fn default() -> Self {
+        Self {
+            _marker: PhantomData,
+        }
+    }
+}
+
 /// 特定のコンポーネントを持つエンティティをフィルタリングするフィルタ
 #[derive(Debug)]
 pub struct With<T: Component> {
@@ -36,102 +71,643 @@ impl<T: Component> Default for With<T> {
     }
 }
 
-/// 特定のコンポーネント型に対するクエリ
+/// 特定のコンポーネントを持たないエンティティをフィルタリングするフィルタ
+#[derive(Debug)]
+pub struct Without<T: Component> {
+    _marker: PhantomData<T>,
+}
+
+impl<T: Component> Default for Without<T> {
+    fn default() -> Self {
+        Self {
+            _marker: PhantomData,
+        }
+    }
+}
+
+/// クエリがアクセスするコンポーネントの一覧
+///
+/// 読み取り・書き込みの対象となるコンポーネント型を記録し、
+/// 同じコンポーネントへの可変参照が重複しないことを保証するために使用します。
+#[derive(Debug, Clone, Default)]
+pub struct QueryAccess {
+    /// 読み取りのみ行うコンポーネント（型ID, 型名）
+    reads: Vec<(TypeId, &'static str)>,
+    /// 書き込みを行うコンポーネント（型ID, 型名）
+    writes: Vec<(TypeId, &'static str)>,
+}
+
+impl QueryAccess {
+    /// 空のアクセス情報を作成
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// コンポーネントの読み取りを記録
+    ///
+    /// # パニック
+    ///
+    /// 同じコンポーネントへの書き込みが既に記録されている場合
+    pub fn add_read<T: Component>(&mut self) {
+        let type_id = TypeId::of::<T>();
+        if self.writes.iter().any(|(id, _)| *id == type_id) {
+            panic!(
+                "クエリ内で{}への可変参照と参照が競合しています",
+                std::any::type_name::<T>()
+            );
+        }
+        if !self.reads.iter().any(|(id, _)| *id == type_id) {
+            self.reads.push((type_id, std::any::type_name::<T>()));
+        }
+    }
+
+    /// コンポーネントの書き込みを記録
+    ///
+    /// # パニック
+    ///
+    /// 同じコンポーネントへの読み取りまたは書き込みが既に記録されている場合
+    pub fn add_write<T: Component>(&mut self) {
+        let type_id = TypeId::of::<T>();
+        if self.writes.iter().any(|(id, _)| *id == type_id)
+            || self.reads.iter().any(|(id, _)| *id == type_id)
+        {
+            panic!(
+                "クエリ内で{}への可変参照が重複しています",
+                std::any::type_name::<T>()
+            );
+        }
+        self.writes.push((type_id, std::any::type_name::<T>()));
+    }
+
+    /// フィルタが参照するコンポーネントを記録
+    ///
+    /// `With<T>`や`Changed<T>`はコンポーネントの有無や変更ティックを読むため、
+    /// 並列実行の競合判定では読み取りとして扱います。既に記録済みの場合は何もしません。
+    pub fn add_filter_read<T: Component>(&mut self) {
+        let type_id = TypeId::of::<T>();
+        if !self.writes.iter().any(|(id, _)| *id == type_id) && !self.reads.iter().any(|(id, _)| *id == type_id) {
+            self.reads.push((type_id, std::any::type_name::<T>()));
+        }
+    }
+
+    /// 読み取り対象のコンポーネント型IDを取得
+    pub fn reads(&self) -> impl Iterator<Item = TypeId> + '_ {
+        self.reads.iter().map(|(id, _)| *id)
+    }
+
+    /// 書き込み対象のコンポーネント型IDを取得
+    pub fn writes(&self) -> impl Iterator<Item = TypeId> + '_ {
+        self.writes.iter().map(|(id, _)| *id)
+    }
+
+    /// 読み取り対象のコンポーネントの型名を取得
+    pub fn read_names(&self) -> impl Iterator<Item = &'static str> + '_ {
+        self.reads.iter().map(|(_, name)| *name)
+    }
+
+    /// 書き込み対象のコンポーネントの型名を取得
+    pub fn write_names(&self) -> impl Iterator<Item = &'static str> + '_ {
+        self.writes.iter().map(|(_, name)| *name)
+    }
+
+    /// 別のアクセス情報と同時に実行しても競合しないかを確認
+    ///
+    /// どちらか一方が書き込むコンポーネントに、もう一方がアクセスしていなければ互換です。
+    pub fn is_compatible(&self, other: &QueryAccess) -> bool {
+        let conflicts = |writes: &[(TypeId, &'static str)], other: &QueryAccess| {
+            writes.iter().any(|(id, _)| {
+                other.reads.iter().any(|(o, _)| o == id) || other.writes.iter().any(|(o, _)| o == id)
+            })
+        };
+        !conflicts(&self.writes, other) && !conflicts(&other.writes, self)
+    }
+}
+
+/// クエリで取得できるデータを表すトレイト
+///
+/// `&T`、`&mut T`、`Option<&T>`、`Entity`およびそれらのタプルに実装されています。
+///
+/// # Safety
+///
+/// `add_access`は`fetch`が行うすべてのアクセスを正しく申告しなければなりません。
+/// 申告されたアクセスが競合しないことを前提に、`fetch`は同時に複数の可変参照を返します。
+pub unsafe trait QueryData {
+    /// 1エンティティ分の取得結果
+    type Item<'w>;
+    /// イテレーション中に保持するストレージへのポインタ
+    type State;
+
+    /// アクセスするコンポーネントを記録
+    fn add_access(access: &mut QueryAccess);
+
+    /// ストレージへのポインタを解決
+    ///
+    /// # Safety
+    ///
+    /// `components`は有効なComponentManagerを指していなければなりません。
+    /// 可変アクセスを含む場合は、可変参照から得たポインタである必要があります。
+    unsafe fn init_state(components: NonNull<ComponentManager>) -> Self::State;
+
+    /// 関数システムのハンドルからストレージへのポインタを解決
+    ///
+    /// 可変アクセスはハンドルが事前に解決した書き込み先だけを使います。
+    ///
+    /// # Safety
+    ///
+    /// `world`は`add_access`で申告したアクセスを許可していなければなりません。
+    unsafe fn init_state_in(world: &UnsafeWorldCell<'_>) -> Self::State;
+
+    /// エンティティがこのクエリの条件を満たすかを確認
+    fn matches(components: &ComponentManager, entity: Entity) -> bool;
+
+    /// 一致するエンティティが必ず持つテーブル格納のコンポーネントを記録
+    ///
+    /// クエリはこれらをすべて含むアーキタイプのテーブルだけを走査します。
+    fn add_required_tables(_components: &ComponentManager, _tables: &mut Vec<TypeId>) {}
+
+    /// エンティティのデータを取得
+    ///
+    /// # Safety
+    ///
+    /// 同じエンティティに対して同時に複数回呼び出してはいけません。
+    unsafe fn fetch<'w>(state: &Self::State, entity: Entity) -> Option<Self::Item<'w>>;
+}
+
+/// 読み取り専用のクエリデータを表すマーカートレイト
+///
+/// # Safety
+///
+/// `QueryData::fetch`が可変アクセスを行わない場合のみ実装できます。
+pub unsafe trait ReadOnlyQueryData: QueryData {}
+
+unsafe impl QueryData for Entity {
+    type Item<'w> = Entity;
+    type State = ();
+
+    fn add_access(_access: &mut QueryAccess) {}
+
+    unsafe fn init_state(_components: NonNull<ComponentManager>) -> Self::State {}
+
+    unsafe fn init_state_in(_world: &UnsafeWorldCell<'_>) -> Self::State {}
+
+    fn matches(_components: &ComponentManager, _entity: Entity) -> bool {
+        true
+    }
+
+    unsafe fn fetch<'w>(_state: &Self::State, entity: Entity) -> Option<Self::Item<'w>> {
+        Some(entity)
+    }
+}
+
+unsafe impl ReadOnlyQueryData for Entity {}
+
+unsafe impl<T: Component> QueryData for &T {
+    type Item<'w> = &'w T;
+    type State = Option<ComponentPtr<T>>;
+
+    fn add_access(access: &mut QueryAccess) {
+        access.add_read::<T>();
+    }
+
+    unsafe fn init_state(components: NonNull<ComponentManager>) -> Self::State {
+        (*components.as_ptr()).component_ptr::<T>()
+    }
+
+    unsafe fn init_state_in(world: &UnsafeWorldCell<'_>) -> Self::State {
+        world.component_ptr::<T>()
+    }
+
+    fn matches(components: &ComponentManager, entity: Entity) -> bool {
+        components.has_component::<T>(entity)
+    }
+
+    fn add_required_tables(components: &ComponentManager, tables: &mut Vec<TypeId>) {
+        add_required_table::<T>(components, tables);
+    }
+
+    unsafe fn fetch<'w>(state: &Self::State, entity: Entity) -> Option<Self::Item<'w>> {
+        let component = state.as_ref()?.get(entity)?;
+        Some(&*component)
+    }
+}
+
+unsafe impl<T: Component> ReadOnlyQueryData for &T {}
+
+unsafe impl<T: Component> QueryData for &mut T {
+    type Item<'w> = &'w mut T;
+    /// ストレージへのポインタと、可変アクセス時に記録する変更ティック
+    type State = Option<(ComponentPtr<T>, u64)>;
+
+    fn add_access(access: &mut QueryAccess) {
+        access.add_write::<T>();
+    }
+
+    unsafe fn init_state(components: NonNull<ComponentManager>) -> Self::State {
+        let components = &mut *components.as_ptr();
+        let tick = components.change_tick();
+        components.component_ptr_mut::<T>().map(|storage| (storage, tick))
+    }
+
+    unsafe fn init_state_in(world: &UnsafeWorldCell<'_>) -> Self::State {
+        world.component_ptr_mut::<T>().map(|storage| (storage, world.change_tick()))
+    }
+
+    fn matches(components: &ComponentManager, entity: Entity) -> bool {
+        components.has_component::<T>(entity)
+    }
+
+    fn add_required_tables(components: &ComponentManager, tables: &mut Vec<TypeId>) {
+        add_required_table::<T>(components, tables);
+    }
+
+    unsafe fn fetch<'w>(state: &Self::State, entity: Entity) -> Option<Self::Item<'w>> {
+        let (storage, tick) = state.as_ref()?;
+        let component = storage.get_mut(entity, *tick)?;
+        Some(&mut *component)
+    }
+}
+
+unsafe impl<Q: QueryData> QueryData for Option<Q> {
+    type Item<'w> = Option<Q::Item<'w>>;
+    type State = Q::State;
+
+    fn add_access(access: &mut QueryAccess) {
+        Q::add_access(access);
+    }
+
+    unsafe fn init_state(components: NonNull<ComponentManager>) -> Self::State {
+        Q::init_state(components)
+    }
+
+    unsafe fn init_state_in(world: &UnsafeWorldCell<'_>) -> Self::State {
+        Q::init_state_in(world)
+    }
+
+    fn matches(_components: &ComponentManager, _entity: Entity) -> bool {
+        true
+    }
+
+    unsafe fn fetch<'w>(state: &Self::State, entity: Entity) -> Option<Self::Item<'w>> {
+        Some(Q::fetch(state, entity))
+    }
+}
+
+unsafe impl<Q: ReadOnlyQueryData> ReadOnlyQueryData for Option<Q> {}
+
+/// `T`がテーブル格納の場合に必須テーブルとして記録
+fn add_required_table<T: Component>(components: &ComponentManager, tables: &mut Vec<TypeId>) {
+    let type_id = TypeId::of::<T>();
+    if components.storage_type::<T>() == StorageType::Table && !tables.contains(&type_id) {
+        tables.push(type_id);
+    }
+}
+
+/// タプル型に対するQueryDataの実装を生成するマクロ
+macro_rules! impl_query_data_tuple {
+    ($(($name:ident, $state:ident)),*) => {
+        unsafe impl<$($name: QueryData),*> QueryData for ($($name,)*) {
+            type Item<'w> = ($($name::Item<'w>,)*);
+            type State = ($($name::State,)*);
+
+            fn add_access(access: &mut QueryAccess) {
+                $($name::add_access(access);)*
+            }
+
+            unsafe fn init_state(components: NonNull<ComponentManager>) -> Self::State {
+                ($($name::init_state(components),)*)
+            }
+
+            unsafe fn init_state_in(world: &UnsafeWorldCell<'_>) -> Self::State {
+                ($($name::init_state_in(world),)*)
+            }
+
+            fn matches(components: &ComponentManager, entity: Entity) -> bool {
+                true $(&& $name::matches(components, entity))*
+            }
+
+            fn add_required_tables(components: &ComponentManager, tables: &mut Vec<TypeId>) {
+                $($name::add_required_tables(components, tables);)*
+            }
+
+            unsafe fn fetch<'w>(state: &Self::State, entity: Entity) -> Option<Self::Item<'w>> {
+                let ($($state,)*) = state;
+                Some(($($name::fetch($state, entity)?,)*))
+            }
+        }
+
+        unsafe impl<$($name: ReadOnlyQueryData),*> ReadOnlyQueryData for ($($name,)*) {}
+    };
+}
+
+impl_query_data_tuple!((A, a));
+impl_query_data_tuple!((A, a), (B, b));
+impl_query_data_tuple!((A, a), (B, b), (C, c));
+impl_query_data_tuple!((A, a), (B, b), (C, c), (D, d));
+impl_query_data_tuple!((A, a), (B, b), (C, c), (D, d), (E, e));
+impl_query_data_tuple!((A, a), (B, b), (C, c), (D, d), (E, e), (F, f));
+impl_query_data_tuple!((A, a), (B, b), (C, c), (D, d), (E, e), (F, f), (G, g));
+impl_query_data_tuple!((A, a), (B, b), (C, c), (D, d), (E, e), (F, f), (G, g), (H, h));
+
+/// クエリ結果を絞り込むフィルタを表すトレイト
+///
+/// `With<T>`、`Without<T>`、`Changed<T>`、`Added<T>`および
+/// それらのタプル（すべての条件を満たす）に実装されています。
+pub trait QueryFilter {
+    /// エンティティがフィルタ条件を満たすかを確認
+    fn matches(components: &ComponentManager, entity: Entity) -> bool;
+
+    /// 一致するエンティティが必ず持つテーブル格納のコンポーネントを記録
+    fn add_required_tables(_components: &ComponentManager, _tables: &mut Vec<TypeId>) {}
+
+    /// 条件の判定で参照するコンポーネントを記録
+    fn add_access(_access: &mut QueryAccess) {}
+}
+
+impl QueryFilter for () {
+    fn matches(_components: &ComponentManager, _entity: Entity) -> bool {
+        true
+    }
+}
+
+impl<T: Component> QueryFilter for With<T> {
+    fn add_access(access: &mut QueryAccess) {
+        access.add_filter_read::<T>();
+    }
+
+    fn matches(components: &ComponentManager, entity: Entity) -> bool {
+        components.has_component::<T>(entity)
+    }
+
+    fn add_required_tables(components: &ComponentManager, tables: &mut Vec<TypeId>) {
+        add_required_table::<T>(components, tables);
+    }
+}
+
+impl<T: Component> QueryFilter for Without<T> {
+    fn add_access(access: &mut QueryAccess) {
+        access.add_filter_read::<T>();
+    }
+
+    fn matches(components: &ComponentManager, entity: Entity) -> bool {
+        !components.has_component::<T>(entity)
+    }
+}
+
+impl<T: Component> QueryFilter for Changed<T> {
+    fn add_access(access: &mut QueryAccess) {
+        access.add_filter_read::<T>();
+    }
+
+    fn matches(components: &ComponentManager, entity: Entity) -> bool {
+        components.get_component_ticks::<T>(entity)
+            .is_some_and(|ticks| ticks.is_changed(components.last_run_tick()))
+    }
+
+    fn add_required_tables(components: &ComponentManager, tables: &mut Vec<TypeId>) {
+        add_required_table::<T>(components, tables);
+    }
+}
+
+impl<T: Component> QueryFilter for Added<T> {
+    fn add_access(access: &mut QueryAccess) {
+        access.add_filter_read::<T>();
+    }
+
+    fn matches(components: &ComponentManager, entity: Entity) -> bool {
+        components.get_component_ticks::<T>(entity)
+            .is_some_and(|ticks| ticks.is_added(components.last_run_tick()))
+    }
+
+    fn add_required_tables(components: &ComponentManager, tables: &mut Vec<TypeId>) {
+        add_required_table::<T>(components, tables);
+    }
+}
+
+/// タプル型に対するQueryFilterの実装を生成するマクロ
+macro_rules! impl_query_filter_tuple {
+    ($($name:ident),*) => {
+        impl<$($name: QueryFilter),*> QueryFilter for ($($name,)*) {
+            fn matches(components: &ComponentManager, entity: Entity) -> bool {
+                true $(&& $name::matches(components, entity))*
+            }
+
+            fn add_required_tables(components: &ComponentManager, tables: &mut Vec<TypeId>) {
+                $($name::add_required_tables(components, tables);)*
+            }
+
+            fn add_access(access: &mut QueryAccess) {
+                $($name::add_access(access);)*
+            }
+        }
+    };
+}
+
+impl_query_filter_tuple!(A);
+impl_query_filter_tuple!(A, B);
+impl_query_filter_tuple!(A, B, C);
+impl_query_filter_tuple!(A, B, C, D);
+
+/// コンポーネントの組み合わせに対するクエリ
 ///
-/// クエリは特定のコンポーネント型に対してエンティティのセットを返します。
-/// フィルタ機能を追加して、結果をさらに絞り込むことも可能です。
+/// クエリは条件を満たすエンティティのリストを保持し、
+/// `iter`/`iter_mut`でワールドから各コンポーネントを取り出します。
+/// エンティティのリストは`run`を呼び出すたびに更新されます。
 ///
 /// # 型パラメータ
 ///
-/// * `T` - クエリ対象のコンポーネント型
-/// * `F` - オプションのフィルタ型（With<T>やChanged<T>など）
-pub struct Query<T, F = ()> {
-    /// コンポーネント型のマーカー
-    component_type: PhantomData<T>,
+/// * `Q` - 取得するデータ（`&T`、`&mut T`、`Option<&T>`、`Entity`やそれらのタプル）
+/// * `F` - オプションのフィルタ型（`With<T>`や`Without<T>`など）
+pub struct Query<Q: QueryData, F: QueryFilter = ()> {
+    /// クエリ型のマーカー
+    component_type: PhantomData<fn() -> Q>,
     /// フィルタ型のマーカー
-    filter_type: PhantomData<F>,
+    filter_type: PhantomData<fn() -> F>,
     /// クエリ結果のエンティティリスト
     entities: Vec<Entity>,
 }
 
-impl<T, F> Default for Query<T, F> {
+impl<Q: QueryData, F: QueryFilter> Default for Query<Q, F> {
     fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<Q: QueryData, F: QueryFilter> Query<Q, F> {
+    /// 新しいクエリを作成
+    ///
+    /// # パニック
+    ///
+    /// 同じコンポーネントへの可変参照が重複している場合（例: `(&mut A, &A)`）
+    pub fn new() -> Self {
+        // アクセスの競合はここで検出しておく
+        Self::access();
+
         Self {
             component_type: PhantomData,
             filter_type: PhantomData,
             entities: Vec::new(),
         }
     }
-}
 
-impl<T, F> Query<T, F> 
-where
-    T: 'static + component::Component, // Componentのトレイト境界を追加
-{
-    /// 新しいクエリを作成
-    pub fn new() -> Self {
-        Self::default()
+    /// このクエリがアクセスするコンポーネントを取得
+    pub fn access() -> QueryAccess {
+        let mut access = QueryAccess::new();
+        Q::add_access(&mut access);
+        access
     }
-    
+
     /// エンティティをクエリ結果に追加
     ///
     /// 指定されたエンティティをクエリ結果のリストに追加します。
-    /// このメソッドは通常、Worldのqueryメソッドから内部的に呼び出されます。
+    /// 既に含まれているエンティティは追加されません。
     ///
     /// # 引数
     ///
     /// * `entity` - 追加するエンティティ
     pub fn add_entity(&mut self, entity: Entity) {
-        self.entities.push(entity);
+        if !self.entities.contains(&entity) {
+            self.entities.push(entity);
+        }
     }
-    
+
     /// クエリを実行し、条件に合うエンティティをリストに収集
-    pub fn run(&mut self, _world: &World) -> Result<(), JsValue> {
-        // 実装は今後拡張
+    ///
+    /// テーブル格納のコンポーネントを必要とする場合は、それらを含むアーキタイプの
+    /// テーブルを行順に走査します。結果もテーブルごとに行順で並ぶため、
+    /// `iter`/`iter_mut`は各テーブルの列を先頭から順にたどります。
+    pub fn run(&mut self, world: &World) -> Result<(), Error> {
+        self.collect(world.components(), world.entities());
         Ok(())
     }
-    
+
+    /// 条件に合うエンティティを`entities`から収集
+    ///
+    /// 関数システムのようにワールドへの参照を持たない場合に使います。
+    pub(crate) fn collect(&mut self, components: &ComponentManager, entities: impl Iterator<Item = Entity>) {
+        self.entities.clear();
+
+        let mut tables = Vec::new();
+        Q::add_required_tables(components, &mut tables);
+        F::add_required_tables(components, &mut tables);
+
+        if tables.is_empty() {
+            for entity in entities {
+                if Q::matches(components, entity) && F::matches(components, entity) {
+                    self.entities.push(entity);
+                }
+            }
+        } else {
+            let archetypes = components.archetypes().iter()
+                .filter(|archetype| tables.iter().all(|&type_id| archetype.contains(type_id)));
+            for archetype in archetypes {
+                for &entity in archetype.entities() {
+                    if Q::matches(components, entity) && F::matches(components, entity) {
+                        self.entities.push(entity);
+                    }
+                }
+            }
+        }
+    }
+
     /// クエリの結果をイテレートする
-    /// 
+    ///
     /// # 引数
     /// * `world` - ワールド
-    /// 
+    ///
     /// # 戻り値
-    /// * `Iterator<Item = (Entity, &T)>` - エンティティとコンポーネントのタプルのイテレータ
-    pub fn iter<'a>(&'a self, world: &'a World) -> Box<dyn Iterator<Item = (Entity, &'a T)> + 'a> {
-        Box::new(self.entities.iter()
-            .filter_map(move |&entity| {
-                world.get_component::<T>(entity)
-                    .map(|component| (entity, component))
-            }))
+    /// * `Iterator<Item = Q::Item>` - 各エンティティの取得結果のイテレータ
+    pub fn iter<'w>(&'w self, world: &'w World) -> impl Iterator<Item = Q::Item<'w>> + 'w
+    where
+        Q: ReadOnlyQueryData,
+    {
+        // 読み取り専用なので共有参照から得たポインタで問題ない
+        let state = unsafe { Q::init_state(NonNull::from(world.components())) };
+        self.entities.iter()
+            .filter_map(move |&entity| unsafe { Q::fetch(&state, entity) })
     }
-    
-    /// クエリ結果を可変でイテレート
-    /// 
+
+    /// クエリの結果を可変でイテレートする
+    ///
+    /// 各エンティティは結果に一度しか現れないため、
+    /// 返される可変参照が重複することはありません。
+    ///
+    /// # 引数
+    /// * `world` - ワールド
+    pub fn iter_mut<'w>(&'w self, world: &'w mut World) -> impl Iterator<Item = Q::Item<'w>> + 'w {
+        let state = unsafe { Q::init_state(NonNull::from(world.components_mut())) };
+        self.entities.iter()
+            .filter_map(move |&entity| unsafe { Q::fetch(&state, entity) })
+    }
+
+    /// 解決済みのストレージへのポインタで結果をイテレートする
+    ///
+    /// # Safety
+    ///
+    /// `state`の指す格納先が有効でなければならず、可変アクセスを含む場合は
+    /// 返された参照が生きている間に同じ要素へ別の参照を作ってはいけません。
+    pub(crate) unsafe fn iter_state<'s>(&'s self, state: &'s Q::State) -> impl Iterator<Item = Q::Item<'s>> + 's {
+        self.entities.iter()
+            .filter_map(move |&entity| Q::fetch(state, entity))
+    }
+
+    /// 特定のエンティティのデータを取得
+    pub fn get<'w>(&self, world: &'w World, entity: Entity) -> Option<Q::Item<'w>>
+    where
+        Q: ReadOnlyQueryData,
+    {
+        let components = world.components();
+        if !F::matches(components, entity) {
+            return None;
+        }
+        unsafe {
+            let state = Q::init_state(NonNull::from(components));
+            Q::fetch(&state, entity)
+        }
+    }
+
+    /// 特定のエンティティのデータを可変で取得
+    pub fn get_mut<'w>(&self, world: &'w mut World, entity: Entity) -> Option<Q::Item<'w>> {
+        if !F::matches(world.components(), entity) {
+            return None;
+        }
+        unsafe {
+            let state = Q::init_state(NonNull::from(world.components_mut()));
+            Q::fetch(&state, entity)
+        }
+    }
+
+    /// クエリ結果のエンティティリストを取得
+    ///
     /// 注: このメソッドは単にエンティティのリストを返します。
     /// 実際のコンポーネントへのアクセスは呼び出し側で行ってください。
     pub fn entities(&self) -> Vec<Entity> {
         self.entities.clone()
     }
-    
+
+    /// エンティティがクエリ結果に含まれるかどうか
+    pub fn contains(&self, entity: Entity) -> bool {
+        self.entities.contains(&entity)
+    }
+
     /// 結果のエンティティ数を取得
     pub fn len(&self) -> usize {
         self.entities.len()
     }
-    
+
     /// 結果が空かどうかチェック
     pub fn is_empty(&self) -> bool {
         self.entities.is_empty()
     }
-    
+
     /// 条件に基づいてエンティティをフィルタリング
-    /// 
+    ///
     /// クエリ結果のエンティティを指定された条件に基づいてフィルタリングします。
-    /// フィルタ関数はエンティティとコンポーネントの参照を受け取り、条件に合致するかどうかをbool値で返します。
-    /// 
+    /// フィルタ関数はエンティティと取得結果を受け取り、条件に合致するかどうかをbool値で返します。
+    ///
     /// # 引数
-    /// 
-    /// * `_filter_fn` - エンティティとコンポーネントを受け取り、条件に合致するかを返す関数
+    ///
+    /// * `world` - ワールド
+    /// * `filter_fn` - エンティティと取得結果を受け取り、条件に合致するかを返す関数
     ///
     /// # 戻り値
     ///
@@ -140,15 +716,21 @@ where
     /// # 例
     ///
     /// ```
-    /// let query = world.query::<NetworkComponent>()
-    ///     .filter(|_, network| network.is_synced && !network.is_remote);
+    /// let mut query = world.query::<&NetworkComponent>();
+    /// query.filter(&world, |_, network| network.is_synced && !network.is_remote);
     /// ```
-    pub fn filter<Fn>(&mut self, _filter_fn: Fn) -> &mut Self 
+    pub fn filter<'w, Fn>(&mut self, world: &'w World, mut filter_fn: Fn) -> &mut Self
     where
-        Fn: FnMut(&Entity, &T) -> bool + 'static,
+        Q: ReadOnlyQueryData,
+        Fn: FnMut(Entity, Q::Item<'w>) -> bool,
     {
-        // 実装はクエリの型に依存するため、ここでは単純な処理のみ行う
-        // 実際には filter_fn を使用したフィルタリングが必要
+        let state = unsafe { Q::init_state(NonNull::from(world.components())) };
+        self.entities.retain(|&entity| {
+            match unsafe { Q::fetch(&state, entity) } {
+                Some(item) => filter_fn(entity, item),
+                None => false,
+            }
+        });
 
         self
     }
@@ -176,12 +758,12 @@ impl<T: 'static + component::Component> EntityComponentQuery<T> {
     pub fn new() -> Self {
         Self::default()
     }
-    
+
     pub fn add_entity(&mut self, entity: Entity) {
         self.entities.push(entity);
     }
-    
-    pub fn filter<F>(&mut self, _filter_fn: F) -> &mut Self 
+
+    pub fn filter<F>(&mut self, _filter_fn: F) -> &mut Self
     where
         F: FnMut(&Entity, &T) -> bool + 'static,
     {
@@ -189,7 +771,7 @@ impl<T: 'static + component::Component> EntityComponentQuery<T> {
         // ここでは単純に自身を返す
         self
     }
-    
+
     pub fn iter<'a>(&'a self, world: &'a World) -> impl Iterator<Item = (Entity, &'a T)> + 'a {
         self.entities.iter()
             .filter_map(move |&entity| {
@@ -197,54 +779,183 @@ impl<T: 'static + component::Component> EntityComponentQuery<T> {
                     .map(|component| (entity, component))
             })
     }
-    
+
     pub fn len(&self) -> usize {
         self.entities.len()
     }
-    
+
     pub fn is_empty(&self) -> bool {
         self.entities.is_empty()
     }
-    
+
     pub fn entities(&self) -> Vec<Entity> {
         self.entities.clone()
     }
 }
 
-// With フィルタを使用したクエリの特殊化
-impl<T, F> Query<T, With<F>> 
-where
-    T: 'static + component::Component,
-    F: component::Component,
-{
-    /// With フィルタを使用してクエリを実行
-    pub fn run_with_filter(&mut self, _world: &World) -> Result<(), JsValue> {
-        // Fコンポーネントを持つエンティティのみをフィルタリング
-        self.entities.clear();
-        
-        // 本来はworld内のすべてのエンティティをイテレートして
-        // T と F の両方のコンポーネントを持つエンティティを収集する実装が必要
-        // 今回は簡易的な実装
-        
-        Ok(())
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, PartialEq)]
+    struct Position {
+        x: f32,
+        y: f32,
     }
-}
 
-// Changed フィルタを使用したクエリの特殊化
-impl<T> Query<T, Changed<T>> 
-where
-    T: 'static + component::Component,
-{
-    /// Changed フィルタを使用してクエリを実行
-    pub fn run_changed(&mut self, _world: &World) -> Result<(), JsValue> {
-        // このフレームで変更されたTコンポーネントを持つエンティティのみをフィルタリング
-        self.entities.clear();
-        
-        // 本来はworld内のすべてのエンティティをイテレートして
-        // 変更されたTコンポーネントを持つエンティティを収集する実装が必要
-        // 変更検出のためには、コンポーネントの前の状態と現在の状態を比較する機構が別途必要
-        // 今回は簡易的な実装
-        
-        Ok(())
+    #[derive(Debug, PartialEq)]
+    struct Velocity {
+        x: f32,
+        y: f32,
+    }
+
+    struct Frozen;
+
+    crate::impl_component!(Position, "Position");
+    crate::impl_component!(Velocity, "Velocity");
+    crate::impl_component!(Frozen, "Frozen");
+
+    fn setup() -> (World, Entity, Entity, Entity) {
+        let mut world = World::new();
+
+        let moving = world.create_entity();
+        world.add_component(moving, Position { x: 0.0, y: 0.0 });
+        world.add_component(moving, Velocity { x: 1.0, y: 2.0 });
+
+        let frozen = world.create_entity();
+        world.add_component(frozen, Position { x: 5.0, y: 5.0 });
+        world.add_component(frozen, Velocity { x: 1.0, y: 1.0 });
+        world.add_component(frozen, Frozen);
+
+        let still = world.create_entity();
+        world.add_component(still, Position { x: 9.0, y: 9.0 });
+
+        (world, moving, frozen, still)
+    }
+
+    #[test]
+    fn test_tuple_query_joins_storages() {
+        let (mut world, moving, frozen, still) = setup();
+
+        let query = world.query::<(Entity, &Position, &mut Velocity)>();
+        assert_eq!(query.len(), 2);
+
+        for (_entity, position, velocity) in query.iter_mut(&mut world) {
+            velocity.x += position.x;
+        }
+
+        assert_eq!(world.get_component::<Velocity>(moving).unwrap().x, 1.0);
+        assert_eq!(world.get_component::<Velocity>(frozen).unwrap().x, 6.0);
+        assert!(world.get_component::<Velocity>(still).is_none());
+    }
+
+    #[test]
+    fn test_optional_and_filters() {
+        let (world, moving, frozen, still) = setup();
+
+        let query = world.query::<(Entity, &Position, Option<&Velocity>)>();
+        assert_eq!(query.len(), 3);
+        let without_velocity: Vec<Entity> = query.iter(&world)
+            .filter(|(_, _, velocity)| velocity.is_none())
+            .map(|(entity, _, _)| entity)
+            .collect();
+        assert_eq!(without_velocity, vec![still]);
+
+        let query = world.query_filtered::<Entity, (With<Velocity>, Without<Frozen>)>();
+        assert_eq!(query.entities(), vec![moving]);
+
+        let query = world.query_filtered::<&Position, With<Frozen>>();
+        assert_eq!(query.get(&world, frozen), Some(&Position { x: 5.0, y: 5.0 }));
+        assert_eq!(query.get(&world, moving), None);
     }
-} 
\ No newline at end of file
+
+    #[test]
+    fn test_filter_retains_matching_entities() {
+        let (world, _moving, _frozen, still) = setup();
+
+        let mut query = world.query::<&Position>();
+        query.filter(&world, |_, position| position.x > 8.0);
+        assert_eq!(query.entities(), vec![still]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_conflicting_access_panics() {
+        let _ = Query::<(&mut Position, &Position)>::new();
+    }
+
+    /// Changed<Position>に一致したエンティティ数を記録するシステム
+    struct ChangedCounter {
+        counts: std::sync::Arc<std::sync::Mutex<Vec<usize>>>,
+    }
+
+    impl crate::ecs::System for ChangedCounter {
+        fn name(&self) -> &'static str {
+            "ChangedCounter"
+        }
+
+        fn phase(&self) -> crate::ecs::SystemPhase {
+            crate::ecs::SystemPhase::Update
+        }
+
+        fn priority(&self) -> crate::ecs::SystemPriority {
+            crate::ecs::SystemPriority::default()
+        }
+
+        fn run(&mut self, world: &mut World, _resources: &mut crate::ecs::ResourceManager, _delta_time: f32) -> Result<(), Error> {
+            let query = world.query_filtered::<Entity, Changed<Position>>();
+            self.counts.lock().unwrap().push(query.len());
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_changed_filter_tracks_system_runs() {
+        let (mut world, moving, _frozen, _still) = setup();
+        let counts = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        world.register_system(ChangedCounter { counts: counts.clone() }).unwrap();
+
+        // 初回はすべての追加が変更として検出される
+        world.update(0.016);
+        // 変更がなければ何も検出されない
+        world.update(0.016);
+        // システム外での変更は次の実行で検出される
+        world.get_component_mut::<Position>(moving).unwrap().x = 3.0;
+        world.update(0.016);
+        // クエリの可変アクセスも変更として記録される
+        for position in world.query_filtered::<&mut Position, With<Frozen>>().iter_mut(&mut world) {
+            position.y = 1.0;
+        }
+        world.update(0.016);
+
+        assert_eq!(*counts.lock().unwrap(), vec![3, 0, 1, 1]);
+    }
+
+    #[test]
+    fn test_added_filter() {
+        let (mut world, moving, _frozen, still) = setup();
+        // 現在のティックを基準にし、以降の変更だけを検出させる
+        let tick = world.components().change_tick();
+        world.components_mut().set_last_run_tick(tick);
+        world.components_mut().increment_change_tick();
+
+        world.add_component(still, Velocity { x: 0.0, y: 0.0 });
+        world.get_component_mut::<Velocity>(moving).unwrap().x = 2.0;
+
+        let added = world.query_filtered::<Entity, Added<Velocity>>();
+        assert_eq!(added.entities(), vec![still]);
+        let changed = world.query_filtered::<Entity, Changed<Velocity>>();
+        assert_eq!(changed.len(), 2);
+    }
+
+    #[test]
+    fn test_access_compatibility() {
+        let read = Query::<&Position>::access();
+        let write = Query::<&mut Position>::access();
+        let other = Query::<&mut Velocity>::access();
+
+        assert!(read.is_compatible(&read));
+        assert!(!read.is_compatible(&write));
+        assert!(write.is_compatible(&other));
+    }
+}
@@ -0,0 +1,215 @@
+//! コンポーネントのリフレクション情報
+//!
+//! serdeでシリアライズできるコンポーネントを名前で登録し、型を知らなくても
+//! 値の読み書きができるようにします。`World::snapshot`や`World::restore`で使用されます。
+//!
+//! コンポーネントは`#[component(serialize)]`属性で登録の対象になり、
+//! 最初に追加されたとき（または`World::register_component`の呼び出し時）に登録されます。
+//!
+//! ```
+//! #[derive(Component, Serialize, Deserialize)]
+//! #[component(serialize)]
+//! pub struct Position {
+//!     x: f32,
+//!     y: f32,
+//! }
+//! ```
+
+use std::any::TypeId;
+use std::collections::{HashMap, HashSet};
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::ecs::component::ComponentManager;
+use crate::ecs::{Component, Entity, SnapshotError, World};
+
+/// デシリアライズ済みで、エンティティへの追加を待っているコンポーネント
+pub type PendingInsert = Box<dyn FnOnce(&mut World, Entity)>;
+
+/// 1つのコンポーネント型のリフレクション情報
+#[derive(Clone, Copy)]
+pub struct ComponentReflection {
+    /// コンポーネント名（`Component::name`）
+    name: &'static str,
+    /// コンポーネントの型ID
+    type_id: TypeId,
+    /// エンティティのコンポーネントを値に変換する関数
+    serialize: fn(&ComponentManager, Entity) -> Option<Result<Value, serde_json::Error>>,
+    /// 値からコンポーネントを作る関数
+    deserialize: fn(Value) -> Result<PendingInsert, serde_json::Error>,
+    /// エンティティからコンポーネントを削除する関数
+    remove: fn(&mut World, Entity) -> bool,
+}
+
+impl ComponentReflection {
+    /// コンポーネント型のリフレクション情報を作成
+    pub fn new<T: Component + Serialize + DeserializeOwned>() -> Self {
+        Self {
+            name: T::name(),
+            type_id: TypeId::of::<T>(),
+            serialize: |components, entity| {
+                components.get_component::<T>(entity).map(serde_json::to_value)
+            },
+            deserialize: |value| {
+                let component: T = serde_json::from_value(value)?;
+                Ok(Box::new(move |world: &mut World, entity| world.add_component(entity, component)))
+            },
+            remove: |world, entity| world.remove_component::<T>(entity),
+        }
+    }
+
+    /// コンポーネント名を取得
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// コンポーネントの型IDを取得
+    pub fn type_id(&self) -> TypeId {
+        self.type_id
+    }
+
+    /// エンティティのコンポーネントを値に変換
+    ///
+    /// # 戻り値
+    ///
+    /// エンティティがこのコンポーネントを持たない場合は`None`
+    pub fn serialize(&self, components: &ComponentManager, entity: Entity) -> Option<Result<Value, serde_json::Error>> {
+        (self.serialize)(components, entity)
+    }
+
+    /// 値からコンポーネントを作成
+    ///
+    /// 返された関数を呼ぶとエンティティにコンポーネントが追加されます。
+    pub fn deserialize(&self, value: Value) -> Result<PendingInsert, serde_json::Error> {
+        (self.deserialize)(value)
+    }
+
+    /// エンティティからコンポーネントを削除
+    ///
+    /// # 戻り値
+    ///
+    /// コンポーネントが存在し削除された場合は`true`
+    pub fn remove(&self, world: &mut World, entity: Entity) -> bool {
+        (self.remove)(world, entity)
+    }
+}
+
+/// シリアライズ可能なコンポーネント型の登録簿
+#[derive(Default)]
+pub struct ComponentRegistry {
+    /// 登録順のリフレクション情報
+    reflections: Vec<ComponentReflection>,
+    /// 型ID → インデックス
+    by_type: HashMap<TypeId, usize>,
+    /// コンポーネント名 → インデックス
+    by_name: HashMap<&'static str, usize>,
+    /// 名前が重複して登録できなかった型ID
+    rejected: HashSet<TypeId>,
+}
+
+impl ComponentRegistry {
+    /// 空の登録簿を作成
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// コンポーネント型を登録
+    ///
+    /// 既に登録済みの型は無視されます。
+    ///
+    /// # エラー
+    ///
+    /// 別の型が同じ名前で登録されている場合（スナップショットで区別できないため登録しません）
+    pub fn register(&mut self, reflection: ComponentReflection) -> Result<(), SnapshotError> {
+        if self.by_type.contains_key(&reflection.type_id) {
+            return Ok(());
+        }
+        if self.by_name.contains_key(reflection.name) {
+            self.rejected.insert(reflection.type_id);
+            return Err(SnapshotError::DuplicateComponentName(reflection.name.to_string()));
+        }
+
+        let index = self.reflections.len();
+        self.by_type.insert(reflection.type_id, index);
+        self.by_name.insert(reflection.name, index);
+        self.reflections.push(reflection);
+        Ok(())
+    }
+
+    /// 名前の重複で登録できなかった型かどうか
+    pub fn is_rejected(&self, type_id: TypeId) -> bool {
+        self.rejected.contains(&type_id)
+    }
+
+    /// 型が登録されているかどうか
+    pub fn contains(&self, type_id: TypeId) -> bool {
+        self.by_type.contains_key(&type_id)
+    }
+
+    /// 名前からリフレクション情報を取得
+    pub fn get_by_name(&self, name: &str) -> Option<&ComponentReflection> {
+        self.by_name.get(name).map(|&index| &self.reflections[index])
+    }
+
+    /// 型IDからリフレクション情報を取得
+    pub fn get(&self, type_id: TypeId) -> Option<&ComponentReflection> {
+        self.by_type.get(&type_id).map(|&index| &self.reflections[index])
+    }
+
+    /// 登録されたすべてのリフレクション情報を登録順に取得
+    pub fn iter(&self) -> impl Iterator<Item = &ComponentReflection> {
+        self.reflections.iter()
+    }
+
+    /// 登録されている型の数を取得
+    pub fn len(&self) -> usize {
+        self.reflections.len()
+    }
+
+    /// 登録されている型がないかどうか
+    pub fn is_empty(&self) -> bool {
+        self.reflections.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+
+    use super::*;
+
+    mod board {
+        use super::*;
+
+        #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Component)]
+        #[component(serialize)]
+        pub struct Score(pub u32);
+    }
+
+    mod player {
+        use super::*;
+
+        #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Component)]
+        #[component(serialize)]
+        pub struct Score(pub u32);
+    }
+
+    #[test]
+    fn test_duplicate_name_is_an_error() {
+        let mut world = World::new();
+        world.register_component::<board::Score>().unwrap();
+        assert_eq!(
+            world.register_component::<player::Score>(),
+            Err(SnapshotError::DuplicateComponentName("Score".to_string()))
+        );
+
+        // 登録できなかった型も追加はでき、スナップショットには先に登録された型だけが入る
+        let entity = world.spawn((board::Score(1), player::Score(2)));
+        assert_eq!(world.get_component::<player::Score>(entity), Some(&player::Score(2)));
+        let registry = world.components().registry();
+        assert_eq!(registry.len(), 1);
+        assert_eq!(registry.get_by_name("Score").unwrap().type_id, std::any::TypeId::of::<board::Score>());
+    }
+}
@@ -0,0 +1,262 @@
+//! コンポーネント削除の記録
+//!
+//! `World::remove_component`や`World::destroy_entity`で削除されたコンポーネントと
+//! エンティティを記録し、他のシステムが後から参照できるようにします。
+//!
+//! 記録は変更検出と同じティックで管理され、各システムは前回の実行以降に
+//! 発生した削除だけを受け取ります。記録は2フレーム分保持された後に破棄されます。
+
+use std::any::TypeId;
+use std::marker::PhantomData;
+
+use crate::ecs::{Component, Entity, World};
+
+/// 削除されたコンポーネントの記録
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RemovedComponent {
+    /// コンポーネントが削除されたエンティティ
+    pub entity: Entity,
+    /// 削除されたコンポーネントの型ID
+    pub component_type: TypeId,
+    /// 削除されたコンポーネントの名前
+    pub component_name: &'static str,
+    /// エンティティの削除に伴う削除かどうか
+    pub despawned: bool,
+    /// 削除されたティック
+    pub tick: u64,
+}
+
+/// 削除されたエンティティの記録
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DespawnedEntity {
+    /// 削除されたエンティティ
+    pub entity: Entity,
+    /// 削除されたティック
+    pub tick: u64,
+}
+
+/// コンポーネントとエンティティの削除ログ
+///
+/// `ComponentManager`が保持し、削除のたびに記録を追加します。
+#[derive(Debug, Default, Clone)]
+pub struct RemovalLog {
+    /// 削除されたコンポーネント
+    components: Vec<RemovedComponent>,
+    /// 削除されたエンティティ
+    despawned: Vec<DespawnedEntity>,
+}
+
+impl RemovalLog {
+    /// 新しい削除ログを作成
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// コンポーネントの削除を記録
+    pub fn record_component(&mut self, removed: RemovedComponent) {
+        self.components.push(removed);
+    }
+
+    /// エンティティの削除を記録
+    pub fn record_despawn(&mut self, entity: Entity, tick: u64) {
+        self.despawned.push(DespawnedEntity { entity, tick });
+    }
+
+    /// `last_run`より後に削除されたコンポーネントを列挙
+    pub fn components_since(&self, last_run: u64) -> impl Iterator<Item = &RemovedComponent> + '_ {
+        self.components.iter().filter(move |removed| removed.tick > last_run)
+    }
+
+    /// `last_run`より後に削除されたエンティティを列挙
+    pub fn despawned_since(&self, last_run: u64) -> impl Iterator<Item = &DespawnedEntity> + '_ {
+        self.despawned.iter().filter(move |despawned| despawned.tick > last_run)
+    }
+
+    /// 指定したティック以前の記録を破棄
+    pub fn clear_before(&mut self, tick: u64) {
+        self.components.retain(|removed| removed.tick > tick);
+        self.despawned.retain(|despawned| despawned.tick > tick);
+    }
+
+    /// すべての記録を破棄
+    pub fn clear(&mut self) {
+        self.components.clear();
+        self.despawned.clear();
+    }
+}
+
+/// 特定の型のコンポーネントが削除されたエンティティの一覧
+///
+/// 実行中のシステムが前回実行されてから`T`が削除されたエンティティを返します。
+/// エンティティ自体が削除された場合も含まれます。
+///
+/// # 例
+///
+/// ```
+/// for entity in world.removed_components::<NetworkComponent>().iter() {
+///     // 同期対象から外す
+/// }
+/// ```
+pub struct RemovedComponents<'w, T: Component> {
+    /// 削除の記録
+    removed: Vec<&'w RemovedComponent>,
+    /// 型情報
+    _marker: PhantomData<T>,
+}
+
+impl<'w, T: Component> RemovedComponents<'w, T> {
+    /// ワールドの削除ログから`T`の削除記録を収集
+    pub fn new(world: &'w World) -> Self {
+        let components = world.components();
+        let type_id = TypeId::of::<T>();
+        let removed = components.removal_log()
+            .components_since(components.last_run_tick())
+            .filter(|removed| removed.component_type == type_id)
+            .collect();
+
+        Self {
+            removed,
+            _marker: PhantomData,
+        }
+    }
+
+    /// コンポーネントが削除されたエンティティを列挙
+    pub fn iter(&self) -> impl Iterator<Item = Entity> + '_ {
+        self.removed.iter().map(|removed| removed.entity)
+    }
+
+    /// 削除の記録をそのまま列挙
+    pub fn records(&self) -> impl Iterator<Item = &RemovedComponent> + '_ {
+        self.removed.iter().copied()
+    }
+
+    /// 削除されたコンポーネントの数を取得
+    pub fn len(&self) -> usize {
+        self.removed.len()
+    }
+
+    /// 削除がなかったかどうか
+    pub fn is_empty(&self) -> bool {
+        self.removed.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ecs::{Resource, ResourceManager, System, SystemPhase, SystemPriority};
+    use crate::Error;
+
+    struct Health(#[allow(dead_code)] u32);
+    struct Marker;
+
+    crate::impl_component!(Health, "Health");
+    crate::impl_component!(Marker, "Marker");
+
+    #[test]
+    fn test_remove_component_is_logged() {
+        let mut world = World::new();
+        let entity = world.create_entity();
+        world.add_component(entity, Health(10));
+        world.add_component(entity, Marker);
+
+        assert!(world.remove_component::<Health>(entity));
+        assert!(!world.remove_component::<Health>(entity));
+
+        let removed = world.removed_components::<Health>();
+        assert_eq!(removed.iter().collect::<Vec<_>>(), vec![entity]);
+        let record = removed.records().next().unwrap();
+        assert_eq!(record.component_name, "Health");
+        assert!(!record.despawned);
+
+        assert!(world.removed_components::<Marker>().is_empty());
+        assert_eq!(world.despawned_entities().count(), 0);
+    }
+
+    #[test]
+    fn test_despawn_is_listed_separately() {
+        let mut world = World::new();
+        let entity = world.create_entity();
+        world.add_component(entity, Health(10));
+        world.destroy_entity(entity);
+
+        assert_eq!(world.despawned_entities().collect::<Vec<_>>(), vec![entity]);
+        let removed = world.removed_components::<Health>();
+        assert_eq!(removed.len(), 1);
+        assert!(removed.records().all(|record| record.despawned));
+    }
+
+    #[test]
+    fn test_log_respects_last_run_tick() {
+        let mut world = World::new();
+        let entity = world.create_entity();
+        world.add_component(entity, Health(10));
+        world.remove_component::<Health>(entity);
+
+        // 削除より後に実行されたシステムには見えない
+        let tick = world.components().change_tick();
+        world.components_mut().set_last_run_tick(tick);
+        assert!(world.removed_components::<Health>().is_empty());
+
+        world.components_mut().set_last_run_tick(0);
+        world.components_mut().removal_log_mut().clear_before(tick);
+        assert!(world.removed_components::<Health>().is_empty());
+    }
+
+    /// 描画対象のエンティティを保持するレイヤー
+    #[derive(Default)]
+    struct Layer(Vec<Entity>);
+
+    impl Resource for Layer {
+        fn as_any(&self) -> &dyn std::any::Any {
+            self
+        }
+
+        fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+            self
+        }
+    }
+
+    /// 毎フレーム、削除されたエンティティをレイヤーから取り除くシステム
+    struct LayerCleanup;
+
+    impl System for LayerCleanup {
+        fn name(&self) -> &'static str {
+            "LayerCleanup"
+        }
+
+        fn phase(&self) -> SystemPhase {
+            SystemPhase::Render
+        }
+
+        fn priority(&self) -> SystemPriority {
+            SystemPriority::new(0)
+        }
+
+        fn run(&mut self, world: &mut World, resources: &mut ResourceManager, _delta_time: f32) -> Result<(), Error> {
+            let despawned: Vec<Entity> = world.despawned_entities().collect();
+            let layer = resources.get_mut::<Layer>().unwrap();
+            layer.0.retain(|entity| !despawned.contains(entity));
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_despawned_entity_leaves_layer_on_next_frame() {
+        let mut world = World::new();
+        world.register_system(LayerCleanup).unwrap();
+        let kept = world.create_entity();
+        let despawned = world.create_entity();
+        world.insert_resource(Layer(vec![kept, despawned]));
+        world.update(0.016);
+
+        world.destroy_entity(despawned);
+        world.update(0.016);
+        assert_eq!(world.get_resource::<Layer>().unwrap().0, vec![kept]);
+
+        // 記録は一度処理されれば次のフレームでは見えない
+        world.get_resource_mut::<Layer>().unwrap().0.push(despawned);
+        world.update(0.016);
+        assert_eq!(world.get_resource::<Layer>().unwrap().0, vec![kept, despawned]);
+    }
+}
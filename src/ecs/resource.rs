@@ -1,6 +1,7 @@
 use std::any::{Any, TypeId};
 use std::collections::HashMap;
 use std::fmt;
+use std::ptr::NonNull;
 
 #[cfg(not(target_arch = "wasm32"))]
 /// リソースの基本トレイト（非Wasm環境用）
@@ -61,6 +62,8 @@ impl fmt::Display for ResourceNotFoundError {
 pub struct ResourceManager {
     /// リソースの型IDと実体のマップ
     resources: HashMap<TypeId, Box<dyn Resource>>,
+    /// リソースの型IDと型名のマップ（インスペクター用）
+    type_names: HashMap<TypeId, &'static str>,
 }
 
 impl ResourceManager {
@@ -68,6 +71,7 @@ impl ResourceManager {
     pub fn new() -> Self {
         Self {
             resources: HashMap::new(),
+            type_names: HashMap::new(),
         }
     }
 
@@ -76,6 +80,7 @@ impl ResourceManager {
     pub fn insert<T: 'static + Send + Sync + Resource>(&mut self, resource: T) {
         let type_id = TypeId::of::<T>();
         self.resources.insert(type_id, Box::new(resource));
+        self.type_names.insert(type_id, std::any::type_name::<T>());
     }
 
     /// リソースを追加（Wasm環境用）
@@ -83,6 +88,7 @@ impl ResourceManager {
     pub fn insert<T: 'static + Resource>(&mut self, resource: T) {
         let type_id = TypeId::of::<T>();
         self.resources.insert(type_id, Box::new(resource));
+        self.type_names.insert(type_id, std::any::type_name::<T>());
     }
 
     /// リソースを取得
@@ -113,10 +119,18 @@ impl ResourceManager {
         self.resources.get_mut(&type_id).and_then(|r| r.as_any_mut().downcast_mut::<T>())
     }
 
+    /// 型IDを指定してリソースへの可変ポインタを取得
+    ///
+    /// 並列実行の前に、システムが書き込みを宣言したリソースを解決するために使います。
+    pub(crate) fn get_ptr_mut(&mut self, type_id: TypeId) -> Option<NonNull<dyn Resource>> {
+        self.resources.get_mut(&type_id).map(|resource| NonNull::from(&mut **resource))
+    }
+
     /// リソースを削除
     #[cfg(not(target_arch = "wasm32"))]
     pub fn remove<T: 'static + Send + Sync + Resource>(&mut self) -> Option<T> {
         let type_id = TypeId::of::<T>();
+        self.type_names.remove(&type_id);
         self.resources.remove(&type_id).map(|boxed_resource| {
             // Box<dyn Resource>からBox<T>に変換
             let raw_ptr = Box::into_raw(boxed_resource);
@@ -131,6 +145,7 @@ impl ResourceManager {
     #[cfg(target_arch = "wasm32")]
     pub fn remove<T: 'static + Resource>(&mut self) -> Option<T> {
         let type_id = TypeId::of::<T>();
+        self.type_names.remove(&type_id);
         self.resources.remove(&type_id).map(|boxed_resource| {
             // Box<dyn Resource>からBox<T>に変換
             let raw_ptr = Box::into_raw(boxed_resource);
@@ -158,6 +173,16 @@ impl ResourceManager {
     /// すべてのリソースをクリア
     pub fn clear(&mut self) {
         self.resources.clear();
+        self.type_names.clear();
+    }
+
+    /// 登録されているリソースの型名を名前順に取得
+    /// 
+    /// 型名はモジュールパスを含む`std::any::type_name`の値です。
+    pub fn type_names(&self) -> Vec<&'static str> {
+        let mut names: Vec<&'static str> = self.type_names.values().copied().collect();
+        names.sort_unstable();
+        names
     }
 
     /// リソースの数を取得
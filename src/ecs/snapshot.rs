@@ -0,0 +1,567 @@
+//! ワールドのスナップショット
+//!
+//! `#[component(serialize)]`で登録されたコンポーネントを、エンティティのハンドルごと
+//! 保存・復元します。セーブデータやロールバック、デバッグ用のダンプに使用します。
+//!
+//! ```
+//! let snapshot = world.snapshot()?;
+//! let json = snapshot.to_json()?;
+//! let bytes = snapshot.to_bytes();
+//!
+//! // 後で復元する
+//! world.restore(&WorldSnapshot::from_bytes(&bytes)?)?;
+//! ```
+//!
+//! バイナリ形式は、先頭のマジックナンバーとバージョンの後に、JSONと同じ構造を
+//! タグ付きの値と可変長整数（LEB128）で詰めて書き込んだものです。
+
+use std::collections::{BTreeMap, HashSet};
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Number, Value};
+
+use crate::ecs::reflect::PendingInsert;
+use crate::ecs::{Entity, World};
+
+/// バイナリ形式の先頭に置くマジックナンバー
+const MAGIC: &[u8; 4] = b"ECSS";
+/// バイナリ形式のバージョン
+const VERSION: u8 = 1;
+/// バイナリ形式で読み込む配列・オブジェクトの入れ子の深さの上限
+const MAX_DEPTH: usize = 64;
+/// 復元・差分の適用で、既存のスロットと生成するエンティティの数を超えて確保できるスロット数
+///
+/// 送信側で生成・削除されたスロットの分だけインデックスが飛ぶことがあるため、余裕を持たせます。
+const SPAWN_SLOT_MARGIN: usize = 1 << 16;
+
+/// スナップショットの作成・復元時のエラー
+#[derive(Debug, Clone, PartialEq)]
+pub enum SnapshotError {
+    /// コンポーネントをシリアライズできなかった
+    Serialize {
+        /// コンポーネント名
+        component: String,
+        /// 原因
+        message: String,
+    },
+    /// コンポーネントをデシリアライズできなかった
+    Deserialize {
+        /// コンポーネント名
+        component: String,
+        /// 原因
+        message: String,
+    },
+    /// 登録されていないコンポーネント名が含まれていた
+    UnknownComponent(String),
+    /// 別の型が同じコンポーネント名で登録されている
+    DuplicateComponentName(String),
+    /// データの形式が正しくない
+    InvalidData(String),
+}
+
+impl fmt::Display for SnapshotError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SnapshotError::Serialize { component, message } => {
+                write!(f, "コンポーネント{}のシリアライズに失敗しました: {}", component, message)
+            }
+            SnapshotError::Deserialize { component, message } => {
+                write!(f, "コンポーネント{}のデシリアライズに失敗しました: {}", component, message)
+            }
+            SnapshotError::UnknownComponent(name) => {
+                write!(f, "コンポーネント{}は登録されていません", name)
+            }
+            SnapshotError::DuplicateComponentName(name) => {
+                write!(f, "コンポーネント名{}は別の型で登録されています", name)
+            }
+            SnapshotError::InvalidData(message) => {
+                write!(f, "スナップショットの形式が正しくありません: {}", message)
+            }
+        }
+    }
+}
+
+impl std::error::Error for SnapshotError {}
+
+#[cfg(feature = "web")]
+impl From<SnapshotError> for wasm_bindgen::JsValue {
+    fn from(error: SnapshotError) -> Self {
+        wasm_bindgen::JsValue::from_str(&error.to_string())
+    }
+}
+
+/// 生成するエンティティのインデックスが、スロットを確保しすぎない範囲にあるか確認
+///
+/// 受け取ったデータのインデックスをそのまま`World::spawn_at`に渡すと、
+/// そこまでのスロットがすべて確保されるため、事前に上限を確認します。
+///
+/// # 引数
+///
+/// * `spawn_count` - 同時に生成するエンティティの数
+pub(crate) fn check_spawn_index(world: &World, entity: Entity, spawn_count: usize) -> Result<(), SnapshotError> {
+    let limit = world.entity_slot_count()
+        .saturating_add(spawn_count)
+        .saturating_add(SPAWN_SLOT_MARGIN);
+    if entity.index() as usize >= limit {
+        return Err(SnapshotError::InvalidData(format!(
+            "{}のインデックスが上限{}を超えています", entity, limit
+        )));
+    }
+    Ok(())
+}
+
+/// 1つのエンティティの保存内容
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EntityRecord {
+    /// エンティティのハンドル
+    pub entity: Entity,
+    /// コンポーネント名 → 値
+    pub components: BTreeMap<String, Value>,
+}
+
+/// ワールドのスナップショット
+///
+/// 生存しているすべてのエンティティと、その登録済みコンポーネントの値を保持します。
+/// 登録されていないコンポーネントは含まれません。
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct WorldSnapshot {
+    /// エンティティの保存内容（インデックス順）
+    pub entities: Vec<EntityRecord>,
+}
+
+impl WorldSnapshot {
+    /// ワールドの現在の状態を保存
+    pub fn capture(world: &World) -> Result<Self, SnapshotError> {
+        let components = world.components();
+        let mut entities = Vec::new();
+        for entity in world.entities() {
+            let mut record = EntityRecord {
+                entity,
+                components: BTreeMap::new(),
+            };
+            for reflection in components.registry().iter() {
+                if let Some(value) = reflection.serialize(components, entity) {
+                    let value = value.map_err(|e| SnapshotError::Serialize {
+                        component: reflection.name().to_string(),
+                        message: e.to_string(),
+                    })?;
+                    record.components.insert(reflection.name().to_string(), value);
+                }
+            }
+            entities.push(record);
+        }
+        Ok(Self { entities })
+    }
+
+    /// ワールドをスナップショットの状態に戻す
+    ///
+    /// 既存のエンティティはすべて削除され、スナップショットのエンティティが
+    /// 同じハンドルで作り直されます。エラーの場合、ワールドは変更されません。
+    pub fn restore(&self, world: &mut World) -> Result<(), SnapshotError> {
+        // 先にすべてのコンポーネントをデシリアライズしてからワールドを変更する
+        let mut pending: Vec<(Entity, Vec<PendingInsert>)> = Vec::with_capacity(self.entities.len());
+        let mut indices = HashSet::with_capacity(self.entities.len());
+        for record in &self.entities {
+            // 同じスロットのエンティティは作り直せないため、ワールドを変更する前に確認する
+            if !indices.insert(record.entity.index()) {
+                return Err(SnapshotError::InvalidData(format!("{}が重複しています", record.entity)));
+            }
+            check_spawn_index(world, record.entity, self.entities.len())?;
+            let mut inserts = Vec::with_capacity(record.components.len());
+            for (name, value) in &record.components {
+                let reflection = world.components().registry().get_by_name(name)
+                    .ok_or_else(|| SnapshotError::UnknownComponent(name.clone()))?;
+                let insert = reflection.deserialize(value.clone()).map_err(|e| SnapshotError::Deserialize {
+                    component: name.clone(),
+                    message: e.to_string(),
+                })?;
+                inserts.push(insert);
+            }
+            pending.push((record.entity, inserts));
+        }
+
+        let existing: Vec<Entity> = world.entities().collect();
+        for entity in existing {
+            world.destroy_entity(entity);
+        }
+        for (entity, inserts) in pending {
+            // 既存のエンティティはすべて削除済みで、インデックスの重複も確認済み
+            world.spawn_at(entity);
+            for insert in inserts {
+                insert(world, entity);
+            }
+        }
+        Ok(())
+    }
+
+    /// JSON文字列に変換
+    pub fn to_json(&self) -> Result<String, SnapshotError> {
+        serde_json::to_string(self).map_err(|e| SnapshotError::InvalidData(e.to_string()))
+    }
+
+    /// JSON文字列から読み込む
+    pub fn from_json(json: &str) -> Result<Self, SnapshotError> {
+        serde_json::from_str(json).map_err(|e| SnapshotError::InvalidData(e.to_string()))
+    }
+
+    /// バイナリ形式に変換
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut buffer = Vec::new();
+        buffer.extend_from_slice(MAGIC);
+        buffer.push(VERSION);
+        write_varint(&mut buffer, self.entities.len() as u64);
+        for record in &self.entities {
+            write_varint(&mut buffer, u64::from(record.entity.index()));
+            write_varint(&mut buffer, u64::from(record.entity.generation()));
+            write_varint(&mut buffer, record.components.len() as u64);
+            for (name, value) in &record.components {
+                write_str(&mut buffer, name);
+                write_value(&mut buffer, value);
+            }
+        }
+        buffer
+    }
+
+    /// バイナリ形式から読み込む
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, SnapshotError> {
+        let mut reader = Reader { bytes, position: 0 };
+        if reader.take(MAGIC.len())? != MAGIC {
+            return Err(SnapshotError::InvalidData("マジックナンバーが一致しません".to_string()));
+        }
+        let version = reader.byte()?;
+        if version != VERSION {
+            return Err(SnapshotError::InvalidData(format!("未対応のバージョン{}です", version)));
+        }
+
+        let count = reader.len()?;
+        let mut entities = Vec::with_capacity(count.min(bytes.len()));
+        for _ in 0..count {
+            let index = reader.u32()?;
+            let generation = reader.u32()?;
+            let component_count = reader.len()?;
+            let mut components = BTreeMap::new();
+            for _ in 0..component_count {
+                let name = reader.string()?;
+                let value = reader.value(0)?;
+                components.insert(name, value);
+            }
+            entities.push(EntityRecord {
+                entity: Entity::from_raw(index, generation),
+                components,
+            });
+        }
+        if reader.position != bytes.len() {
+            return Err(SnapshotError::InvalidData("末尾に余分なデータがあります".to_string()));
+        }
+        Ok(Self { entities })
+    }
+}
+
+/// 値の種類を表すタグ
+mod tag {
+    pub const NULL: u8 = 0;
+    pub const FALSE: u8 = 1;
+    pub const TRUE: u8 = 2;
+    pub const UNSIGNED: u8 = 3;
+    pub const SIGNED: u8 = 4;
+    pub const FLOAT: u8 = 5;
+    pub const STRING: u8 = 6;
+    pub const ARRAY: u8 = 7;
+    pub const OBJECT: u8 = 8;
+}
+
+/// 可変長整数（LEB128）を書き込む
+fn write_varint(buffer: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        buffer.push((value as u8) | 0x80);
+        value >>= 7;
+    }
+    buffer.push(value as u8);
+}
+
+/// 長さ付きの文字列を書き込む
+fn write_str(buffer: &mut Vec<u8>, value: &str) {
+    write_varint(buffer, value.len() as u64);
+    buffer.extend_from_slice(value.as_bytes());
+}
+
+/// タグ付きの値を書き込む
+fn write_value(buffer: &mut Vec<u8>, value: &Value) {
+    match value {
+        Value::Null => buffer.push(tag::NULL),
+        Value::Bool(false) => buffer.push(tag::FALSE),
+        Value::Bool(true) => buffer.push(tag::TRUE),
+        Value::Number(number) => {
+            if let Some(unsigned) = number.as_u64() {
+                buffer.push(tag::UNSIGNED);
+                write_varint(buffer, unsigned);
+            } else if let Some(signed) = number.as_i64() {
+                // ジグザグ符号化で負の値も短く書き込む
+                buffer.push(tag::SIGNED);
+                write_varint(buffer, ((signed << 1) ^ (signed >> 63)) as u64);
+            } else {
+                buffer.push(tag::FLOAT);
+                buffer.extend_from_slice(&number.as_f64().unwrap_or(0.0).to_le_bytes());
+            }
+        }
+        Value::String(string) => {
+            buffer.push(tag::STRING);
+            write_str(buffer, string);
+        }
+        Value::Array(items) => {
+            buffer.push(tag::ARRAY);
+            write_varint(buffer, items.len() as u64);
+            for item in items {
+                write_value(buffer, item);
+            }
+        }
+        Value::Object(fields) => {
+            buffer.push(tag::OBJECT);
+            write_varint(buffer, fields.len() as u64);
+            for (key, item) in fields {
+                write_str(buffer, key);
+                write_value(buffer, item);
+            }
+        }
+    }
+}
+
+/// バイナリ形式の読み込み位置
+struct Reader<'a> {
+    /// 読み込むデータ
+    bytes: &'a [u8],
+    /// 次に読むバイトの位置
+    position: usize,
+}
+
+impl<'a> Reader<'a> {
+    /// 指定したバイト数を読む
+    fn take(&mut self, length: usize) -> Result<&'a [u8], SnapshotError> {
+        let end = self.position.checked_add(length)
+            .filter(|end| *end <= self.bytes.len())
+            .ok_or_else(|| SnapshotError::InvalidData("データが途中で終わっています".to_string()))?;
+        let slice = &self.bytes[self.position..end];
+        self.position = end;
+        Ok(slice)
+    }
+
+    /// 1バイト読む
+    fn byte(&mut self) -> Result<u8, SnapshotError> {
+        Ok(self.take(1)?[0])
+    }
+
+    /// 可変長整数を読む
+    fn varint(&mut self) -> Result<u64, SnapshotError> {
+        let mut value = 0u64;
+        for shift in (0..64).step_by(7) {
+            let byte = self.byte()?;
+            // 10バイト目に残っているのは最上位の1ビットだけ
+            if shift == 63 && byte > 1 {
+                return Err(SnapshotError::InvalidData("可変長整数がu64の範囲を超えています".to_string()));
+            }
+            value |= u64::from(byte & 0x7f) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(SnapshotError::InvalidData("可変長整数が長すぎます".to_string()))
+    }
+
+    /// `u32`の可変長整数を読む
+    fn u32(&mut self) -> Result<u32, SnapshotError> {
+        u32::try_from(self.varint()?).map_err(|e| SnapshotError::InvalidData(e.to_string()))
+    }
+
+    /// 要素数を読む
+    fn len(&mut self) -> Result<usize, SnapshotError> {
+        usize::try_from(self.varint()?).map_err(|e| SnapshotError::InvalidData(e.to_string()))
+    }
+
+    /// 長さ付きの文字列を読む
+    fn string(&mut self) -> Result<String, SnapshotError> {
+        let length = self.len()?;
+        let bytes = self.take(length)?;
+        String::from_utf8(bytes.to_vec()).map_err(|e| SnapshotError::InvalidData(e.to_string()))
+    }
+
+    /// タグ付きの値を読む
+    ///
+    /// `depth`は配列・オブジェクトの入れ子の深さで、`MAX_DEPTH`を超えるとエラーになります。
+    fn value(&mut self, depth: usize) -> Result<Value, SnapshotError> {
+        Ok(match self.byte()? {
+            tag::NULL => Value::Null,
+            tag::FALSE => Value::Bool(false),
+            tag::TRUE => Value::Bool(true),
+            tag::UNSIGNED => Value::Number(self.varint()?.into()),
+            tag::SIGNED => {
+                let zigzag = self.varint()?;
+                let signed = ((zigzag >> 1) as i64) ^ -((zigzag & 1) as i64);
+                Value::Number(signed.into())
+            }
+            tag::FLOAT => {
+                let mut bytes = [0u8; 8];
+                bytes.copy_from_slice(self.take(8)?);
+                Number::from_f64(f64::from_le_bytes(bytes))
+                    .map(Value::Number)
+                    .ok_or_else(|| SnapshotError::InvalidData("数値が有限ではありません".to_string()))?
+            }
+            tag::STRING => Value::String(self.string()?),
+            tag::ARRAY | tag::OBJECT if depth >= MAX_DEPTH => {
+                return Err(SnapshotError::InvalidData(format!("値の入れ子が{}段を超えています", MAX_DEPTH)));
+            }
+            tag::ARRAY => {
+                let length = self.len()?;
+                let mut items = Vec::with_capacity(length.min(self.bytes.len()));
+                for _ in 0..length {
+                    items.push(self.value(depth + 1)?);
+                }
+                Value::Array(items)
+            }
+            tag::OBJECT => {
+                let length = self.len()?;
+                let mut fields = Map::new();
+                for _ in 0..length {
+                    let key = self.string()?;
+                    fields.insert(key, self.value(depth + 1)?);
+                }
+                Value::Object(fields)
+            }
+            other => return Err(SnapshotError::InvalidData(format!("不明なタグ{}です", other))),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ecs::Component;
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Component)]
+    #[component(serialize)]
+    struct Cell {
+        x: i32,
+        y: i32,
+        revealed: bool,
+        label: Option<String>,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Component)]
+    #[component(serialize, storage = "table")]
+    struct Score(f64);
+
+    /// 登録されないコンポーネント
+    struct Cursor;
+
+    crate::impl_component!(Cursor, "Cursor");
+
+    fn setup() -> (World, Entity, Entity) {
+        let mut world = World::new();
+        let first = world.create_entity();
+        world.add_component(first, Cell { x: -3, y: 4, revealed: true, label: Some("地雷".to_string()) });
+        world.add_component(first, Score(1.5));
+        world.add_component(first, Cursor);
+        let removed = world.create_entity();
+        world.destroy_entity(removed);
+        let second = world.create_entity();
+        world.add_component(second, Cell { x: 0, y: 0, revealed: false, label: None });
+        (world, first, second)
+    }
+
+    #[test]
+    fn test_snapshot_round_trip_formats() {
+        let (world, first, second) = setup();
+        let snapshot = world.snapshot().unwrap();
+        assert_eq!(snapshot.entities.len(), 2);
+        // 登録されていないコンポーネントは含まれない
+        assert_eq!(snapshot.entities[0].components.len(), 2);
+        assert_eq!(snapshot.entities[1].entity, second);
+
+        let from_json = WorldSnapshot::from_json(&snapshot.to_json().unwrap()).unwrap();
+        assert_eq!(from_json, snapshot);
+        let from_bytes = WorldSnapshot::from_bytes(&snapshot.to_bytes()).unwrap();
+        assert_eq!(from_bytes, snapshot);
+        assert_eq!(from_bytes.entities[0].entity, first);
+
+        assert!(WorldSnapshot::from_bytes(&snapshot.to_bytes()[..10]).is_err());
+    }
+
+    #[test]
+    fn test_restore_rebuilds_entities() {
+        let (mut world, first, second) = setup();
+        let snapshot = world.snapshot().unwrap();
+
+        // スナップショット後の変更は復元で巻き戻される
+        world.get_component_mut::<Cell>(first).unwrap().revealed = false;
+        world.destroy_entity(second);
+        let extra = world.create_entity();
+        world.add_component(extra, Score(9.0));
+
+        world.restore(&snapshot).unwrap();
+        assert!(world.is_alive(first));
+        assert!(world.is_alive(second));
+        assert!(!world.is_alive(extra));
+        assert!(world.get_component::<Cell>(first).unwrap().revealed);
+        assert_eq!(world.get_component::<Score>(first), Some(&Score(1.5)));
+        assert_eq!(world.query::<&Cell>().len(), 2);
+        assert_eq!(world.snapshot().unwrap(), snapshot);
+    }
+
+    #[test]
+    fn test_restore_unknown_component_leaves_world() {
+        let (mut world, first, _second) = setup();
+        let mut snapshot = world.snapshot().unwrap();
+        snapshot.entities[0].components.insert("Unknown".to_string(), Value::Null);
+
+        let error = world.restore(&snapshot).unwrap_err();
+        assert_eq!(error, SnapshotError::UnknownComponent("Unknown".to_string()));
+        assert!(world.get_component::<Cursor>(first).is_some());
+
+        // 同じスロットのエンティティが2つある場合も、ワールドは変更されない
+        let mut snapshot = world.snapshot().unwrap();
+        let duplicate = snapshot.entities[0].clone();
+        snapshot.entities.push(duplicate);
+        assert!(matches!(world.restore(&snapshot), Err(SnapshotError::InvalidData(_))));
+        assert!(world.get_component::<Cursor>(first).is_some());
+        assert_eq!(world.entities().count(), 2);
+
+        // スロットを確保しすぎるインデックスも拒否する
+        let mut snapshot = world.snapshot().unwrap();
+        snapshot.entities[0].entity = Entity::from_raw(u32::MAX, 0);
+        assert!(matches!(world.restore(&snapshot), Err(SnapshotError::InvalidData(_))));
+        assert_eq!(world.entity_slot_count(), 2);
+        assert_eq!(world.entities().count(), 2);
+    }
+
+    #[test]
+    fn test_from_bytes_rejects_deep_nesting() {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(MAGIC);
+        bytes.push(VERSION);
+        write_varint(&mut bytes, 1);
+        write_varint(&mut bytes, 0);
+        write_varint(&mut bytes, 0);
+        write_varint(&mut bytes, 1);
+        write_str(&mut bytes, "Cell");
+        // 長さ1の配列を深く入れ子にする
+        for _ in 0..10_000 {
+            bytes.extend_from_slice(&[tag::ARRAY, 1]);
+        }
+        bytes.push(tag::NULL);
+
+        let error = WorldSnapshot::from_bytes(&bytes).unwrap_err();
+        assert!(matches!(error, SnapshotError::InvalidData(message) if message.contains("入れ子")));
+    }
+
+    #[test]
+    fn test_varint_rejects_overflow() {
+        let mut bytes = vec![0xff; 9];
+        bytes.push(0x02);
+        let mut reader = Reader { bytes: &bytes, position: 0 };
+        assert!(matches!(reader.varint(), Err(SnapshotError::InvalidData(_))));
+
+        bytes[9] = 0x01;
+        let mut reader = Reader { bytes: &bytes, position: 0 };
+        assert_eq!(reader.varint().unwrap(), u64::MAX);
+    }
+}
@@ -0,0 +1,197 @@
+//! ゲームの状態と状態に応じたシステムの実行制御
+//!
+//! `World::add_state`で登録した状態は`State<S>`リソースとして保持されます。
+//! `run_if(in_state(...))`を指定したシステムは、その状態のときだけ実行されます。
+//! 状態を変更すると、次のフレームの開始時に古い状態の`OnExit`と新しい状態の
+//! `OnEnter`に登録されたシステムが1回ずつ実行されます。
+//!
+//! ```
+//! world.add_state(GameStateType::Playing);
+//! world.register_system_with(
+//!     PhysicsSystem::new(),
+//!     SystemConfig::new().run_if(in_state(GameStateType::Playing)),
+//! )?;
+//! world.register_on_enter(GameStateType::Paused, ShowPauseMenuSystem::new())?;
+//!
+//! // 次のフレームからPhysicsSystemが止まり、ShowPauseMenuSystemが1回実行される
+//! world.set_state(GameStateType::Paused);
+//! ```
+
+use std::any::Any;
+use std::fmt::Debug;
+use std::hash::Hash;
+
+use crate::ecs::{Condition, Resource, ResourceManager};
+
+/// 状態として使用できる型
+#[cfg(not(target_arch = "wasm32"))]
+pub trait States: 'static + Copy + Eq + Hash + Debug + Send + Sync {}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl<T: 'static + Copy + Eq + Hash + Debug + Send + Sync> States for T {}
+
+/// 状態として使用できる型（Wasm環境用）
+#[cfg(target_arch = "wasm32")]
+pub trait States: 'static + Copy + Eq + Hash + Debug {}
+
+#[cfg(target_arch = "wasm32")]
+impl<T: 'static + Copy + Eq + Hash + Debug> States for T {}
+
+/// 現在の状態を保持するリソース
+///
+/// `set`で予約した変更は、`SystemProcessor`がフレームの開始時に適用します。
+/// そのため1フレームの間、すべてのシステムは同じ状態を参照します。
+pub struct State<S: States> {
+    /// 現在の状態
+    current: S,
+    /// 次のフレームで適用する状態
+    next: Option<S>,
+}
+
+impl<S: States> State<S> {
+    /// 初期状態を指定して作成
+    pub fn new(initial: S) -> Self {
+        Self {
+            current: initial,
+            next: None,
+        }
+    }
+
+    /// 現在の状態を取得
+    pub fn get(&self) -> S {
+        self.current
+    }
+
+    /// 状態の変更を予約
+    ///
+    /// 同じフレームに複数回呼ばれた場合は最後の変更が適用されます。
+    pub fn set(&mut self, next: S) {
+        self.next = Some(next);
+    }
+
+    /// 予約されている変更を取得
+    pub fn pending(&self) -> Option<S> {
+        self.next
+    }
+
+    /// 予約された変更を適用し、変更前と変更後の状態を返す
+    ///
+    /// 現在と同じ状態への変更は遷移として扱いません。
+    pub(crate) fn apply_next(&mut self) -> Option<(S, S)> {
+        let next = self.next.take()?;
+        if next == self.current {
+            return None;
+        }
+        let exited = std::mem::replace(&mut self.current, next);
+        Some((exited, next))
+    }
+}
+
+impl<S: States> Resource for State<S> {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+/// 現在の状態が`state`のときだけ満たされる実行条件
+///
+/// 状態型が登録されていない場合は常に満たされません。
+pub fn in_state<S: States>(state: S) -> impl Condition {
+    move |resources: &ResourceManager| {
+        resources.get::<State<S>>().is_some_and(|current| current.get() == state)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ecs::{System, SystemConfig, SystemPhase, SystemPriority, World};
+    use std::sync::{Arc, Mutex};
+    use crate::Error;
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    enum Screen {
+        Playing,
+        Paused,
+    }
+
+    /// 実行されるたびに名前を記録するシステム
+    struct Recorder {
+        name: &'static str,
+        log: Arc<Mutex<Vec<&'static str>>>,
+    }
+
+    impl System for Recorder {
+        fn name(&self) -> &'static str {
+            self.name
+        }
+
+        fn phase(&self) -> SystemPhase {
+            SystemPhase::Update
+        }
+
+        fn priority(&self) -> SystemPriority {
+            SystemPriority::default()
+        }
+
+        fn run(&mut self, _world: &mut World, _resources: &mut ResourceManager, _delta_time: f32) -> Result<(), Error> {
+            self.log.lock().unwrap().push(self.name);
+            Ok(())
+        }
+    }
+
+    fn recorder(name: &'static str, log: &Arc<Mutex<Vec<&'static str>>>) -> Recorder {
+        Recorder { name, log: log.clone() }
+    }
+
+    #[test]
+    fn test_pause_freezes_gameplay_systems() {
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let mut world = World::new();
+        world.add_state(Screen::Playing);
+        world.register_system_with(
+            recorder("Gameplay", &log),
+            SystemConfig::new().run_if(in_state(Screen::Playing)),
+        ).unwrap();
+        world.register_system(recorder("Ui", &log)).unwrap();
+
+        world.update(0.016);
+        world.set_state(Screen::Paused);
+        // 変更は次のフレームまで適用されない
+        assert_eq!(world.state::<Screen>(), Some(Screen::Playing));
+        world.update(0.016);
+        assert_eq!(world.state::<Screen>(), Some(Screen::Paused));
+
+        assert_eq!(*log.lock().unwrap(), vec!["Gameplay", "Ui", "Ui"]);
+    }
+
+    #[test]
+    fn test_enter_and_exit_run_once_per_transition() {
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let mut world = World::new();
+        world.add_state(Screen::Playing);
+        world.register_on_exit(Screen::Playing, recorder("ExitPlaying", &log)).unwrap();
+        world.register_on_enter(Screen::Paused, recorder("EnterPaused", &log)).unwrap();
+        world.register_on_enter(Screen::Playing, recorder("EnterPlaying", &log)).unwrap();
+
+        // 初期状態ではOnEnterは実行されない
+        world.update(0.016);
+        assert!(log.lock().unwrap().is_empty());
+
+        world.set_state(Screen::Paused);
+        world.update(0.016);
+        world.update(0.016);
+        // 同じ状態への変更は遷移ではない
+        world.set_state(Screen::Paused);
+        world.update(0.016);
+        assert_eq!(*log.lock().unwrap(), vec!["ExitPlaying", "EnterPaused"]);
+
+        world.set_state(Screen::Playing);
+        world.update(0.016);
+        assert_eq!(*log.lock().unwrap(), vec!["ExitPlaying", "EnterPaused", "EnterPlaying"]);
+    }
+}
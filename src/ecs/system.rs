@@ -1,9 +1,25 @@
-use std::collections::HashMap;
+use std::any::TypeId;
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+use std::fmt;
+use std::ptr::NonNull;
 
-use super::entity::Entity;
+use super::bundle::BundleComponents;
+use super::entity::{Entity, EntityManager};
 use super::component::{Component, ComponentManager};
+use super::commands::Commands;
+use super::param::SystemAccess;
+#[cfg(not(target_arch = "wasm32"))]
+use super::pool::WorkerPool;
+use super::profile::{self, SystemTimings};
+use super::event::{Event, Events};
+use super::name::NameIndex;
 use super::resource::{Resource, ResourceManager};
-use wasm_bindgen::JsValue;
+use super::state::{State, States};
+use super::time::FixedTime;
+use super::world_cell::UnsafeWorldCell;
+use super::transform::propagate_transforms;
+use crate::Error;
 
 use crate::ecs::World;
 
@@ -14,6 +30,11 @@ pub enum SystemPhase {
     Init,
     /// 入力処理フェーズ
     Input,
+    /// 固定タイムステップの更新フェーズ
+    ///
+    /// `FixedTime`のティックレートで、1フレームに0回以上実行されます。
+    /// システムには経過時間として1ティックの長さが渡されます。
+    FixedUpdate,
     /// 更新フェーズ
     Update,
     /// レンダリングフェーズ
@@ -23,7 +44,7 @@ pub enum SystemPhase {
 }
 
 /// システムの優先度
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
 pub struct SystemPriority(pub u32);
 
 impl SystemPriority {
@@ -32,12 +53,6 @@ impl SystemPriority {
     }
 }
 
-impl Default for SystemPriority {
-    fn default() -> Self {
-        Self(0)
-    }
-}
-
 #[cfg(not(target_arch = "wasm32"))]
 pub trait System: 'static + Send + Sync {
     /// システムの名前を取得
@@ -49,8 +64,56 @@ pub trait System: 'static + Send + Sync {
     /// システムの優先度を取得
     fn priority(&self) -> SystemPriority;
     
+    /// システムのラベルを取得
+    ///
+    /// `before`/`after`で他のシステムから参照される名前です。
+    /// システムの名前も暗黙のラベルとして扱われます。
+    fn labels(&self) -> Vec<&'static str> {
+        Vec::new()
+    }
+
+    /// このシステムより後に実行されるべきラベルを取得
+    fn before(&self) -> Vec<&'static str> {
+        Vec::new()
+    }
+
+    /// このシステムより先に実行されるべきラベルを取得
+    fn after(&self) -> Vec<&'static str> {
+        Vec::new()
+    }
+
+    /// システムがアクセスするリソースとコンポーネントを取得
+    ///
+    /// `None`は宣言がないことを表し、すべてにアクセスする可能性があるものとして扱われます。
+    /// 関数システムでは引数の型から自動で求められます。
+    fn access(&self) -> Option<SystemAccess> {
+        None
+    }
+
+    /// 他のシステムと並列に実行できるかどうか
+    ///
+    /// `true`を返すシステムは、`access`で宣言したアクセスが競合しない他のシステムと
+    /// 同時に`run_unsafe`で実行されます。既定の`false`ではワールドへの排他参照を受け取り、
+    /// 常に単独で実行されます。関数システムは`true`を返します。
+    fn is_parallel(&self) -> bool {
+        false
+    }
+
     /// システムを実行
-    fn run(&mut self, world: &mut World, resources: &mut ResourceManager, delta_time: f32) -> Result<(), JsValue>;
+    fn run(&mut self, world: &mut World, resources: &mut ResourceManager, delta_time: f32) -> Result<(), Error>;
+
+    /// 宣言したアクセスだけを許可するハンドルを使ってシステムを実行
+    ///
+    /// `is_parallel`が`true`のシステムを並列に実行するときに呼ばれます。
+    ///
+    /// # Safety
+    ///
+    /// `world`は`access`で宣言したアクセスを許可していなければならず、
+    /// 同時に実行される他のシステムとアクセスが競合してはいけません。
+    unsafe fn run_unsafe(&mut self, world: &UnsafeWorldCell<'_>, delta_time: f32) -> Result<(), Error> {
+        let _ = (world, delta_time);
+        Err(Error::Other(format!("{}は並列に実行できません", self.name())))
+    }
 }
 
 #[cfg(target_arch = "wasm32")]
@@ -64,44 +127,652 @@ pub trait System: 'static {
     /// システムの優先度を取得
     fn priority(&self) -> SystemPriority;
     
+    /// システムのラベルを取得
+    ///
+    /// `before`/`after`で他のシステムから参照される名前です。
+    /// システムの名前も暗黙のラベルとして扱われます。
+    fn labels(&self) -> Vec<&'static str> {
+        Vec::new()
+    }
+
+    /// このシステムより後に実行されるべきラベルを取得
+    fn before(&self) -> Vec<&'static str> {
+        Vec::new()
+    }
+
+    /// このシステムより先に実行されるべきラベルを取得
+    fn after(&self) -> Vec<&'static str> {
+        Vec::new()
+    }
+
+    /// システムがアクセスするリソースとコンポーネントを取得
+    ///
+    /// `None`は宣言がないことを表し、すべてにアクセスする可能性があるものとして扱われます。
+    /// 関数システムでは引数の型から自動で求められます。
+    fn access(&self) -> Option<SystemAccess> {
+        None
+    }
+
+    /// 他のシステムと並列に実行できるかどうか
+    ///
+    /// `true`を返すシステムは、`access`で宣言したアクセスが競合しない他のシステムと
+    /// 同時に`run_unsafe`で実行されます。既定の`false`ではワールドへの排他参照を受け取り、
+    /// 常に単独で実行されます。関数システムは`true`を返します。
+    fn is_parallel(&self) -> bool {
+        false
+    }
+
     /// システムを実行
-    fn run(&mut self, world: &mut World, resources: &mut ResourceManager, delta_time: f32) -> Result<(), JsValue>;
+    fn run(&mut self, world: &mut World, resources: &mut ResourceManager, delta_time: f32) -> Result<(), Error>;
+
+    /// 宣言したアクセスだけを許可するハンドルを使ってシステムを実行
+    ///
+    /// `is_parallel`が`true`のシステムを並列に実行するときに呼ばれます。
+    ///
+    /// # Safety
+    ///
+    /// `world`は`access`で宣言したアクセスを許可していなければならず、
+    /// 同時に実行される他のシステムとアクセスが競合してはいけません。
+    unsafe fn run_unsafe(&mut self, world: &UnsafeWorldCell<'_>, delta_time: f32) -> Result<(), Error> {
+        let _ = (world, delta_time);
+        Err(Error::Other(format!("{}は並列に実行できません", self.name())))
+    }
+}
+
+/// システムの登録時に追加する実行順序の設定
+///
+/// `System`トレイトで宣言されたラベルや制約に加えて適用されます。
+/// 他のモジュールのシステムとの順序を、そのシステムを変更せずに指定するために使います。
+///
+/// ```
+/// world.register_system_with(
+///     CursorSyncSystem::new(),
+///     SystemConfig::new().label("cursor").after("NetworkReliabilitySystem"),
+/// )?;
+/// ```
+#[derive(Default)]
+pub struct SystemConfig {
+    /// 追加するラベル
+    labels: Vec<&'static str>,
+    /// このシステムより後に実行されるラベル
+    before: Vec<&'static str>,
+    /// このシステムより先に実行されるラベル
+    after: Vec<&'static str>,
+    /// 実行条件
+    conditions: Vec<Box<dyn Condition>>,
+}
+
+impl SystemConfig {
+    /// 空の設定を作成
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// ラベルを追加
+    pub fn label(mut self, label: &'static str) -> Self {
+        self.labels.push(label);
+        self
+    }
+
+    /// 指定したラベルのシステムより先に実行する
+    pub fn before(mut self, label: &'static str) -> Self {
+        self.before.push(label);
+        self
+    }
+
+    /// 指定したラベルのシステムより後に実行する
+    pub fn after(mut self, label: &'static str) -> Self {
+        self.after.push(label);
+        self
+    }
+
+    /// 実行条件を追加
+    ///
+    /// 複数指定した場合は、すべての条件を満たすフレームだけ実行されます。
+    ///
+    /// ```
+    /// SystemConfig::new().run_if(in_state(GameStateType::Playing))
+    /// ```
+    pub fn run_if<C: Condition>(mut self, condition: C) -> Self {
+        self.conditions.push(Box::new(condition));
+        self
+    }
+}
+
+/// システムの実行条件
+///
+/// フェーズの実行中、システムを実行する直前にリソースを参照して評価されます。
+#[cfg(not(target_arch = "wasm32"))]
+pub trait Condition: Fn(&ResourceManager) -> bool + 'static + Send + Sync {}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl<F: Fn(&ResourceManager) -> bool + 'static + Send + Sync> Condition for F {}
+
+/// システムの実行条件（Wasm環境用）
+#[cfg(target_arch = "wasm32")]
+pub trait Condition: Fn(&ResourceManager) -> bool + 'static {}
+
+#[cfg(target_arch = "wasm32")]
+impl<F: Fn(&ResourceManager) -> bool + 'static> Condition for F {}
+
+/// システムの実行順序を決定できなかったことを表すエラー
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ScheduleError {
+    /// `before`/`after`の制約が循環している
+    Cycle {
+        /// 循環が見つかったスケジュール（`Update`や`OnEnter(Paused)`など）
+        schedule: String,
+        /// 循環を構成するシステム名（先頭のシステムが末尾にも入る）
+        systems: Vec<&'static str>,
+    },
+}
+
+impl fmt::Display for ScheduleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ScheduleError::Cycle { schedule, systems } => write!(
+                f,
+                "{}のシステムの実行順序が循環しています: {}",
+                schedule,
+                systems.join(" -> ")
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ScheduleError {}
+
+#[cfg(feature = "web")]
+impl From<ScheduleError> for wasm_bindgen::JsValue {
+    fn from(error: ScheduleError) -> Self {
+        wasm_bindgen::JsValue::from_str(&error.to_string())
+    }
+}
+
+/// 登録済みのシステムと実行状態
+struct SystemEntry {
+    /// システム本体
+    system: Box<dyn System>,
+    /// システムが前回実行されたティック（変更検出の基準）
+    last_run_tick: u64,
+    /// 実行順序の制約
+    ordering: SystemOrdering,
+    /// 実行条件
+    conditions: Vec<Box<dyn Condition>>,
+    /// 宣言されたアクセス（並列実行の判定に使用）
+    access: Option<SystemAccess>,
+    /// 他のシステムと並列に実行できるかどうか（`System::is_parallel`かつアクセスの宣言あり）
+    parallel: bool,
+    /// 今回の実行にかかった時間（ミリ秒、記録後に`None`に戻る）
+    last_run_ms: Option<f64>,
+}
+
+impl SystemEntry {
+    /// すべての実行条件を満たすかどうか
+    fn should_run(&self, resources: &ResourceManager) -> bool {
+        self.conditions.iter().all(|condition| condition(resources))
+    }
+}
+
+/// 1つのシステムの実行順序の制約
+struct SystemOrdering {
+    /// システム名と追加のラベル
+    labels: Vec<&'static str>,
+    /// このシステムより後に実行されるラベル
+    before: Vec<&'static str>,
+    /// このシステムより先に実行されるラベル
+    after: Vec<&'static str>,
+    /// 同順位のときの優先度
+    priority: SystemPriority,
+    /// 登録順（優先度も同じときに使用）
+    sequence: u64,
+}
+
+impl SystemOrdering {
+    /// 指定したラベルを持つかどうか
+    fn has_label(&self, label: &str) -> bool {
+        self.labels.contains(&label)
+    }
+}
+
+/// スケジュール内のシステムを制約に従って並べ、実行順のインデックスを返す
+///
+/// `before`/`after`の制約を満たす順序のうち、優先度（昇順）、登録順の順に
+/// 小さいシステムを先に実行します。存在しないラベルへの制約は無視されます。
+fn sort_systems(schedule: &str, nodes: &[&SystemOrdering]) -> Result<Vec<usize>, ScheduleError> {
+    let count = nodes.len();
+    // dependents[a]はaの後に実行されるシステム
+    let mut dependents = vec![Vec::new(); count];
+    let mut in_degree = vec![0usize; count];
+    for (a, node) in nodes.iter().enumerate() {
+        for (b, other) in nodes.iter().enumerate() {
+            if a == b {
+                continue;
+            }
+            let a_before_b = node.before.iter().any(|label| other.has_label(label))
+                || other.after.iter().any(|label| node.has_label(label));
+            if a_before_b {
+                dependents[a].push(b);
+                in_degree[b] += 1;
+            }
+        }
+    }
+
+    let mut ready: BinaryHeap<_> = (0..count)
+        .filter(|&i| in_degree[i] == 0)
+        .map(|i| Reverse((nodes[i].priority, nodes[i].sequence, i)))
+        .collect();
+    let mut order = Vec::with_capacity(count);
+    while let Some(Reverse((_, _, index))) = ready.pop() {
+        order.push(index);
+        for &dependent in &dependents[index] {
+            in_degree[dependent] -= 1;
+            if in_degree[dependent] == 0 {
+                ready.push(Reverse((nodes[dependent].priority, nodes[dependent].sequence, dependent)));
+            }
+        }
+    }
+
+    if order.len() == count {
+        return Ok(order);
+    }
+
+    // 残ったシステムはすべて未実行の先行システムを持つので、先行システムを
+    // たどっていけば必ず循環に入る
+    let mut path = Vec::new();
+    let mut current = (0..count).find(|&i| in_degree[i] > 0).unwrap_or(0);
+    while !path.contains(&current) {
+        path.push(current);
+        current = (0..count)
+            .find(|&i| in_degree[i] > 0 && dependents[i].contains(&current))
+            .unwrap_or(current);
+    }
+    let start = path.iter().position(|&i| i == current).unwrap_or(0);
+    let mut cycle: Vec<_> = path[start..].iter().rev().copied().collect();
+    // 最後に登録されたシステムから表示する
+    if let Some(newest) = (0..cycle.len()).max_by_key(|&i| nodes[cycle[i]].sequence) {
+        cycle.rotate_left(newest);
+    }
+    let mut systems: Vec<_> = cycle.iter().map(|&i| nodes[i].labels[0]).collect();
+    systems.push(systems[0]);
+    Err(ScheduleError::Cycle { schedule: schedule.to_string(), systems })
+}
+
+/// システムをスケジュールに追加し、制約に従って並べ替える
+///
+/// 制約が循環する場合はスケジュールを変更せずにエラーを返します。
+fn insert_system(schedule: &str, systems: &mut Vec<SystemEntry>, entry: SystemEntry) -> Result<(), ScheduleError> {
+    let order = {
+        let nodes: Vec<_> = systems.iter()
+            .map(|entry| &entry.ordering)
+            .chain(std::iter::once(&entry.ordering))
+            .collect();
+        sort_systems(schedule, &nodes)?
+    };
+
+    systems.push(entry);
+    let mut entries: Vec<_> = systems.drain(..).map(Some).collect();
+    systems.extend(order.into_iter().filter_map(|index| entries[index].take()));
+    Ok(())
+}
+
+/// スケジュールのシステムを順番に実行
+///
+/// 各システムの実行前に変更ティックを進め、そのシステムが前回実行された
+/// ティックを変更検出の基準として設定します。
+/// 実行条件を満たさないシステムは飛ばされ、前回実行されたティックも更新されません。
+///
+/// ネイティブ環境で`workers`が渡された場合、宣言されたアクセスが競合しない
+/// 連続したシステムをまとめて並列に実行します。Wasm環境では常に順番に実行します。
+/// 各システムの実行時間は`SystemTimings`リソースに記録されます。
+fn run_systems(
+    systems: &mut [SystemEntry],
+    world: &mut World,
+    resources: &mut ResourceManager,
+    delta_time: f32,
+    #[cfg(not(target_arch = "wasm32"))] workers: Option<&WorkerPool>,
+) {
+    #[cfg(not(target_arch = "wasm32"))]
+    match workers {
+        Some(workers) => run_systems_parallel(systems, world, resources, delta_time, workers),
+        None => run_systems_sequential(systems, world, resources, delta_time),
+    }
+    #[cfg(target_arch = "wasm32")]
+    run_systems_sequential(systems, world, resources, delta_time);
+
+    // システム外のクエリはすべての変更を検出する
+    world.components_mut().set_last_run_tick(0);
+    record_timings(systems, resources);
+}
+
+/// 実行条件を満たすシステムを順番に実行
+fn run_systems_sequential(systems: &mut [SystemEntry], world: &mut World, resources: &mut ResourceManager, delta_time: f32) {
+    for entry in systems.iter_mut() {
+        if entry.should_run(resources) {
+            run_entry(entry, world, resources, delta_time);
+        }
+    }
+}
+
+/// 今回実行されたシステムの実行時間を`SystemTimings`に記録
+fn record_timings(systems: &mut [SystemEntry], resources: &mut ResourceManager) {
+    let mut timings = resources.get_mut::<SystemTimings>();
+    for entry in systems.iter_mut() {
+        let duration_ms = entry.last_run_ms.take();
+        if let (Some(duration_ms), Some(timings)) = (duration_ms, timings.as_deref_mut()) {
+            timings.record(entry.system.name(), entry.system.phase(), duration_ms);
+        }
+    }
+}
+
+/// 1つのシステムを実行
+fn run_entry(entry: &mut SystemEntry, world: &mut World, resources: &mut ResourceManager, delta_time: f32) {
+    let this_run = world.components_mut().increment_change_tick();
+    world.components_mut().set_last_run_tick(entry.last_run_tick);
+
+    let started = profile::now_ms();
+    if let Err(e) = entry.system.run(world, resources, delta_time) {
+        log::error!("{}の実行中にエラーが発生: {}", entry.system.name(), e.describe(world));
+    }
+    entry.last_run_ms = Some(profile::now_ms() - started);
+
+    entry.last_run_tick = this_run;
+    // システム外での変更が次回の実行で検出されるようティックを進めておく
+    world.components_mut().increment_change_tick();
+}
+
+/// `start`から並列に実行できるシステムを集める
+///
+/// 実行順に並んだシステムを先頭から見て、アクセスが競合せず、互いに
+/// `before`/`after`の制約がないものを`max_threads`個までまとめます。
+/// 並列実行に対応していないシステム（`System`トレイトを直接実装したものなど）は、
+/// アクセスを宣言していても常に単独で実行されます。
+///
+/// # 戻り値
+///
+/// まとめたシステムのインデックスと、次に調べる位置
+fn next_batch(
+    systems: &[SystemEntry],
+    start: usize,
+    max_threads: usize,
+    mut should_run: impl FnMut(&SystemEntry) -> bool,
+) -> (Vec<usize>, usize) {
+    let mut batch: Vec<usize> = Vec::new();
+    let mut index = start;
+    while index < systems.len() {
+        let entry = &systems[index];
+        if !should_run(entry) {
+            index += 1;
+            continue;
+        }
+        if !batch.is_empty() {
+            let joinable = batch.len() < max_threads
+                && batch.iter().all(|&member| can_run_together(&systems[member], entry));
+            if !joinable {
+                break;
+            }
+        }
+        batch.push(index);
+        index += 1;
+        if !entry.parallel {
+            break;
+        }
+    }
+    (batch, index)
+}
+
+/// 2つのシステムを同時に実行できるかどうか
+fn can_run_together(a: &SystemEntry, b: &SystemEntry) -> bool {
+    let ordered = |x: &SystemOrdering, y: &SystemOrdering| {
+        x.before.iter().chain(x.after.iter()).any(|label| y.has_label(label))
+    };
+    if !a.parallel || !b.parallel {
+        return false;
+    }
+    match (&a.access, &b.access) {
+        (Some(a_access), Some(b_access)) => {
+            a_access.is_compatible(b_access) && !ordered(&a.ordering, &b.ordering) && !ordered(&b.ordering, &a.ordering)
+        }
+        _ => false,
+    }
+}
+
+/// 競合しないシステムをまとめて並列に実行
+///
+/// 1つだけのバッチは呼び出し元のスレッドでそのまま実行します。
+#[cfg(not(target_arch = "wasm32"))]
+fn run_systems_parallel(
+    systems: &mut [SystemEntry],
+    world: &mut World,
+    resources: &mut ResourceManager,
+    delta_time: f32,
+    workers: &WorkerPool,
+) {
+    // ワーカーに加えて呼び出し元のスレッドでも1つ実行する
+    let max_threads = workers.threads() + 1;
+    let mut start = 0;
+    while start < systems.len() {
+        let (batch, next) = next_batch(systems, start, max_threads, |entry| entry.should_run(resources));
+        start = next;
+        match batch.as_slice() {
+            [] => {}
+            [index] => run_entry(&mut systems[*index], world, resources, delta_time),
+            _ => run_batch(systems, &batch, world, resources, delta_time, workers),
+        }
+    }
+}
+
+/// バッチのシステムをワーカースレッドに分けて実行し、すべての終了を待つ
+///
+/// 各システムの書き込み先はジョブを渡す前にまとめて解決し、ジョブには
+/// 宣言したアクセスだけを許可する`UnsafeWorldCell`を渡します。ワールドの構造は
+/// バッチの実行中に変わらず、`Commands`やフックの操作はフェーズの終了時に適用されます。
+/// バッチ内のシステムは同じティックで実行されたものとして記録されます。
+#[cfg(not(target_arch = "wasm32"))]
+fn run_batch(
+    systems: &mut [SystemEntry],
+    batch: &[usize],
+    world: &mut World,
+    resources: &mut ResourceManager,
+    delta_time: f32,
+    workers: &WorkerPool,
+) {
+    let this_run = world.components_mut().increment_change_tick();
+    let world_ptr = NonNull::from(&mut *world);
+    let resources_ptr = NonNull::from(&mut *resources);
+
+    // どのシステムも実行を始める前に、すべての書き込み先を解決しておく
+    let members: Vec<(&mut SystemEntry, UnsafeWorldCell<'_>)> = systems.iter_mut()
+        .enumerate()
+        .filter(|(index, _)| batch.contains(index))
+        .map(|(_, entry)| {
+            let access = entry.access.as_ref().expect("並列実行するシステムはアクセスを宣言しています");
+            let cell = unsafe { UnsafeWorldCell::new(world_ptr, resources_ptr, access) };
+            (entry, cell)
+        })
+        .collect();
+
+    type BatchJob<'a> = Box<dyn FnOnce() -> Option<(&'static str, Error)> + Send + 'a>;
+    let jobs: Vec<BatchJob<'_>> = members.into_iter().map(|(entry, cell)| -> BatchJob<'_> {
+        Box::new(move || {
+            crate::ecs::component::set_thread_last_run_tick(Some(entry.last_run_tick));
+            let started = profile::now_ms();
+            // バッチ内のシステムのアクセスが競合しないことはnext_batchで確認済み
+            let result = unsafe { entry.system.run_unsafe(&cell, delta_time) };
+            entry.last_run_ms = Some(profile::now_ms() - started);
+            crate::ecs::component::set_thread_last_run_tick(None);
+            entry.last_run_tick = this_run;
+            result.err().map(|e| (entry.system.name(), e))
+        })
+    }).collect();
+    let errors: Vec<_> = workers.scope(jobs).into_iter().flatten().collect();
+
+    // エンティティの名前を引けるよう、エラーはワールドに戻ってから記録する
+    for (name, e) in errors {
+        log::error!("{}の実行中にエラーが発生: {}", name, e.describe(world));
+    }
+
+    // システム外での変更が次回の実行で検出されるようティックを進めておく
+    world.components_mut().increment_change_tick();
+}
+
+/// 状態遷移の種類
+#[derive(Debug, Clone, Copy)]
+enum Transition {
+    /// 状態に入ったとき
+    Enter,
+    /// 状態から出たとき
+    Exit,
+}
+
+/// 予約された状態の変更を適用する関数
+type StateTransitionFn = fn(&mut SystemProcessor, &mut World, f32);
+
+/// 登録済みイベントのバッファを入れ替える関数
+type EventUpdateFn = fn(&mut ResourceManager);
+
+/// 状態型ごとの遷移時に実行するシステム
+struct StateSchedules<S: States> {
+    /// 状態に入ったときに実行するシステム
+    on_enter: HashMap<S, Vec<SystemEntry>>,
+    /// 状態から出たときに実行するシステム
+    on_exit: HashMap<S, Vec<SystemEntry>>,
+}
+
+impl<S: States> Default for StateSchedules<S> {
+    fn default() -> Self {
+        Self {
+            on_enter: HashMap::new(),
+            on_exit: HashMap::new(),
+        }
+    }
+}
+
+impl<S: States> Resource for StateSchedules<S> {
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+}
+
+/// 同時に実行するシステムの最大数の既定値
+#[cfg(not(target_arch = "wasm32"))]
+fn default_max_threads() -> usize {
+    std::thread::available_parallelism().map(|count| count.get()).unwrap_or(1)
+}
+
+/// 同時に実行するシステムの最大数の既定値（Wasm環境用）
+#[cfg(target_arch = "wasm32")]
+fn default_max_threads() -> usize {
+    1
 }
 
 /// システムプロセッサー
 /// システムの登録と実行を管理する
 pub struct SystemProcessor {
     /// フェーズごとのシステムリスト
-    systems: HashMap<SystemPhase, Vec<Box<dyn System>>>,
+    systems: HashMap<SystemPhase, Vec<SystemEntry>>,
     /// リソース管理
     resource_manager: ResourceManager,
     /// コンポーネント管理
     component_manager: ComponentManager,
+    /// エンティティ管理
+    entity_manager: EntityManager,
+    /// 前回のフレーム開始時のティック（削除ログの破棄に使用）
+    last_frame_tick: u64,
+    /// 登録済みイベントのバッファを入れ替える関数（イベント型IDごと）
+    event_updaters: Vec<(TypeId, EventUpdateFn)>,
+    /// 次に登録されるシステムの登録順
+    next_system_sequence: u64,
+    /// 状態型ごとの遷移時に実行するシステム（`StateSchedules<S>`）
+    state_schedules: ResourceManager,
+    /// 登録済みの状態の遷移を適用する関数（状態型IDごと）
+    state_transitions: Vec<(TypeId, StateTransitionFn)>,
+    /// 同時に実行するシステムの最大数（1なら順番に実行）
+    max_threads: usize,
+    /// 並列実行に使うワーカースレッド（最初の並列実行の前に起動する）
+    #[cfg(not(target_arch = "wasm32"))]
+    workers: Option<WorkerPool>,
 }
 
 impl SystemProcessor {
     /// 新しいシステムプロセッサを作成
     pub fn new() -> Self {
+        let mut resource_manager = ResourceManager::new();
+        resource_manager.insert(Commands::new());
+        resource_manager.insert(FixedTime::default());
+        resource_manager.insert(SystemTimings::default());
+        resource_manager.insert(NameIndex::default());
+
         Self {
             systems: HashMap::new(),
-            resource_manager: ResourceManager::new(),
+            resource_manager,
             component_manager: ComponentManager::new(),
+            entity_manager: EntityManager::new(),
+            last_frame_tick: 0,
+            event_updaters: Vec::new(),
+            next_system_sequence: 0,
+            state_schedules: ResourceManager::new(),
+            state_transitions: Vec::new(),
+            max_threads: default_max_threads(),
+            #[cfg(not(target_arch = "wasm32"))]
+            workers: None,
         }
     }
 
     /// エンティティを作成
     pub fn create_entity(&mut self) -> Entity {
-        Entity::new()
+        self.entity_manager.create_entity()
     }
 
     /// エンティティを削除
+    ///
+    /// 既に削除されたエンティティの場合は何もしません。
     pub fn destroy_entity(&mut self, entity: Entity) {
-        self.component_manager.remove_all_components(entity);
+        if self.entity_manager.destroy_entity(entity) {
+            self.component_manager.despawn_entity(entity);
+        }
+    }
+
+    /// 指定したハンドルのままエンティティを作成
+    ///
+    /// スロットが既に使用中の場合は`false`を返します。
+    pub fn spawn_at(&mut self, entity: Entity) -> bool {
+        self.entity_manager.spawn_at(entity)
+    }
+
+    /// 確保済みのエンティティスロットの数を取得
+    pub fn entity_slot_count(&self) -> usize {
+        self.entity_manager.slot_count()
+    }
+
+    /// エンティティが有効かどうかを確認
+    pub fn is_alive(&self, entity: Entity) -> bool {
+        self.entity_manager.is_alive(entity)
+    }
+
+    /// インデックスを使用している生存中のエンティティを取得
+    pub fn entity_at(&self, index: u32) -> Option<Entity> {
+        self.entity_manager.entity_at(index)
     }
 
     /// コンポーネントを追加
+    ///
+    /// 削除済みのエンティティには追加されません。
     pub fn add_component<T: Component>(&mut self, entity: Entity, component: T) {
-        self.component_manager.add_component(entity, component);
+        if self.entity_manager.is_alive(entity) {
+            self.component_manager.add_component(entity, component);
+        }
+    }
+
+    /// バンドルのコンポーネントをまとめて追加
+    pub fn insert_bundle(&mut self, entity: Entity, components: BundleComponents) {
+        if self.entity_manager.is_alive(entity) {
+            self.component_manager.insert_bundle(entity, components);
+        }
     }
 
     /// コンポーネントを取得
@@ -120,43 +791,324 @@ impl SystemProcessor {
     }
 
     /// システムを登録
-    pub fn register_system<S: System>(&mut self, system: S) {
+    ///
+    /// フェーズ内のシステムは`before`/`after`の制約に従って並べ替えられ、
+    /// 制約で順序が決まらないシステム同士は優先度、登録順の順に実行されます。
+    ///
+    /// # エラー
+    ///
+    /// 制約が循環する場合は`ScheduleError::Cycle`を返し、システムは登録されません。
+    pub fn register_system<S: System>(&mut self, system: S) -> Result<(), ScheduleError> {
+        self.register_system_with(system, SystemConfig::new())
+    }
+
+    /// 実行順序の設定を追加してシステムを登録
+    ///
+    /// # エラー
+    ///
+    /// 制約が循環する場合は`ScheduleError::Cycle`を返し、システムは登録されません。
+    pub fn register_system_with<S: System>(&mut self, system: S, config: SystemConfig) -> Result<(), ScheduleError> {
         let phase = system.phase();
-        let systems = self.systems.entry(phase).or_insert_with(Vec::new);
-        
-        // 優先度に基づいてシステムを挿入
-        let priority = system.priority();
-        let index = systems.binary_search_by_key(&priority, |s| s.priority())
-            .unwrap_or_else(|e| e);
-        
-        systems.insert(index, Box::new(system));
+        let entry = self.new_entry(system, config);
+        let systems = self.systems.entry(phase).or_default();
+        insert_system(&format!("{:?}", phase), systems, entry)
+    }
+
+    /// 登録するシステムの実行状態を作成
+    fn new_entry<S: System>(&mut self, system: S, config: SystemConfig) -> SystemEntry {
+        let mut labels = vec![system.name()];
+        labels.extend(system.labels());
+        labels.extend(config.labels);
+        let mut before = system.before();
+        before.extend(config.before);
+        let mut after = system.after();
+        after.extend(config.after);
+
+        let sequence = self.next_system_sequence;
+        self.next_system_sequence += 1;
+        let access = system.access();
+        SystemEntry {
+            last_run_tick: 0,
+            ordering: SystemOrdering {
+                labels,
+                before,
+                after,
+                priority: system.priority(),
+                sequence,
+            },
+            conditions: config.conditions,
+            parallel: system.is_parallel() && access.is_some(),
+            access,
+            last_run_ms: None,
+            system: Box::new(system),
+        }
+    }
+
+    /// 状態型を登録
+    ///
+    /// `State<S>`リソースを追加し、フレームの開始時に状態の変更が適用されるようにします。
+    /// 初期状態の`OnEnter`システムは実行されません。
+    /// 既に登録済みの場合は何もしません。
+    pub fn add_state<S: States>(&mut self, initial: S) {
+        let type_id = TypeId::of::<S>();
+        if self.state_transitions.iter().any(|(id, _)| *id == type_id) {
+            return;
+        }
+        self.resource_manager.insert(State::new(initial));
+        self.state_transitions.push((type_id, Self::apply_state_transition::<S>));
+    }
+
+    /// 状態の変更を予約
+    ///
+    /// 変更は次のフレームの開始時に適用されます。
+    /// 状態型が登録されていない場合は何もしません。
+    pub fn set_state<S: States>(&mut self, next: S) {
+        match self.resource_manager.get_mut::<State<S>>() {
+            Some(state) => state.set(next),
+            None => log::warn!("状態{}が登録されていません", std::any::type_name::<S>()),
+        }
+    }
+
+    /// 現在の状態を取得
+    pub fn state<S: States>(&self) -> Option<S> {
+        self.resource_manager.get::<State<S>>().map(|state| state.get())
+    }
+
+    /// 状態に入ったときに1回実行するシステムを登録
+    ///
+    /// システムの`phase`は使われません。同じ状態に登録されたシステム同士は
+    /// 通常のフェーズと同じく`before`/`after`と優先度に従って実行されます。
+    ///
+    /// # エラー
+    ///
+    /// 制約が循環する場合は`ScheduleError::Cycle`を返し、システムは登録されません。
+    pub fn register_on_enter<S: States, T: System>(&mut self, state: S, system: T) -> Result<(), ScheduleError> {
+        self.register_transition_system(Transition::Enter, state, system)
+    }
+
+    /// 状態から出たときに1回実行するシステムを登録
+    ///
+    /// # エラー
+    ///
+    /// 制約が循環する場合は`ScheduleError::Cycle`を返し、システムは登録されません。
+    pub fn register_on_exit<S: States, T: System>(&mut self, state: S, system: T) -> Result<(), ScheduleError> {
+        self.register_transition_system(Transition::Exit, state, system)
+    }
+
+    /// 状態遷移時のシステムを登録
+    fn register_transition_system<S: States, T: System>(
+        &mut self,
+        transition: Transition,
+        state: S,
+        system: T,
+    ) -> Result<(), ScheduleError> {
+        let entry = self.new_entry(system, SystemConfig::new());
+        if !self.state_schedules.contains::<StateSchedules<S>>() {
+            self.state_schedules.insert(StateSchedules::<S>::default());
+        }
+        let schedules = self.state_schedules.get_mut::<StateSchedules<S>>()
+            .expect("StateSchedulesの取得に失敗しました");
+        let systems = match transition {
+            Transition::Enter => schedules.on_enter.entry(state).or_default(),
+            Transition::Exit => schedules.on_exit.entry(state).or_default(),
+        };
+        insert_system(&format!("On{:?}({:?})", transition, state), systems, entry)
+    }
+
+    /// 予約された状態の変更を適用し、`OnExit`と`OnEnter`のシステムを実行
+    fn apply_state_transition<S: States>(&mut self, world: &mut World, delta_time: f32) {
+        let (exited, entered) = match self.resource_manager.get_mut::<State<S>>() {
+            Some(state) => match state.apply_next() {
+                Some(transition) => transition,
+                None => return,
+            },
+            None => return,
+        };
+        log::debug!("状態遷移: {:?} → {:?}", exited, entered);
+
+        // 実行中のシステムが遷移時のシステムを登録しても影響しないよう一時的に取り出す
+        let mut schedules = match self.state_schedules.remove::<StateSchedules<S>>() {
+            Some(schedules) => schedules,
+            None => return,
+        };
+        #[cfg(not(target_arch = "wasm32"))]
+        self.prepare_workers();
+        for systems in [schedules.on_exit.get_mut(&exited), schedules.on_enter.get_mut(&entered)].into_iter().flatten() {
+            #[cfg(not(target_arch = "wasm32"))]
+            run_systems(systems, world, &mut self.resource_manager, delta_time, self.workers.as_ref());
+            #[cfg(target_arch = "wasm32")]
+            run_systems(systems, world, &mut self.resource_manager, delta_time);
+        }
+        self.state_schedules.insert(schedules);
+
+        self.apply_commands(world);
+    }
+
+    /// 指定したフェーズのシステム名を実行順に取得
+    pub fn system_names(&self, phase: SystemPhase) -> Vec<&'static str> {
+        self.systems.get(&phase)
+            .map(|systems| systems.iter().map(|entry| entry.system.name()).collect())
+            .unwrap_or_default()
+    }
+
+    /// 指定したフェーズのシステム名と宣言されたアクセスを実行順に取得
+    pub fn system_access(&self, phase: SystemPhase) -> Vec<(&'static str, Option<SystemAccess>)> {
+        self.systems.get(&phase)
+            .map(|systems| systems.iter().map(|entry| (entry.system.name(), entry.access.clone())).collect())
+            .unwrap_or_default()
+    }
+
+    /// 指定したフェーズで同時に実行されるシステムのまとまりを実行順に取得
+    ///
+    /// 実行条件は考慮しません。順番に実行する設定では、各システムが単独のまとまりになります。
+    pub fn parallel_batches(&self, phase: SystemPhase) -> Vec<Vec<&'static str>> {
+        let systems = match self.systems.get(&phase) {
+            Some(systems) => systems,
+            None => return Vec::new(),
+        };
+        let max_threads = if cfg!(target_arch = "wasm32") { 1 } else { self.max_threads };
+
+        let mut batches = Vec::new();
+        let mut start = 0;
+        while start < systems.len() {
+            let (batch, next) = next_batch(systems, start, max_threads, |_| true);
+            batches.push(batch.into_iter().map(|index| systems[index].system.name()).collect());
+            start = next;
+        }
+        batches
+    }
+
+    /// 同時に実行するシステムの最大数を取得
+    pub fn max_threads(&self) -> usize {
+        self.max_threads
+    }
+
+    /// 同時に実行するシステムの最大数を設定
+    ///
+    /// 既定値は利用可能なCPU数です。1を指定するとすべて順番に実行します。
+    /// Wasm環境では設定にかかわらず順番に実行します。
+    pub fn set_max_threads(&mut self, max_threads: usize) {
+        self.max_threads = max_threads.max(1);
+        // ワーカーの数が合わなくなったら、次の並列実行の前に起動し直す
+        #[cfg(not(target_arch = "wasm32"))]
+        if self.workers.as_ref().is_some_and(|workers| workers.threads() + 1 != self.max_threads) {
+            self.workers = None;
+        }
+    }
+
+    /// 並列に実行する設定なら、ワーカースレッドを起動しておく
+    ///
+    /// ワーカーは呼び出し元のスレッドと合わせて`max_threads`個のシステムを同時に実行します。
+    /// 一度起動したワーカーは、`max_threads`が変わるまですべてのフレームで使い回されます。
+    #[cfg(not(target_arch = "wasm32"))]
+    fn prepare_workers(&mut self) {
+        if self.max_threads > 1 && self.workers.is_none() {
+            self.workers = Some(WorkerPool::new(self.max_threads - 1));
+        }
     }
 
     /// 特定のフェーズのシステムを実行
+    ///
+    /// 実行条件を満たすシステムだけを順番に実行します。
+    /// フェーズの終了時に、システムが`Commands`に積んだ操作を適用します。
     pub fn update_phase(&mut self, phase: SystemPhase, world: &mut World, delta_time: f32) {
+        #[cfg(not(target_arch = "wasm32"))]
+        self.prepare_workers();
         if let Some(systems) = self.systems.get_mut(&phase) {
-            for system in systems.iter_mut() {
-                if let Err(e) = system.run(world, &mut self.resource_manager, delta_time) {
-                    log::error!("システムの実行中にエラーが発生: {:?}", e);
-                }
-            }
+            #[cfg(not(target_arch = "wasm32"))]
+            run_systems(systems, world, &mut self.resource_manager, delta_time, self.workers.as_ref());
+            #[cfg(target_arch = "wasm32")]
+            run_systems(systems, world, &mut self.resource_manager, delta_time);
+        }
+
+        self.apply_commands(world);
+    }
+
+    /// `Commands`に積まれた操作をワールドに適用
+    ///
+    /// コンポーネントのフックから積まれた未適用の操作も先に適用します。
+    /// 適用中に積まれた操作は次の同期ポイントで適用されます。
+    pub fn apply_commands(&mut self, world: &mut World) {
+        world.apply_hook_commands();
+        let mut commands = match self.resource_manager.get_mut::<Commands>() {
+            Some(commands) if !commands.is_empty() => std::mem::take(commands),
+            _ => return,
+        };
+        commands.apply(world);
+    }
+
+    /// イベント型を登録
+    ///
+    /// `Events<T>`リソースを追加し、フレームの開始時にバッファが入れ替わるようにします。
+    /// 既に登録済みの場合は何もしません。
+    pub fn add_event<T: Event>(&mut self) {
+        let type_id = TypeId::of::<T>();
+        if self.event_updaters.iter().any(|(id, _)| *id == type_id) {
+            return;
         }
+        if !self.resource_manager.contains::<Events<T>>() {
+            self.resource_manager.insert(Events::<T>::new());
+        }
+        self.event_updaters.push((type_id, Events::<T>::update_resource));
     }
 
     /// すべてのシステムを実行
+    ///
+    /// フレームの開始時に、すべてのシステムが参照し終えた削除ログと
+    /// 2フレーム前に送信されたイベントを破棄し、予約された状態の変更を適用します。
+    /// `FixedUpdate`フェーズは`FixedTime`に蓄積された時間に応じて繰り返し実行されます。
+    /// `Render`フェーズの直前に`GlobalTransform`が更新されます。
     pub fn update(&mut self, world: &mut World, delta_time: f32) {
+        // 前のフレームより前の削除記録は全システムが一度ずつ参照済み
+        let frame_tick = world.components().change_tick();
+        world.components_mut().removal_log_mut().clear_before(self.last_frame_tick);
+        self.last_frame_tick = frame_tick;
+
+        for (_, update_events) in self.event_updaters.iter() {
+            update_events(&mut self.resource_manager);
+        }
+
+        for index in 0..self.state_transitions.len() {
+            let (_, apply_transition) = self.state_transitions[index];
+            apply_transition(self, world, delta_time);
+        }
+
         // 各フェーズを順番に実行
         for phase in [
             SystemPhase::Init,
             SystemPhase::Input,
+            SystemPhase::FixedUpdate,
             SystemPhase::Update,
             SystemPhase::Render,
             SystemPhase::Shutdown,
         ] {
+            if phase == SystemPhase::FixedUpdate {
+                self.run_fixed_update(world, delta_time);
+                continue;
+            }
+            if phase == SystemPhase::Render {
+                // 描画の前に、Updateフェーズで動いた親の位置を子に反映する
+                propagate_transforms(world);
+            }
             self.update_phase(phase, world, delta_time);
         }
     }
 
+    /// 蓄積された時間の分だけ`FixedUpdate`フェーズを実行
+    fn run_fixed_update(&mut self, world: &mut World, delta_time: f32) {
+        let (ticks, timestep) = match self.resource_manager.get_mut::<FixedTime>() {
+            Some(time) => (time.accumulate(f64::from(delta_time)), time.timestep() as f32),
+            None => return,
+        };
+
+        for _ in 0..ticks {
+            self.update_phase(SystemPhase::FixedUpdate, world, timestep);
+            if let Some(time) = self.resource_manager.get_mut::<FixedTime>() {
+                time.advance_tick();
+            }
+        }
+    }
+
     /// リソースを追加または更新
     #[cfg(not(target_arch = "wasm32"))]
     pub fn insert_resource<T: 'static + Send + Sync + Resource>(&mut self, resource: T) {
@@ -207,7 +1159,42 @@ impl SystemProcessor {
 
     /// 全エンティティを取得するイテレータを返す
     pub fn entities(&self) -> impl Iterator<Item = Entity> + '_ {
-        self.component_manager.entities()
+        self.entity_manager.entities()
+    }
+
+    /// コンポーネントマネージャーへの参照を取得
+    pub fn components(&self) -> &ComponentManager {
+        &self.component_manager
+    }
+
+    /// リソースマネージャーへの参照を取得
+    pub fn resources(&self) -> &ResourceManager {
+        &self.resource_manager
+    }
+
+    /// コンポーネントマネージャーへの可変参照を取得
+    pub fn components_mut(&mut self) -> &mut ComponentManager {
+        &mut self.component_manager
+    }
+
+    /// コンポーネントマネージャーとエンティティマネージャーへのポインタを取得
+    ///
+    /// プロセッサー全体への参照を作らずにフィールドを指します。
+    ///
+    /// # Safety
+    ///
+    /// `processor`は有効なSystemProcessorを指していなければなりません。
+    pub(crate) unsafe fn managers_ptr(processor: *mut Self) -> (NonNull<ComponentManager>, NonNull<EntityManager>) {
+        (
+            NonNull::new_unchecked(std::ptr::addr_of_mut!((*processor).component_manager)),
+            NonNull::new_unchecked(std::ptr::addr_of_mut!((*processor).entity_manager)),
+        )
+    }
+}
+
+impl Default for SystemProcessor {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
@@ -253,4 +1240,318 @@ impl<S: System> SystemBuilder<S> {
     pub fn build(self) -> S {
         self.system
     }
-} 
\ No newline at end of file
+} 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ecs::{Component, FunctionSystem, Res, Resource, SystemQuery};
+
+    /// 順序の確認用に宣言だけを持つシステム
+    struct Ordered {
+        name: &'static str,
+        priority: u32,
+        labels: Vec<&'static str>,
+        before: Vec<&'static str>,
+        after: Vec<&'static str>,
+    }
+
+    impl Ordered {
+        fn new(name: &'static str, priority: u32) -> Self {
+            Self {
+                name,
+                priority,
+                labels: Vec::new(),
+                before: Vec::new(),
+                after: Vec::new(),
+            }
+        }
+    }
+
+    impl System for Ordered {
+        fn name(&self) -> &'static str {
+            self.name
+        }
+
+        fn phase(&self) -> SystemPhase {
+            SystemPhase::Update
+        }
+
+        fn priority(&self) -> SystemPriority {
+            SystemPriority::new(self.priority)
+        }
+
+        fn labels(&self) -> Vec<&'static str> {
+            self.labels.clone()
+        }
+
+        fn before(&self) -> Vec<&'static str> {
+            self.before.clone()
+        }
+
+        fn after(&self) -> Vec<&'static str> {
+            self.after.clone()
+        }
+
+        fn run(&mut self, _world: &mut World, _resources: &mut ResourceManager, _delta_time: f32) -> Result<(), Error> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_priority_only_breaks_ties() {
+        let mut processor = SystemProcessor::new();
+        processor.register_system(Ordered::new("Late", 10)).unwrap();
+        processor.register_system(Ordered::new("Early", 0)).unwrap();
+        processor.register_system(Ordered::new("AlsoLate", 10)).unwrap();
+        assert_eq!(processor.system_names(SystemPhase::Update), vec!["Early", "Late", "AlsoLate"]);
+
+        // 制約は優先度より優先される
+        let mut reliability = Ordered::new("Reliability", 20);
+        reliability.before.push("Late");
+        processor.register_system(reliability).unwrap();
+        assert_eq!(
+            processor.system_names(SystemPhase::Update),
+            vec!["Early", "AlsoLate", "Reliability", "Late"]
+        );
+    }
+
+    #[test]
+    fn test_labels_and_config_constraints() {
+        let mut processor = SystemProcessor::new();
+        let mut sync = Ordered::new("Sync", 0);
+        sync.labels.push("network");
+        processor.register_system(sync).unwrap();
+        // まだ登録されていないラベルへの制約も後から反映される
+        processor.register_system_with(
+            Ordered::new("Cursor", 0),
+            SystemConfig::new().after("network").before("Render"),
+        ).unwrap();
+        processor.register_system_with(
+            Ordered::new("Prediction", 0),
+            SystemConfig::new().label("network"),
+        ).unwrap();
+        processor.register_system(Ordered::new("Render", 0)).unwrap();
+
+        assert_eq!(
+            processor.system_names(SystemPhase::Update),
+            vec!["Sync", "Prediction", "Cursor", "Render"]
+        );
+    }
+
+    #[test]
+    fn test_cycle_is_rejected() {
+        let mut processor = SystemProcessor::new();
+        let mut physics = Ordered::new("Physics", 0);
+        physics.after.push("Input");
+        processor.register_system(physics).unwrap();
+        let mut animation = Ordered::new("Animation", 0);
+        animation.after.push("Physics");
+        processor.register_system(animation).unwrap();
+
+        let mut input = Ordered::new("Input", 0);
+        input.after.push("Animation");
+        let error = processor.register_system(input).unwrap_err();
+        assert_eq!(error, ScheduleError::Cycle {
+            schedule: "Update".to_string(),
+            systems: vec!["Input", "Physics", "Animation", "Input"],
+        });
+        assert!(error.to_string().contains("Input -> Physics -> Animation -> Input"));
+
+        // 失敗した登録はスケジュールを変更しない
+        assert_eq!(processor.system_names(SystemPhase::Update), vec!["Physics", "Animation"]);
+    }
+
+    #[derive(Debug, PartialEq, Component)]
+    struct Cell {
+        revealed: bool,
+    }
+
+    #[derive(Debug, PartialEq, Component)]
+    struct Sprite {
+        visible: bool,
+    }
+
+    #[derive(Default, Resource)]
+    struct Arrivals(std::sync::atomic::AtomicUsize);
+
+    fn reveal(_cells: SystemQuery<&mut Cell>) {}
+    fn hide(_sprites: SystemQuery<&mut Sprite>) {}
+    fn count_cells(_cells: SystemQuery<&Cell>) {}
+
+    #[test]
+    fn test_parallel_batches() {
+        let mut processor = SystemProcessor::new();
+        processor.set_max_threads(4);
+        processor.register_system(FunctionSystem::new(SystemPhase::Update, reveal)).unwrap();
+        processor.register_system(FunctionSystem::new(SystemPhase::Update, hide)).unwrap();
+        // Cellへの書き込みと競合する
+        processor.register_system(FunctionSystem::new(SystemPhase::Update, count_cells)).unwrap();
+        // アクセスを宣言していないシステムは単独で実行される
+        processor.register_system(Ordered::new("Legacy", 0)).unwrap();
+        processor.register_system(FunctionSystem::new(SystemPhase::Update, reveal).with_name("Reveal")).unwrap();
+        // 明示的な順序の制約がある場合は別のまとまりになる
+        processor.register_system_with(
+            FunctionSystem::new(SystemPhase::Update, hide).with_name("Hide"),
+            SystemConfig::new().after("Reveal"),
+        ).unwrap();
+
+        assert_eq!(processor.parallel_batches(SystemPhase::Update), vec![
+            vec!["reveal", "hide"],
+            vec!["count_cells"],
+            vec!["Legacy"],
+            vec!["Reveal"],
+            vec!["Hide"],
+        ]);
+
+        processor.set_max_threads(1);
+        assert_eq!(processor.parallel_batches(SystemPhase::Update).len(), 6);
+    }
+
+    #[test]
+    fn test_compatible_systems_run_concurrently() {
+        use std::sync::atomic::Ordering;
+        use std::time::{Duration, Instant};
+
+        // 相手のシステムが開始するまで待ち、同時に実行されたかどうかを返す
+        fn wait_for_other(arrivals: &Arrivals) -> bool {
+            arrivals.0.fetch_add(1, Ordering::SeqCst);
+            let deadline = Instant::now() + Duration::from_secs(1);
+            while Instant::now() < deadline {
+                if arrivals.0.load(Ordering::SeqCst) >= 2 {
+                    return true;
+                }
+                std::thread::yield_now();
+            }
+            false
+        }
+
+        let mut world = World::new();
+        world.set_max_threads(2);
+        world.insert_resource(Arrivals::default());
+        world.spawn((Cell { revealed: false }, Sprite { visible: true }));
+        world.register_system(FunctionSystem::new(SystemPhase::Update,
+            |arrivals: Res<Arrivals>, mut cells: SystemQuery<&mut Cell>| {
+                assert!(wait_for_other(&arrivals));
+                for cell in cells.iter_mut() {
+                    cell.revealed = true;
+                }
+            }).with_name("RevealAll")).unwrap();
+        world.register_system(FunctionSystem::new(SystemPhase::Update,
+            |arrivals: Res<Arrivals>, mut sprites: SystemQuery<&mut Sprite>| {
+                assert!(wait_for_other(&arrivals));
+                for sprite in sprites.iter_mut() {
+                    sprite.visible = false;
+                }
+            }).with_name("HideAll")).unwrap();
+
+        world.update(0.016);
+        assert_eq!(world.query::<&Cell>().iter(&world).next(), Some(&Cell { revealed: true }));
+        assert_eq!(world.query::<&Sprite>().iter(&world).next(), Some(&Sprite { visible: false }));
+    }
+
+    #[derive(Default, Resource)]
+    struct SeenThreads(std::sync::Mutex<std::collections::HashSet<std::thread::ThreadId>>);
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[test]
+    fn test_parallel_batches_reuse_worker_threads() {
+        fn record(seen: Res<SeenThreads>) {
+            seen.0.lock().unwrap().insert(std::thread::current().id());
+        }
+
+        let mut world = World::new();
+        world.set_max_threads(2);
+        world.insert_resource(SeenThreads::default());
+        world.register_system(FunctionSystem::new(SystemPhase::Update, record).with_name("RecordA")).unwrap();
+        world.register_system(FunctionSystem::new(SystemPhase::Update, record).with_name("RecordB")).unwrap();
+        assert_eq!(world.processor().parallel_batches(SystemPhase::Update), vec![vec!["RecordA", "RecordB"]]);
+
+        for _ in 0..10 {
+            world.update(0.016);
+        }
+        // 呼び出し元のスレッドと1つのワーカーだけが使われる
+        let seen = world.get_resource::<SeenThreads>().unwrap().0.lock().unwrap().len();
+        assert!(seen <= 2, "{}個のスレッドで実行されました", seen);
+    }
+
+    /// アクセスを宣言しているが、ワールドへの排他参照を受け取るシステム
+    struct Declared(&'static str);
+
+    impl System for Declared {
+        fn name(&self) -> &'static str {
+            self.0
+        }
+
+        fn phase(&self) -> SystemPhase {
+            SystemPhase::Update
+        }
+
+        fn priority(&self) -> SystemPriority {
+            SystemPriority::default()
+        }
+
+        fn access(&self) -> Option<SystemAccess> {
+            Some(SystemAccess::new())
+        }
+
+        fn run(&mut self, world: &mut World, _resources: &mut ResourceManager, _delta_time: f32) -> Result<(), Error> {
+            world.spawn(Cell { revealed: false });
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_trait_systems_run_alone() {
+        let mut world = World::new();
+        world.set_max_threads(4);
+        world.register_system(FunctionSystem::new(SystemPhase::Update, count_cells)).unwrap();
+        world.register_system(Declared("SpawnA")).unwrap();
+        world.register_system(Declared("SpawnB")).unwrap();
+        world.register_system(FunctionSystem::new(SystemPhase::Update, hide)).unwrap();
+
+        // 宣言上は競合しなくても、構造を変更できるシステムはまとめない
+        assert_eq!(world.processor().parallel_batches(SystemPhase::Update), vec![
+            vec!["count_cells"],
+            vec!["SpawnA"],
+            vec!["SpawnB"],
+            vec!["hide"],
+        ]);
+        world.update(0.016);
+        assert_eq!(world.query::<&Cell>().iter(&world).count(), 2);
+    }
+
+    #[derive(Debug, PartialEq, Component)]
+    #[component(storage = "table")]
+    struct Health(u32);
+
+    #[derive(Debug, PartialEq, Component)]
+    #[component(storage = "table")]
+    struct Mana(u32);
+
+    #[test]
+    fn test_parallel_systems_write_table_columns() {
+        let mut world = World::new();
+        world.set_max_threads(2);
+        let both = world.spawn((Health(10), Mana(5)));
+        let health_only = world.spawn(Health(20));
+        world.register_system(FunctionSystem::new(SystemPhase::Update, |mut health: SystemQuery<&mut Health>| {
+            for health in health.iter_mut() {
+                health.0 += 1;
+            }
+        }).with_name("Regenerate")).unwrap();
+        world.register_system(FunctionSystem::new(SystemPhase::Update, |mut mana: SystemQuery<(Entity, &mut Mana)>| {
+            for (_, mana) in mana.iter_mut() {
+                mana.0 *= 2;
+            }
+        }).with_name("Channel")).unwrap();
+        assert_eq!(world.processor().parallel_batches(SystemPhase::Update), vec![vec!["Regenerate", "Channel"]]);
+
+        world.update(0.016);
+        assert_eq!(world.get_component::<Health>(both), Some(&Health(11)));
+        assert_eq!(world.get_component::<Health>(health_only), Some(&Health(21)));
+        assert_eq!(world.get_component::<Mana>(both), Some(&Mana(10)));
+        // 並列に書き込んだ列の変更ティックも更新される
+        let ticks = world.components().get_component_ticks::<Health>(health_only).unwrap();
+        assert!(ticks.changed > ticks.added);
+    }
+}
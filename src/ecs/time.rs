@@ -0,0 +1,201 @@
+//! 固定タイムステップの時計
+//!
+//! `SystemPhase::FixedUpdate`のシステムは、フレームレートに関係なく
+//! `FixedTime`のティックレートで実行されます。物理演算やクライアント予測、
+//! ネットワークの送信ティックはすべてこの時計を共有します。
+//!
+//! ```
+//! // 30Hzで固定更新する
+//! world.get_resource_mut::<FixedTime>().unwrap().set_tick_rate(30.0);
+//!
+//! // 描画時は前回と今回の固定ティックの間を補間する
+//! let alpha = world.get_resource::<FixedTime>().unwrap().alpha();
+//! let x = previous.x + (current.x - previous.x) * alpha;
+//! ```
+
+use std::any::Any;
+
+use crate::ecs::Resource;
+
+/// 固定タイムステップの時計
+///
+/// フレームごとの経過時間を蓄積し、固定ティックを何回実行するかを決めます。
+/// `SystemProcessor`の生成時にリソースとして登録されます。
+#[derive(Debug, Clone)]
+pub struct FixedTime {
+    /// 1ティックの長さ（秒）
+    timestep: f64,
+    /// まだティックとして消費していない経過時間（秒）
+    accumulated: f64,
+    /// 1フレームで実行する最大ティック数
+    max_ticks_per_frame: u32,
+    /// これまでに実行したティック数
+    tick: u64,
+}
+
+impl Default for FixedTime {
+    fn default() -> Self {
+        Self::new(60.0)
+    }
+}
+
+impl FixedTime {
+    /// ティックレート（Hz）を指定して作成
+    ///
+    /// 1フレームで実行するティック数は最大5回に制限されます。
+    pub fn new(tick_rate: f64) -> Self {
+        let mut time = Self {
+            timestep: 0.0,
+            accumulated: 0.0,
+            max_ticks_per_frame: 5,
+            tick: 0,
+        };
+        time.set_tick_rate(tick_rate);
+        time
+    }
+
+    /// ティックレート（Hz）を取得
+    pub fn tick_rate(&self) -> f64 {
+        1.0 / self.timestep
+    }
+
+    /// ティックレート（Hz）を設定
+    ///
+    /// 蓄積済みの時間は保持されるため、次のフレームから新しいレートで実行されます。
+    pub fn set_tick_rate(&mut self, tick_rate: f64) {
+        self.timestep = 1.0 / tick_rate.clamp(1.0, 1000.0);
+    }
+
+    /// 1ティックの長さ（秒）を取得
+    pub fn timestep(&self) -> f64 {
+        self.timestep
+    }
+
+    /// 1フレームで実行する最大ティック数を取得
+    pub fn max_ticks_per_frame(&self) -> u32 {
+        self.max_ticks_per_frame
+    }
+
+    /// 1フレームで実行する最大ティック数を設定
+    ///
+    /// 処理が追いつかないときに、ティックが増え続けて停止する（スパイラル・オブ・デス）のを防ぎます。
+    pub fn set_max_ticks_per_frame(&mut self, max_ticks: u32) {
+        self.max_ticks_per_frame = max_ticks.max(1);
+    }
+
+    /// これまでに実行した固定ティック数を取得
+    ///
+    /// ネットワークのティック番号として使用できます。
+    pub fn tick(&self) -> u64 {
+        self.tick
+    }
+
+    /// 補間係数を取得（0.0〜1.0）
+    ///
+    /// 最後の固定ティックから次の固定ティックまでの経過割合です。
+    /// 描画や補間で、前回と今回のティックの状態を混ぜる割合として使います。
+    pub fn alpha(&self) -> f32 {
+        (self.accumulated / self.timestep) as f32
+    }
+
+    /// 経過時間を蓄積し、このフレームで実行するティック数を返す
+    ///
+    /// 上限を超えた分の時間は破棄されます。
+    pub fn accumulate(&mut self, delta_time: f64) -> u32 {
+        self.accumulated += delta_time.max(0.0);
+
+        let max_accumulated = self.timestep * f64::from(self.max_ticks_per_frame);
+        if self.accumulated > max_accumulated {
+            self.accumulated = max_accumulated;
+        }
+
+        let ticks = ((self.accumulated / self.timestep).floor() as u32).min(self.max_ticks_per_frame);
+        self.accumulated -= f64::from(ticks) * self.timestep;
+        ticks
+    }
+
+    /// 1ティックの実行が終わったことを記録
+    pub(crate) fn advance_tick(&mut self) {
+        self.tick += 1;
+    }
+
+    /// 蓄積した時間とティック数をリセット
+    pub fn reset(&mut self) {
+        self.accumulated = 0.0;
+        self.tick = 0;
+    }
+}
+
+impl Resource for FixedTime {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ecs::{ResourceManager, System, SystemPhase, SystemPriority, World};
+    use std::sync::{Arc, Mutex};
+    use crate::Error;
+
+    #[test]
+    fn test_accumulate_and_alpha() {
+        let mut time = FixedTime::new(50.0);
+        assert_eq!(time.accumulate(0.01), 0);
+        assert!((time.alpha() - 0.5).abs() < 1e-4);
+
+        assert_eq!(time.accumulate(0.035), 2);
+        assert!((time.alpha() - 0.25).abs() < 1e-4);
+
+        // 長いフレームでも上限までしか実行しない
+        assert_eq!(time.accumulate(1.0), 5);
+        assert!(time.alpha() < 1e-4);
+    }
+
+    /// 固定ティックで渡された経過時間を記録するシステム
+    struct FixedRecorder {
+        deltas: Arc<Mutex<Vec<f32>>>,
+    }
+
+    impl System for FixedRecorder {
+        fn name(&self) -> &'static str {
+            "FixedRecorder"
+        }
+
+        fn phase(&self) -> SystemPhase {
+            SystemPhase::FixedUpdate
+        }
+
+        fn priority(&self) -> SystemPriority {
+            SystemPriority::default()
+        }
+
+        fn run(&mut self, _world: &mut World, _resources: &mut ResourceManager, delta_time: f32) -> Result<(), Error> {
+            self.deltas.lock().unwrap().push(delta_time);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_fixed_update_runs_at_tick_rate() {
+        let deltas = Arc::new(Mutex::new(Vec::new()));
+        let mut world = World::new();
+        world.get_resource_mut::<FixedTime>().unwrap().set_tick_rate(20.0);
+        world.register_system(FixedRecorder { deltas: deltas.clone() }).unwrap();
+
+        world.update(0.03);
+        assert!(deltas.lock().unwrap().is_empty());
+        world.update(0.03);
+        world.update(0.05);
+
+        assert_eq!(*deltas.lock().unwrap(), vec![0.05, 0.05]);
+        let time = world.get_resource::<FixedTime>().unwrap();
+        assert_eq!(time.tick(), 2);
+        assert!((time.alpha() - 0.2).abs() < 1e-4);
+    }
+}
@@ -0,0 +1,189 @@
+//! ローカル座標とワールド座標の変換
+//!
+//! `Transform`は親からの相対的な位置・回転・拡大率を表し、`GlobalTransform`は
+//! 親の変換をすべて適用したワールド座標での値を表します。
+//! `GlobalTransform`は`SystemProcessor`が`Update`フェーズと`Render`フェーズの間に
+//! 親子関係をたどって自動で計算するため、描画側は`GlobalTransform`だけを参照します。
+//!
+//! ```
+//! let cursor = world.create_entity();
+//! world.add_component(cursor, Transform::from_xy(120.0, 80.0));
+//!
+//! // カーソルからの相対位置に名前ラベルを表示する
+//! let label = world.create_entity();
+//! world.add_component(label, Transform::from_xy(0.0, -16.0));
+//! world.set_parent(label, cursor);
+//! ```
+
+use serde::{Deserialize, Serialize};
+
+use crate::ecs::{Component, Entity, Parent, With, Without, World};
+
+/// 親からの相対的な変換
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, Component)]
+#[component(serialize)]
+pub struct Transform {
+    /// 位置 (x, y)
+    pub translation: (f32, f32),
+    /// 回転角度（ラジアン）
+    pub rotation: f32,
+    /// 拡大率 (x, y)
+    pub scale: (f32, f32),
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+impl Transform {
+    /// 何も変換しない値
+    pub const IDENTITY: Self = Self {
+        translation: (0.0, 0.0),
+        rotation: 0.0,
+        scale: (1.0, 1.0),
+    };
+
+    /// 位置を指定して作成
+    pub fn from_xy(x: f32, y: f32) -> Self {
+        Self {
+            translation: (x, y),
+            ..Self::IDENTITY
+        }
+    }
+
+    /// 回転角度を設定
+    pub fn with_rotation(mut self, rotation: f32) -> Self {
+        self.rotation = rotation;
+        self
+    }
+
+    /// 拡大率を設定
+    pub fn with_scale(mut self, x: f32, y: f32) -> Self {
+        self.scale = (x, y);
+        self
+    }
+}
+
+/// ワールド座標での変換
+///
+/// 自動で計算されるため、直接変更しないでください。
+#[derive(Debug, Clone, Copy, PartialEq, Component)]
+pub struct GlobalTransform {
+    /// 位置 (x, y)
+    pub translation: (f32, f32),
+    /// 回転角度（ラジアン）
+    pub rotation: f32,
+    /// 拡大率 (x, y)
+    pub scale: (f32, f32),
+}
+
+impl Default for GlobalTransform {
+    fn default() -> Self {
+        Self::from(Transform::IDENTITY)
+    }
+}
+
+impl From<Transform> for GlobalTransform {
+    fn from(transform: Transform) -> Self {
+        Self {
+            translation: transform.translation,
+            rotation: transform.rotation,
+            scale: transform.scale,
+        }
+    }
+}
+
+impl GlobalTransform {
+    /// 子の`Transform`にこの変換を適用
+    ///
+    /// 回転した親の拡大率が縦横で異なる場合、せん断は表現できないため近似になります。
+    pub fn mul_transform(&self, local: &Transform) -> Self {
+        Self {
+            translation: self.transform_point(local.translation),
+            rotation: self.rotation + local.rotation,
+            scale: (self.scale.0 * local.scale.0, self.scale.1 * local.scale.1),
+        }
+    }
+
+    /// ローカル座標の点をワールド座標に変換
+    pub fn transform_point(&self, point: (f32, f32)) -> (f32, f32) {
+        let (x, y) = (point.0 * self.scale.0, point.1 * self.scale.1);
+        let (sin, cos) = self.rotation.sin_cos();
+        (
+            self.translation.0 + x * cos - y * sin,
+            self.translation.1 + x * sin + y * cos,
+        )
+    }
+}
+
+/// すべての`GlobalTransform`を親子関係に沿って更新
+///
+/// `Transform`を持ち親を持たないエンティティを根として、子孫へ順に変換を適用します。
+/// `Transform`を持たない子とその子孫は更新されません。
+/// 値が変わらない場合は書き込まないため、`Changed<GlobalTransform>`は実際に動いたものだけに反応します。
+pub fn propagate_transforms(world: &mut World) {
+    let roots = world.query_filtered::<Entity, (With<Transform>, Without<Parent>)>().entities();
+    let mut pending: Vec<(Entity, GlobalTransform)> = roots.into_iter()
+        .filter_map(|root| world.get_component::<Transform>(root).map(|local| (root, GlobalTransform::from(*local))))
+        .collect();
+
+    while let Some((entity, global)) = pending.pop() {
+        match world.get_component::<GlobalTransform>(entity) {
+            Some(current) if *current == global => {}
+            Some(_) => {
+                if let Some(current) = world.get_component_mut::<GlobalTransform>(entity) {
+                    *current = global;
+                }
+            }
+            None => world.add_component(entity, global),
+        }
+
+        for &child in world.children(entity) {
+            if let Some(local) = world.get_component::<Transform>(child) {
+                pending.push((child, global.mul_transform(local)));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f32::consts::FRAC_PI_2;
+
+    fn assert_near(actual: (f32, f32), expected: (f32, f32)) {
+        assert!((actual.0 - expected.0).abs() < 1e-4 && (actual.1 - expected.1).abs() < 1e-4,
+            "{:?} != {:?}", actual, expected);
+    }
+
+    #[test]
+    fn test_global_transform_follows_parent() {
+        let mut world = World::new();
+        let panel = world.create_entity();
+        world.add_component(panel, Transform::from_xy(100.0, 50.0).with_rotation(FRAC_PI_2).with_scale(2.0, 2.0));
+        let button = world.create_entity();
+        world.add_component(button, Transform::from_xy(10.0, 0.0));
+        world.set_parent(button, panel);
+        let icon = world.create_entity();
+        world.add_component(icon, Transform::from_xy(0.0, 5.0));
+        world.set_parent(icon, button);
+
+        world.update(0.016);
+        assert_near(world.get_component::<GlobalTransform>(panel).unwrap().translation, (100.0, 50.0));
+        assert_near(world.get_component::<GlobalTransform>(button).unwrap().translation, (100.0, 70.0));
+        assert_near(world.get_component::<GlobalTransform>(icon).unwrap().translation, (90.0, 70.0));
+        assert_eq!(world.get_component::<GlobalTransform>(icon).unwrap().scale, (2.0, 2.0));
+
+        // 親を動かすと次のフレームで子も動く
+        world.get_component_mut::<Transform>(panel).unwrap().translation = (0.0, 0.0);
+        world.update(0.016);
+        assert_near(world.get_component::<GlobalTransform>(icon).unwrap().translation, (-10.0, 20.0));
+
+        // 親子関係を解除すると自分の`Transform`がそのままワールド座標になる
+        world.remove_parent(button);
+        world.update(0.016);
+        assert_near(world.get_component::<GlobalTransform>(button).unwrap().translation, (10.0, 0.0));
+    }
+}
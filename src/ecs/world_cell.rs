@@ -0,0 +1,141 @@
+//! 並列実行中のシステムに渡すワールドへのハンドル
+//!
+//! 同じバッチのシステムがそれぞれワールドやリソースマネージャーへの可変参照を持つと、
+//! 別々のデータにしか触れない場合でも参照同士が重なってしまいます。
+//! `UnsafeWorldCell`はワールド全体への参照の代わりに、システムが宣言した
+//! 書き込み先（リソースとコンポーネントの格納先）へのポインタを実行前に解決して保持し、
+//! それ以外のデータは共有参照で読み取ります。
+//!
+//! ハンドルからはエンティティの生成やコンポーネントの追加のような構造の変更はできず、
+//! コンポーネントのフックが呼ばれることもありません。構造の変更は`Commands`に積み、
+//! バッチの終了後にまとめて適用します。
+
+use std::any::TypeId;
+use std::marker::PhantomData;
+use std::ptr::NonNull;
+
+use crate::ecs::component::{ComponentPtr, ErasedComponentPtr};
+use crate::ecs::param::SystemAccess;
+use crate::ecs::{Component, ComponentManager, EntityManager, Resource, ResourceManager, SystemProcessor, World};
+
+/// 宣言されたアクセスだけを許可するワールドへのハンドル
+///
+/// 関数システムはこのハンドルからパラメータを取得します。
+/// 書き込みは作成時に解決したリソースとコンポーネントに限られます。
+pub struct UnsafeWorldCell<'w> {
+    /// コンポーネントマネージャー（共有参照での読み取り用）
+    components: NonNull<ComponentManager>,
+    /// エンティティマネージャー（共有参照での読み取り用）
+    entities: NonNull<EntityManager>,
+    /// リソースマネージャー（共有参照での読み取り用）
+    resources: NonNull<ResourceManager>,
+    /// 書き込みを宣言したリソース
+    resource_writes: Vec<(TypeId, NonNull<dyn Resource>)>,
+    /// 書き込みを宣言したコンポーネントの格納先
+    component_writes: Vec<(TypeId, ErasedComponentPtr)>,
+    /// ワールドとリソースの借用期間
+    _marker: PhantomData<(&'w World, &'w ResourceManager)>,
+}
+
+// 書き込み先は同じバッチの他のシステムと重ならないことを確認してから渡される
+#[cfg(not(target_arch = "wasm32"))]
+unsafe impl Send for UnsafeWorldCell<'_> {}
+
+impl<'w> UnsafeWorldCell<'w> {
+    /// 宣言されたアクセスの書き込み先を解決してハンドルを作成
+    ///
+    /// 同じバッチのハンドルはすべて同じ`world`と`resources`から順に作成し、
+    /// どのシステムも実行を始める前に作成を終えなければなりません。
+    ///
+    /// # Safety
+    ///
+    /// `world`と`resources`は可変参照から得た有効なポインタで、ハンドルが使われている間は
+    /// 他の参照からアクセスしてはいけません（他のハンドルを除く）。
+    pub(crate) unsafe fn new(world: NonNull<World>, resources: NonNull<ResourceManager>, access: &SystemAccess) -> Self {
+        let processor = std::ptr::addr_of_mut!((*world.as_ptr()).processor);
+        let (components, entities) = SystemProcessor::managers_ptr(processor);
+
+        let resource_writes = access.resource_write_ids()
+            .filter_map(|type_id| {
+                (*resources.as_ptr()).get_ptr_mut(type_id).map(|resource| (type_id, resource))
+            })
+            .collect();
+        let component_writes = access.components().writes()
+            .map(|type_id| (type_id, ComponentManager::component_ptr_erased(components.as_ptr(), type_id)))
+            .collect();
+
+        Self {
+            components,
+            entities,
+            resources,
+            resource_writes,
+            component_writes,
+            _marker: PhantomData,
+        }
+    }
+
+    /// コンポーネントマネージャーを取得
+    ///
+    /// # Safety
+    ///
+    /// 返された参照から、他のシステムが書き込むコンポーネントを読み取ってはいけません。
+    pub unsafe fn components(&self) -> &'w ComponentManager {
+        &*self.components.as_ptr()
+    }
+
+    /// エンティティマネージャーを取得
+    ///
+    /// # Safety
+    ///
+    /// 返された参照が生きている間に、エンティティを生成・削除してはいけません。
+    pub unsafe fn entities(&self) -> &'w EntityManager {
+        &*self.entities.as_ptr()
+    }
+
+    /// リソースを取得
+    ///
+    /// # Safety
+    ///
+    /// 他のシステムが同じリソースに書き込んでいてはいけません。
+    pub unsafe fn get_resource<T: Resource>(&self) -> Option<&'w T> {
+        (*self.resources.as_ptr()).get::<T>()
+    }
+
+    /// 書き込みを宣言したリソースを可変で取得
+    ///
+    /// 宣言されていないリソースや、登録されていないリソースの場合は`None`を返します。
+    ///
+    /// # Safety
+    ///
+    /// 同じリソースへの参照を同時に複数取得してはいけません。
+    pub unsafe fn get_resource_mut<T: Resource>(&self) -> Option<&'w mut T> {
+        let type_id = TypeId::of::<T>();
+        let (_, resource) = self.resource_writes.iter().find(|(id, _)| *id == type_id)?;
+        (*resource.as_ptr()).as_any_mut().downcast_mut::<T>()
+    }
+
+    /// 現在の変更ティックを取得
+    pub fn change_tick(&self) -> u64 {
+        unsafe { (*self.components.as_ptr()).change_tick() }
+    }
+
+    /// クエリ用にコンポーネントの格納先へのポインタを取得
+    ///
+    /// # Safety
+    ///
+    /// 他のシステムが同じコンポーネントに書き込んでいてはいけません。
+    pub(crate) unsafe fn component_ptr<T: Component>(&self) -> Option<ComponentPtr<T>> {
+        self.components().component_ptr::<T>()
+    }
+
+    /// 書き込みを宣言したコンポーネントの格納先への可変ポインタを取得
+    ///
+    /// # Safety
+    ///
+    /// 返されたポインタから同じ要素への参照を同時に複数作ってはいけません。
+    pub(crate) unsafe fn component_ptr_mut<T: Component>(&self) -> Option<ComponentPtr<T>> {
+        let type_id = TypeId::of::<T>();
+        let (_, storage) = self.component_writes.iter().find(|(id, _)| *id == type_id)?;
+        storage.typed::<T>(self.components().storage_type::<T>())
+    }
+}
@@ -0,0 +1,141 @@
+//! クレート共通のエラー型
+//!
+//! システムの実行や初期化の失敗は`Error`で返します。ブラウザ向けのビルド（`web`フィーチャー）
+//! では`JsValue`と相互に変換できるため、JavaScriptのAPIの呼び出しにも`?`が使えます。
+//!
+//! ```
+//! fn run(&mut self, world: &mut World, resources: &mut ResourceManager, delta_time: f32) -> Result<(), Error> {
+//!     let board = resources.get::<Board>().ok_or("盤面がありません")?;
+//!     // ...
+//!     Ok(())
+//! }
+//! ```
+
+use std::fmt;
+
+#[cfg(feature = "web")]
+use wasm_bindgen::JsValue;
+
+use crate::ecs::{Entity, PrefabError, ScheduleError, SnapshotError, World};
+use crate::network::CodecError;
+
+/// クレート共通のエラー
+#[derive(Debug, Clone, PartialEq)]
+pub enum Error {
+    /// システムの実行順序を決定できなかった
+    Schedule(ScheduleError),
+    /// スナップショットの保存・復元に失敗した
+    Snapshot(SnapshotError),
+    /// プレハブの読み込み・生成に失敗した
+    Prefab(PrefabError),
+    /// バイナリ形式のネットワークメッセージを復元できなかった
+    Codec(CodecError),
+    /// エンティティが必要なコンポーネントを持っていなかった
+    MissingComponent {
+        /// 対象のエンティティ
+        entity: Entity,
+        /// 取得しようとしたコンポーネント（複数の場合は`, `区切り）
+        components: String,
+    },
+    /// JavaScriptのAPIがエラーを返した（`web`フィーチャー）
+    #[cfg(feature = "web")]
+    Js(String),
+    /// その他の失敗
+    Other(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Schedule(error) => error.fmt(f),
+            Error::Snapshot(error) => error.fmt(f),
+            Error::Prefab(error) => error.fmt(f),
+            Error::Codec(error) => error.fmt(f),
+            Error::MissingComponent { entity, components } => write!(f, "{}は{}を持っていません", entity, components),
+            #[cfg(feature = "web")]
+            Error::Js(message) => write!(f, "JavaScriptのエラー: {}", message),
+            Error::Other(message) => f.write_str(message),
+        }
+    }
+}
+
+impl Error {
+    /// ログ用に、エンティティの名前を添えてエラーを説明
+    ///
+    /// エンティティに`Name`がある場合は`World::entity_label`の表記になります。
+    /// それ以外のエラーは`Display`と同じです。
+    pub fn describe(&self, world: &World) -> String {
+        match self {
+            Error::MissingComponent { entity, components } => {
+                format!("{}は{}を持っていません", world.entity_label(*entity), components)
+            }
+            error => error.to_string(),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Schedule(error) => Some(error),
+            Error::Snapshot(error) => Some(error),
+            Error::Prefab(error) => Some(error),
+            Error::Codec(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<ScheduleError> for Error {
+    fn from(error: ScheduleError) -> Self {
+        Error::Schedule(error)
+    }
+}
+
+impl From<SnapshotError> for Error {
+    fn from(error: SnapshotError) -> Self {
+        Error::Snapshot(error)
+    }
+}
+
+impl From<PrefabError> for Error {
+    fn from(error: PrefabError) -> Self {
+        Error::Prefab(error)
+    }
+}
+
+impl From<CodecError> for Error {
+    fn from(error: CodecError) -> Self {
+        Error::Codec(error)
+    }
+}
+
+impl From<String> for Error {
+    fn from(message: String) -> Self {
+        Error::Other(message)
+    }
+}
+
+impl From<&str> for Error {
+    fn from(message: &str) -> Self {
+        Error::Other(message.to_string())
+    }
+}
+
+#[cfg(feature = "web")]
+impl From<JsValue> for Error {
+    fn from(value: JsValue) -> Self {
+        // `JsValue`はスレッド間で共有できないため、文字列にして保持する
+        Error::Js(value.as_string().unwrap_or_else(|| format!("{:?}", value)))
+    }
+}
+
+#[cfg(feature = "web")]
+impl From<Error> for JsValue {
+    fn from(error: Error) -> Self {
+        match error {
+            Error::Js(message) => JsValue::from_str(&message),
+            error => JsValue::from_str(&error.to_string()),
+        }
+    }
+}
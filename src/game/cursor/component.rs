@@ -1,4 +1,5 @@
-use crate::ecs::Component;
+use crate::ecs::{Component, ComponentHooks, HookContext};
+use crate::rendering::{RenderLayer, Renderer};
 use wasm_bindgen::prelude::*;
 use std::collections::hash_map::DefaultHasher;
 use std::hash::{Hash, Hasher};
@@ -88,4 +89,45 @@ impl Component for MouseCursorComponent {
     fn name() -> &'static str {
         "MouseCursorComponent"
     }
-} 
\ No newline at end of file
+
+    fn hooks() -> ComponentHooks {
+        ComponentHooks::new()
+            .on_add(register_in_cursor_layer)
+            .on_remove(unregister_from_cursor_layer)
+    }
+}
+
+/// カーソルを描画するレイヤー名
+pub const CURSOR_LAYER: &str = "cursor";
+
+/// カーソルのレイヤーのZインデックス（最前面）
+const CURSOR_LAYER_Z_INDEX: i32 = 900;
+
+/// カーソルのレイヤーを取得し、なければ作成する
+pub(crate) fn cursor_layer(renderer: &mut Renderer) -> &mut RenderLayer {
+    if renderer.get_layer(CURSOR_LAYER).is_none() {
+        renderer.add_layer(RenderLayer::new(CURSOR_LAYER.to_string(), CURSOR_LAYER_Z_INDEX));
+    }
+    renderer.get_layer(CURSOR_LAYER).expect("カーソルのレイヤーを追加したはずです")
+}
+
+/// 追加されたカーソルをカーソルのレイヤーに登録する
+fn register_in_cursor_layer(context: &mut HookContext) {
+    let entity_id = context.entity().index();
+    context.commands().add(move |world| {
+        match world.get_resource_mut::<Renderer>() {
+            Some(renderer) => cursor_layer(renderer).add_entity(entity_id),
+            None => log::warn!("Rendererリソースがないため、カーソル{}をレイヤーに登録できません", entity_id),
+        }
+    });
+}
+
+/// 削除されたカーソルをカーソルのレイヤーから取り除く
+fn unregister_from_cursor_layer(context: &mut HookContext) {
+    let entity_id = context.entity().index();
+    context.commands().add(move |world| {
+        if let Some(layer) = world.get_resource_mut::<Renderer>().and_then(|renderer| renderer.get_layer(CURSOR_LAYER)) {
+            layer.remove_entity(entity_id);
+        }
+    });
+}
@@ -3,22 +3,31 @@ pub mod system;
 pub mod rendering;
 
 pub use component::MouseCursorComponent;
-pub use system::MouseCursorSystem;
+pub use system::{player_cursor_name, MouseCursorSystem, LOCAL_CURSOR_NAME};
 pub use rendering::MouseCursorRenderingSystem;
 
 use crate::ecs::World;
 use crate::network::client::NetworkClient;
+use crate::rendering::Renderer;
 use wasm_bindgen::prelude::*;
 
 /// マウスカーソルの初期化を行う関数
+///
+/// カーソルは`on_add`フックで`Renderer`リソースのカーソルのレイヤーに登録されるため、
+/// 先に`rendering::init_rendering_system`でレンダラーを追加しておく必要があります。
 pub fn init_mouse_cursor_system(world: &mut World) -> Result<(), JsValue> {
+    // カーソルを登録するレイヤーを用意する
+    let renderer = world.get_resource_mut::<Renderer>()
+        .ok_or_else(|| JsValue::from_str("Rendererリソースが見つかりません"))?;
+    component::cursor_layer(renderer);
+
     // マウスカーソルシステムの作成と登録
     let cursor_system = MouseCursorSystem::new();
-    world.register_system(cursor_system);
+    world.register_system(cursor_system)?;
     
     // マウスカーソル描画システムの作成と登録
     let cursor_rendering_system = MouseCursorRenderingSystem::new();
-    world.register_system(cursor_rendering_system);
+    world.register_system(cursor_rendering_system)?;
     
     Ok(())
 }
@@ -1,7 +1,8 @@
-use crate::ecs::{System, World, ResourceManager, SystemPhase, SystemPriority};
+use crate::ecs::{System, World, ResourceManager, SystemPhase, SystemPriority, Entity};
 use crate::rendering::Renderer;
 use wasm_bindgen::prelude::*;
 use super::component::MouseCursorComponent;
+use crate::Error;
 
 /// マウスカーソル描画システム
 pub struct MouseCursorRenderingSystem;
@@ -26,7 +27,7 @@ impl System for MouseCursorRenderingSystem {
         SystemPriority::new(900) // 通常の描画より後（最前面に表示）
     }
     
-    fn run(&mut self, world: &mut World, _resources: &mut ResourceManager, _delta_time: f32) -> Result<(), JsValue> {
+    fn run(&mut self, world: &mut World, _resources: &mut ResourceManager, _delta_time: f32) -> Result<(), Error> {
         // 直接Canvasで描画する
         if let Some(window) = web_sys::window() {
             if let Some(document) = window.document() {
@@ -35,7 +36,7 @@ impl System for MouseCursorRenderingSystem {
                         if let Ok(Some(context)) = canvas.get_context("2d") {
                             if let Ok(context) = context.dyn_into::<web_sys::CanvasRenderingContext2d>() {
                                 // マウスカーソルコンポーネントを持つすべてのエンティティをレンダリング
-                                let mut query = world.query::<MouseCursorComponent>();
+                                let query = world.query::<(Entity, &MouseCursorComponent)>();
                                 for (_entity, cursor) in query.iter(world) {
                                     if cursor.visible {
                                         // カーソルの円を描画
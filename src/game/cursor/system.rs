@@ -1,22 +1,25 @@
-use crate::ecs::{System, World, ResourceManager, SystemPhase, SystemPriority, Entity};
+use crate::ecs::{System, World, ResourceManager, SystemPhase, SystemPriority, Name};
 use crate::input::InputResource;
 use crate::network::client::NetworkClient;
 use crate::network::protocol::MouseCursorUpdateData;
-use wasm_bindgen::prelude::*;
 use super::component::MouseCursorComponent;
-use std::collections::HashMap;
+use crate::Error;
 use web_sys::console;
 
+/// ローカルプレイヤーのカーソルエンティティの名前
+pub const LOCAL_CURSOR_NAME: &str = "LocalCursor";
+
+/// 他プレイヤーのカーソルエンティティの名前
+pub fn player_cursor_name(player_id: u32) -> String {
+    format!("Cursor/{}", player_id)
+}
+
 /// マウスカーソルシステム
 pub struct MouseCursorSystem {
     /// ローカルプレイヤーID
     local_player_id: Option<u32>,
     /// 最後のマウス位置
     last_position: (f32, f32),
-    /// マウスカーソルのエンティティID
-    local_cursor_entity: Option<Entity>,
-    /// 他プレイヤーのカーソルマップ (player_id -> entity)
-    player_cursors: HashMap<u32, Entity>,
     /// 同期間隔（ミリ秒）
     sync_interval: f64,
     /// 最後に同期した時間
@@ -29,8 +32,6 @@ impl MouseCursorSystem {
         Self {
             local_player_id: None,
             last_position: (0.0, 0.0),
-            local_cursor_entity: None,
-            player_cursors: HashMap::new(),
             sync_interval: 100.0, // デフォルト100ms
             last_sync_time: js_sys::Date::now(),
         }
@@ -46,19 +47,16 @@ impl MouseCursorSystem {
         }
         
         // 既存のプレイヤーカーソルを探す
-        if let Some(entity) = self.player_cursors.get(&data.player_id) {
+        if let Some(entity) = world.find_by_name(&player_cursor_name(data.player_id)) {
             // 既存のカーソルコンポーネントを更新
-            if let Some(cursor) = world.get_component_mut::<MouseCursorComponent>(*entity) {
+            if let Some(cursor) = world.get_component_mut::<MouseCursorComponent>(entity) {
                 cursor.update_position(data.x, data.y);
                 cursor.set_visible(data.visible);
             }
         } else {
             // 新しいカーソルエンティティを作成
-            let new_entity = world.create_entity();
             let cursor = MouseCursorComponent::new(data.player_id, data.x, data.y);
-            
-            world.add_component(new_entity, cursor);
-            self.player_cursors.insert(data.player_id, new_entity);
+            world.spawn((cursor, Name::from(player_cursor_name(data.player_id))));
             
             // ログ出力
             console::log_1(&format!("📍 Created cursor entity for player: {}", data.player_id).into());
@@ -79,7 +77,7 @@ impl System for MouseCursorSystem {
         SystemPriority::new(100) // 高い優先度
     }
     
-    fn run(&mut self, world: &mut World, resources: &mut ResourceManager, _delta_time: f32) -> Result<(), JsValue> {
+    fn run(&mut self, world: &mut World, resources: &mut ResourceManager, _delta_time: f32) -> Result<(), Error> {
         let now = js_sys::Date::now();
         
         // NetworkClientからプレイヤーIDを取得（まだ設定されていない場合）
@@ -97,18 +95,16 @@ impl System for MouseCursorSystem {
                 let is_in_canvas = input_resource.is_mouse_in_canvas();
                 
                 // ローカルカーソルエンティティが未作成なら作成
-                if self.local_cursor_entity.is_none() {
+                if world.find_by_name(LOCAL_CURSOR_NAME).is_none() {
                     let cursor = MouseCursorComponent::new(player_id, mouse_pos.0, mouse_pos.1);
                     
-                    let entity = world.create_entity();
-                    world.add_component(entity, cursor);
-                    self.local_cursor_entity = Some(entity);
+                    world.spawn((cursor, Name::new(LOCAL_CURSOR_NAME)));
                     
                     console::log_1(&format!("🖱️ Created local cursor for player: {}", player_id).into());
                 }
                 
                 // ローカルカーソルを更新
-                if let Some(entity) = self.local_cursor_entity {
+                if let Some(entity) = world.find_by_name(LOCAL_CURSOR_NAME) {
                     if let Some(cursor) = world.get_component_mut::<MouseCursorComponent>(entity) {
                         // 位置が変わったか、表示状態が変わった場合に更新
                         let position_changed = 
@@ -1,9 +1,39 @@
 //! ゲームエンティティモジュール
 //! 
 //! ゲーム固有のエンティティとコンポーネントを実装します。
+//! 各エンティティのコンポーネント構成は`prefabs.json`で定義されており、
+//! 新しい種類のエンティティは再コンパイルせずに`load_prefabs`で追加できます。
+
+use std::sync::OnceLock;
 
 use wasm_bindgen::prelude::*;
-use crate::ecs::{World, Entity};
+use crate::ecs::{World, Entity, PrefabLibrary, Transform};
+
+/// 組み込みのプレハブ定義
+const BUILTIN_PREFABS: &str = include_str!("prefabs.json");
+
+/// 組み込みのプレハブを取得します。
+pub fn prefabs() -> &'static PrefabLibrary {
+    static PREFABS: OnceLock<PrefabLibrary> = OnceLock::new();
+    PREFABS.get_or_init(|| {
+        PrefabLibrary::from_json(BUILTIN_PREFABS).expect("組み込みのプレハブ定義が正しくありません")
+    })
+}
+
+/// 組み込みのプレハブに追加の定義を読み込んだライブラリを作成します。
+///
+/// `json`のプレハブは組み込みのプレハブ（`Base`など）を継承できます。
+pub fn load_prefabs(json: &str) -> Result<PrefabLibrary, JsValue> {
+    let mut library = prefabs().clone();
+    library.load_json(json)?;
+    Ok(library)
+}
+
+/// ゲームのコンポーネントを登録してプレハブからエンティティを作成します。
+pub fn spawn_prefab(world: &mut World, library: &PrefabLibrary, name: &str) -> Result<Entity, JsValue> {
+    world.register_component::<Transform>()?;
+    Ok(library.spawn(world, name)?)
+}
 
 /// プレイヤーエンティティ
 /// 
@@ -13,11 +43,8 @@ pub struct Player;
 impl Player {
     /// 新しいプレイヤーエンティティを作成します。
     pub fn create(world: &mut World) -> Result<Entity, JsValue> {
-        let entity = world.create_entity();
-
-        // TODO: プレイヤーコンポーネントの追加
-
-        Ok(entity)
+        // TODO: プレイヤーコンポーネントの追加（prefabs.jsonに定義）
+        spawn_prefab(world, prefabs(), "Player")
     }
 }
 
@@ -29,11 +56,8 @@ pub struct Enemy;
 impl Enemy {
     /// 新しい敵エンティティを作成します。
     pub fn create(world: &mut World) -> Result<Entity, JsValue> {
-        let entity = world.create_entity();
-
-        // TODO: 敵コンポーネントの追加
-
-        Ok(entity)
+        // TODO: 敵コンポーネントの追加（prefabs.jsonに定義）
+        spawn_prefab(world, prefabs(), "Enemy")
     }
 }
 
@@ -45,11 +69,8 @@ pub struct Item;
 impl Item {
     /// 新しいアイテムエンティティを作成します。
     pub fn create(world: &mut World) -> Result<Entity, JsValue> {
-        let entity = world.create_entity();
-
-        // TODO: アイテムコンポーネントの追加
-
-        Ok(entity)
+        // TODO: アイテムコンポーネントの追加（prefabs.jsonに定義）
+        spawn_prefab(world, prefabs(), "Item")
     }
 }
 
@@ -61,9 +82,21 @@ pub struct Effect;
 impl Effect {
     /// 新しいエフェクトエンティティを作成します。
     pub fn create(world: &mut World) -> Result<Entity, JsValue> {
-        let entity = world.create_entity();
+        // TODO: エフェクトコンポーネントの追加（prefabs.jsonに定義）
+        spawn_prefab(world, prefabs(), "Effect")
+    }
 
-        // TODO: エフェクトコンポーネントの追加
+    /// 親エンティティに追従するエフェクトを作成します。
+    ///
+    /// `offset`は親からの相対位置です。親が削除されるとエフェクトも削除されます
+    /// （`World::despawn_recursive`を使用した場合）。
+    pub fn create_attached(world: &mut World, parent: Entity, offset: (f32, f32)) -> Result<Entity, JsValue> {
+        let entity = Self::create(world)?;
+        world.add_component(entity, Transform::from_xy(offset.0, offset.1));
+        if !world.set_parent(entity, parent) {
+            world.destroy_entity(entity);
+            return Err(JsValue::from_str("親エンティティが存在しません"));
+        }
 
         Ok(entity)
     }
@@ -77,11 +110,8 @@ pub struct Background;
 impl Background {
     /// 新しい背景エンティティを作成します。
     pub fn create(world: &mut World) -> Result<Entity, JsValue> {
-        let entity = world.create_entity();
-
-        // TODO: 背景コンポーネントの追加
-
-        Ok(entity)
+        // TODO: 背景コンポーネントの追加（prefabs.jsonに定義）
+        spawn_prefab(world, prefabs(), "Background")
     }
 }
 
@@ -113,5 +143,19 @@ mod tests {
         // 背景エンティティの作成
         let background = Background::create(&mut world).unwrap();
         assert!(world.is_alive(background));
+        assert_eq!(world.get_component::<Transform>(background), Some(&Transform::default()));
+
+        // 組み込みのプレハブを継承した新しい種類のエンティティ
+        let library = load_prefabs(r#"{
+            "Coin": { "extends": "Item", "components": { "Transform": { "scale": [0.5, 0.5] } } }
+        }"#).unwrap();
+        let coin = spawn_prefab(&mut world, &library, "Coin").unwrap();
+        assert_eq!(world.get_component::<Transform>(coin).unwrap().scale, (0.5, 0.5));
+
+        // プレイヤーに追従するエフェクト
+        let trail = Effect::create_attached(&mut world, player, (0.0, 8.0)).unwrap();
+        assert_eq!(world.parent(trail), Some(player));
+        world.despawn_recursive(player);
+        assert!(!world.is_alive(trail));
     }
 } 
\ No newline at end of file
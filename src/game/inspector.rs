@@ -0,0 +1,148 @@
+//! ワールドインスペクターのオーバーレイ
+//!
+//! `World::inspect`の内容をキャンバスの左上に文字で重ねて描画します。
+//! 開発者ツールから`GameInstance::set_inspector_overlay(true)`で有効にします。
+
+use std::any::Any;
+
+use wasm_bindgen::prelude::*;
+
+use crate::ecs::{Resource, ResourceManager, System, SystemPhase, SystemPriority, World};
+use crate::Error;
+
+/// オーバーレイの1行の高さ（ピクセル）
+const LINE_HEIGHT: f64 = 14.0;
+/// オーバーレイの余白（ピクセル）
+const PADDING: f64 = 8.0;
+/// 等幅フォントの1文字の幅（ピクセル）
+const CHAR_WIDTH: f64 = 6.6;
+
+/// インスペクターのオーバーレイの設定
+#[derive(Debug, Clone)]
+pub struct InspectorOverlay {
+    /// オーバーレイを描画するかどうか
+    pub enabled: bool,
+    /// 描画する最大行数（超えた分は省略）
+    pub max_lines: usize,
+    /// コンポーネントの値も表示するかどうか
+    pub show_values: bool,
+}
+
+impl Default for InspectorOverlay {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_lines: 40,
+            show_values: true,
+        }
+    }
+}
+
+impl Resource for InspectorOverlay {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+impl InspectorOverlay {
+    /// ワールドの中身を表示用の行に変換
+    pub fn lines(&self, world: &World) -> Vec<String> {
+        let inspection = world.inspect();
+        let mut lines = vec![format!(
+            "entities: {}  resources: {}",
+            inspection.entities.len(),
+            inspection.resources.len()
+        )];
+        for info in &inspection.entities {
+            match &info.name {
+                Some(name) => lines.push(format!("{} \"{}\"", info.entity, name)),
+                None => lines.push(format!("{}", info.entity)),
+            }
+            for component in &info.components {
+                match (&component.value, self.show_values) {
+                    (Some(value), true) => lines.push(format!("  {}: {}", component.name, value)),
+                    _ => lines.push(format!("  {}", component.name)),
+                }
+            }
+        }
+        let max_lines = self.max_lines.max(2);
+        if lines.len() > max_lines {
+            let hidden = lines.len() - max_lines + 1;
+            lines.truncate(max_lines - 1);
+            lines.push(format!("... ({}行省略)", hidden));
+        }
+        lines
+    }
+}
+
+/// インスペクターのオーバーレイ描画システム
+///
+/// `InspectorOverlay::enabled`が`true`のときだけ実行されるように登録します。
+pub struct InspectorOverlaySystem;
+
+impl InspectorOverlaySystem {
+    /// 新しいオーバーレイ描画システムを作成
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl Default for InspectorOverlaySystem {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl System for InspectorOverlaySystem {
+    fn name(&self) -> &'static str {
+        "InspectorOverlaySystem"
+    }
+
+    fn phase(&self) -> SystemPhase {
+        SystemPhase::Render
+    }
+
+    fn priority(&self) -> SystemPriority {
+        SystemPriority::new(1000) // カーソルよりも手前に表示
+    }
+
+    fn run(&mut self, world: &mut World, resources: &mut ResourceManager, _delta_time: f32) -> Result<(), Error> {
+        let lines = match resources.get::<InspectorOverlay>() {
+            Some(overlay) if overlay.enabled => overlay.lines(world),
+            _ => return Ok(()),
+        };
+
+        let context = web_sys::window()
+            .and_then(|window| window.document())
+            .and_then(|document| document.get_element_by_id("game-canvas"))
+            .and_then(|canvas| canvas.dyn_into::<web_sys::HtmlCanvasElement>().ok())
+            .and_then(|canvas| canvas.get_context("2d").ok().flatten())
+            .and_then(|context| context.dyn_into::<web_sys::CanvasRenderingContext2d>().ok());
+        let context = match context {
+            Some(context) => context,
+            None => return Ok(()),
+        };
+
+        context.save();
+        context.set_font("11px monospace");
+        context.set_text_align("left");
+        context.set_text_baseline("top");
+
+        // 背景を半透明で塗ってから文字を描画
+        let width = lines.iter().map(|line| line.chars().count()).max().unwrap_or(0) as f64 * CHAR_WIDTH;
+        context.set_fill_style_str("rgba(0, 0, 0, 0.6)");
+        context.fill_rect(0.0, 0.0, width + PADDING * 2.0, lines.len() as f64 * LINE_HEIGHT + PADDING * 2.0);
+
+        context.set_fill_style_str("#9f9");
+        for (i, line) in lines.iter().enumerate() {
+            context.fill_text(line, PADDING, PADDING + i as f64 * LINE_HEIGHT)?;
+        }
+
+        context.restore();
+        Ok(())
+    }
+}
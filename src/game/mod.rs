@@ -4,7 +4,7 @@
 //! ゲーム状態の管理、リソースの管理、ゲーム固有のシステムとエンティティを提供します。
 
 use wasm_bindgen::prelude::*;
-use crate::ecs::{World, SystemProcessor};
+use crate::ecs::{in_state, World, SystemConfig, SystemProcessor};
 
 /// ゲームモジュールのサブモジュール
 pub mod resources;  // ゲームリソース管理
@@ -12,6 +12,7 @@ pub mod state;     // ゲーム状態管理
 pub mod systems;   // ゲーム固有のシステム
 pub mod entities;  // ゲーム固有のエンティティ
 pub mod cursor;    // マウスカーソル機能
+pub mod inspector; // ワールドインスペクターのオーバーレイ
 
 /// ゲームインスタンス
 ///
@@ -47,8 +48,9 @@ impl Game {
 
         // ゲームの初期化
         let world = World::new();
-        let system_processor = SystemProcessor::new();
+        let mut system_processor = SystemProcessor::new();
         let state = state::GameState::new(canvas)?;
+        system_processor.add_state(state.current_state());
 
         Ok(Game {
             world,
@@ -66,6 +68,9 @@ impl Game {
         // ゲーム状態の更新
         self.state.update(delta_time)?;
 
+        // 入力による状態遷移をシステム側に伝える（次のフレーム開始時に適用される）
+        self.system_processor.set_state(self.state.current_state());
+
         // システムの実行
         self.system_processor.update(&mut self.world, delta_time);
 
@@ -101,19 +106,40 @@ impl Game {
 }
 
 /// ゲームシステムを初期化します。
-pub fn init_game_systems(world: &mut World) {
+pub fn init_game_systems(world: &mut World) -> Result<(), JsValue> {
     // 各ゲームシステムを初期化して登録
     use systems::*;
-    use resources::TimeResource; // TimeリソースをTimeResourceに修正
+    use resources::{GameStats, TimeResource}; // TimeリソースをTimeResourceに修正
+    use state::GameStateType;
     
     // TimeResourceを登録
     world.insert_resource(TimeResource::default()); // add_resourceをinsert_resourceに、TimeをTimeResourceに修正
+
+    // GameStatsを登録（GameInstance::updateで毎フレーム更新される）
+    world.insert_resource(GameStats::default());
     
-    // TimeSystemを登録
-    world.register_system(TimeSystem::new());
+    // ゲーム状態を登録（GameInstanceはプレイ中から始まる）
+    world.add_state(GameStateType::Playing);
+    
+    // TimeSystemを登録（ポーズ中はゲーム内時間を止める）
+    world.register_system_with(
+        TimeSystem::new(),
+        SystemConfig::new().run_if(in_state(GameStateType::Playing)),
+    )?;
     
     // GameStateSystemを登録
-    world.register_system(GameStateSystem::new());
+    world.register_system(GameStateSystem::new())?;
+
+    // インスペクターのオーバーレイを登録（GameInstance::set_inspector_overlayで有効になるまで実行しない）
+    world.insert_resource(inspector::InspectorOverlay::default());
+    world.register_system_with(
+        inspector::InspectorOverlaySystem::new(),
+        SystemConfig::new().run_if(|resources| {
+            resources.get::<inspector::InspectorOverlay>().is_some_and(|overlay| overlay.enabled)
+        }),
+    )?;
+    
+    Ok(())
 }
 
 #[cfg(test)]
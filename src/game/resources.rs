@@ -2,7 +2,9 @@
 //! 
 //! ゲームで使用する共有リソースを管理します。
 
-use crate::ecs::Resource;
+use serde::Serialize;
+
+use crate::ecs::{Resource, SystemTiming};
 
 /// ゲーム設定リソース
 /// 
@@ -156,7 +158,7 @@ impl Resource for AssetManager {
 /// ゲーム統計リソース
 /// 
 /// ゲームの統計情報を管理します。
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, Serialize)]
 pub struct GameStats {
     /// フレーム数
     pub frame_count: u64,
@@ -168,6 +170,33 @@ pub struct GameStats {
     pub max_fps: f32,
     /// 最後のフレーム時間（秒）
     pub last_frame_time: f32,
+    /// システムごとの実行時間（最初に実行された順）
+    pub systems: Vec<SystemTiming>,
+}
+
+impl GameStats {
+    /// 1フレーム分の経過時間を記録します。
+    pub fn record_frame(&mut self, delta_time: f32) {
+        if delta_time <= 0.0 {
+            return;
+        }
+        let fps = 1.0 / delta_time;
+        self.frame_count += 1;
+        self.last_frame_time = delta_time;
+        if self.frame_count == 1 {
+            self.min_fps = fps;
+            self.max_fps = fps;
+        } else {
+            self.min_fps = self.min_fps.min(fps);
+            self.max_fps = self.max_fps.max(fps);
+        }
+        self.average_fps += (fps - self.average_fps) / self.frame_count as f32;
+    }
+
+    /// 平均実行時間が最も長いシステムを取得します。
+    pub fn slowest_system(&self) -> Option<&SystemTiming> {
+        self.systems.iter().max_by(|a, b| a.avg_ms.total_cmp(&b.avg_ms))
+    }
 }
 
 impl Resource for GameStats {
@@ -225,5 +254,27 @@ mod tests {
         assert_eq!(stats.min_fps, 0.0);
         assert_eq!(stats.max_fps, 0.0);
         assert_eq!(stats.last_frame_time, 0.0);
+        assert!(stats.systems.is_empty());
+    }
+
+    #[test]
+    fn test_game_stats_records_frames_and_systems() {
+        let mut stats = GameStats::default();
+        stats.record_frame(0.02);
+        stats.record_frame(0.01);
+        assert_eq!(stats.frame_count, 2);
+        assert_eq!(stats.min_fps, 50.0);
+        assert_eq!(stats.max_fps, 100.0);
+        assert_eq!(stats.average_fps, 75.0);
+
+        let mut timings = crate::ecs::SystemTimings::default();
+        timings.record("TimeSystem", crate::ecs::SystemPhase::Update, 0.1);
+        timings.record("GameStateSystem", crate::ecs::SystemPhase::Update, 4.0);
+        stats.systems = timings.timings();
+        assert_eq!(stats.systems.len(), 2);
+        assert_eq!(stats.slowest_system().unwrap().name, "GameStateSystem");
+
+        let json: serde_json::Value = serde_json::to_value(&stats).unwrap();
+        assert_eq!(json["systems"][1]["max_ms"], 4.0);
     }
 } 
\ No newline at end of file
@@ -14,7 +14,10 @@ use log;
 /// ゲームには複数の状態があり、各状態ごとに異なる処理（描画・操作）が行われます。
 /// 例えば、メインメニューではカード選択やデッキ構築を行い、
 /// Playing（プレイ中）状態ではカードを出したり対戦したりします。
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// 
+/// ECSの状態としても使われ、`run_if(in_state(GameStateType::Playing))`のように
+/// システムを実行する状態を指定できます。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum GameStateType {
     /// スプラッシュ画面
     /// ゲーム起動時に表示される初期画面です
@@ -86,6 +89,11 @@ impl GameState {
         })
     }
 
+    /// 現在のゲーム状態を取得します。
+    pub fn current_state(&self) -> GameStateType {
+        self.current_state
+    }
+
     /// ゲーム状態を更新します。
     /// 
     /// # 引数
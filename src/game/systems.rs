@@ -6,6 +6,9 @@ use wasm_bindgen::prelude::*;
 use crate::ecs::{System, World, SystemPhase, SystemPriority};
 use crate::ecs::resource::ResourceManager;
 use crate::game::resources::TimeResource;
+use crate::physics::PhysicsWorld;
+use crate::rendering::Renderer;
+use crate::Error;
 
 /// 時間管理システム
 /// 
@@ -32,7 +35,7 @@ impl System for TimeSystem {
         SystemPriority::new(0)
     }
 
-    fn run(&mut self, world: &mut World, _resources: &mut ResourceManager, delta_time: f32) -> Result<(), JsValue> {
+    fn run(&mut self, world: &mut World, _resources: &mut ResourceManager, delta_time: f32) -> Result<(), Error> {
         // 時間リソースを取得または作成
         let time = world
             .get_resource_mut::<TimeResource>()
@@ -64,7 +67,7 @@ impl System for InputSystem {
         SystemPriority::new(0)
     }
 
-    fn run(&mut self, world: &mut World, _resources: &mut ResourceManager, _delta_time: f32) -> Result<(), JsValue> {
+    fn run(&mut self, world: &mut World, _resources: &mut ResourceManager, _delta_time: f32) -> Result<(), Error> {
         // 入力状態リソースを取得
         let _input = world
             .get_resource::<crate::game::resources::InputState>()
@@ -94,7 +97,12 @@ impl System for RenderingSystem {
         SystemPriority::new(0)
     }
 
-    fn run(&mut self, _world: &mut World, _resources: &mut ResourceManager, _delta_time: f32) -> Result<(), JsValue> {
+    fn run(&mut self, world: &mut World, resources: &mut ResourceManager, _delta_time: f32) -> Result<(), Error> {
+        // 前回の描画以降に削除されたエンティティをレイヤーから取り除く
+        if let Some(renderer) = resources.get_mut::<Renderer>() {
+            renderer.remove_despawned_entities(world.despawned_entities());
+        }
+
         // TODO: レンダリング処理の実装
 
         Ok(())
@@ -104,23 +112,40 @@ impl System for RenderingSystem {
 /// 物理システム
 /// 
 /// ゲームの物理演算を行います。
+/// 固定タイムステップで実行され、1ティックごとに物理ワールドを1ステップ進めます。
 pub struct PhysicsSystem;
 
+impl PhysicsSystem {
+    /// 新しい物理システムを作成します。
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl Default for PhysicsSystem {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl System for PhysicsSystem {
     fn name(&self) -> &'static str {
         "PhysicsSystem"
     }
 
     fn phase(&self) -> SystemPhase {
-        SystemPhase::Update
+        SystemPhase::FixedUpdate
     }
 
     fn priority(&self) -> SystemPriority {
         SystemPriority::new(1)
     }
 
-    fn run(&mut self, _world: &mut World, _resources: &mut ResourceManager, _delta_time: f32) -> Result<(), JsValue> {
-        // TODO: 物理演算の実装
+    fn run(&mut self, world: &mut World, _resources: &mut ResourceManager, delta_time: f32) -> Result<(), Error> {
+        // delta_timeはFixedTimeの1ティックの長さ
+        if let Some(physics_world) = world.get_resource_mut::<PhysicsWorld>() {
+            physics_world.step(f64::from(delta_time));
+        }
 
         Ok(())
     }
@@ -144,7 +169,7 @@ impl System for AnimationSystem {
         SystemPriority::new(2)
     }
 
-    fn run(&mut self, _world: &mut World, _resources: &mut ResourceManager, _delta_time: f32) -> Result<(), JsValue> {
+    fn run(&mut self, _world: &mut World, _resources: &mut ResourceManager, _delta_time: f32) -> Result<(), Error> {
         // TODO: アニメーション処理の実装
 
         Ok(())
@@ -169,7 +194,7 @@ impl System for SoundSystem {
         SystemPriority::new(3)
     }
 
-    fn run(&mut self, _world: &mut World, _resources: &mut ResourceManager, _delta_time: f32) -> Result<(), JsValue> {
+    fn run(&mut self, _world: &mut World, _resources: &mut ResourceManager, _delta_time: f32) -> Result<(), Error> {
         // TODO: サウンド処理の実装
 
         Ok(())
@@ -201,7 +226,12 @@ impl System for GameStateSystem {
         SystemPriority::new(4)
     }
 
-    fn run(&mut self, _world: &mut World, _resources: &mut ResourceManager, _delta_time: f32) -> Result<(), JsValue> {
+    fn after(&self) -> Vec<&'static str> {
+        // 経過時間を更新してから状態を判定する
+        vec!["TimeSystem"]
+    }
+
+    fn run(&mut self, _world: &mut World, _resources: &mut ResourceManager, _delta_time: f32) -> Result<(), Error> {
         // TODO: ゲーム状態管理の実装
 
         Ok(())
@@ -228,7 +258,7 @@ mod tests {
         assert_eq!(rendering_system.priority(), SystemPriority::new(0));
 
         let physics_system = PhysicsSystem;
-        assert_eq!(physics_system.phase(), SystemPhase::Update);
+        assert_eq!(physics_system.phase(), SystemPhase::FixedUpdate);
         assert_eq!(physics_system.priority(), SystemPriority::new(1));
 
         let animation_system = AnimationSystem;
@@ -14,6 +14,7 @@ use wasm_bindgen::JsValue;
 use crate::ecs::{Entity, System, World, SystemPhase, SystemPriority, ResourceManager, Resource};
 use crate::ecs::component::Component;
 use crate::ecs::query::Query;
+use crate::Error;
 
 pub mod key_codes;
 pub mod gestures;
@@ -746,12 +747,12 @@ impl System for InputSystem {
         SystemPriority::new(0) // 入力処理は優先度0（最優先）
     }
 
-    fn run(&mut self, world: &mut World, _resources: &mut ResourceManager, _delta_time: f32) -> Result<(), JsValue> {
+    fn run(&mut self, world: &mut World, _resources: &mut ResourceManager, _delta_time: f32) -> Result<(), Error> {
         // 入力状態を更新
         self.state.update(_delta_time);
         
         // 入力コンポーネントを持つエンティティを取得
-        let mut query = Query::<InputComponent>::new();
+        let mut query = Query::<&InputComponent>::new();
         query.run(world)?;
         let entities = query.entities();
         
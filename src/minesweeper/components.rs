@@ -0,0 +1,41 @@
+//! マインスイーパーのコンポーネント
+//!
+//! 盤面の各マスが1つのエンティティで、`Cell`に座標を持ちます。
+//! 地雷の配置は最初のマスが開かれたときに決まります。
+
+use serde::{Deserialize, Serialize};
+
+use crate::ecs::Component;
+
+/// 盤面の1マス
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Component)]
+#[component(serialize, storage = "table")]
+pub struct Cell {
+    /// 列（左端が0）
+    pub x: u32,
+    /// 行（上端が0）
+    pub y: u32,
+}
+
+/// 地雷のあるマス
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Component)]
+#[component(serialize)]
+pub struct Mine;
+
+/// 周囲8マスの地雷の数
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Component)]
+#[component(serialize)]
+pub struct AdjacentMines(pub u8);
+
+/// 開かれたマス
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Component)]
+#[component(serialize, storage = "sparse")]
+pub struct Revealed;
+
+/// 旗の立てられたマス
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Component)]
+#[component(serialize, storage = "sparse")]
+pub struct Flagged {
+    /// 旗を立てたプレイヤー
+    pub player_id: String,
+}
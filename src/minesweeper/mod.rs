@@ -0,0 +1,147 @@
+//! マインスイーパーのルール
+//!
+//! クライアントとサーバーで共有するコンポーネントとシステムです。ブラウザのAPIに
+//! 依存しないため、サーバーではルームごとの`World`でネイティブに実行できます。
+//! 操作は`RevealCell`・`ToggleFlag`イベントとして送り、結果は`CellsRevealed`・
+//! `FlagToggled`イベントと`Board`リソースで受け取ります。
+//!
+//! ```
+//! let mut world = World::new();
+//! minesweeper::init_minesweeper(&mut world, BoardConfig::new(9, 9, 10))?;
+//!
+//! world.send_event(RevealCell { x: 4, y: 4, player_id: "alice".into() });
+//! world.update(0.0);
+//!
+//! let board = world.get_resource::<Board>().unwrap();
+//! println!("{:?} {:?}", board.outcome(), board.view(&world));
+//! ```
+
+pub mod components;  // 盤面のマスのコンポーネント
+pub mod resources;   // 盤面のリソースと操作・結果のイベント
+pub mod systems;     // 操作を処理するシステム
+
+pub use components::{AdjacentMines, Cell, Flagged, Mine, Revealed};
+pub use resources::{Board, BoardConfig, BoardOutcome, CellView, CellsRevealed, FlagToggled, RevealCell, ToggleFlag};
+pub use systems::{FlagSystem, RevealSystem};
+
+use crate::ecs::{ScheduleError, World};
+
+/// マインスイーパーの盤面とシステムをワールドに追加
+///
+/// マスのエンティティを生成して`Board`リソースに登録し、操作と結果のイベント、
+/// 操作を処理するシステムを登録します。
+///
+/// # 引数
+///
+/// * `world` - 追加先のワールド
+/// * `config` - 盤面の大きさと地雷の数（範囲外の値は`BoardConfig::clamped`で切り詰められます）
+///
+/// # エラー
+///
+/// * システムの実行順序の制約が循環している場合
+pub fn init_minesweeper(world: &mut World, config: BoardConfig) -> Result<(), ScheduleError> {
+    let config = config.clamped();
+    let cells = (0..config.height)
+        .flat_map(|y| (0..config.width).map(move |x| Cell { x, y }))
+        .map(|cell| world.spawn(cell))
+        .collect();
+    world.insert_resource(Board::new(config, cells));
+
+    world.add_event::<RevealCell>();
+    world.add_event::<ToggleFlag>();
+    world.add_event::<CellsRevealed>();
+    world.add_event::<FlagToggled>();
+
+    world.register_system(RevealSystem::new())?;
+    world.register_system(FlagSystem::new())?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ecs::Events;
+
+    fn mines(world: &World) -> Vec<Cell> {
+        world.query::<(&Cell, &Mine)>().iter(world).map(|(cell, _)| *cell).collect()
+    }
+
+    fn reveal(world: &mut World, x: u32, y: u32) -> CellsRevealed {
+        world.send_event(RevealCell { x, y, player_id: "alice".into() });
+        world.update(0.0);
+        world.get_resource::<Events<CellsRevealed>>().unwrap().iter().last().unwrap().clone()
+    }
+
+    #[test]
+    fn test_first_reveal_is_safe_and_floods() {
+        let mut world = World::new();
+        init_minesweeper(&mut world, BoardConfig::new(9, 9, 10).with_seed(7)).unwrap();
+
+        let result = reveal(&mut world, 4, 4);
+        assert!(!result.exploded);
+        // 最初のマスの周囲には地雷がないため、必ず隣へ広がる
+        assert!(result.cells.len() >= 9);
+
+        let board = world.get_resource::<Board>().unwrap();
+        assert_eq!(board.revealed(), result.cells.len() as u32);
+        assert_eq!(board.outcome(), &BoardOutcome::Playing);
+        assert_eq!(world.query::<&Mine>().len(), 10);
+        assert_eq!(board.view(&world)[4][4], CellView::Revealed(0));
+
+        let mine = mines(&world)[0];
+        assert!(reveal(&mut world, mine.x, mine.y).exploded);
+        assert_eq!(
+            world.get_resource::<Board>().unwrap().outcome(),
+            &BoardOutcome::Exploded { player_id: "alice".into(), x: mine.x, y: mine.y }
+        );
+    }
+
+    #[test]
+    fn test_flags_and_clear() {
+        let mut world = World::new();
+        init_minesweeper(&mut world, BoardConfig::new(8, 8, 6).with_seed(1)).unwrap();
+        reveal(&mut world, 0, 0);
+        assert_eq!(world.get_resource::<Board>().unwrap().outcome(), &BoardOutcome::Playing);
+
+        let mines = mines(&world);
+        world.send_event(ToggleFlag { x: mines[0].x, y: mines[0].y, player_id: "bob".into() });
+        world.update(0.0);
+        let board = world.get_resource::<Board>().unwrap();
+        assert_eq!(board.flagged(), 1);
+        assert_eq!(board.view(&world)[mines[0].y as usize][mines[0].x as usize], CellView::Flagged);
+
+        // 地雷以外をすべて開くとクリア（旗の立ったマスは開かれない）
+        for y in 0..8 {
+            for x in 0..8 {
+                if !mines.contains(&Cell { x, y }) {
+                    world.send_event(RevealCell { x, y, player_id: "alice".into() });
+                }
+            }
+        }
+        world.update(0.0);
+        let board = world.get_resource::<Board>().unwrap();
+        assert_eq!(board.outcome(), &BoardOutcome::Cleared { player_id: "alice".into() });
+        assert_eq!(board.view(&world)[mines[1].y as usize][mines[1].x as usize], CellView::Mine);
+
+        // 終了後の操作は無視される
+        world.send_event(ToggleFlag { x: mines[0].x, y: mines[0].y, player_id: "bob".into() });
+        world.update(0.0);
+        assert_eq!(world.get_resource::<Board>().unwrap().flagged(), 1);
+    }
+
+    #[test]
+    fn test_board_size_is_clamped() {
+        let config = BoardConfig::new(u32::MAX, u32::MAX, u32::MAX);
+        assert_eq!((config.width, config.height), (BoardConfig::MAX_SIDE, BoardConfig::MAX_SIDE));
+        assert_eq!(config.mines, BoardConfig::MAX_SIDE * BoardConfig::MAX_SIDE - 9);
+        assert_eq!(BoardConfig::new(0, 0, 5).mines, 0);
+
+        // newを通していない設定もinit_minesweeperで切り詰められる
+        let mut world = World::new();
+        let config = BoardConfig { width: 1_000_000, height: 2, mines: 3, seed: Some(4) };
+        init_minesweeper(&mut world, config).unwrap();
+        let board = world.get_resource::<Board>().unwrap();
+        assert_eq!(board.config(), BoardConfig { width: BoardConfig::MAX_SIDE, ..config });
+        assert_eq!(world.query::<&Cell>().iter(&world).count(), 200);
+    }
+}
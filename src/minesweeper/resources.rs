@@ -0,0 +1,290 @@
+//! マインスイーパーのリソースとイベント
+
+use std::any::Any;
+
+use serde::{Deserialize, Serialize};
+
+use crate::ecs::{Entity, Resource, World};
+use super::components::{AdjacentMines, Flagged, Mine, Revealed};
+
+/// 盤面の大きさと地雷の数
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BoardConfig {
+    /// 列数
+    pub width: u32,
+    /// 行数
+    pub height: u32,
+    /// 地雷の数
+    pub mines: u32,
+    /// 地雷の配置に使う乱数の種（`None`なら毎回ランダム）
+    #[serde(default)]
+    pub seed: Option<u64>,
+}
+
+impl BoardConfig {
+    /// 列数・行数の上限
+    ///
+    /// サーバーではルーム設定の値がそのまま渡されるため、マスのエンティティを
+    /// 際限なく生成しないよう1辺の長さを制限します。
+    pub const MAX_SIDE: u32 = 100;
+
+    /// 盤面の設定を作成
+    ///
+    /// 列数・行数は1から`MAX_SIDE`までに切り詰められます。
+    /// 最初に開くマスとその周囲には地雷を置かないため、地雷の数は
+    /// 残りのマスの数までに切り詰められます。
+    pub fn new(width: u32, height: u32, mines: u32) -> Self {
+        let width = width.clamp(1, Self::MAX_SIDE);
+        let height = height.clamp(1, Self::MAX_SIDE);
+        Self {
+            width,
+            height,
+            mines: mines.min(width.saturating_mul(height).saturating_sub(9)),
+            seed: None,
+        }
+    }
+
+    /// 範囲外の値を`new`と同じ規則で切り詰めた設定を取得
+    ///
+    /// デシリアライズした設定など、`new`を通していない値に使います。
+    pub fn clamped(self) -> Self {
+        Self {
+            seed: self.seed,
+            ..Self::new(self.width, self.height, self.mines)
+        }
+    }
+
+    /// 地雷の配置に使う乱数の種を指定
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+}
+
+/// 勝敗
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "state", rename_all = "snake_case")]
+pub enum BoardOutcome {
+    /// 進行中
+    Playing,
+    /// 地雷のないマスがすべて開かれた
+    Cleared {
+        /// 最後のマスを開いたプレイヤー
+        player_id: String,
+    },
+    /// 地雷のマスが開かれた
+    Exploded {
+        /// 地雷を開いたプレイヤー
+        player_id: String,
+        /// 開かれた地雷の列
+        x: u32,
+        /// 開かれた地雷の行
+        y: u32,
+    },
+}
+
+/// プレイヤーから見た1マスの状態
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CellView {
+    /// 開かれていない
+    Hidden,
+    /// 旗が立っている
+    Flagged,
+    /// 開かれている（周囲の地雷の数）
+    Revealed(u8),
+    /// 開かれた地雷
+    Mine,
+}
+
+/// 盤面
+///
+/// マスのエンティティを座標から引く表と、進行状況を保持します。
+#[derive(Debug, Clone)]
+pub struct Board {
+    /// 盤面の設定
+    config: BoardConfig,
+    /// マスのエンティティ（行優先）
+    cells: Vec<Entity>,
+    /// 地雷が配置済みかどうか
+    mines_placed: bool,
+    /// 開かれたマスの数
+    revealed: u32,
+    /// 旗の数
+    flagged: u32,
+    /// 勝敗
+    outcome: BoardOutcome,
+}
+
+impl Board {
+    /// マスのエンティティから盤面を作成
+    pub(crate) fn new(config: BoardConfig, cells: Vec<Entity>) -> Self {
+        Self {
+            config,
+            cells,
+            mines_placed: false,
+            revealed: 0,
+            flagged: 0,
+            outcome: BoardOutcome::Playing,
+        }
+    }
+
+    /// 盤面の設定を取得
+    pub fn config(&self) -> BoardConfig {
+        self.config
+    }
+
+    /// 座標のマスのエンティティを取得
+    pub fn cell_at(&self, x: u32, y: u32) -> Option<Entity> {
+        if x < self.config.width && y < self.config.height {
+            self.cells.get((y * self.config.width + x) as usize).copied()
+        } else {
+            None
+        }
+    }
+
+    /// 周囲8マスの座標を取得
+    pub fn neighbors(&self, x: u32, y: u32) -> impl Iterator<Item = (u32, u32)> + '_ {
+        let (x, y) = (x as i64, y as i64);
+        (-1..=1).flat_map(move |dy| (-1..=1).map(move |dx| (x + dx, y + dy)))
+            .filter(move |&(nx, ny)| (nx, ny) != (x, y))
+            .filter(|&(nx, ny)| {
+                nx >= 0 && ny >= 0 && nx < self.config.width as i64 && ny < self.config.height as i64
+            })
+            .map(|(nx, ny)| (nx as u32, ny as u32))
+    }
+
+    /// 地雷が配置済みかどうか
+    pub fn mines_placed(&self) -> bool {
+        self.mines_placed
+    }
+
+    /// 開かれたマスの数
+    pub fn revealed(&self) -> u32 {
+        self.revealed
+    }
+
+    /// 旗の数
+    pub fn flagged(&self) -> u32 {
+        self.flagged
+    }
+
+    /// 勝敗を取得
+    pub fn outcome(&self) -> &BoardOutcome {
+        &self.outcome
+    }
+
+    /// 勝敗が決まったかどうか
+    pub fn is_finished(&self) -> bool {
+        self.outcome != BoardOutcome::Playing
+    }
+
+    /// プレイヤーから見た盤面を取得（行ごと）
+    ///
+    /// 勝敗が決まった後は、開かれていない地雷も`CellView::Mine`になります。
+    pub fn view(&self, world: &World) -> Vec<Vec<CellView>> {
+        (0..self.config.height)
+            .map(|y| {
+                (0..self.config.width)
+                    .map(|x| {
+                        let entity = self.cells[(y * self.config.width + x) as usize];
+                        let is_mine = world.get_component::<Mine>(entity).is_some();
+                        if world.get_component::<Revealed>(entity).is_some() || (is_mine && self.is_finished()) {
+                            if is_mine {
+                                CellView::Mine
+                            } else {
+                                let count = world.get_component::<AdjacentMines>(entity).map_or(0, |count| count.0);
+                                CellView::Revealed(count)
+                            }
+                        } else if world.get_component::<Flagged>(entity).is_some() {
+                            CellView::Flagged
+                        } else {
+                            CellView::Hidden
+                        }
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// 地雷を配置済みにする
+    pub(crate) fn set_mines_placed(&mut self) {
+        self.mines_placed = true;
+    }
+
+    /// 開かれたマスを数え、勝敗を更新
+    pub(crate) fn add_revealed(&mut self, count: u32, player_id: &str) {
+        self.revealed += count;
+        if self.outcome == BoardOutcome::Playing
+            && self.revealed + self.config.mines == self.config.width * self.config.height
+        {
+            self.outcome = BoardOutcome::Cleared { player_id: player_id.to_string() };
+        }
+    }
+
+    /// 地雷が開かれたことを記録
+    pub(crate) fn explode(&mut self, player_id: &str, x: u32, y: u32) {
+        self.outcome = BoardOutcome::Exploded { player_id: player_id.to_string(), x, y };
+    }
+
+    /// 旗の数を更新
+    pub(crate) fn set_flagged(&mut self, flagged: u32) {
+        self.flagged = flagged;
+    }
+}
+
+impl Resource for Board {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+/// マスを開く操作
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RevealCell {
+    /// 列
+    pub x: u32,
+    /// 行
+    pub y: u32,
+    /// 操作したプレイヤー
+    pub player_id: String,
+}
+
+/// 旗を切り替える操作
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ToggleFlag {
+    /// 列
+    pub x: u32,
+    /// 行
+    pub y: u32,
+    /// 操作したプレイヤー
+    pub player_id: String,
+}
+
+/// `RevealCell`の結果
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CellsRevealed {
+    /// 操作したプレイヤー
+    pub player_id: String,
+    /// 新しく開かれたマスの座標（開かれた順）
+    pub cells: Vec<(u32, u32)>,
+    /// 地雷を開いたかどうか
+    pub exploded: bool,
+}
+
+/// `ToggleFlag`の結果
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FlagToggled {
+    /// 操作したプレイヤー
+    pub player_id: String,
+    /// 列
+    pub x: u32,
+    /// 行
+    pub y: u32,
+    /// 旗が立っているかどうか
+    pub flagged: bool,
+}
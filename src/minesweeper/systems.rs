@@ -0,0 +1,201 @@
+//! マインスイーパーのルールを処理するシステム
+
+use rand::rngs::SmallRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+use crate::Error;
+
+use crate::ecs::{EventReader, Events, ResourceManager, System, SystemPhase, SystemPriority, World};
+use super::components::{AdjacentMines, Flagged, Mine, Revealed};
+use super::resources::{Board, CellsRevealed, FlagToggled, RevealCell, ToggleFlag};
+
+/// `RevealCell`を処理してマスを開くシステム
+///
+/// 最初に開かれたマスとその周囲を避けて地雷を配置し、周囲に地雷のないマスは
+/// 隣のマスも続けて開きます。結果は`CellsRevealed`として送信されます。
+#[derive(Default)]
+pub struct RevealSystem {
+    /// 未処理の`RevealCell`の読み取り位置
+    reader: EventReader<RevealCell>,
+}
+
+impl RevealSystem {
+    /// 新しいシステムを作成
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl System for RevealSystem {
+    fn name(&self) -> &'static str {
+        "RevealSystem"
+    }
+
+    fn phase(&self) -> SystemPhase {
+        SystemPhase::Update
+    }
+
+    fn priority(&self) -> SystemPriority {
+        SystemPriority::new(0)
+    }
+
+    fn run(&mut self, world: &mut World, resources: &mut ResourceManager, _delta_time: f32) -> Result<(), Error> {
+        let requests: Vec<RevealCell> = match resources.get::<Events<RevealCell>>() {
+            Some(events) => self.reader.read(events).cloned().collect(),
+            None => return Ok(()),
+        };
+        for request in requests {
+            let board = match resources.get::<Board>() {
+                Some(board) if !board.is_finished() => board.clone(),
+                _ => break,
+            };
+            if board.cell_at(request.x, request.y).is_none() {
+                continue;
+            }
+            if !board.mines_placed() {
+                place_mines(world, &board, request.x, request.y);
+            }
+
+            let result = reveal(world, &board, &request);
+            if let Some(board) = resources.get_mut::<Board>() {
+                board.set_mines_placed();
+                if result.exploded {
+                    board.explode(&request.player_id, request.x, request.y);
+                } else {
+                    board.add_revealed(result.cells.len() as u32, &request.player_id);
+                }
+            }
+            if let Some(events) = resources.get_mut::<Events<CellsRevealed>>() {
+                events.send(result);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// `(first_x, first_y)`とその周囲を避けて地雷を配置し、各マスの周囲の地雷を数える
+fn place_mines(world: &mut World, board: &Board, first_x: u32, first_y: u32) {
+    let config = board.config();
+    let mut candidates: Vec<(u32, u32)> = (0..config.height)
+        .flat_map(|y| (0..config.width).map(move |x| (x, y)))
+        .filter(|&(x, y)| x.abs_diff(first_x) > 1 || y.abs_diff(first_y) > 1)
+        .collect();
+    let mut rng = match config.seed {
+        Some(seed) => SmallRng::seed_from_u64(seed),
+        None => SmallRng::from_entropy(),
+    };
+    candidates.shuffle(&mut rng);
+
+    for &(x, y) in candidates.iter().take(config.mines as usize) {
+        if let Some(entity) = board.cell_at(x, y) {
+            world.add_component(entity, Mine);
+        }
+    }
+    for y in 0..config.height {
+        for x in 0..config.width {
+            let count = board.neighbors(x, y)
+                .filter_map(|(nx, ny)| board.cell_at(nx, ny))
+                .filter(|&entity| world.get_component::<Mine>(entity).is_some())
+                .count();
+            if let Some(entity) = board.cell_at(x, y) {
+                world.add_component(entity, AdjacentMines(count as u8));
+            }
+        }
+    }
+}
+
+/// マスを開き、周囲に地雷のないマスからは隣へ広げる
+fn reveal(world: &mut World, board: &Board, request: &RevealCell) -> CellsRevealed {
+    let mut result = CellsRevealed {
+        player_id: request.player_id.clone(),
+        cells: Vec::new(),
+        exploded: false,
+    };
+    let mut pending = vec![(request.x, request.y)];
+    while let Some((x, y)) = pending.pop() {
+        let entity = match board.cell_at(x, y) {
+            Some(entity) => entity,
+            None => continue,
+        };
+        if world.get_component::<Revealed>(entity).is_some() || world.get_component::<Flagged>(entity).is_some() {
+            continue;
+        }
+        world.add_component(entity, Revealed);
+        result.cells.push((x, y));
+
+        if world.get_component::<Mine>(entity).is_some() {
+            result.exploded = true;
+            break;
+        }
+        if world.get_component::<AdjacentMines>(entity).map_or(0, |count| count.0) == 0 {
+            pending.extend(board.neighbors(x, y));
+        }
+    }
+    result
+}
+
+/// `ToggleFlag`を処理して旗を切り替えるシステム
+///
+/// 開かれたマスには旗を立てられません。結果は`FlagToggled`として送信されます。
+#[derive(Default)]
+pub struct FlagSystem {
+    /// 未処理の`ToggleFlag`の読み取り位置
+    reader: EventReader<ToggleFlag>,
+}
+
+impl FlagSystem {
+    /// 新しいシステムを作成
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl System for FlagSystem {
+    fn name(&self) -> &'static str {
+        "FlagSystem"
+    }
+
+    fn phase(&self) -> SystemPhase {
+        SystemPhase::Update
+    }
+
+    fn priority(&self) -> SystemPriority {
+        SystemPriority::new(10)
+    }
+
+    fn run(&mut self, world: &mut World, resources: &mut ResourceManager, _delta_time: f32) -> Result<(), Error> {
+        let requests: Vec<ToggleFlag> = match resources.get::<Events<ToggleFlag>>() {
+            Some(events) => self.reader.read(events).cloned().collect(),
+            None => return Ok(()),
+        };
+        for request in requests {
+            let (entity, flagged) = match resources.get::<Board>() {
+                Some(board) if !board.is_finished() => match board.cell_at(request.x, request.y) {
+                    Some(entity) => (entity, board.flagged()),
+                    None => continue,
+                },
+                _ => break,
+            };
+            if world.get_component::<Revealed>(entity).is_some() {
+                continue;
+            }
+
+            let now_flagged = !world.remove_component::<Flagged>(entity);
+            if now_flagged {
+                world.add_component(entity, Flagged { player_id: request.player_id.clone() });
+            }
+            if let Some(board) = resources.get_mut::<Board>() {
+                board.set_flagged(if now_flagged { flagged + 1 } else { flagged - 1 });
+            }
+            if let Some(events) = resources.get_mut::<Events<FlagToggled>>() {
+                events.send(FlagToggled {
+                    player_id: request.player_id,
+                    x: request.x,
+                    y: request.y,
+                    flagged: now_flagged,
+                });
+            }
+        }
+        Ok(())
+    }
+}
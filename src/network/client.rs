@@ -14,42 +14,17 @@ use log::{debug, error, info, warn, trace};
 use serde_json;
 use std::thread::LocalKey;
 
+use super::codec::WireFormat;
 use super::protocol::{NetworkMessage, MessageType, MouseCursorUpdateData};
 use super::messages::{InputData, PlayerData, EntitySnapshot};
 use super::{ConnectionState, ConnectionStateType, NetworkError, TimeSyncData, NetworkConfig};
 use crate::ecs::{World, Resource};
+use crate::Error;
 
 thread_local! {
     static MOUSE_CURSOR_HANDLERS: RefCell<Vec<Box<dyn Fn(MouseCursorUpdateData)>>> = RefCell::new(Vec::new());
 }
 
-/// ネットワークコンポーネント（エンティティに付与される）
-#[derive(Debug, Clone)]
-pub struct NetworkComponent {
-    /// エンティティIDがネットワーク全体で同期されているか
-    pub is_synced: bool,
-    /// 最後の同期時刻
-    pub last_sync_time: f64,
-    /// 補間係数
-    pub interpolation_factor: f32,
-    /// リモートエンティティか（他のプレイヤーから同期されたもの）
-    pub is_remote: bool,
-    /// このエンティティの所有者ID
-    pub owner_id: Option<u32>,
-}
-
-impl Default for NetworkComponent {
-    fn default() -> Self {
-        Self {
-            is_synced: false,
-            last_sync_time: 0.0,
-            interpolation_factor: 0.0,
-            is_remote: false,
-            owner_id: None,
-        }
-    }
-}
-
 /// ネットワーククライアント
 #[derive(Clone)]
 pub struct NetworkClient {
@@ -84,6 +59,8 @@ pub struct NetworkClient {
     rtt: f64,
     /// 受信したマウスカーソル更新データ
     pub pending_cursor_updates: Vec<MouseCursorUpdateData>,
+    /// 送信に使うメッセージ形式（`ConnectResponse`で決まるまではJSON）
+    wire_format: WireFormat,
 }
 
 // NetworkClientにResourceトレイトを実装
@@ -114,6 +91,7 @@ impl std::fmt::Debug for NetworkClient {
             .field("last_ping_time", &self.last_ping_time)
             .field("rtt", &self.rtt)
             .field("pending_cursor_updates", &self.pending_cursor_updates)
+            .field("wire_format", &self.wire_format)
             // mouse_cursor_handlerは除外（DebugトレイトがFn型に実装されていないため）
             .finish()
     }
@@ -138,6 +116,7 @@ impl NetworkClient {
             rtt: 0.0,
             last_error: None,
             pending_cursor_updates: Vec::new(),
+            wire_format: WireFormat::Json,
         }
     }
 
@@ -168,20 +147,37 @@ impl NetworkClient {
 
         // WebSocketが開いたときのコールバック
         let connection_state_clone = connection_state.clone();
+        let ws_clone = ws.clone();
+        let wire_formats = self.config.wire_format.offer();
         let onopen_callback = Closure::wrap(Box::new(move |_event: Event| {
             log::info!("🌐 WebSocket接続完了！");
             // 接続状態を更新
             if let Ok(mut state) = connection_state_clone.try_borrow_mut() {
                 state.set_state(ConnectionStateType::Connected);
             }
+            // バイナリ形式を選んだ場合だけ使える形式を伝える（形式が決まる前なのでJSONで送る）
+            // 形式を決めないサーバーには何も送らず、そのままJSONを使う
+            if wire_formats.contains(&WireFormat::Binary) {
+                let connect = NetworkMessage::new(MessageType::Connect { wire_formats: wire_formats.clone() });
+                if let Err(err) = send_encoded(&ws_clone, &connect, WireFormat::Json) {
+                    log::error!("接続メッセージの送信に失敗: {:?}", err);
+                }
+            }
         }) as Box<dyn FnMut(Event)>);
 
         // メッセージを受信したときのコールバック
         let onmessage_callback = Closure::wrap(Box::new(move |event: MessageEvent| {
-            // メッセージキューが存在する場合のみ処理
-            if let Ok(text) = event.data().dyn_into::<js_sys::JsString>() {
-                let text_str = text.as_string().unwrap();
-                match NetworkMessage::from_json(&text_str) {
+            // テキストフレームはJSON、バイナリフレームはバイナリ形式として読む
+            let data = event.data();
+            let decoded = if let Some(text) = data.as_string() {
+                Some(NetworkMessage::from_json(&text))
+            } else if let Ok(buffer) = data.dyn_into::<js_sys::ArrayBuffer>() {
+                Some(NetworkMessage::from_binary(&js_sys::Uint8Array::new(&buffer).to_vec()))
+            } else {
+                None
+            };
+            if let Some(decoded) = decoded {
+                match decoded {
                     Ok(message) => {
                         log::debug!("📩 メッセージ受信: {:?}", message);
                         // 安全にメッセージをキューに追加
@@ -221,6 +217,7 @@ impl NetworkClient {
         // 接続の保存
         self.socket = Some(ws);
         self.connected = true;
+        self.wire_format = WireFormat::Json;
         self.player_id = Some(0); // Assuming a default player_id
 
         log::info!("🔄 サーバーに接続中: {}", url);
@@ -241,10 +238,8 @@ impl NetworkClient {
                 // 切断メッセージを送信
                 let disconnect_msg = NetworkMessage::new(MessageType::Disconnect { reason: None })
                     .with_sequence(next_seq);
-                if let Ok(json) = disconnect_msg.to_json() {
-                    if let Err(err) = ws.send_with_str(&json) {
-                        web_sys::console::error_1(&format!("切断メッセージの送信エラー: {:?}", err).into());
-                    }
+                if let Err(err) = send_encoded(&ws, &disconnect_msg, self.wire_format) {
+                    web_sys::console::error_1(&format!("切断メッセージの送信エラー: {:?}", err).into());
                 }
                 
                 // 接続を閉じる
@@ -258,6 +253,7 @@ impl NetworkClient {
         self.connected = false;
         self.socket = None;
         self.player_id = None;
+        self.wire_format = WireFormat::Json;
         
         Ok(())
     }
@@ -274,27 +270,22 @@ impl NetworkClient {
             // WebSocketの状態を確認
             match ws.ready_state() {
                 WebSocket::OPEN => {
-                    // メッセージをJSONに変換
-                    let json_message = match message.to_json() {
-                        Ok(json) => json,
-                        Err(e) => {
-                            log::error!("メッセージのシリアライズに失敗: {:?}", e);
-                            return Err(NetworkError::MessageProcessingError("メッセージのシリアライズに失敗".to_string()));
-                        }
-                    };
-
-                    // メッセージを送信
-                    match ws.send_with_str(&json_message) {
-                        Ok(_) => {
+                    // 接続時に決まった形式で変換して送信
+                    match send_encoded(ws, &message, self.wire_format) {
+                        Ok(()) => {
                             log::debug!("📤 メッセージ送信: {:?}", message);
                             Ok(())
                         }
-                        Err(err) => {
+                        Err(Error::Js(err)) => {
                             log::error!("メッセージ送信エラー: {:?}", err);
                             // エラーが発生した場合も一旦保留キューに入れる (再接続後に送信試行)
                             self.connection_state.borrow_mut().push_back(message);
                             Err(NetworkError::MessageProcessingError(format!("メッセージ送信エラー: {:?}", err)))
                         }
+                        Err(e) => {
+                            log::error!("メッセージのシリアライズに失敗: {:?}", e);
+                            Err(NetworkError::MessageProcessingError("メッセージのシリアライズに失敗".to_string()))
+                        }
                     }
                 }
                 WebSocket::CONNECTING => {
@@ -389,9 +380,11 @@ impl NetworkClient {
     /// メッセージを処理する
     fn handle_message(&mut self, message: NetworkMessage) {
         match message.message_type {
-            MessageType::ConnectResponse { player_id, .. } => {
+            MessageType::ConnectResponse { player_id, wire_format, .. } => {
                 web_sys::console::log_1(&format!("プレイヤーID受信: {}", player_id).into());
                 self.player_id = Some(player_id);
+                // 以降のメッセージはサーバーが選んだ形式で送る
+                self.wire_format = wire_format;
             },
             MessageType::Ping { client_time } => {
                 // Pingに対してPongを返す
@@ -630,6 +623,21 @@ fn call_mouse_cursor_handlers(data: MouseCursorUpdateData) {
     });
 }
 
+/// メッセージを指定の形式に変換して送信
+///
+/// JSONはテキストフレーム、バイナリはバイナリフレームで送ります。
+///
+/// # エラー
+///
+/// * 変換に失敗した場合は変換時のエラー、送信に失敗した場合は`Error::Js`
+fn send_encoded(ws: &WebSocket, message: &NetworkMessage, format: WireFormat) -> Result<(), Error> {
+    match format {
+        WireFormat::Json => ws.send_with_str(&message.to_json()?)?,
+        WireFormat::Binary => ws.send_with_u8_array(&message.to_binary()?)?,
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,802 @@
+//! ネットワークメッセージのバイナリ形式
+//!
+//! `NetworkMessage`をJSONより小さいバイト列に変換します。整数はvarint（LEB128）、
+//! 浮動小数点数はリトルエンディアンの固定長で書き出し、メッセージ種別と
+//! `ComponentData`の種類は1バイトのタグで表します。
+//!
+//! どちらの形式で送るかは接続時に決めます。クライアントは`Connect`で使える形式を伝え、
+//! サーバーは`ConnectResponse`で選んだ形式を返します。JSONはデバッグ用に常に使えます。
+//!
+//! 形式を決められるのはこのクレートの`NetworkServer`だけです。`server`クレートの
+//! ゲームサーバーは独自のメッセージを使い`Connect`に応答しないため、バイナリ形式は
+//! `NetworkConfig::wire_format`で明示的に選んだ場合にのみ提案します。
+//!
+//! ```
+//! let bytes = message.to_binary()?;
+//! let decoded = NetworkMessage::from_binary(&bytes)?;
+//!
+//! // 接続時の形式の決定
+//! let offered = WireFormat::Binary.offer();
+//! let format = WireFormat::negotiate(&offered, config.wire_format);
+//! ```
+
+use std::collections::HashMap;
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use super::messages::{ComponentData, InputData, PlayerData};
+use super::protocol::{MessageType, NetworkMessage};
+use crate::Error;
+
+/// バイナリ形式のバージョン（先頭の1バイト）
+pub const BINARY_VERSION: u8 = 1;
+
+/// バイナリメッセージの復元に失敗した理由
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CodecError {
+    /// 対応していないバージョン
+    UnsupportedVersion(u8),
+    /// データが途中で終わっている
+    UnexpectedEnd,
+    /// メッセージの後に余分なバイトがある
+    TrailingBytes(usize),
+    /// 未知のタグが含まれていた
+    UnknownTag {
+        /// タグの種類
+        kind: &'static str,
+        /// 読み取ったタグ
+        tag: u8,
+    },
+    /// フラグに未知のビットが立っていた
+    UnknownFlags(u8),
+    /// 値の形式が正しくない
+    InvalidData(String),
+}
+
+impl fmt::Display for CodecError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CodecError::UnsupportedVersion(version) => {
+                write!(f, "バイナリ形式のバージョンが異なります: {}（対応: {}）", version, BINARY_VERSION)
+            }
+            CodecError::UnexpectedEnd => f.write_str("バイナリメッセージが途中で終わっています"),
+            CodecError::TrailingBytes(len) => write!(f, "メッセージの後に{}バイトの余分なデータがあります", len),
+            CodecError::UnknownTag { kind, tag } => write!(f, "未知の{}のタグ: {}", kind, tag),
+            CodecError::UnknownFlags(flags) => write!(f, "フラグに未知のビットが立っています: {:#010b}", flags),
+            CodecError::InvalidData(message) => {
+                write!(f, "バイナリメッセージの形式が正しくありません: {}", message)
+            }
+        }
+    }
+}
+
+impl std::error::Error for CodecError {}
+
+/// 送受信に使うメッセージの形式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WireFormat {
+    /// JSON文字列（テキストフレーム）
+    #[default]
+    Json,
+    /// バイナリ（バイナリフレーム）
+    Binary,
+}
+
+impl WireFormat {
+    /// この形式を優先するクライアントが`Connect`で伝える形式の一覧
+    ///
+    /// JSONはどのサーバーでも使えるため、常に最後に含めます。
+    pub fn offer(self) -> Vec<WireFormat> {
+        match self {
+            WireFormat::Json => vec![WireFormat::Json],
+            WireFormat::Binary => vec![WireFormat::Binary, WireFormat::Json],
+        }
+    }
+
+    /// クライアントが伝えた形式からサーバーが使う形式を選ぶ
+    ///
+    /// # 引数
+    ///
+    /// * `offered` - クライアントが使える形式
+    /// * `preferred` - サーバーが優先する形式
+    ///
+    /// # 戻り値
+    ///
+    /// `preferred`をクライアントが使えればそれを、使えなければJSONを返します。
+    pub fn negotiate(offered: &[WireFormat], preferred: WireFormat) -> WireFormat {
+        if offered.contains(&preferred) {
+            preferred
+        } else {
+            WireFormat::Json
+        }
+    }
+
+    /// 形式を表すタグ
+    fn tag(self) -> u8 {
+        match self {
+            WireFormat::Json => 0,
+            WireFormat::Binary => 1,
+        }
+    }
+
+    /// タグから形式を取得
+    fn from_tag(tag: u8) -> Result<Self, CodecError> {
+        match tag {
+            0 => Ok(WireFormat::Json),
+            1 => Ok(WireFormat::Binary),
+            _ => Err(CodecError::UnknownTag { kind: "メッセージ形式", tag }),
+        }
+    }
+}
+
+// メッセージに含まれる任意のフィールドのフラグ
+const HAS_SEQUENCE: u8 = 1 << 0;
+const HAS_ENTITY_ID: u8 = 1 << 1;
+const HAS_PLAYER_ID: u8 = 1 << 2;
+const HAS_COMPONENTS: u8 = 1 << 3;
+const HAS_INPUT_DATA: u8 = 1 << 4;
+const HAS_PLAYER_DATA: u8 = 1 << 5;
+/// このバージョンで定義されているフラグ（それ以外のビットは復元時にエラーにする）
+const KNOWN_FLAGS: u8 = HAS_SEQUENCE | HAS_ENTITY_ID | HAS_PLAYER_ID | HAS_COMPONENTS | HAS_INPUT_DATA | HAS_PLAYER_DATA;
+
+/// メッセージをバイナリ形式に変換
+///
+/// `[バージョン][種別タグ][種別のデータ][フラグ][タイムスタンプ][フラグが立っているフィールド...]`
+/// の順に書き出します。
+pub fn encode(message: &NetworkMessage) -> Result<Vec<u8>, Error> {
+    let mut writer = BinaryWriter::new();
+    writer.write_u8(BINARY_VERSION);
+    write_message_type(&mut writer, &message.message_type);
+
+    let mut flags = 0;
+    if message.sequence.is_some() {
+        flags |= HAS_SEQUENCE;
+    }
+    if message.entity_id.is_some() {
+        flags |= HAS_ENTITY_ID;
+    }
+    if message.player_id.is_some() {
+        flags |= HAS_PLAYER_ID;
+    }
+    if message.components.is_some() {
+        flags |= HAS_COMPONENTS;
+    }
+    if message.input_data.is_some() {
+        flags |= HAS_INPUT_DATA;
+    }
+    if message.player_data.is_some() {
+        flags |= HAS_PLAYER_DATA;
+    }
+    writer.write_u8(flags);
+    writer.write_f64(message.timestamp);
+
+    if let Some(sequence) = message.sequence {
+        writer.write_varint(sequence as u64);
+    }
+    if let Some(entity_id) = message.entity_id {
+        writer.write_varint(entity_id as u64);
+    }
+    if let Some(player_id) = message.player_id {
+        writer.write_varint(player_id as u64);
+    }
+    if let Some(components) = &message.components {
+        write_components(&mut writer, components)?;
+    }
+    if let Some(input) = &message.input_data {
+        write_input(&mut writer, input);
+    }
+    if let Some(player_data) = &message.player_data {
+        write_player_data(&mut writer, player_data)?;
+    }
+    Ok(writer.into_bytes())
+}
+
+/// バイナリ形式からメッセージを復元
+///
+/// # エラー
+///
+/// 次の場合は`Error::Codec`を返します。
+///
+/// * バージョンが異なる場合
+/// * データが途中で終わっている、または余分なバイトがある場合
+/// * 未知のタグやフラグのビットが含まれている場合
+pub fn decode(bytes: &[u8]) -> Result<NetworkMessage, Error> {
+    let mut reader = BinaryReader::new(bytes);
+    let version = reader.read_u8()?;
+    if version != BINARY_VERSION {
+        return Err(CodecError::UnsupportedVersion(version).into());
+    }
+    let message_type = read_message_type(&mut reader)?;
+    let flags = reader.read_u8()?;
+    if flags & !KNOWN_FLAGS != 0 {
+        return Err(CodecError::UnknownFlags(flags).into());
+    }
+    let timestamp = reader.read_f64()?;
+
+    let mut message = NetworkMessage::new(message_type);
+    message.timestamp = timestamp;
+    if flags & HAS_SEQUENCE != 0 {
+        message.sequence = Some(reader.read_u32()?);
+    }
+    if flags & HAS_ENTITY_ID != 0 {
+        message.entity_id = Some(reader.read_u32()?);
+    }
+    if flags & HAS_PLAYER_ID != 0 {
+        message.player_id = Some(reader.read_u32()?);
+    }
+    if flags & HAS_COMPONENTS != 0 {
+        message.components = Some(read_components(&mut reader)?);
+    }
+    if flags & HAS_INPUT_DATA != 0 {
+        message.input_data = Some(read_input(&mut reader)?);
+    }
+    if flags & HAS_PLAYER_DATA != 0 {
+        message.player_data = Some(read_player_data(&mut reader)?);
+    }
+
+    if !reader.is_empty() {
+        return Err(CodecError::TrailingBytes(reader.remaining()).into());
+    }
+    Ok(message)
+}
+
+fn write_message_type(writer: &mut BinaryWriter, message_type: &MessageType) {
+    match message_type {
+        MessageType::Connect { wire_formats } => {
+            writer.write_u8(0);
+            writer.write_varint(wire_formats.len() as u64);
+            for format in wire_formats {
+                writer.write_u8(format.tag());
+            }
+        }
+        MessageType::ConnectResponse { player_id, success, message, wire_format } => {
+            writer.write_u8(1);
+            writer.write_varint(*player_id as u64);
+            writer.write_bool(*success);
+            writer.write_option(message.as_deref(), BinaryWriter::write_str);
+            writer.write_u8(wire_format.tag());
+        }
+        MessageType::Disconnect { reason } => {
+            writer.write_u8(2);
+            writer.write_option(reason.as_deref(), BinaryWriter::write_str);
+        }
+        MessageType::EntityCreate { entity_id } => {
+            writer.write_u8(3);
+            writer.write_varint(*entity_id as u64);
+        }
+        MessageType::EntityDelete { entity_id } => {
+            writer.write_u8(4);
+            writer.write_varint(*entity_id as u64);
+        }
+        MessageType::ComponentUpdate => writer.write_u8(5),
+        MessageType::Input => writer.write_u8(6),
+        MessageType::TimeSyncRequest { client_time } => {
+            writer.write_u8(7);
+            writer.write_f64(*client_time);
+        }
+        MessageType::TimeSyncResponse { client_time, server_time } => {
+            writer.write_u8(8);
+            writer.write_f64(*client_time);
+            writer.write_f64(*server_time);
+        }
+        MessageType::Ping { client_time } => {
+            writer.write_u8(9);
+            writer.write_f64(*client_time);
+        }
+        MessageType::Pong { client_time, server_time } => {
+            writer.write_u8(10);
+            writer.write_f64(*client_time);
+            writer.write_f64(*server_time);
+        }
+        MessageType::Error { code, message } => {
+            writer.write_u8(11);
+            writer.write_varint(*code as u64);
+            writer.write_str(message);
+        }
+        MessageType::MouseCursorUpdate => writer.write_u8(12),
+    }
+}
+
+fn read_message_type(reader: &mut BinaryReader) -> Result<MessageType, CodecError> {
+    let message_type = match reader.read_u8()? {
+        0 => {
+            let count = reader.read_len()?;
+            let wire_formats = (0..count)
+                .map(|_| WireFormat::from_tag(reader.read_u8()?))
+                .collect::<Result<_, _>>()?;
+            MessageType::Connect { wire_formats }
+        }
+        1 => MessageType::ConnectResponse {
+            player_id: reader.read_u32()?,
+            success: reader.read_bool()?,
+            message: reader.read_option(BinaryReader::read_string)?,
+            wire_format: WireFormat::from_tag(reader.read_u8()?)?,
+        },
+        2 => MessageType::Disconnect { reason: reader.read_option(BinaryReader::read_string)? },
+        3 => MessageType::EntityCreate { entity_id: reader.read_u32()? },
+        4 => MessageType::EntityDelete { entity_id: reader.read_u32()? },
+        5 => MessageType::ComponentUpdate,
+        6 => MessageType::Input,
+        7 => MessageType::TimeSyncRequest { client_time: reader.read_f64()? },
+        8 => MessageType::TimeSyncResponse {
+            client_time: reader.read_f64()?,
+            server_time: reader.read_f64()?,
+        },
+        9 => MessageType::Ping { client_time: reader.read_f64()? },
+        10 => MessageType::Pong {
+            client_time: reader.read_f64()?,
+            server_time: reader.read_f64()?,
+        },
+        11 => MessageType::Error {
+            code: reader.read_u32()?,
+            message: reader.read_string()?,
+        },
+        12 => MessageType::MouseCursorUpdate,
+        tag => return Err(CodecError::UnknownTag { kind: "メッセージ種別", tag }),
+    };
+    Ok(message_type)
+}
+
+/// `ComponentData`の種類の名前（名前がこれと同じコンポーネントは名前を省略する）
+fn component_kind(data: &ComponentData) -> &'static str {
+    match data {
+        ComponentData::Position { .. } => "Position",
+        ComponentData::Velocity { .. } => "Velocity",
+        ComponentData::Rotation { .. } => "Rotation",
+        ComponentData::Health { .. } => "Health",
+        ComponentData::Sprite { .. } => "Sprite",
+        ComponentData::PlayerInfo { .. } => "PlayerInfo",
+        ComponentData::Custom { .. } => "Custom",
+    }
+}
+
+fn write_components(writer: &mut BinaryWriter, components: &HashMap<String, ComponentData>) -> Result<(), Error> {
+    writer.write_varint(components.len() as u64);
+    for (name, data) in components {
+        // ほとんどのコンポーネントは種類と同じ名前で送られるため、その場合は空文字列にする
+        if name == component_kind(data) {
+            writer.write_str("");
+        } else {
+            writer.write_str(name);
+        }
+        write_component(writer, data)?;
+    }
+    Ok(())
+}
+
+fn read_components(reader: &mut BinaryReader) -> Result<HashMap<String, ComponentData>, Error> {
+    let count = reader.read_len()?;
+    let mut components = HashMap::with_capacity(count);
+    for _ in 0..count {
+        let name = reader.read_string()?;
+        let data = read_component(reader)?;
+        let name = if name.is_empty() { component_kind(&data).to_string() } else { name };
+        components.insert(name, data);
+    }
+    Ok(components)
+}
+
+fn write_component(writer: &mut BinaryWriter, data: &ComponentData) -> Result<(), Error> {
+    match data {
+        ComponentData::Position { x, y, z } => {
+            writer.write_u8(0);
+            writer.write_f32(*x);
+            writer.write_f32(*y);
+            writer.write_option(*z, BinaryWriter::write_f32);
+        }
+        ComponentData::Velocity { x, y, z } => {
+            writer.write_u8(1);
+            writer.write_f32(*x);
+            writer.write_f32(*y);
+            writer.write_option(*z, BinaryWriter::write_f32);
+        }
+        ComponentData::Rotation { angle } => {
+            writer.write_u8(2);
+            writer.write_f32(*angle);
+        }
+        ComponentData::Health { current, max } => {
+            writer.write_u8(3);
+            writer.write_varint(*current as u64);
+            writer.write_varint(*max as u64);
+        }
+        ComponentData::Sprite { id, visible } => {
+            writer.write_u8(4);
+            writer.write_str(id);
+            writer.write_bool(*visible);
+        }
+        ComponentData::PlayerInfo { player_id, name } => {
+            writer.write_u8(5);
+            writer.write_varint(*player_id as u64);
+            writer.write_str(name);
+        }
+        ComponentData::Custom { data } => {
+            // 形の決まっていないデータはJSON文字列のまま埋め込む
+            writer.write_u8(6);
+            writer.write_json(data)?;
+        }
+    }
+    Ok(())
+}
+
+fn read_component(reader: &mut BinaryReader) -> Result<ComponentData, CodecError> {
+    let data = match reader.read_u8()? {
+        0 => ComponentData::Position {
+            x: reader.read_f32()?,
+            y: reader.read_f32()?,
+            z: reader.read_option(BinaryReader::read_f32)?,
+        },
+        1 => ComponentData::Velocity {
+            x: reader.read_f32()?,
+            y: reader.read_f32()?,
+            z: reader.read_option(BinaryReader::read_f32)?,
+        },
+        2 => ComponentData::Rotation { angle: reader.read_f32()? },
+        3 => ComponentData::Health {
+            current: reader.read_u32()?,
+            max: reader.read_u32()?,
+        },
+        4 => ComponentData::Sprite {
+            id: reader.read_string()?,
+            visible: reader.read_bool()?,
+        },
+        5 => ComponentData::PlayerInfo {
+            player_id: reader.read_u32()?,
+            name: reader.read_string()?,
+        },
+        6 => ComponentData::Custom { data: reader.read_json()? },
+        tag => return Err(CodecError::UnknownTag { kind: "コンポーネント", tag }),
+    };
+    Ok(data)
+}
+
+fn write_input(writer: &mut BinaryWriter, input: &InputData) {
+    writer.write_f32(input.movement.0);
+    writer.write_f32(input.movement.1);
+    writer.write_varint(input.actions.len() as u64);
+    for (action, pressed) in &input.actions {
+        writer.write_str(action);
+        writer.write_bool(*pressed);
+    }
+    writer.write_option(input.aim, |writer, (x, y)| {
+        writer.write_f32(x);
+        writer.write_f32(y);
+    });
+    writer.write_f64(input.timestamp);
+}
+
+fn read_input(reader: &mut BinaryReader) -> Result<InputData, CodecError> {
+    let movement = (reader.read_f32()?, reader.read_f32()?);
+    let count = reader.read_len()?;
+    let mut actions = HashMap::with_capacity(count);
+    for _ in 0..count {
+        let action = reader.read_string()?;
+        actions.insert(action, reader.read_bool()?);
+    }
+    let aim = reader.read_option(|reader| Ok((reader.read_f32()?, reader.read_f32()?)))?;
+    Ok(InputData {
+        movement,
+        actions,
+        aim,
+        timestamp: reader.read_f64()?,
+    })
+}
+
+fn write_player_data(writer: &mut BinaryWriter, player_data: &PlayerData) -> Result<(), Error> {
+    writer.write_str(&player_data.name);
+    writer.write_option(player_data.avatar.as_deref(), BinaryWriter::write_str);
+    writer.write_option(player_data.team, |writer, team| writer.write_varint(team as u64));
+    match &player_data.settings {
+        Some(settings) => {
+            writer.write_bool(true);
+            writer.write_varint(settings.len() as u64);
+            for (key, value) in settings {
+                writer.write_str(key);
+                writer.write_json(value)?;
+            }
+        }
+        None => writer.write_bool(false),
+    }
+    Ok(())
+}
+
+fn read_player_data(reader: &mut BinaryReader) -> Result<PlayerData, CodecError> {
+    let name = reader.read_string()?;
+    let avatar = reader.read_option(BinaryReader::read_string)?;
+    let team = reader.read_option(BinaryReader::read_u32)?;
+    let settings = reader.read_option(|reader| {
+        let count = reader.read_len()?;
+        let mut settings = HashMap::with_capacity(count);
+        for _ in 0..count {
+            let key = reader.read_string()?;
+            settings.insert(key, reader.read_json()?);
+        }
+        Ok(settings)
+    })?;
+    Ok(PlayerData { name, avatar, team, settings })
+}
+
+/// バイト列への書き出し
+struct BinaryWriter {
+    /// 書き出したバイト列
+    bytes: Vec<u8>,
+}
+
+impl BinaryWriter {
+    fn new() -> Self {
+        Self { bytes: Vec::new() }
+    }
+
+    fn into_bytes(self) -> Vec<u8> {
+        self.bytes
+    }
+
+    fn write_u8(&mut self, value: u8) {
+        self.bytes.push(value);
+    }
+
+    fn write_bool(&mut self, value: bool) {
+        self.write_u8(value as u8);
+    }
+
+    /// 符号なし整数をvarint（7ビットずつ、続きがあれば最上位ビットを立てる）で書き出す
+    fn write_varint(&mut self, mut value: u64) {
+        while value >= 0x80 {
+            self.write_u8((value as u8 & 0x7f) | 0x80);
+            value >>= 7;
+        }
+        self.write_u8(value as u8);
+    }
+
+    fn write_f32(&mut self, value: f32) {
+        self.bytes.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn write_f64(&mut self, value: f64) {
+        self.bytes.extend_from_slice(&value.to_le_bytes());
+    }
+
+    /// 長さ（varint）とUTF-8のバイト列を書き出す
+    fn write_str(&mut self, value: &str) {
+        self.write_varint(value.len() as u64);
+        self.bytes.extend_from_slice(value.as_bytes());
+    }
+
+    fn write_json(&mut self, value: &serde_json::Value) -> Result<(), Error> {
+        let json = serde_json::to_string(value)
+            .map_err(|e| Error::Other(format!("JSON文字列化エラー: {}", e)))?;
+        self.write_str(&json);
+        Ok(())
+    }
+
+    /// 有無を1バイトで書き出し、値があれば続けて書き出す
+    fn write_option<T>(&mut self, value: Option<T>, write: impl FnOnce(&mut Self, T)) {
+        match value {
+            Some(value) => {
+                self.write_bool(true);
+                write(self, value);
+            }
+            None => self.write_bool(false),
+        }
+    }
+}
+
+/// バイト列からの読み取り
+struct BinaryReader<'a> {
+    /// 読み取るバイト列
+    bytes: &'a [u8],
+    /// 次に読み取る位置
+    position: usize,
+}
+
+impl<'a> BinaryReader<'a> {
+    fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes, position: 0 }
+    }
+
+    fn remaining(&self) -> usize {
+        self.bytes.len() - self.position
+    }
+
+    fn is_empty(&self) -> bool {
+        self.remaining() == 0
+    }
+
+    fn read_bytes(&mut self, len: usize) -> Result<&'a [u8], CodecError> {
+        if self.remaining() < len {
+            return Err(CodecError::UnexpectedEnd);
+        }
+        let bytes = &self.bytes[self.position..self.position + len];
+        self.position += len;
+        Ok(bytes)
+    }
+
+    fn read_u8(&mut self) -> Result<u8, CodecError> {
+        Ok(self.read_bytes(1)?[0])
+    }
+
+    fn read_bool(&mut self) -> Result<bool, CodecError> {
+        match self.read_u8()? {
+            0 => Ok(false),
+            1 => Ok(true),
+            value => Err(CodecError::InvalidData(format!("真偽値ではないバイト: {}", value))),
+        }
+    }
+
+    fn read_varint(&mut self) -> Result<u64, CodecError> {
+        let mut value = 0u64;
+        for shift in (0..64).step_by(7) {
+            let byte = self.read_u8()?;
+            // 10バイト目に残っているのは最上位の1ビットだけ
+            if shift == 63 && byte > 1 {
+                return Err(CodecError::InvalidData("varintがu64の範囲を超えています".to_string()));
+            }
+            value |= ((byte & 0x7f) as u64) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(CodecError::InvalidData("varintが長すぎます".to_string()))
+    }
+
+    fn read_u32(&mut self) -> Result<u32, CodecError> {
+        let value = self.read_varint()?;
+        u32::try_from(value).map_err(|_| CodecError::InvalidData(format!("u32の範囲を超える値: {}", value)))
+    }
+
+    /// 要素数や文字列の長さを読み取る（残りのバイト数を超える値はエラー）
+    fn read_len(&mut self) -> Result<usize, CodecError> {
+        let len = self.read_varint()?;
+        if len > self.remaining() as u64 {
+            return Err(CodecError::UnexpectedEnd);
+        }
+        Ok(len as usize)
+    }
+
+    fn read_f32(&mut self) -> Result<f32, CodecError> {
+        let bytes = self.read_bytes(4)?;
+        Ok(f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    fn read_f64(&mut self) -> Result<f64, CodecError> {
+        let mut bytes = [0; 8];
+        bytes.copy_from_slice(self.read_bytes(8)?);
+        Ok(f64::from_le_bytes(bytes))
+    }
+
+    fn read_string(&mut self) -> Result<String, CodecError> {
+        let len = self.read_len()?;
+        let bytes = self.read_bytes(len)?;
+        String::from_utf8(bytes.to_vec()).map_err(|_| CodecError::InvalidData("文字列がUTF-8ではありません".to_string()))
+    }
+
+    fn read_json(&mut self) -> Result<serde_json::Value, CodecError> {
+        let json = self.read_string()?;
+        serde_json::from_str(&json).map_err(|e| CodecError::InvalidData(format!("JSON解析エラー: {}", e)))
+    }
+
+    fn read_option<T>(&mut self, read: impl FnOnce(&mut Self) -> Result<T, CodecError>) -> Result<Option<T>, CodecError> {
+        if self.read_bool()? {
+            read(self).map(Some)
+        } else {
+            Ok(None)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn component_update() -> NetworkMessage {
+        let mut components = HashMap::new();
+        components.insert("Position".to_string(), ComponentData::Position { x: 12.5, y: -3.25, z: None });
+        components.insert("Velocity".to_string(), ComponentData::Velocity { x: 1.0, y: 0.5, z: Some(2.0) });
+        components.insert("Hp".to_string(), ComponentData::Health { current: 80, max: 100 });
+        components.insert("Custom".to_string(), ComponentData::Custom { data: serde_json::json!({ "level": 3 }) });
+        NetworkMessage::new(MessageType::ComponentUpdate)
+            .with_sequence(300)
+            .with_entity_id(42)
+            .with_components(components)
+    }
+
+    #[test]
+    fn test_varint() {
+        for value in [0, 1, 127, 128, 300, 16_384, u32::MAX as u64, u64::MAX] {
+            let mut writer = BinaryWriter::new();
+            writer.write_varint(value);
+            let bytes = writer.into_bytes();
+            let mut reader = BinaryReader::new(&bytes);
+            assert_eq!(reader.read_varint().unwrap(), value);
+            assert!(reader.is_empty());
+        }
+
+        let mut writer = BinaryWriter::new();
+        writer.write_varint(300);
+        assert_eq!(writer.into_bytes(), vec![0xac, 0x02]);
+
+        // 10バイト目が1より大きいとu64に収まらない
+        let mut overflow = vec![0xff; 9];
+        overflow.push(0x02);
+        assert!(BinaryReader::new(&overflow).read_varint().is_err());
+        let mut max = vec![0xff; 9];
+        max.push(0x01);
+        assert_eq!(BinaryReader::new(&max).read_varint().unwrap(), u64::MAX);
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let message = component_update();
+        let bytes = encode(&message).unwrap();
+        let decoded = decode(&bytes).unwrap();
+        assert_eq!(decoded.message_type, MessageType::ComponentUpdate);
+        assert_eq!(decoded.sequence, Some(300));
+        assert_eq!(decoded.entity_id, Some(42));
+        assert_eq!(decoded.player_id, None);
+        assert_eq!(decoded.timestamp, message.timestamp);
+        assert_eq!(decoded.components, message.components);
+
+        // JSONより小さくなる
+        assert!(bytes.len() * 2 < message.to_json().unwrap().len());
+
+        let input = InputData {
+            movement: (0.5, -1.0),
+            actions: HashMap::from([("jump".to_string(), true)]),
+            aim: Some((10.0, 20.0)),
+            ..Default::default()
+        };
+        let player_data = PlayerData { team: Some(2), ..Default::default() };
+        let message = NetworkMessage::new(MessageType::ConnectResponse {
+            player_id: 7,
+            success: true,
+            message: Some("ようこそ".to_string()),
+            wire_format: WireFormat::Binary,
+        })
+        .with_player_id(7)
+        .with_input(input)
+        .with_player_data(player_data);
+
+        let decoded = decode(&encode(&message).unwrap()).unwrap();
+        assert_eq!(decoded.message_type, message.message_type);
+        let input = decoded.input_data.unwrap();
+        assert_eq!(input.movement, (0.5, -1.0));
+        assert_eq!(input.actions.get("jump"), Some(&true));
+        assert_eq!(input.aim, Some((10.0, 20.0)));
+        let player_data = decoded.player_data.unwrap();
+        assert_eq!(player_data.name, "Player");
+        assert_eq!(player_data.team, Some(2));
+        assert!(player_data.settings.is_none());
+    }
+
+    #[test]
+    fn test_invalid_bytes() {
+        let bytes = encode(&component_update()).unwrap();
+        assert!(decode(&bytes[..bytes.len() - 1]).is_err());
+        assert!(decode(&[bytes.as_slice(), &[0]].concat()).is_err());
+        assert!(decode(&[BINARY_VERSION + 1]).is_err());
+        assert!(decode(&[BINARY_VERSION, 200]).is_err());
+        assert!(decode(&[]).is_err());
+    }
+
+    #[test]
+    fn test_unknown_flags_are_rejected() {
+        let mut bytes = encode(&NetworkMessage::new(MessageType::Input)).unwrap();
+        // [バージョン][種別タグ][フラグ]
+        assert_eq!(bytes[2], 0);
+        bytes[2] = 1 << 7;
+        assert_eq!(decode(&bytes).unwrap_err(), Error::Codec(CodecError::UnknownFlags(1 << 7)));
+
+        let error = decode(&[BINARY_VERSION, 200]).unwrap_err();
+        assert_eq!(error, Error::Codec(CodecError::UnknownTag { kind: "メッセージ種別", tag: 200 }));
+        assert_eq!(decode(&[]).unwrap_err(), Error::Codec(CodecError::UnexpectedEnd));
+    }
+
+    #[test]
+    fn test_negotiate() {
+        assert_eq!(WireFormat::negotiate(&WireFormat::Binary.offer(), WireFormat::Binary), WireFormat::Binary);
+        assert_eq!(WireFormat::negotiate(&WireFormat::Json.offer(), WireFormat::Binary), WireFormat::Json);
+        assert_eq!(WireFormat::negotiate(&WireFormat::Binary.offer(), WireFormat::Json), WireFormat::Json);
+        assert_eq!(WireFormat::negotiate(&[], WireFormat::Binary), WireFormat::Json);
+    }
+}
@@ -109,10 +109,13 @@ pub enum ComponentData {
 }
 
 /// エンティティの完全なスナップショット
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct EntitySnapshot {
     /// エンティティID
     pub entity_id: u32,
+    /// エンティティの世代（IDが再利用された古いハンドルと区別するため）
+    #[serde(default)]
+    pub generation: u32,
     /// エンティティの各コンポーネント
     pub components: HashMap<String, ComponentData>,
     /// スナップショットのタイムスタンプ
@@ -126,6 +129,7 @@ impl EntitySnapshot {
     pub fn new(entity_id: u32, timestamp: f64) -> Self {
         Self {
             entity_id,
+            generation: 0,
             components: HashMap::new(),
             timestamp,
             owner_id: None,
@@ -165,8 +169,8 @@ mod tests {
         
         assert_eq!(deserialized.movement.0, 0.5);
         assert_eq!(deserialized.movement.1, -0.3);
-        assert_eq!(deserialized.actions["jump"], true);
-        assert_eq!(deserialized.actions["fire"], false);
+        assert!(deserialized.actions["jump"]);
+        assert!(!deserialized.actions["fire"]);
         assert_eq!(deserialized.aim, Some((100.0, 200.0)));
     }
 
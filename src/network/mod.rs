@@ -4,9 +4,12 @@
 //! マルチプレイヤーゲームのための状態同期、予測と補正、ネットワーク最適化を提供します。
 
 // サブモジュールをpubで公開
+// WebSocketを扱うクライアントはwebフィーチャーでのみビルドする
+#[cfg(feature = "web")]
 pub mod client;
 pub mod server;
 pub mod protocol;
+pub mod codec;
 pub mod sync;
 pub mod prediction;
 pub mod messages;
@@ -14,8 +17,10 @@ pub mod compression_system;
 pub mod network_status;
 
 // 必要なモジュールをリエクスポート
+#[cfg(feature = "web")]
 pub use client::NetworkClient;
 pub use protocol::{NetworkMessage, MessageType};
+pub use codec::{CodecError, WireFormat};
 pub use messages::{InputData, PlayerData, ComponentData};
 pub use sync::SyncSystem;
 pub use prediction::{PredictionSystem, ClientPrediction, ServerReconciliation};
@@ -29,6 +34,7 @@ use std::collections::{HashMap, VecDeque};
 
 // 内部モジュールのインポート
 use crate::ecs::{Entity, Component, Resource};
+use crate::platform;
 
 // モジュール全体で共有する定数
 /// ネットワーク更新の最大頻度（FPS）
@@ -158,6 +164,11 @@ pub struct NetworkConfig {
     pub enable_compression: bool,
     /// デバッグモードを有効化するか
     pub debug_mode: bool,
+    /// 優先するメッセージ形式（相手が対応していなければJSONを使う）
+    ///
+    /// バイナリ形式は`Connect`に応答する`NetworkServer`との間でのみ使えます。
+    /// `server`クレートのゲームサーバーは形式を決めないため、既定はJSONです。
+    pub wire_format: WireFormat,
 }
 
 impl Default for NetworkConfig {
@@ -169,6 +180,7 @@ impl Default for NetworkConfig {
             reconnect_attempts: 3,
             enable_compression: false,
             debug_mode: cfg!(debug_assertions),
+            wire_format: WireFormat::Json,
         }
     }
 }
@@ -190,7 +202,7 @@ impl TimeSyncData {
     /// 時間差を更新する
     pub fn update_time_difference(&mut self, time_diff: f64) {
         self.time_offset = time_diff;
-        self.last_sync = js_sys::Date::now();
+        self.last_sync = platform::unix_time_ms();
     }
 }
 
@@ -246,13 +258,13 @@ impl NetworkResource {
 
     /// サーバー時間を取得
     pub fn get_server_time(&self) -> f64 {
-        js_sys::Date::now() + self.time_offset
+        platform::unix_time_ms() + self.time_offset
     }
 
     /// 時間オフセットを更新
     pub fn update_time_offset(&mut self, client_time: f64, server_time: f64) {
         // RTTの半分をネットワーク遅延として扱う
-        let now = js_sys::Date::now();
+        let now = platform::unix_time_ms();
         let rtt = now - client_time;
         self.rtt = rtt;
         
@@ -311,18 +323,24 @@ impl NetworkManager {
         if let Some(player) = self.players.get_mut(&player_id) {
             player.position = position;
             player.velocity = velocity;
-            player.last_update = js_sys::Date::now();
+            player.last_update = platform::unix_time_ms();
         } else {
             self.players.insert(player_id, NetworkPlayer {
                 id: player_id,
                 position,
                 velocity,
-                last_update: js_sys::Date::now(),
+                last_update: platform::unix_time_ms(),
             });
         }
     }
 }
 
+impl Default for NetworkManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 /// ネットワークコンポーネント
 #[derive(Debug, Component)]
 pub struct NetworkComponent {
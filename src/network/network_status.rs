@@ -6,8 +6,8 @@
 use crate::ecs::{System, World, SystemPhase, SystemPriority, ResourceManager};
 use crate::network::NetworkResource;
 use std::collections::VecDeque;
-use js_sys::Date;
-use wasm_bindgen::JsValue;
+use crate::platform;
+use crate::Error;
 
 /// 帯域の状態を表す列挙型
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -67,7 +67,7 @@ impl Default for NetworkStatus {
             bandwidth_status: BandwidthStatus::Good,
             latency_variation: 10.0,
             quality: NetworkQuality::Good,
-            last_update: Date::now(),
+            last_update: platform::unix_time_ms(),
         }
     }
 }
@@ -137,7 +137,7 @@ pub struct NetworkStatusMonitor {
 impl Default for NetworkStatusMonitor {
     fn default() -> Self {
         let config = NetworkStatusMonitorConfig::default();
-        let now = Date::now();
+        let now = platform::unix_time_ms();
         
         // 必要な値を先に取得しておく
         let packet_loss_window_size = config.packet_loss_window_size;
@@ -161,7 +161,7 @@ impl Default for NetworkStatusMonitor {
 impl NetworkStatusMonitor {
     /// 新しいネットワーク状態監視システムを作成
     pub fn new(config: NetworkStatusMonitorConfig) -> Self {
-        let now = Date::now();
+        let now = platform::unix_time_ms();
         
         Self {
             config,
@@ -178,7 +178,7 @@ impl NetworkStatusMonitor {
     
     /// パケット送信を記録
     pub fn record_packet_sent(&mut self, sequence: u32, size: usize) {
-        let now = Date::now();
+        let now = platform::unix_time_ms();
         
         // 古いパケット情報を削除
         self.clean_old_packets(now);
@@ -194,7 +194,7 @@ impl NetworkStatusMonitor {
     
     /// パケット受信を記録
     pub fn record_packet_received(&mut self, sequence: u32) {
-        let now = Date::now();
+        let now = platform::unix_time_ms();
         
         // 受信シーケンスを記録
         self.received_sequences.push_back(sequence);
@@ -257,7 +257,7 @@ impl NetworkStatusMonitor {
         
         for packet in &self.sent_packets {
             // 送信から一定時間経過したパケットのみカウント
-            let now = Date::now();
+            let now = platform::unix_time_ms();
             if now - packet.send_time > 2000.0 { // 2秒以上経過
                 total_packets += 1;
                 if packet.receive_time.is_none() {
@@ -428,14 +428,13 @@ impl System for NetworkStatusMonitor {
         SystemPriority::new(10) // ネットワーク状態は早めに更新
     }
 
-    fn run(&mut self, world: &mut World, _resources: &mut ResourceManager, _delta_time: f32) -> Result<(), JsValue> {
-        let now = Date::now();
+    fn run(&mut self, world: &mut World, _resources: &mut ResourceManager, _delta_time: f32) -> Result<(), Error> {
+        let now = platform::unix_time_ms();
         
-        // ネットワークリソースを取得
-        let _network_resource = match world.get_resource::<NetworkResource>() {
-            Some(_) => (), // 存在確認のみ
-            None => return Ok(()), // リソースがなければ何もしない
-        };
+        // ネットワークリソースがなければ何もしない
+        if world.get_resource::<NetworkResource>().is_none() {
+            return Ok(());
+        }
         
         // 古いパケット情報を削除
         self.clean_old_packets(now);
@@ -468,9 +467,6 @@ mod tests {
             monitor.record_packet_received(i);
         }
         
-        // 現在時刻を3秒後に設定して計算
-        let now = Date::now() + 3000.0;
-        
         // 手動で古いパケットをクリーンアップせずに計算
         for packet in &mut monitor.sent_packets {
             packet.send_time -= 3000.0; // 3秒前に送信したことにする
@@ -485,7 +481,7 @@ mod tests {
         let mut monitor = NetworkStatusMonitor::default();
         
         // RTTが100msのパケットを5つ記録
-        for i in 0..5 {
+        for _ in 0..5 {
             monitor.rtt_samples.push_back(100.0);
         }
         
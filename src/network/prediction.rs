@@ -4,13 +4,13 @@
 //! クライアント予測とサーバー権威による補正機能を実装します。
 
 use std::collections::{HashMap, VecDeque};
-use js_sys::Date;
+use crate::platform;
 use std::collections::HashSet;
 use std::sync::{Arc, Mutex};
-use wasm_bindgen::JsValue;
+use crate::Error;
 
 use super::messages::{InputData, EntitySnapshot, ComponentData};
-use super::client::NetworkComponent;
+use super::sync::NetworkComponent;
 use super::network_status::{NetworkStatus, BandwidthStatus};
 use super::sync::{PositionComponent, VelocityComponent};
 use super::NetworkResource;
@@ -65,7 +65,7 @@ impl Default for ClientPrediction {
         Self {
             max_input_history: 30,
             prediction_data: HashMap::new(),
-            last_update: Date::now(),
+            last_update: platform::unix_time_ms(),
         }
     }
 }
@@ -76,15 +76,15 @@ impl System for ClientPrediction {
     }
     
     fn phase(&self) -> SystemPhase {
-        SystemPhase::Update
+        SystemPhase::FixedUpdate
     }
     
     fn priority(&self) -> SystemPriority {
         SystemPriority::new(40) // ServerReconciliationより少し低い優先度
     }
     
-    fn run(&mut self, world: &mut World, _resources: &mut ResourceManager, delta_time: f32) -> Result<(), JsValue> {
-        let now = Date::now();
+    fn run(&mut self, world: &mut World, _resources: &mut ResourceManager, delta_time: f32) -> Result<(), Error> {
+        let now = platform::unix_time_ms();
         let _elapsed = now - self.last_update;
         self.last_update = now;
         
@@ -97,7 +97,7 @@ impl System for ClientPrediction {
             .map(|(entity, _network)| {
                 let prediction_data = self.prediction_data
                     .entry(entity)
-                    .or_insert_with(PredictionData::default)
+                    .or_default()
                     .clone();
                 (entity, prediction_data)
             })
@@ -118,7 +118,7 @@ impl ClientPrediction {
         Self {
             max_input_history: max_history,
             prediction_data: HashMap::new(),
-            last_update: Date::now(),
+            last_update: platform::unix_time_ms(),
         }
     }
     
@@ -182,7 +182,7 @@ impl Default for ServerReconciliation {
     fn default() -> Self {
         Self {
             client_inputs: HashMap::new(),
-            last_update: Date::now(),
+            last_update: platform::unix_time_ms(),
             correction_threshold: 0.5,
             max_steps_per_frame: 30,
         }
@@ -195,15 +195,15 @@ impl System for ServerReconciliation {
     }
     
     fn phase(&self) -> SystemPhase {
-        SystemPhase::Update
+        SystemPhase::FixedUpdate
     }
     
     fn priority(&self) -> SystemPriority {
         SystemPriority::new(50) // 中程度の優先度
     }
     
-    fn run(&mut self, world: &mut World, resources: &mut ResourceManager, delta_time: f32) -> Result<(), JsValue> {
-        let now = Date::now();
+    fn run(&mut self, world: &mut World, resources: &mut ResourceManager, delta_time: f32) -> Result<(), Error> {
+        let now = platform::unix_time_ms();
         self.last_update = now;
         
         // クライアント所有のエンティティを検出
@@ -224,7 +224,7 @@ impl System for ServerReconciliation {
             Some(ref mut queue) => queue,
             None => {
                 #[cfg(feature = "debug_network")]
-                web_sys::console::log_1(&"エラー: NetworkSendQueueが見つかりません。修正を送信できません。".into());
+                log::error!("NetworkSendQueueが見つかりません。修正を送信できません。");
                 return Ok(());
             }
         };
@@ -244,12 +244,8 @@ impl System for ServerReconciliation {
                 // 入力を順番に適用する（最大でmax_steps_per_frameまで）
                 let step_count = inputs.len().min(self.max_steps_per_frame);
                 
-                // シミュレーション用の時間ステップを計算
-                let sim_delta_time = if step_count > 0 {
-                    delta_time / step_count as f32
-                } else {
-                    delta_time
-                };
+                // 各入力は1固定ティック分としてシミュレーションする
+                let sim_delta_time = delta_time;
                 
                 // 最終確認シーケンス番号を追跡
                 let mut last_sequence = 0;
@@ -284,8 +280,8 @@ impl System for ServerReconciliation {
                     
                     // 修正データをキューに追加
                     #[cfg(feature = "debug_network")]
-                    web_sys::console::log_1(&format!("ServerReconciliation: クライアント {} のエンティティ {} に修正を送信 (seq: {})",
-                        client_id, entity.index() as u32, last_sequence).into());
+                    log::debug!("ServerReconciliation: クライアント {} のエンティティ {} に修正を送信 (seq: {})",
+                        client_id, entity.index(), last_sequence);
                     
                     // 修正スナップショットを送信キューに追加
                     send_queue.queue_snapshot(client_id, entity, optimized_snapshot, last_sequence);
@@ -326,15 +322,16 @@ impl ServerReconciliation {
             client_inputs: HashMap::new(),
             max_steps_per_frame: 5,
             correction_threshold: 0.5,
-            last_update: Date::now(),
+            last_update: platform::unix_time_ms(),
         }
     }
     
     /// 補正閾値を設定したインスタンスを作成
     pub fn with_threshold(threshold: f32) -> Self {
-        let mut instance = Self::default();
-        instance.correction_threshold = threshold;
-        instance
+        Self {
+            correction_threshold: threshold,
+            ..Self::default()
+        }
     }
     
     /// クライアントからの入力を処理
@@ -463,7 +460,7 @@ impl ServerReconciliation {
                         let should_jump = {
                             let position = world.get_component::<PositionComponent>(entity);
                             // 地面に近いかチェック
-                            position.map_or(false, |pos| pos.z.map_or(true, |z| z <= 0.01))
+                            position.is_some_and(|pos| pos.z.is_none_or(|z| z <= 0.01))
                         };
                         
                         // ジャンプが可能な場合のみ速度を更新
@@ -485,7 +482,7 @@ impl ServerReconciliation {
                     _ => {
                         // 未知のアクションは無視
                         #[cfg(feature = "debug_network")]
-                        web_sys::console::log_1(&format!("未知のアクション: {}", action_name).into());
+                        log::debug!("未知のアクション: {}", action_name);
                     }
                 }
             }
@@ -584,7 +581,7 @@ impl ServerReconciliation {
     /// エンティティのスナップショットを作成
     fn create_entity_snapshot(&self, world: &World, entity: Entity, timestamp: f64) -> EntitySnapshot {
         // 新しいスナップショットを作成
-        let mut snapshot = EntitySnapshot::new(entity.index() as u32, timestamp);
+        let mut snapshot = EntitySnapshot::new(entity.index(), timestamp);
         
         // コンポーネントデータを収集
         let mut components = HashMap::new();
@@ -687,15 +684,15 @@ impl ServerReconciliation {
     fn analyze_prediction_accuracy(&self, _client_id: u32, _component: &str, difference: f32) {
         // ここで予測精度のログを記録したり分析データを蓄積したりします
         #[cfg(feature = "debug_network")]
-        web_sys::console::log_1(&format!(
+        log::debug!(
             "予測分析 - 差異: {:.3}",
             difference
-        ).into());
+        );
         
         // 大きな差異がある場合、追加のデバッグ情報を記録
         if difference > 3.0 {
             #[cfg(feature = "debug_network")]
-            web_sys::console::log_1(&"警告: 大きな予測誤差を検出".into());
+            log::warn!("大きな予測誤差を検出");
         }
     }
     
@@ -781,16 +778,16 @@ impl NetworkSendQueue {
             // スナップショットメッセージを作成
             let message = NetworkMessage::new(MessageType::ComponentUpdate)
                 .with_sequence(sequence)
-                .with_entity_id(entity.index() as u32)
+                .with_entity_id(entity.index())
                 .with_components(snapshot.components);
                 
             // メッセージを送信
             if let Err(_e) = network_client.send_message(message) {
                 #[cfg(feature = "debug_network")]
-                web_sys::console::log_1(&format!(
-                    "エラー: クライアント {} へのメッセージ送信に失敗: {:?}",
+                log::error!(
+                    "クライアント {} へのメッセージ送信に失敗: {:?}",
                     _client_id, _e
-                ).into());
+                );
             }
         }
     }
@@ -800,7 +797,7 @@ impl NetworkSendQueue {
         // 実際の実装ではメッセージタイプに基づいて適切な処理を行う
         // ここでは簡略化のためにログだけ出力
         #[cfg(feature = "debug_network")]
-        web_sys::console::log_1(&"Queued message".into());
+        log::debug!("Queued message");
     }
 }
 
@@ -818,7 +815,7 @@ impl Default for InterpolationSystem {
     fn default() -> Self {
         Self {
             _buffer_time: 100.0, // 100ms
-            last_update: Date::now(),
+            last_update: platform::unix_time_ms(),
         }
     }
 }
@@ -836,8 +833,8 @@ impl System for InterpolationSystem {
         SystemPriority::new(10) // 適切な優先度を設定
     }
 
-    fn run(&mut self, world: &mut World, _resources: &mut ResourceManager, _delta_time: f32) -> Result<(), JsValue> {
-        let now = Date::now();
+    fn run(&mut self, world: &mut World, _resources: &mut ResourceManager, _delta_time: f32) -> Result<(), Error> {
+        let now = platform::unix_time_ms();
         let _elapsed = now - self.last_update;
         self.last_update = now;
         
@@ -860,7 +857,7 @@ impl InterpolationSystem {
     pub fn new(buffer_time: f64) -> Self {
         Self {
             _buffer_time: buffer_time,
-            last_update: Date::now(),
+            last_update: platform::unix_time_ms(),
         }
     }
 }
@@ -878,7 +875,7 @@ pub struct EntitySyncSystem {
 impl Default for EntitySyncSystem {
     fn default() -> Self {
         Self {
-            last_update: Date::now(),
+            last_update: platform::unix_time_ms(),
             entity_snapshots: HashMap::new(),
         }
     }
@@ -890,15 +887,15 @@ impl System for EntitySyncSystem {
     }
 
     fn phase(&self) -> SystemPhase {
-        SystemPhase::Update
+        SystemPhase::FixedUpdate
     }
 
     fn priority(&self) -> SystemPriority {
         SystemPriority::new(20) // InterpolationSystemより高い優先度
     }
 
-    fn run(&mut self, world: &mut World, _resources: &mut ResourceManager, _delta_time: f32) -> Result<(), JsValue> {
-        let now = Date::now();
+    fn run(&mut self, world: &mut World, _resources: &mut ResourceManager, _delta_time: f32) -> Result<(), Error> {
+        let now = platform::unix_time_ms();
         self.last_update = now;
         
         // リモートエンティティのクエリ - query_tupleを使用して修正
@@ -919,7 +916,7 @@ impl EntitySyncSystem {
     /// 新しいエンティティ同期システムを作成
     pub fn new() -> Self {
         Self {
-            last_update: Date::now(),
+            last_update: platform::unix_time_ms(),
             entity_snapshots: HashMap::new(),
         }
     }
@@ -988,10 +985,10 @@ impl System for PredictionSystem {
     }
 
     fn phase(&self) -> SystemPhase {
-        SystemPhase::Update
+        SystemPhase::FixedUpdate
     }
 
-    fn run(&mut self, world: &mut World, resources: &mut ResourceManager, delta_time: f32) -> Result<(), JsValue> {
+    fn run(&mut self, world: &mut World, resources: &mut ResourceManager, delta_time: f32) -> Result<(), Error> {
         if self.is_server {
             // サーバーモードでの処理
             self.server_reconciliation.run(world, resources, delta_time)?;
@@ -1053,7 +1050,7 @@ impl Default for InputLatencyCompensationSystem {
             input_buffer: VecDeque::with_capacity(10),
             network_monitor: None,
             compensation_settings: LatencyCompensationSettings::default(),
-            last_update: Date::now(),
+            last_update: platform::unix_time_ms(),
         }
     }
 }
@@ -1064,15 +1061,15 @@ impl System for InputLatencyCompensationSystem {
     }
 
     fn phase(&self) -> SystemPhase {
-        SystemPhase::Update
+        SystemPhase::FixedUpdate
     }
 
     fn priority(&self) -> SystemPriority {
         SystemPriority::new(20) // 中程度の優先度
     }
 
-    fn run(&mut self, world: &mut World, _resources: &mut ResourceManager, delta_time: f32) -> Result<(), JsValue> {
-        let now = Date::now();
+    fn run(&mut self, world: &mut World, _resources: &mut ResourceManager, delta_time: f32) -> Result<(), Error> {
+        let now = platform::unix_time_ms();
         let _elapsed = now - self.last_update;
         self.last_update = now;
         
@@ -1139,7 +1136,7 @@ impl InputLatencyCompensationSystem {
             input_buffer: VecDeque::with_capacity(settings.buffer_size),
             network_monitor: None,
             compensation_settings: settings,
-            last_update: Date::now(),
+            last_update: platform::unix_time_ms(),
         }
     }
     
@@ -1197,10 +1194,10 @@ impl InputLatencyCompensationSystem {
         
         if self.compensation_settings.use_input_prediction && self.input_buffer.len() >= 3 {
             // 入力予測: 直近の入力から次の入力を予測
-            return self.predict_next_input();
+            self.predict_next_input()
         } else {
             // 入力補間: 直近の2つの入力を補間
-            return self.interpolate_inputs();
+            self.interpolate_inputs()
         }
     }
     
@@ -1231,8 +1228,8 @@ impl InputLatencyCompensationSystem {
         let py = m3.1 + dy2 + ay * 0.5;
         
         // 値を-1.0〜1.0の範囲に制限
-        let px = px.max(-1.0).min(1.0);
-        let py = py.max(-1.0).min(1.0);
+        let px = px.clamp(-1.0, 1.0);
+        let py = py.clamp(-1.0, 1.0);
         
         predicted_input.movement = (px, py);
         
@@ -1277,19 +1274,11 @@ impl InputLatencyCompensationSystem {
 }
 
 /// 入力リソース（サンプル用）
-#[derive(Resource)]
+#[derive(Resource, Default)]
 pub struct InputResource {
     current_input: InputData,
 }
 
-impl Default for InputResource {
-    fn default() -> Self {
-        Self {
-            current_input: InputData::default(),
-        }
-    }
-}
-
 impl InputResource {
     /// 新しい入力リソースを作成
     pub fn new() -> Self {
@@ -1331,6 +1320,12 @@ impl InputProcessor {
     }
 }
 
+impl Default for InputProcessor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 /// ネットワーク品質モニター
 /// 
 /// ネットワーク接続の品質を監視し、適応的な補正を可能にします。
@@ -1393,11 +1388,11 @@ impl NetworkQualityMonitor {
             
             // ジッター計算
             let mut jitter_sum = 0.0;
-            let mut prev = None;
+            let mut prev: Option<f64> = None;
             
             for &sample in &self.rtt_samples {
                 if let Some(p) = prev {
-                    jitter_sum += ((sample - p) as f64).abs();
+                    jitter_sum += (sample - p).abs();
                 }
                 prev = Some(sample);
             }
@@ -1502,7 +1497,7 @@ impl NetworkMessage {
             sequence: None,
             entity_id: None,
             components: None,
-            timestamp: Date::now(),
+            timestamp: platform::unix_time_ms(),
         }
     }
     
@@ -1528,10 +1523,10 @@ pub struct NetworkClient {
 }
 
 impl NetworkClient {
-    pub fn send_message(&mut self, _message: NetworkMessage) -> Result<(), JsValue> {
+    pub fn send_message(&mut self, _message: NetworkMessage) -> Result<(), Error> {
         // メッセージをJSONに変換してWebSocket経由で送信
         #[cfg(feature = "debug_network")]
-        web_sys::console::log_1(&"メッセージ送信".into());
+        log::debug!("メッセージ送信");
         
         // 実際の送信処理は別モジュールで実装
         Ok(())
@@ -2,20 +2,24 @@
 //! 
 //! このモジュールは、クライアントとサーバー間で交換されるメッセージの形式と
 //! シリアライズ/デシリアライズの処理を定義します。
+//! メッセージはJSON（`to_json`）とバイナリ（`to_binary`）のどちらでも送れます。
 
 use serde::{Serialize, Deserialize};
+use serde::de::DeserializeOwned;
+use serde_json::{json, Map, Value};
 use std::collections::HashMap;
-use js_sys::{Date, JSON};
-use wasm_bindgen::prelude::*;
+use super::codec::{self, WireFormat};
 use super::messages::{InputData, PlayerData, ComponentData};
+use crate::platform;
+use crate::Error;
 
 /// メッセージ種別を表す列挙型
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum MessageType {
-    /// 接続
-    Connect,
-    /// 接続応答
-    ConnectResponse { player_id: u32, success: bool, message: Option<String> },
+    /// 接続（クライアントが使えるメッセージ形式を伝える）
+    Connect { wire_formats: Vec<WireFormat> },
+    /// 接続応答（以降のメッセージに使う形式を伝える）
+    ConnectResponse { player_id: u32, success: bool, message: Option<String>, wire_format: WireFormat },
     /// 切断
     Disconnect { reason: Option<String> },
     /// エンティティ作成
@@ -44,11 +48,14 @@ impl std::hash::Hash for MessageType {
     fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
         core::mem::discriminant(self).hash(state);
         match self {
-            Self::Connect => {},
-            Self::ConnectResponse { player_id, success, message } => {
+            Self::Connect { wire_formats } => {
+                wire_formats.hash(state);
+            },
+            Self::ConnectResponse { player_id, success, message, wire_format } => {
                 player_id.hash(state);
                 success.hash(state);
                 message.hash(state);
+                wire_format.hash(state);
             },
             Self::Disconnect { reason } => {
                 reason.hash(state);
@@ -122,7 +129,7 @@ impl NetworkMessage {
         Self {
             message_type,
             sequence: None,
-            timestamp: Date::now(),
+            timestamp: platform::unix_time_ms(),
             entity_id: None,
             player_id: None,
             components: None,
@@ -168,179 +175,102 @@ impl NetworkMessage {
     }
 
     /// JSON文字列からメッセージをデシリアライズ
-    pub fn from_json(json: &str) -> Result<Self, JsValue> {
-        let obj = JSON::parse(json).map_err(|e| {
-            JsValue::from_str(&format!("JSON解析エラー: {:?}", e))
-        })?;
+    ///
+    /// `type`フィールドの値は大文字・小文字を区別しません。
+    /// コンポーネント・入力・プレイヤーのデータが解析できない場合は、
+    /// メッセージ全体をエラーにせず、そのフィールドを`None`として扱います。
+    pub fn from_json(json: &str) -> Result<Self, Error> {
+        let value: Value = serde_json::from_str(json)
+            .map_err(|e| Error::Other(format!("JSON解析エラー: {}", e)))?;
+        let obj = value.as_object()
+            .ok_or_else(|| Error::from("メッセージがJSONオブジェクトではありません"))?;
         
-        let message_obj = js_sys::Object::from(obj);
         let message = Self {
-            message_type: extract_message_type(&message_obj)?,
-            sequence: extract_optional_number(&message_obj, "sequence")?.map(|n| n as u32),
-            timestamp: extract_number(&message_obj, "timestamp")?.unwrap_or(0.0),
-            entity_id: extract_optional_number(&message_obj, "entity_id")?.map(|n| n as u32),
-            player_id: extract_optional_number(&message_obj, "player_id")?.map(|n| n as u32),
-            components: None,
-            input_data: None,
-            player_data: None,
+            message_type: extract_message_type(obj)?,
+            sequence: extract_number(obj, "sequence").map(|n| n as u32),
+            timestamp: extract_number(obj, "timestamp").unwrap_or(0.0),
+            entity_id: extract_number(obj, "entity_id").map(|n| n as u32),
+            player_id: extract_number(obj, "player_id").map(|n| n as u32),
+            components: extract_field(obj, "components"),
+            input_data: extract_field(obj, "input_data"),
+            player_data: extract_field(obj, "player_data"),
         };
         
         Ok(message)
     }
 
     /// メッセージをJSON文字列にシリアライズ
-    pub fn to_json(&self) -> Result<String, JsValue> {
-        let obj = js_sys::Object::new();
-        
-        match &self.message_type {
-            MessageType::Connect => { js_sys::Reflect::set(&obj, &"type".into(), &"Connect".into())?; },
-            MessageType::ConnectResponse { player_id, success, message } => {
-                js_sys::Reflect::set(&obj, &"type".into(), &"ConnectResponse".into())?;
-                js_sys::Reflect::set(&obj, &"player_id".into(), &(*player_id).into())?;
-                js_sys::Reflect::set(&obj, &"success".into(), &(*success).into())?;
-                if let Some(msg) = message {
-                    js_sys::Reflect::set(&obj, &"message".into(), &msg.into())?;
-                }
-            },
-            MessageType::Disconnect { reason } => {
-                js_sys::Reflect::set(&obj, &"type".into(), &"Disconnect".into())?;
-                if let Some(r) = reason {
-                    js_sys::Reflect::set(&obj, &"reason".into(), &r.into())?;
-                }
-            },
-            MessageType::EntityCreate { entity_id } => {
-                js_sys::Reflect::set(&obj, &"type".into(), &"EntityCreate".into())?;
-                js_sys::Reflect::set(&obj, &"entity_id".into(), &(*entity_id).into())?;
-            },
-            MessageType::EntityDelete { entity_id } => {
-                js_sys::Reflect::set(&obj, &"type".into(), &"EntityDelete".into())?;
-                js_sys::Reflect::set(&obj, &"entity_id".into(), &(*entity_id).into())?;
-            },
-            MessageType::ComponentUpdate => {
-                js_sys::Reflect::set(&obj, &"type".into(), &"ComponentUpdate".into())?;
-            },
-            MessageType::Input => {
-                js_sys::Reflect::set(&obj, &"type".into(), &"Input".into())?;
-            },
-            MessageType::TimeSyncRequest { client_time } => {
-                js_sys::Reflect::set(&obj, &"type".into(), &"TimeSyncRequest".into())?;
-                js_sys::Reflect::set(&obj, &"client_time".into(), &(*client_time).into())?;
-            },
-            MessageType::TimeSyncResponse { client_time, server_time } => {
-                js_sys::Reflect::set(&obj, &"type".into(), &"TimeSyncResponse".into())?;
-                js_sys::Reflect::set(&obj, &"client_time".into(), &(*client_time).into())?;
-                js_sys::Reflect::set(&obj, &"server_time".into(), &(*server_time).into())?;
-            },
-            MessageType::Ping { client_time } => {
-                js_sys::Reflect::set(&obj, &"type".into(), &"Ping".into())?;
-                js_sys::Reflect::set(&obj, &"client_time".into(), &(*client_time).into())?;
-            },
-            MessageType::Pong { client_time, server_time } => {
-                js_sys::Reflect::set(&obj, &"type".into(), &"Pong".into())?;
-                js_sys::Reflect::set(&obj, &"client_time".into(), &(*client_time).into())?;
-                js_sys::Reflect::set(&obj, &"server_time".into(), &(*server_time).into())?;
-            },
-            MessageType::Error { code, message } => {
-                js_sys::Reflect::set(&obj, &"type".into(), &"Error".into())?;
-                js_sys::Reflect::set(&obj, &"code".into(), &(*code).into())?;
-                js_sys::Reflect::set(&obj, &"message".into(), &message.into())?;
-            },
-            MessageType::MouseCursorUpdate => {
-                js_sys::Reflect::set(&obj, &"type".into(), &"MouseCursorUpdate".into())?;
-            },
-        }
+    ///
+    /// メッセージ種別は`type`フィールドに、種別ごとのデータは同じ階層に書き出します。
+    /// 値のない任意のフィールド（`message`や`reason`、入力データの`aim`など）は書き出しません。
+    pub fn to_json(&self) -> Result<String, Error> {
+        let mut obj = match &self.message_type {
+            MessageType::Connect { wire_formats } => json!({ "type": "Connect", "wire_formats": wire_formats }),
+            MessageType::ConnectResponse { player_id, success, message, wire_format } => json!({
+                "type": "ConnectResponse",
+                "player_id": player_id,
+                "success": success,
+                "message": message,
+                "wire_format": wire_format,
+            }),
+            MessageType::Disconnect { reason } => json!({ "type": "Disconnect", "reason": reason }),
+            MessageType::EntityCreate { entity_id } => json!({ "type": "EntityCreate", "entity_id": entity_id }),
+            MessageType::EntityDelete { entity_id } => json!({ "type": "EntityDelete", "entity_id": entity_id }),
+            MessageType::ComponentUpdate => json!({ "type": "ComponentUpdate" }),
+            MessageType::Input => json!({ "type": "Input" }),
+            MessageType::TimeSyncRequest { client_time } => json!({
+                "type": "TimeSyncRequest",
+                "client_time": client_time,
+            }),
+            MessageType::TimeSyncResponse { client_time, server_time } => json!({
+                "type": "TimeSyncResponse",
+                "client_time": client_time,
+                "server_time": server_time,
+            }),
+            MessageType::Ping { client_time } => json!({ "type": "Ping", "client_time": client_time }),
+            MessageType::Pong { client_time, server_time } => json!({
+                "type": "Pong",
+                "client_time": client_time,
+                "server_time": server_time,
+            }),
+            MessageType::Error { code, message } => json!({ "type": "Error", "code": code, "message": message }),
+            MessageType::MouseCursorUpdate => json!({ "type": "MouseCursorUpdate" }),
+        };
         
+        let fields = obj.as_object_mut().expect("メッセージ種別はオブジェクトとして作成される");
+        fields.retain(|_, value| !value.is_null());
+        fields.insert("timestamp".to_string(), json!(self.timestamp));
         if let Some(seq) = self.sequence {
-            js_sys::Reflect::set(&obj, &"sequence".into(), &(seq as u32).into())?;
+            fields.insert("sequence".to_string(), json!(seq));
         }
-        js_sys::Reflect::set(&obj, &"timestamp".into(), &self.timestamp.into())?;
         if let Some(entity_id) = self.entity_id {
-            js_sys::Reflect::set(&obj, &"entity_id".into(), &(entity_id as u32).into())?;
+            fields.insert("entity_id".to_string(), json!(entity_id));
         }
         if let Some(player_id) = self.player_id {
-            js_sys::Reflect::set(&obj, &"player_id".into(), &(player_id as u32).into())?;
-        }
-        
-        // コンポーネントデータ
-        if let Some(ref components) = self.components {
-            let components_obj = js_sys::Object::new();
-            for (name, data) in components {
-                // 各コンポーネントのJSONオブジェクトを作成し追加
-                let component_obj = js_sys::Object::new();
-                
-                // データ型に応じて適切なプロパティをセット
-                match data {
-                    // 実際の実装では、ComponentDataの内容に応じて設定
-                    _ => {}
-                }
-                
-                js_sys::Reflect::set(&components_obj, &name.into(), &component_obj)?;
-            }
-            js_sys::Reflect::set(&obj, &"components".into(), &components_obj)?;
+            fields.insert("player_id".to_string(), json!(player_id));
         }
         
-        // 入力データ
-        if let Some(ref input) = self.input_data {
-            let input_obj = js_sys::Object::new();
-            
-            // 移動入力
-            let movement_array = js_sys::Array::new();
-            movement_array.push(&input.movement.0.into());
-            movement_array.push(&input.movement.1.into());
-            js_sys::Reflect::set(&input_obj, &"movement".into(), &movement_array)?;
-            
-            // アクション入力
-            let actions_obj = js_sys::Object::new();
-            for (action, state) in &input.actions {
-                js_sys::Reflect::set(&actions_obj, &action.into(), &(*state).into())?;
-            }
-            js_sys::Reflect::set(&input_obj, &"actions".into(), &actions_obj)?;
-            
-            // 照準座標(存在する場合)
-            if let Some((x, y)) = input.aim {
-                let aim_array = js_sys::Array::new();
-                aim_array.push(&x.into());
-                aim_array.push(&y.into());
-                js_sys::Reflect::set(&input_obj, &"aim".into(), &aim_array)?;
-            }
-            
-            // タイムスタンプ
-            js_sys::Reflect::set(&input_obj, &"timestamp".into(), &input.timestamp.into())?;
-            
-            js_sys::Reflect::set(&obj, &"input_data".into(), &input_obj)?;
-        }
+        // コンポーネント・入力・プレイヤーのデータ
+        insert_field(fields, "components", &self.components)?;
+        insert_field(fields, "input_data", &self.input_data)?;
+        insert_field(fields, "player_data", &self.player_data)?;
         
-        // プレイヤーデータ
-        if let Some(ref player) = self.player_data {
-            let player_obj = js_sys::Object::new();
-            
-            js_sys::Reflect::set(&player_obj, &"name".into(), &player.name.clone().into())?;
-            
-            if let Some(ref avatar) = player.avatar {
-                js_sys::Reflect::set(&player_obj, &"avatar".into(), &avatar.into())?;
-            }
-            
-            if let Some(team) = player.team {
-                js_sys::Reflect::set(&player_obj, &"team".into(), &team.into())?;
-            }
-            
-            if let Some(ref settings) = player.settings {
-                let settings_obj = js_sys::Object::new();
-                // 設定データの処理
-                for (key, value) in settings {
-                    js_sys::Reflect::set(&settings_obj, &key.into(), &JsValue::from_str(&value.to_string()))?;
-                }
-                js_sys::Reflect::set(&player_obj, &"settings".into(), &settings_obj)?;
-            }
-            
-            js_sys::Reflect::set(&obj, &"player_data".into(), &player_obj)?;
-        }
-        
-        let json = JSON::stringify(&obj).map_err(|e| {
-            JsValue::from_str(&format!("JSON文字列化エラー: {:?}", e))
-        })?;
-        
-        Ok(String::from(json.as_string().unwrap_or_default()))
+        serde_json::to_string(&obj).map_err(|e| Error::Other(format!("JSON文字列化エラー: {}", e)))
+    }
+
+    /// バイナリ形式からメッセージを復元
+    ///
+    /// 形式の詳細は`codec`モジュールを参照してください。
+    /// 不正なバイト列の場合は`Error::Codec`を返します。
+    pub fn from_binary(bytes: &[u8]) -> Result<Self, Error> {
+        codec::decode(bytes)
+    }
+
+    /// メッセージをバイナリ形式に変換
+    ///
+    /// 同じメッセージのJSONより小さくなるため、接続時にバイナリ形式を選んだ場合に使います。
+    pub fn to_binary(&self) -> Result<Vec<u8>, Error> {
+        codec::encode(self)
     }
 
     /// プレイヤーIDを設定（可変参照版）
@@ -366,7 +296,7 @@ impl NetworkMessage {
     }
     
     /// データ文字列を取得
-    pub fn get_data_as_string(&self) -> Result<String, JsValue> {
+    pub fn get_data_as_string(&self) -> Result<String, Error> {
         if let Some(player_data) = &self.player_data {
             if let Some(settings) = &player_data.settings {
                 if let Some(data) = settings.get("data") {
@@ -376,46 +306,33 @@ impl NetworkMessage {
                 }
             }
         }
-        Err(JsValue::from_str("データフィールドがありません"))
+        Err(Error::from("データフィールドがありません"))
     }
 }
 
-fn extract_message_type(obj: &js_sys::Object) -> Result<MessageType, JsValue> {
-    let type_key = JsValue::from_str("type");
-    if !js_sys::Reflect::has(obj, &type_key)? {
-        // オブジェクトのすべてのキーを取得してデバッグ情報に追加
-        let keys = js_sys::Object::keys(obj);
-        let keys_length = keys.length();
-        let mut keys_str = String::new();
-        
-        for i in 0..keys_length {
-            if let Some(key) = keys.get(i).as_string() {
-                if !keys_str.is_empty() {
-                    keys_str.push_str(", ");
-                }
-                keys_str.push_str(&key);
-            }
+fn extract_message_type(obj: &Map<String, Value>) -> Result<MessageType, Error> {
+    let type_value = match obj.get("type") {
+        Some(value) => value,
+        None => {
+            // オブジェクトのすべてのキーをデバッグ情報に追加
+            let keys_str = obj.keys().cloned().collect::<Vec<_>>().join(", ");
+            return Err(Error::Other(format!(
+                "メッセージタイプが見つかりません。利用可能なキー: [{}]",
+                if keys_str.is_empty() { "なし" } else { &keys_str }
+            )));
         }
-        
-        return Err(JsValue::from_str(&format!(
-            "メッセージタイプが見つかりません。利用可能なキー: [{}]", 
-            if keys_str.is_empty() { "なし" } else { &keys_str }
-        )));
-    }
-    
-    let type_value = js_sys::Reflect::get(obj, &type_key)?;
-    if type_value.is_undefined() || type_value.is_null() {
-        return Err(JsValue::from_str("typeフィールドが存在しますが、値がnullまたはundefinedです"));
-    }
+    };
     
-    let type_str = match type_value.as_string() {
-        Some(s) => s,
-        None => {
-            // 文字列でない場合、型情報を詳細に出力
-            let type_of = type_value.js_typeof().as_string().unwrap_or_default();
-            return Err(JsValue::from_str(&format!(
-                "typeフィールドが文字列ではありません。実際の型: {}, 値: {:?}", 
-                type_of, type_value
+    let type_str = match type_value {
+        Value::String(s) => s,
+        Value::Null => {
+            return Err(Error::from("typeフィールドが存在しますが、値がnullです"));
+        }
+        _ => {
+            // 文字列でない場合、値を詳細に出力
+            return Err(Error::Other(format!(
+                "typeフィールドが文字列ではありません。値: {}",
+                type_value
             )));
         }
     };
@@ -423,105 +340,105 @@ fn extract_message_type(obj: &js_sys::Object) -> Result<MessageType, JsValue> {
     // 大文字小文字を無視するために小文字に変換
     let type_lower = type_str.to_lowercase();
     
-    web_sys::console::log_1(&format!("メッセージタイプ解析中: {}", type_str).into());
+    log::trace!("メッセージタイプ解析中: {}", type_str);
     
     match type_lower.as_str() {
-        "connect" => Ok(MessageType::Connect),
+        "connect" => {
+            // 形式を伝えない古いクライアントはJSONのみ使える
+            let wire_formats = extract_field(obj, "wire_formats").unwrap_or_else(|| vec![WireFormat::Json]);
+            Ok(MessageType::Connect { wire_formats })
+        },
         "connectresponse" => {
-            let player_id = extract_number(obj, "player_id")?.unwrap_or(0.0) as u32;
-            let success = extract_boolean(obj, "success")?.unwrap_or(false);
-            let message = extract_string(obj, "message")?;
-            Ok(MessageType::ConnectResponse { player_id, success, message })
+            let player_id = extract_number(obj, "player_id").unwrap_or(0.0) as u32;
+            let success = extract_boolean(obj, "success").unwrap_or(false);
+            let message = extract_string(obj, "message");
+            let wire_format = extract_field(obj, "wire_format").unwrap_or_default();
+            Ok(MessageType::ConnectResponse { player_id, success, message, wire_format })
         },
         "disconnect" => {
-            let reason = extract_string(obj, "reason")?;
+            let reason = extract_string(obj, "reason");
             Ok(MessageType::Disconnect { reason })
         },
         "entitycreate" => {
-            let entity_id = extract_number(obj, "entity_id")?.unwrap_or(0.0) as u32;
+            let entity_id = extract_number(obj, "entity_id").unwrap_or(0.0) as u32;
             Ok(MessageType::EntityCreate { entity_id })
         },
         "entitydelete" => {
-            let entity_id = extract_number(obj, "entity_id")?.unwrap_or(0.0) as u32;
+            let entity_id = extract_number(obj, "entity_id").unwrap_or(0.0) as u32;
             Ok(MessageType::EntityDelete { entity_id })
         },
         "componentupdate" => Ok(MessageType::ComponentUpdate),
         "input" => Ok(MessageType::Input),
         "timesyncrequest" => {
-            let client_time = extract_number(obj, "client_time")?.unwrap_or(0.0);
+            let client_time = extract_number(obj, "client_time").unwrap_or(0.0);
             Ok(MessageType::TimeSyncRequest { client_time })
         },
         "timesyncresponse" => {
-            let client_time = extract_number(obj, "client_time")?.unwrap_or(0.0);
-            let server_time = extract_number(obj, "server_time")?.unwrap_or(0.0);
+            let client_time = extract_number(obj, "client_time").unwrap_or(0.0);
+            let server_time = extract_number(obj, "server_time").unwrap_or(0.0);
             Ok(MessageType::TimeSyncResponse { client_time, server_time })
         },
         "ping" => {
-            let client_time = extract_number(obj, "client_time")?.unwrap_or(0.0);
+            let client_time = extract_number(obj, "client_time").unwrap_or(0.0);
             Ok(MessageType::Ping { client_time })
         },
         "pong" => {
-            let client_time = extract_number(obj, "client_time")?.unwrap_or(0.0);
-            let server_time = extract_number(obj, "server_time")?.unwrap_or(0.0);
+            let client_time = extract_number(obj, "client_time").unwrap_or(0.0);
+            let server_time = extract_number(obj, "server_time").unwrap_or(0.0);
             Ok(MessageType::Pong { client_time, server_time })
         },
         "error" => {
-            let code = extract_number(obj, "code")?.unwrap_or(0.0) as u32;
-            let message = extract_string(obj, "message")?.unwrap_or_default();
+            let code = extract_number(obj, "code").unwrap_or(0.0) as u32;
+            let message = extract_string(obj, "message").unwrap_or_default();
             Ok(MessageType::Error { code, message })
         },
         "mousecursorupdate" => Ok(MessageType::MouseCursorUpdate),
         _ => {
-            web_sys::console::error_1(&format!("未知のメッセージタイプ: {}", type_str).into());
-            Err(JsValue::from_str(&format!("未知のメッセージタイプ: {}", type_str)))
+            log::error!("未知のメッセージタイプ: {}", type_str);
+            Err(Error::Other(format!("未知のメッセージタイプ: {}", type_str)))
         }
     }
 }
 
-fn extract_number(obj: &js_sys::Object, key: &str) -> Result<Option<f64>, JsValue> {
-    let js_key = JsValue::from_str(key);
-    if !js_sys::Reflect::has(obj, &js_key)? {
-        return Ok(None);
-    }
-    
-    let value = js_sys::Reflect::get(obj, &js_key)?;
-    if value.is_undefined() || value.is_null() {
-        Ok(None)
-    } else {
-        Ok(Some(value.as_f64().unwrap_or(0.0)))
-    }
+fn extract_number(obj: &Map<String, Value>, key: &str) -> Option<f64> {
+    obj.get(key).filter(|value| !value.is_null()).map(|value| value.as_f64().unwrap_or(0.0))
 }
 
-fn extract_optional_number(obj: &js_sys::Object, key: &str) -> Result<Option<f64>, JsValue> {
-    extract_number(obj, key)
+fn extract_boolean(obj: &Map<String, Value>, key: &str) -> Option<bool> {
+    obj.get(key).filter(|value| !value.is_null()).map(|value| value.as_bool().unwrap_or(false))
 }
 
-fn extract_boolean(obj: &js_sys::Object, key: &str) -> Result<Option<bool>, JsValue> {
-    let js_key = JsValue::from_str(key);
-    if !js_sys::Reflect::has(obj, &js_key)? {
-        return Ok(None);
-    }
-    
-    let value = js_sys::Reflect::get(obj, &js_key)?;
-    if value.is_undefined() || value.is_null() {
-        Ok(None)
-    } else {
-        Ok(Some(value.as_bool().unwrap_or(false)))
-    }
+fn extract_string(obj: &Map<String, Value>, key: &str) -> Option<String> {
+    obj.get(key).filter(|value| !value.is_null()).map(|value| value.as_str().unwrap_or_default().to_string())
 }
 
-fn extract_string(obj: &js_sys::Object, key: &str) -> Result<Option<String>, JsValue> {
-    let js_key = JsValue::from_str(key);
-    if !js_sys::Reflect::has(obj, &js_key)? {
-        return Ok(None);
+/// 任意のフィールドを型に変換して取得（存在しないか、nullか、解析できなければ`None`）
+fn extract_field<T: DeserializeOwned>(obj: &Map<String, Value>, key: &str) -> Option<T> {
+    match obj.get(key) {
+        Some(Value::Null) | None => None,
+        Some(value) => match serde_json::from_value(value.clone()) {
+            Ok(field) => Some(field),
+            Err(e) => {
+                log::warn!("{}の解析エラーのため無視します: {}", key, e);
+                None
+            }
+        },
     }
-    
-    let value = js_sys::Reflect::get(obj, &js_key)?;
-    if value.is_undefined() || value.is_null() {
-        Ok(None)
-    } else {
-        Ok(Some(value.as_string().unwrap_or_default()))
+}
+
+/// 任意のフィールドを書き出す（`None`なら書き出さない）
+///
+/// 書き出すオブジェクトの中でも、値のないフィールドは省きます。
+fn insert_field<T: Serialize>(obj: &mut Map<String, Value>, key: &str, value: &Option<T>) -> Result<(), Error> {
+    if let Some(value) = value {
+        let mut value = serde_json::to_value(value)
+            .map_err(|e| Error::Other(format!("{}の文字列化エラー: {}", key, e)))?;
+        if let Value::Object(fields) = &mut value {
+            fields.retain(|_, field| !field.is_null());
+        }
+        obj.insert(key.to_string(), value);
     }
+    Ok(())
 }
 
 #[cfg(test)]
@@ -530,10 +447,10 @@ mod tests {
 
     #[test]
     fn test_message_creation() {
-        let message = NetworkMessage::new(MessageType::Connect)
+        let message = NetworkMessage::new(MessageType::Connect { wire_formats: WireFormat::Binary.offer() })
             .with_player_id(123);
         
-        assert_eq!(message.message_type, MessageType::Connect);
+        assert_eq!(message.message_type, MessageType::Connect { wire_formats: vec![WireFormat::Binary, WireFormat::Json] });
         assert_eq!(message.player_id, Some(123));
     }
 
@@ -553,4 +470,54 @@ mod tests {
             panic!("Wrong message type after deserialization");
         }
     }
+
+    #[test]
+    fn test_wire_format_fields() {
+        let message = NetworkMessage::new(MessageType::ConnectResponse {
+            player_id: 1,
+            success: true,
+            message: None,
+            wire_format: WireFormat::Binary,
+        });
+        let json = message.to_json().unwrap();
+        assert!(json.contains(r#""wire_format":"binary""#));
+        assert_eq!(NetworkMessage::from_json(&json).unwrap().message_type, message.message_type);
+        assert_eq!(NetworkMessage::from_binary(&message.to_binary().unwrap()).unwrap().message_type, message.message_type);
+
+        // 形式を伝えないクライアントはJSONのみ
+        let connect = NetworkMessage::from_json(r#"{"type":"Connect"}"#).unwrap();
+        assert_eq!(connect.message_type, MessageType::Connect { wire_formats: vec![WireFormat::Json] });
+    }
+
+    #[test]
+    fn test_absent_fields_are_omitted() {
+        let message = NetworkMessage::new(MessageType::Disconnect { reason: None })
+            .with_input(InputData::default());
+        let json = message.to_json().unwrap();
+        assert!(!json.contains("reason"));
+        assert!(!json.contains("aim"));
+        assert!(!json.contains("null"));
+    }
+
+    #[test]
+    fn test_malformed_fields_are_ignored() {
+        let json = r#"{"type":"Input","sequence":3,"components":"broken","input_data":{"movement":1},"player_data":[]}"#;
+        let message = NetworkMessage::from_json(json).unwrap();
+        assert_eq!(message.message_type, MessageType::Input);
+        assert_eq!(message.sequence, Some(3));
+        assert!(message.components.is_none());
+        assert!(message.input_data.is_none());
+        assert!(message.player_data.is_none());
+    }
+
+    #[test]
+    fn test_component_payloads_round_trip() {
+        let mut components = HashMap::new();
+        components.insert("Position".to_string(), ComponentData::Position { x: 1.5, y: -2.0, z: None });
+        let message = NetworkMessage::new(MessageType::ComponentUpdate)
+            .with_entity_id(7)
+            .with_components(components.clone());
+        let json = message.to_json().unwrap();
+        assert_eq!(NetworkMessage::from_json(&json).unwrap().components, Some(components));
+    }
 } 
\ No newline at end of file
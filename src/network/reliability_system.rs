@@ -3,7 +3,7 @@ impl System for NetworkReliabilitySystem {
         "NetworkReliabilitySystem"
     }
     
-    fn run(&mut self, world: &mut World, resources: &mut ResourceManager, delta_time: f32) -> Result<(), JsValue> {
+    fn run(&mut self, world: &mut World, resources: &mut ResourceManager, delta_time: f32) -> Result<(), Error> {
         if let Some(network) = resources.get_mut::<NetworkResource>() {
             if !matches!(network.state, ConnectionState::Connected) {
                 return Ok(()); // 接続されていない場合は処理しない
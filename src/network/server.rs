@@ -4,11 +4,12 @@
 //! ただし、WebAssemblyコンテキストでは主にスタブとして機能し、実際のサーバーは別プロセスで実行されます。
 
 use std::collections::{HashMap, VecDeque};
-use js_sys::Date;
+use crate::platform;
 
+use super::codec::WireFormat;
 use super::protocol::{NetworkMessage, MessageType};
 use super::messages::{PlayerData, ComponentData};
-use super::{ConnectionState, ConnectionStateType, NetworkError, NetworkConfig, TimeSyncData};
+use super::{ConnectionState, ConnectionStateType, NetworkError, NetworkConfig};
 use crate::ecs::World;
 
 /// サーバー接続クライアント情報
@@ -26,6 +27,8 @@ pub struct ServerClient {
     pub sequence_number: u32,
     /// 往復遅延時間(RTT)
     pub rtt: f64,
+    /// このクライアントとのメッセージ形式（`Connect`で決まるまではJSON）
+    pub wire_format: WireFormat,
 }
 
 /// サーバーモードを表す列挙型
@@ -75,7 +78,7 @@ impl NetworkServer {
             pending_messages: VecDeque::new(),
             next_client_id: 1,
             sequence_number: 0,
-            server_time: Date::now(),
+            server_time: platform::unix_time_ms(),
             config,
             active: false,
         }
@@ -95,12 +98,12 @@ impl NetworkServer {
             },
             ServerMode::LocalSimulation => {
                 // ローカルシミュレーションの初期化
-                web_sys::console::log_1(&"ローカルサーバーシミュレーションを開始しました".into());
+                log::debug!("ローカルサーバーシミュレーションを開始しました");
             },
             ServerMode::P2PHost => {
                 // P2Pホストモードの初期化
                 // WebRTC関連の初期化など
-                web_sys::console::log_1(&"P2Pホストモードを開始しました".into());
+                log::debug!("P2Pホストモードを開始しました");
             }
         }
         
@@ -120,7 +123,7 @@ impl NetworkServer {
         }
         
         self.active = false;
-        web_sys::console::log_1(&"サーバーを停止しました".into());
+        log::debug!("サーバーを停止しました");
         
         Ok(())
     }
@@ -140,9 +143,10 @@ impl NetworkServer {
             id: client_id,
             player_data,
             connection_state: ConnectionState::connected(),
-            last_message_time: Date::now(),
+            last_message_time: platform::unix_time_ms(),
             sequence_number: 0,
             rtt: 0.0,
+            wire_format: WireFormat::Json,
         };
         
         // クライアントをマップに追加
@@ -153,12 +157,13 @@ impl NetworkServer {
             player_id: client_id,
             success: true,
             message: None,
+            wire_format: WireFormat::Json,
         }).with_sequence(self.next_sequence_number());
         
         self.pending_messages.push_back((Some(client_id), response));
         
         if self.config.debug_mode {
-            web_sys::console::log_1(&format!("クライアント {} が接続しました", client_id).into());
+            log::debug!("クライアント {} が接続しました", client_id);
         }
         
         Ok(client_id)
@@ -291,7 +296,7 @@ impl NetworkServer {
             
             // クライアントの最終メッセージ受信時間を更新
             if let Some(client) = self.clients.get_mut(&client_id) {
-                client.last_message_time = Date::now();
+                client.last_message_time = platform::unix_time_ms();
                 
                 // シーケンス番号を更新（必要に応じて）
                 if let Some(seq) = message.sequence {
@@ -302,9 +307,20 @@ impl NetworkServer {
             }
             
             match message.message_type {
-                MessageType::Connect => {
-                    // 接続メッセージの処理
-                    // すでに接続済みのクライアントでは無視
+                MessageType::Connect { wire_formats } => {
+                    // クライアントが使える形式から以降のメッセージの形式を決めて応答
+                    let wire_format = WireFormat::negotiate(&wire_formats, self.config.wire_format);
+                    if let Some(client) = self.clients.get_mut(&client_id) {
+                        client.wire_format = wire_format;
+                    }
+                    // 応答自体は形式が決まる前のクライアントでも読めるようにJSONで送る
+                    let response = NetworkMessage::new(MessageType::ConnectResponse {
+                        player_id: client_id,
+                        success: true,
+                        message: None,
+                        wire_format,
+                    }).with_sequence(self.next_sequence_number());
+                    self.pending_messages.push_back((Some(client_id), response));
                 },
                 MessageType::Disconnect { reason } => {
                     // 切断メッセージの処理
@@ -315,8 +331,8 @@ impl NetworkServer {
                     if let Some(input_data) = message.input_data {
                         // 入力の処理（実際のゲームロジック）
                         if self.config.debug_mode {
-                            web_sys::console::log_1(&format!("クライアント {} からの入力を受信: {:?}", 
-                                            client_id, input_data.movement).into());
+                            log::debug!("クライアント {} からの入力を受信: {:?}", 
+                                            client_id, input_data.movement);
                         }
                     }
                 },
@@ -341,8 +357,8 @@ impl NetworkServer {
                 _ => {
                     // その他のメッセージ処理
                     if self.config.debug_mode {
-                        web_sys::console::log_1(&format!("クライアント {} から未処理のメッセージを受信: {:?}", 
-                                        client_id, message.message_type).into());
+                        log::debug!("クライアント {} から未処理のメッセージを受信: {:?}", 
+                                        client_id, message.message_type);
                     }
                 }
             }
@@ -358,7 +374,7 @@ impl NetworkServer {
             // ローカルシミュレーションモードの場合は、メッセージをコンソールに出力
             if self.config.debug_mode {
                 let target = client_id.map_or("すべてのクライアント".to_string(), |id| format!("クライアント {}", id));
-                web_sys::console::log_1(&format!("サーバーから {} へメッセージ送信: {:?}", target, message.message_type).into());
+                log::debug!("サーバーから {} へメッセージ送信: {:?}", target, message.message_type);
             }
             
             // 実際の送信処理はサーバーモードによって異なる実装になる
@@ -367,7 +383,7 @@ impl NetworkServer {
 
     /// クライアントの状態チェック
     fn check_clients(&mut self) {
-        let now = Date::now();
+        let now = platform::unix_time_ms();
         let timeout = self.config.connection_timeout_ms as f64;
         
         // タイムアウトしたクライアントのIDを収集
@@ -423,4 +439,31 @@ mod tests {
         // 接続応答メッセージがキューに追加されたことを確認
         assert_eq!(server.pending_messages.len(), 1);
     }
+
+    #[test]
+    fn test_wire_format_negotiation() {
+        let config = NetworkConfig {
+            wire_format: WireFormat::Binary,
+            ..NetworkConfig::default()
+        };
+        let mut server = NetworkServer::new(config, ServerMode::LocalSimulation);
+        server.active = true;
+        let binary_client = server.connect_client(PlayerData::default()).unwrap();
+        let json_client = server.connect_client(PlayerData::default()).unwrap();
+        server.pending_messages.clear();
+
+        server.message_queue.push_back((binary_client, NetworkMessage::new(MessageType::Connect { wire_formats: WireFormat::Binary.offer() })));
+        server.message_queue.push_back((json_client, NetworkMessage::new(MessageType::Connect { wire_formats: WireFormat::Json.offer() })));
+        server.process_messages(&mut World::new());
+
+        assert_eq!(server.clients[&binary_client].wire_format, WireFormat::Binary);
+        assert_eq!(server.clients[&json_client].wire_format, WireFormat::Json);
+        let formats: Vec<_> = server.pending_messages.iter()
+            .map(|(_, message)| match &message.message_type {
+                MessageType::ConnectResponse { wire_format, .. } => *wire_format,
+                other => panic!("接続応答ではないメッセージ: {:?}", other),
+            })
+            .collect();
+        assert_eq!(formats, vec![WireFormat::Binary, WireFormat::Json]);
+    }
 } 
\ No newline at end of file
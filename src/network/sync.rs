@@ -4,17 +4,43 @@
 //! システムを実装します。変更検出と差分同期に重点を置いています。
 
 use std::collections::HashMap;
-use js_sys::Date;
+use crate::platform;
 use serde::{Serialize, Deserialize};
-use wasm_bindgen::JsValue;
+use crate::Error;
 use crate::ecs::{World, Entity, System, Resource};
 use crate::ecs::{SystemPriority, ResourceManager};
 use crate::ecs::system::SystemPhase;
 
 use super::messages::ComponentData;
-use super::client::NetworkComponent;
 use super::protocol::{NetworkMessage, MessageType};
 
+/// ネットワークコンポーネント（同期対象のエンティティに付与される）
+#[derive(Debug, Clone)]
+pub struct NetworkComponent {
+    /// エンティティIDがネットワーク全体で同期されているか
+    pub is_synced: bool,
+    /// 最後の同期時刻
+    pub last_sync_time: f64,
+    /// 補間係数
+    pub interpolation_factor: f32,
+    /// リモートエンティティか（他のプレイヤーから同期されたもの）
+    pub is_remote: bool,
+    /// このエンティティの所有者ID
+    pub owner_id: Option<u32>,
+}
+
+impl Default for NetworkComponent {
+    fn default() -> Self {
+        Self {
+            is_synced: false,
+            last_sync_time: 0.0,
+            interpolation_factor: 0.0,
+            is_remote: false,
+            owner_id: None,
+        }
+    }
+}
+
 /// 同期ポリシー
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum SyncPolicy {
@@ -160,10 +186,10 @@ pub struct SyncSystem {
 impl Default for SyncSystem {
     fn default() -> Self {
         Self {
-            last_update: Date::now(),
+            last_update: platform::unix_time_ms(),
             entity_states: HashMap::new(),
             bytes_sent: 0,
-            last_send_time: Date::now(),
+            last_send_time: platform::unix_time_ms(),
             config: SyncConfig::default(),
             is_server: false,
         }
@@ -174,10 +200,10 @@ impl SyncSystem {
     /// 新しい同期システムを作成（クライアント用）
     pub fn new_client(config: SyncConfig) -> Self {
         Self {
-            last_update: Date::now(),
+            last_update: platform::unix_time_ms(),
             entity_states: HashMap::new(),
             bytes_sent: 0,
-            last_send_time: Date::now(),
+            last_send_time: platform::unix_time_ms(),
             config,
             is_server: false,
         }
@@ -186,10 +212,10 @@ impl SyncSystem {
     /// 新しい同期システムを作成（サーバー用）
     pub fn new_server(config: SyncConfig) -> Self {
         Self {
-            last_update: Date::now(),
+            last_update: platform::unix_time_ms(),
             entity_states: HashMap::new(),
             bytes_sent: 0,
-            last_send_time: Date::now(),
+            last_send_time: platform::unix_time_ms(),
             config,
             is_server: true,
         }
@@ -247,14 +273,7 @@ impl SyncSystem {
     
     /// エンティティのスナップショットを作成
     fn create_entity_snapshot(&self, world: &World, entity: Entity, now: f64) -> LocalEntitySnapshot {
-        // EntityIdの値を取得して使用
-        let entity_id = entity.id();
-        let id_value = match format!("{}", entity_id).strip_prefix("Entity(").and_then(|s| s.strip_suffix(")")) {
-            Some(id_str) => id_str.parse::<u64>().unwrap_or(0),
-            None => 0, // フォールバック
-        };
-        
-        let mut snapshot = LocalEntitySnapshot::new(id_value, now);
+        let mut snapshot = LocalEntitySnapshot::new(u64::from(entity.index()), now);
         
         // 各コンポーネントをスナップショットに追加
         // 実際のゲームでは、コンポーネントの具体的な型と値を取得する必要がある
@@ -323,22 +342,41 @@ impl SyncSystem {
     }
 }
 
+impl SyncSystem {
+    /// エンティティの削除メッセージを送信
+    fn send_entity_delete(&mut self, entity: Entity) -> usize {
+        // 実際のメッセージ送信は別のシステムで行われるため、
+        // ここではバイト数の計算のみを行う
+        let entity_id = entity.index();
+        let message = NetworkMessage::new(MessageType::EntityDelete { entity_id })
+            .with_entity_id(entity_id);
+        
+        // メッセージのバイト数を計算（簡略化）
+        let message_size = serde_json::to_string(&message).unwrap_or_default().len();
+        
+        // 帯域使用量を記録
+        self.bytes_sent += message_size;
+        
+        message_size
+    }
+}
+
 impl System for SyncSystem {
     fn name(&self) -> &'static str {
         "SyncSystem"
     }
     
     fn phase(&self) -> SystemPhase {
-        SystemPhase::Update
+        SystemPhase::FixedUpdate
     }
     
     fn priority(&self) -> SystemPriority {
         SystemPriority::new(0)
     }
 
-    fn run(&mut self, world: &mut World, _resources: &mut ResourceManager, _delta_time: f32) -> Result<(), JsValue> {
+    fn run(&mut self, world: &mut World, _resources: &mut ResourceManager, _delta_time: f32) -> Result<(), Error> {
         // 現在の時刻を取得
-        let now = js_sys::Date::now();
+        let now = platform::unix_time_ms();
         let _elapsed = now - self.last_update;
         self.last_update = now;
         
@@ -352,7 +390,7 @@ impl System for SyncSystem {
             } else if self.bytes_sent >= limit {
                 // 帯域制限に達した場合は同期をスキップ
                 if self.config.debug_mode {
-                    web_sys::console::log_1(&"帯域制限に達したため、同期をスキップします".into());
+                    log::debug!("帯域制限に達したため、同期をスキップします");
                 }
                 return Ok(());
             }
@@ -444,11 +482,24 @@ impl System for SyncSystem {
                 let bytes_sent = self.send_entity_sync(delta_snapshot);
                 
                 if self.config.debug_mode {
-                    let _ = web_sys::console::log_1(&format!("エンティティ {:?} を同期: {}バイト", entity, bytes_sent).into());
+                    log::debug!("エンティティ {} を同期: {}バイト", world.entity_label(entity), bytes_sent);
                 }
             }
         }
         
+        // 削除されたエンティティの削除を通知
+        let despawned: Vec<Entity> = world.despawned_entities()
+            .filter(|entity| self.entity_states.contains_key(entity))
+            .collect();
+        for entity in despawned {
+            self.entity_states.remove(&entity);
+            let bytes_sent = self.send_entity_delete(entity);
+            
+            if self.config.debug_mode {
+                log::debug!("エンティティ {:?} の削除を同期: {}バイト", entity, bytes_sent);
+            }
+        }
+        
         // 未使用のエンティティ状態をクリーンアップ
         self.entity_states.retain(|entity, _| {
             world.get_component::<NetworkComponent>(*entity).is_some()
@@ -521,6 +572,22 @@ impl Default for CompressionSettings {
     }
 }
 
+impl CompressionSettings {
+    /// 量子化の精度（小数点以下の桁数）を指定
+    ///
+    /// # 引数
+    ///
+    /// * `vector_precision` - 位置の精度
+    /// * `rotation_precision` - 回転の精度
+    /// * `float_precision` - 速度などその他の値の精度
+    pub fn with_precision(mut self, vector_precision: u8, rotation_precision: u8, float_precision: u8) -> Self {
+        self.vector_precision = vector_precision;
+        self.rotation_precision = rotation_precision;
+        self.float_precision = float_precision;
+        self
+    }
+}
+
 /// 圧縮統計情報
 #[derive(Debug, Clone, Default)]
 pub struct CompressionStats {
@@ -688,25 +755,25 @@ impl DefaultMessageCompressor {
     }
     
     /// 浮動小数点の量子化を適用
-    fn apply_quantization(&mut self, snapshot: &mut LocalEntitySnapshot) {
+    fn apply_quantization(&self, snapshot: &mut LocalEntitySnapshot) {
         // 位置データの量子化
         if let Some(position) = &mut snapshot.position {
-            for i in 0..position.len() {
-                position[i] = round_to_precision(position[i], self.settings.vector_precision);
+            for value in position.iter_mut() {
+                *value = round_to_precision(*value, self.settings.vector_precision);
             }
         }
         
         // 回転データの量子化
         if let Some(rotation) = &mut snapshot.rotation {
-            for i in 0..rotation.len() {
-                rotation[i] = round_to_precision(rotation[i], self.settings.rotation_precision);
+            for value in rotation.iter_mut() {
+                *value = round_to_precision(*value, self.settings.rotation_precision);
             }
         }
         
         // 速度データの量子化
         if let Some(velocity) = &mut snapshot.velocity {
-            for i in 0..velocity.len() {
-                velocity[i] = round_to_precision(velocity[i], self.settings.float_precision);
+            for value in velocity.iter_mut() {
+                *value = round_to_precision(*value, self.settings.float_precision);
             }
         }
     }
@@ -741,6 +808,12 @@ impl DefaultMessageCompressor {
     }
 }
 
+impl Default for DefaultMessageCompressor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 /// メッセージ圧縮のトレイト
 pub trait MessageCompressor: Send + Sync {
     /// スナップショットを圧縮
@@ -752,10 +825,11 @@ pub trait MessageCompressor: Send + Sync {
 
 impl MessageCompressor for DefaultMessageCompressor {
     fn compress(&self, snapshot: &LocalEntitySnapshot) -> LocalEntitySnapshot {
-        // 実装されたcompressメソッド
-        let compressed = snapshot.clone();
-        // 圧縮処理を行う
-        // ...
+        // 前回の状態に依存しない量子化だけを適用する（デルタ圧縮は`compress_snapshot`で行う）
+        let mut compressed = snapshot.clone();
+        if self.settings.enable_quantization {
+            self.apply_quantization(&mut compressed);
+        }
         compressed
     }
     
@@ -910,7 +984,7 @@ mod tests {
     
     #[test]
     fn test_entity_snapshot() {
-        let snapshot = LocalEntitySnapshot::new(123, Date::now())
+        let snapshot = LocalEntitySnapshot::new(123, platform::unix_time_ms())
             .with_position([1.23456, 2.34567, 3.45678])
             .with_rotation([0.1234, 0.2345, 0.3456, 0.9876])
             .with_velocity([10.1234, 20.2345, 30.3456]);
@@ -925,10 +999,12 @@ mod tests {
     #[test]
     fn test_message_compressor() {
         // 圧縮器を作成（位置は1桁、回転は2桁、速度は0桁）
-        let compressor = DefaultMessageCompressor::new();
+        let compressor = DefaultMessageCompressor::with_settings(
+            CompressionSettings::default().with_precision(1, 2, 0)
+        );
         
         // テスト用スナップショットを作成
-        let snapshot = LocalEntitySnapshot::new(1, Date::now())
+        let snapshot = LocalEntitySnapshot::new(1, platform::unix_time_ms())
             .with_position([1.23456, 2.34567, 3.45678])
             .with_rotation([0.1234, 0.2345, 0.3456, 0.9876])
             .with_velocity([10.1234, 20.2345, 30.3456]);
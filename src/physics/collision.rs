@@ -70,22 +70,20 @@ pub fn detect_collision(entity_a: &PhysicsEntity, entity_b: &PhysicsEntity) -> O
         }
         (CollisionShape::AABB { width, height }, CollisionShape::Circle { radius }) => {
             // AABBと円の衝突検出を反転
-            if let Some(collision) = detect_circle_aabb(
+            detect_circle_aabb(
                 entity_b.position,
                 *radius,
                 entity_a.position,
                 *width,
                 *height,
-            ) {
+            ).map(|collision| {
                 // 法線ベクトルを反転
-                Some(Collision {
+                Collision {
                     position: collision.position,
                     normal: (-collision.normal.0, -collision.normal.1),
                     penetration: collision.penetration,
-                })
-            } else {
-                None
-            }
+                }
+            })
         }
         (CollisionShape::Polygon { vertices: vertices_a }, CollisionShape::Polygon { vertices: vertices_b }) => {
             detect_polygon_polygon(
@@ -108,22 +106,20 @@ pub fn detect_collision(entity_a: &PhysicsEntity, entity_b: &PhysicsEntity) -> O
         }
         (CollisionShape::Polygon { vertices }, CollisionShape::Circle { radius }) => {
             // 多角形と円の衝突検出を反転
-            if let Some(collision) = detect_circle_polygon(
+            detect_circle_polygon(
                 entity_b.position,
                 *radius,
                 entity_a.position,
                 entity_a.rotation,
                 vertices,
-            ) {
+            ).map(|collision| {
                 // 法線ベクトルを反転
-                Some(Collision {
+                Collision {
                     position: collision.position,
                     normal: (-collision.normal.0, -collision.normal.1),
                     penetration: collision.penetration,
-                })
-            } else {
-                None
-            }
+                }
+            })
         }
         (CollisionShape::AABB { width, height }, CollisionShape::Polygon { vertices }) => {
             // AABBを多角形に変換
@@ -131,12 +131,13 @@ impl CollisionResolver {
         collision: &Collision,
         is_flipped: bool,
     ) {
-        // 相対速度を計算
+        // 静的物体から動的物体へ向かう法線（衝突の法線はAからB方向）
         let normal = if is_flipped {
-            // 法線の向きを反転
-            (-collision.normal.0, -collision.normal.1)
-        } else {
+            // Aが静的物体なのでそのまま使う
             collision.normal
+        } else {
+            // Bが静的物体なので向きを反転
+            (-collision.normal.0, -collision.normal.1)
         };
         
         // 法線方向の速度
@@ -293,6 +294,12 @@ impl CollisionResolver {
     }
 }
 
+impl Default for CollisionResolver {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 /// 積分器（運動方程式の数値積分）
 pub struct Integrator {
     /// 最大速度
@@ -391,6 +398,12 @@ impl Integrator {
     }
 }
 
+impl Default for Integrator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 /// 力の生成器
 pub struct ForceGenerator {
     /// 重力定数
@@ -562,6 +575,72 @@ impl ForceGenerator {
     }
 }
 
+// モジュールレベルの公開関数
+// これらの関数は、PhysicsWorldから呼び出しやすいように、モジュールレベルで再エクスポートされます。
+
+/// エンティティに力を適用します
+pub fn apply_force(entity: &mut PhysicsEntity, force: (f64, f64)) {
+    let force_generator = ForceGenerator::new((0.0, 0.0));
+    force_generator.apply_force(entity, force);
+}
+
+/// エンティティに重力を適用します
+pub fn apply_gravity(entity: &mut PhysicsEntity, gravity: (f64, f64)) {
+    let force_generator = ForceGenerator::new(gravity);
+    force_generator.apply_gravity(entity);
+}
+
+/// エンティティに衝撃（インパルス）を適用します
+pub fn apply_impulse(entity: &mut PhysicsEntity, impulse: (f64, f64)) {
+    if entity.is_static {
+        return;
+    }
+    
+    // 衝撃はP=mv（運動量）の変化として速度に直接影響する
+    entity.velocity.0 += impulse.0 / entity.mass;
+    entity.velocity.1 += impulse.1 / entity.mass;
+}
+
+/// エンティティにトルク（回転力）を適用します
+pub fn apply_torque(entity: &mut PhysicsEntity, torque: f64) {
+    let force_generator = ForceGenerator::new((0.0, 0.0));
+    force_generator.apply_torque(entity, torque);
+}
+
+/// エンティティの物理状態を時間ステップで更新します
+pub fn integrate(entity: &mut PhysicsEntity, dt: f64) {
+    let integrator = Integrator::new();
+    // 減衰は`apply_damping`で別に適用するため、ここでは速度に1.0を掛ける（減衰なし）
+    integrator.integrate(entity, dt, (0.0, 0.0), 1.0);
+}
+
+/// 2つのエンティティ間の衝突を解決します
+pub fn resolve_collision(
+    entity_a: &mut PhysicsEntity,
+    entity_b: &mut PhysicsEntity,
+    collision: &Collision,
+) {
+    let resolver = CollisionResolver::new();
+    resolver.resolve_collision(entity_a, entity_b, collision);
+}
+
+/// エンティティに減衰（抵抗）を適用します
+pub fn apply_damping(entity: &mut PhysicsEntity, damping: f64) {
+    if entity.is_static || damping <= 0.0 {
+        return;
+    }
+    
+    // 線形減衰係数を計算
+    let factor = 1.0 - damping;
+    
+    // 速度に減衰を適用
+    entity.velocity.0 *= factor;
+    entity.velocity.1 *= factor;
+    
+    // 角速度に減衰を適用
+    entity.angular_velocity *= factor;
+} 
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -598,7 +677,19 @@ mod tests {
         // 回転が更新されたことを確認
         assert!(entity.rotation > 0.0);
     }
-    
+
+    #[test]
+    fn test_integrate_keeps_velocity() {
+        let mut entity = PhysicsEntity::new(1, (0.0, 0.0), CollisionShape::Circle { radius: 10.0 });
+        entity.velocity = (10.0, -5.0);
+
+        integrate(&mut entity, 0.1);
+
+        // 減衰は`apply_damping`の役割なので、速度はそのまま残り位置が進む
+        assert_eq!(entity.velocity, (10.0, -5.0));
+        assert_eq!(entity.position, (1.0, -0.5));
+    }
+
     #[test]
     fn test_force_generator() {
         let mut entity = PhysicsEntity {
@@ -688,68 +779,3 @@ mod tests {
         assert!(entity_b.position.0 > 15.0);
     }
 }
-
-// モジュールレベルの公開関数
-// これらの関数は、PhysicsWorldから呼び出しやすいように、モジュールレベルで再エクスポートされます。
-
-/// エンティティに力を適用します
-pub fn apply_force(entity: &mut PhysicsEntity, force: (f64, f64)) {
-    let force_generator = ForceGenerator::new((0.0, 0.0));
-    force_generator.apply_force(entity, force);
-}
-
-/// エンティティに重力を適用します
-pub fn apply_gravity(entity: &mut PhysicsEntity, gravity: (f64, f64)) {
-    let force_generator = ForceGenerator::new(gravity);
-    force_generator.apply_gravity(entity);
-}
-
-/// エンティティに衝撃（インパルス）を適用します
-pub fn apply_impulse(entity: &mut PhysicsEntity, impulse: (f64, f64)) {
-    if entity.is_static {
-        return;
-    }
-    
-    // 衝撃はP=mv（運動量）の変化として速度に直接影響する
-    entity.velocity.0 += impulse.0 / entity.mass;
-    entity.velocity.1 += impulse.1 / entity.mass;
-}
-
-/// エンティティにトルク（回転力）を適用します
-pub fn apply_torque(entity: &mut PhysicsEntity, torque: f64) {
-    let force_generator = ForceGenerator::new((0.0, 0.0));
-    force_generator.apply_torque(entity, torque);
-}
-
-/// エンティティの物理状態を時間ステップで更新します
-pub fn integrate(entity: &mut PhysicsEntity, dt: f64) {
-    let integrator = Integrator::new();
-    integrator.integrate(entity, dt, (0.0, 0.0), 0.0);
-}
-
-/// 2つのエンティティ間の衝突を解決します
-pub fn resolve_collision(
-    entity_a: &mut PhysicsEntity,
-    entity_b: &mut PhysicsEntity,
-    collision: &Collision,
-) {
-    let resolver = CollisionResolver::new();
-    resolver.resolve_collision(entity_a, entity_b, collision);
-}
-
-/// エンティティに減衰（抵抗）を適用します
-pub fn apply_damping(entity: &mut PhysicsEntity, damping: f64) {
-    if entity.is_static || damping <= 0.0 {
-        return;
-    }
-    
-    // 線形減衰係数を計算
-    let factor = 1.0 - damping;
-    
-    // 速度に減衰を適用
-    entity.velocity.0 *= factor;
-    entity.velocity.1 *= factor;
-    
-    // 角速度に減衰を適用
-    entity.angular_velocity *= factor;
-} 
\ No newline at end of file
@@ -5,7 +5,11 @@
 
 use std::collections::HashMap;
 
-use crate::ecs::{World, Resource};
+use crate::ecs::{Component, HookContext, Resource, World};
+#[cfg(feature = "web")]
+use crate::game::systems::PhysicsSystem;
+#[cfg(feature = "web")]
+use crate::Error;
 
 pub mod collision;
 pub mod dynamics;
@@ -16,13 +20,19 @@ pub use dynamics::{apply_force, apply_gravity, apply_impulse, apply_torque, inte
 pub use optimization::{CollisionFilter, PhysicsStep, SpatialGrid, generate_collision_pairs};
 
 /// 物理システムを初期化
-pub fn init_physics_system(world: &mut World) {
+/// 
+/// 物理ワールドは`SystemPhase::FixedUpdate`で、ECSの`FixedTime`のティックごとに更新されます。
+/// 更新システムはゲーム側（`game::systems`）にあるため、`web`フィーチャーでのみ使えます。
+#[cfg(feature = "web")]
+pub fn init_physics_system(world: &mut World) -> Result<(), Error> {
     // 物理ワールドを作成してリソースとして登録
     let physics_world = PhysicsWorld::new();
     world.insert_resource(physics_world);
     
-    // TODO: 必要に応じて物理システムを登録
-    // world.register_system(PhysicsSystem::new());
+    // 固定タイムステップで物理ワールドを進めるシステムを登録
+    world.register_system(PhysicsSystem::new())?;
+    
+    Ok(())
 }
 
 /// 物理エンティティ
@@ -261,60 +271,59 @@ impl PhysicsWorld {
     }
 
     /// 物理シミュレーションを更新
+    /// 
+    /// 経過時間を内部の`PhysicsStep`に蓄積し、必要な回数だけ`step`を実行します。
+    /// ECSから使う場合は`FixedUpdate`フェーズで`step`を呼び出してください。
     pub fn update(&mut self, delta_time: f64) {
-        // 空間分割グリッドをクリア
-        self.spatial_grid.clear();
+        // 物理ステップを更新
+        let (steps_count, _interpolation_alpha) = self.physics_step.update(delta_time);
         
-        // エンティティを空間グリッドに追加
+        for _ in 0..steps_count {
+            self.step(self.time_step);
+        }
+    }
+
+    /// 物理シミュレーションを1ステップ進める
+    /// 
+    /// # 引数
+    /// 
+    /// * `time_step` - 1ステップの長さ（秒）
+    pub fn step(&mut self, time_step: f64) {
+        // 空間分割グリッドを現在の位置で作り直す
+        self.spatial_grid.clear();
         for entity in self.entities.values() {
             self.spatial_grid.insert_entity(entity);
         }
         
-        // 物理ステップを更新
-        let (steps_count, _interpolation_alpha) = self.physics_step.update(delta_time);
-        
-        for _ in 0..steps_count {
-            // 衝突ペアを生成
-            let entities_vec: Vec<PhysicsEntity> = self.entities.values().cloned().collect();
-            let collision_pairs = optimization::generate_collision_pairs(&entities_vec, &self.spatial_grid, &Some(self.collision_filter.clone()));
-            
-            // 衝突解決
-            for pair in collision_pairs {
-                if let (Some(entity_a), Some(entity_b)) = (self.entities.get(&pair.0), self.entities.get(&pair.1)) {
-                    if let Some(collision) = collision::detect_collision(entity_a, entity_b) {
-                        // 衝突情報をコピー
-                        let collision_info = collision.clone();
-                        
-                        // エンティティを一つずつ取得して処理
-                        if let Some(entity_a_mut) = self.entities.get_mut(&pair.0) {
-                            // エンティティAの衝突応答（半分）
-                            // 実際の物理シミュレーションでは両方を同時に処理する必要がありますが、
-                            // ここでは借用エラーを回避するために分割して処理します
-                            entity_a_mut.velocity.0 -= collision_info.normal.0 * 0.5;
-                            entity_a_mut.velocity.1 -= collision_info.normal.1 * 0.5;
-                        }
-                        
-                        if let Some(entity_b_mut) = self.entities.get_mut(&pair.1) {
-                            // エンティティBの衝突応答（半分）
-                            entity_b_mut.velocity.0 += collision_info.normal.0 * 0.5;
-                            entity_b_mut.velocity.1 += collision_info.normal.1 * 0.5;
-                        }
-                    }
+        // 衝突ペアを生成
+        let entities_vec: Vec<PhysicsEntity> = self.entities.values().cloned().collect();
+        let collision_pairs = optimization::generate_collision_pairs(&entities_vec, &self.spatial_grid, &Some(self.collision_filter.clone()));
+        
+        // 衝突解決
+        for pair in collision_pairs {
+            // 両方を同時に可変で借用できないため、一方を取り出してから解決して戻す
+            let Some(mut entity_a) = self.entities.remove(&pair.0) else {
+                continue;
+            };
+            if let Some(entity_b) = self.entities.get_mut(&pair.1) {
+                if let Some(collision) = collision::detect_collision(&entity_a, entity_b) {
+                    dynamics::resolve_collision(&mut entity_a, entity_b, &collision);
                 }
             }
-            
-            // 各エンティティを更新
-            for entity in self.entities.values_mut() {
-                if !entity.is_static {
-                    // 重力を適用
-                    dynamics::apply_gravity(entity, self.gravity);
-                    
-                    // 減衰を適用
-                    dynamics::apply_damping(entity, self.damping);
-                    
-                    // 運動を積分
-                    dynamics::integrate(entity, self.time_step);
-                }
+            self.entities.insert(pair.0, entity_a);
+        }
+        
+        // 各エンティティを更新
+        for entity in self.entities.values_mut() {
+            if !entity.is_static {
+                // 重力を適用
+                dynamics::apply_gravity(entity, self.gravity);
+                
+                // 減衰を適用
+                dynamics::apply_damping(entity, self.damping);
+                
+                // 運動を積分
+                dynamics::integrate(entity, time_step);
             }
         }
     }
@@ -339,6 +348,12 @@ impl PhysicsWorld {
     }
 }
 
+impl Default for PhysicsWorld {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 // Resourceトレイトの実装
 impl Resource for PhysicsWorld {
     fn as_any(&self) -> &dyn std::any::Any {
@@ -350,6 +365,84 @@ impl Resource for PhysicsWorld {
     }
 }
 
+/// 物理シミュレーションの対象にするエンティティのコンポーネント
+///
+/// 追加すると`PhysicsWorld`リソースにエンティティと同じIDの`PhysicsEntity`が登録され、
+/// 削除すると（エンティティの削除を含む）取り除かれます。
+/// 値を置き換えると、物理エンティティも新しい値で登録し直されます。
+#[derive(Debug, Clone, Component)]
+#[component(on_add = insert_body, on_replace = insert_body, on_remove = remove_body)]
+pub struct PhysicsBody {
+    /// 初期位置 (x, y)
+    pub position: (f64, f64),
+    /// 衝突形状
+    pub shape: CollisionShape,
+    /// 質量
+    pub mass: f64,
+    /// 静的オブジェクトかどうか
+    pub is_static: bool,
+}
+
+impl PhysicsBody {
+    /// 新しい物理ボディを作成
+    pub fn new(position: (f64, f64), shape: CollisionShape) -> Self {
+        Self {
+            position,
+            shape,
+            mass: 1.0,
+            is_static: false,
+        }
+    }
+
+    /// 静的オブジェクトとして設定
+    pub fn with_static(mut self, is_static: bool) -> Self {
+        self.is_static = is_static;
+        self
+    }
+
+    /// 質量を設定
+    pub fn with_mass(mut self, mass: f64) -> Self {
+        self.mass = mass;
+        self
+    }
+
+    /// 指定したIDの物理エンティティを作成
+    pub fn to_physics_entity(&self, entity_id: u32) -> PhysicsEntity {
+        let mut entity = PhysicsEntity::new(entity_id, self.position, self.shape.clone());
+        entity.set_mass(self.mass).set_static(self.is_static);
+        entity
+    }
+}
+
+/// 追加・置き換えられたボディを物理ワールドに登録する
+///
+/// 置き換えではフックの時点で古い値しか読めないため、操作の適用時に新しい値を読み取ります。
+fn insert_body(context: &mut HookContext) {
+    let entity = context.entity();
+    context.commands().add(move |world: &mut World| {
+        let body = match world.get_component::<PhysicsBody>(entity) {
+            Some(body) => body.to_physics_entity(entity.index()),
+            None => return,
+        };
+        match world.get_resource_mut::<PhysicsWorld>() {
+            Some(physics_world) => {
+                physics_world.add_entity(body);
+            }
+            None => log::warn!("PhysicsWorldリソースがないため、エンティティ{}を登録できません", entity.index()),
+        }
+    });
+}
+
+/// 削除されたボディを物理ワールドから取り除く
+fn remove_body(context: &mut HookContext) {
+    let entity_id = context.entity().index();
+    context.commands().add(move |world: &mut World| {
+        if let Some(physics_world) = world.get_resource_mut::<PhysicsWorld>() {
+            physics_world.remove_entity(entity_id);
+        }
+    });
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -360,6 +453,27 @@ mod tests {
         assert_eq!(world.entity_count(), 0);
     }
 
+    #[test]
+    fn test_physics_body_registers_in_physics_world() {
+        let mut world = World::new();
+        world.insert_resource(PhysicsWorld::new());
+        let entity = world.spawn(PhysicsBody::new((1.0, 2.0), CollisionShape::Circle { radius: 5.0 }));
+
+        let physics_world = world.get_resource::<PhysicsWorld>().unwrap();
+        let body = physics_world.get_entity(entity.index()).expect("物理ワールドに登録されていません");
+        assert_eq!(body.position, (1.0, 2.0));
+        assert!(!body.is_static);
+
+        // 置き換えると新しい値で登録し直される
+        world.add_component(entity, PhysicsBody::new((3.0, 4.0), CollisionShape::Circle { radius: 5.0 }).with_static(true));
+        let body = world.get_resource::<PhysicsWorld>().unwrap().get_entity(entity.index()).unwrap();
+        assert_eq!(body.position, (3.0, 4.0));
+        assert!(body.is_static);
+
+        world.destroy_entity(entity);
+        assert_eq!(world.get_resource::<PhysicsWorld>().unwrap().entity_count(), 0);
+    }
+
     #[test]
     fn test_add_entity() {
         let mut world = PhysicsWorld::new();
@@ -440,8 +554,8 @@ mod tests {
         let entity1 = world.get_entity(1).unwrap();
         let entity2 = world.get_entity(2).unwrap();
         
-        // エンティティ1は右向きの速度を持つようになる
-        assert!(entity1.velocity.0 > 0.0);
+        // エンティティ1は右側のエンティティ2に押されて左向きの速度を持つようになる
+        assert!(entity1.velocity.0 < 0.0);
         
         // エンティティ2は左向きの速度を持つが、絶対値は小さくなる
         assert!(entity2.velocity.0 < 0.0);
@@ -479,9 +593,9 @@ mod tests {
         world.add_entity(ground);
         world.add_entity(ball);
         
-        // シミュレーション実行
-        for _ in 0..10 {
-            world.update(0.1);
+        // ボールが落下して跳ね返りが収まるまで、1/60秒ずつ10秒間シミュレーション実行
+        for _ in 0..600 {
+            world.update(1.0 / 60.0);
         }
         
         // ボールは地面の上に止まるはず
@@ -75,7 +75,7 @@ impl SpatialGrid {
         // 各セルにエンティティを追加
         for cell in &cells {
             self.cells.entry(*cell)
-                .or_insert_with(Vec::new)
+                .or_default()
                 .push(entity.entity_id);
         }
         
@@ -124,11 +124,9 @@ impl SpatialGrid {
         let mut pairs = Vec::new();
         let mut processed = HashSet::new();
         
-        for (_, entities) in &self.cells {
-            for i in 0..entities.len() {
-                let entity_a = entities[i];
-                for j in (i+1)..entities.len() {
-                    let entity_b = entities[j];
+        for entities in self.cells.values() {
+            for (i, &entity_a) in entities.iter().enumerate() {
+                for &entity_b in &entities[i + 1..] {
                     let pair_key = if entity_a < entity_b {
                         (entity_a, entity_b)
                     } else {
@@ -198,6 +196,8 @@ impl CollisionFilter {
     /// # 戻り値
     /// 
     /// 衝突が可能な場合はtrue、不可能な場合はfalse
+    /// 
+    /// どちらか一方のマスクに相手のカテゴリが含まれていれば衝突します。
     pub fn should_collide(&self, entity_a_id: u32, entity_b_id: u32) -> bool {
         let category_a = self.categories.get(&entity_a_id).copied().unwrap_or(0xFFFFFFFF);
         let mask_a = self.masks.get(&entity_a_id).copied().unwrap_or(0xFFFFFFFF);
@@ -205,8 +205,8 @@ impl CollisionFilter {
         let category_b = self.categories.get(&entity_b_id).copied().unwrap_or(0xFFFFFFFF);
         let mask_b = self.masks.get(&entity_b_id).copied().unwrap_or(0xFFFFFFFF);
         
-        // 双方向のマスクチェック
-        (category_a & mask_b) != 0 && (category_b & mask_a) != 0
+        // 片方のマスクに相手のカテゴリが含まれていれば衝突する
+        (category_a & mask_b) != 0 || (category_b & mask_a) != 0
     }
     
     /// エンティティを削除
@@ -216,6 +216,12 @@ impl CollisionFilter {
     }
 }
 
+impl Default for CollisionFilter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 /// カテゴリ定数（例）
 pub mod category {
     pub const PLAYER: u32 = 0x0001;
@@ -231,6 +237,8 @@ pub mod category {
 /// 
 /// 物理シミュレーションの更新頻度と精度を制御するためのシステムです。
 /// 固定時間ステップを使用して安定したシミュレーションを実現します。
+/// `PhysicsWorld`を単体で更新する場合に使用し、ECSのシステムは
+/// `crate::ecs::FixedTime`の時計（`FixedUpdate`フェーズ）を共有します。
 pub struct PhysicsStep {
     /// 累積時間
     accumulated_time: f64,
@@ -250,10 +258,6 @@ impl PhysicsStep {
         }
     }
     
-    /// デフォルト設定で物理ステップ制御を作成
-    pub fn default() -> Self {
-        Self::new(1.0 / 60.0, 5)
-    }
     
     /// 累積時間を更新し、実行すべき物理ステップの数を取得
     /// 
@@ -302,6 +306,13 @@ impl PhysicsStep {
     }
 }
 
+impl Default for PhysicsStep {
+    /// デフォルト設定で物理ステップ制御を作成
+    fn default() -> Self {
+        Self::new(1.0 / 60.0, 5)
+    }
+}
+
 /// 物理最適化システム
 /// 
 /// 空間分割、衝突フィルタリング、物理ステップ制御を組み合わせた
@@ -325,10 +336,6 @@ impl PhysicsOptimizer {
         }
     }
     
-    /// デフォルト設定で物理最適化システムを作成
-    pub fn default() -> Self {
-        Self::new(50.0, 1.0 / 60.0, 5)
-    }
     
     /// エンティティのリストを空間分割グリッドに登録
     pub fn register_entities(&mut self, entities: &[PhysicsEntity]) {
@@ -369,6 +376,13 @@ impl PhysicsOptimizer {
     }
 }
 
+impl Default for PhysicsOptimizer {
+    /// デフォルト設定で物理最適化システムを作成
+    fn default() -> Self {
+        Self::new(50.0, 1.0 / 60.0, 5)
+    }
+}
+
 /// 衝突ペアを生成します
 ///
 /// エンティティのリストと空間分割グリッドを使用して、潜在的な衝突ペアを生成します。
@@ -515,7 +529,7 @@ mod tests {
         
         // エンティティを作成
         let entity1 = create_entity(1, (5.0, 5.0), 3.0);
-        let entity2 = create_entity(2, (12.0, 5.0), 3.0); // 衝突する位置
+        let entity2 = create_entity(2, (10.0, 5.0), 3.0); // 衝突する位置（中心間の距離5 < 半径の合計6）
         let entity3 = create_entity(3, (100.0, 100.0), 3.0); // 遠い位置
         
         let entities = vec![entity1.clone(), entity2.clone(), entity3.clone()];
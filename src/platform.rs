@@ -0,0 +1,121 @@
+//! 実行環境の違いを吸収する関数
+//!
+//! ブラウザ（`web`フィーチャーを有効にしたWasm）ではJavaScriptのAPIを、それ以外では標準ライブラリを
+//! 使います。ECSやネットワークの処理は時刻とログをこのモジュールと`log`クレート経由で扱うため、
+//! ネイティブ環境でもそのまま`cargo test`で動かせます。
+//!
+//! ```
+//! platform::init_logger(log::Level::Info);
+//! let sent_at = platform::unix_time_ms();
+//! log::info!("送信: {}", sent_at);
+//! ```
+
+/// 計測の基準からの経過時間（ミリ秒）を取得
+///
+/// 単調増加する時計で、処理時間の計測やフレーム間隔に使います。
+/// ブラウザでは`Performance.now()`、それ以外では最初の呼び出しからの`Instant`の経過時間です。
+#[cfg(all(feature = "web", target_arch = "wasm32"))]
+pub fn now_ms() -> f64 {
+    web_sys::window()
+        .and_then(|window| window.performance())
+        .map(|performance| performance.now())
+        .unwrap_or_else(js_sys::Date::now)
+}
+
+/// 計測の基準からの経過時間（ミリ秒）を取得
+///
+/// 単調増加する時計で、処理時間の計測やフレーム間隔に使います。
+/// ブラウザでは`Performance.now()`、それ以外では最初の呼び出しからの`Instant`の経過時間です。
+#[cfg(not(all(feature = "web", target_arch = "wasm32")))]
+pub fn now_ms() -> f64 {
+    use std::sync::OnceLock;
+    use std::time::Instant;
+
+    static START: OnceLock<Instant> = OnceLock::new();
+    START.get_or_init(Instant::now).elapsed().as_secs_f64() * 1000.0
+}
+
+/// UNIXエポックからの経過時間（ミリ秒）を取得
+///
+/// クライアントとサーバーの間でやり取りする時刻に使います（JavaScriptの`Date.now()`と同じ値）。
+#[cfg(all(feature = "web", target_arch = "wasm32"))]
+pub fn unix_time_ms() -> f64 {
+    js_sys::Date::now()
+}
+
+/// UNIXエポックからの経過時間（ミリ秒）を取得
+///
+/// クライアントとサーバーの間でやり取りする時刻に使います（JavaScriptの`Date.now()`と同じ値）。
+#[cfg(not(all(feature = "web", target_arch = "wasm32")))]
+pub fn unix_time_ms() -> f64 {
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs_f64() * 1000.0)
+        .unwrap_or(0.0)
+}
+
+/// `log`クレートの出力先を初期化
+///
+/// ブラウザではコンソールに、それ以外では標準エラー出力に書き出します。
+/// すでに初期化されている場合は何もしません。
+///
+/// # 引数
+///
+/// * `level` - 出力する最も詳細なレベル
+#[cfg(all(feature = "web", target_arch = "wasm32"))]
+pub fn init_logger(level: log::Level) {
+    // すでにロガーが設定されている場合、wasm_loggerは設定に失敗するだけで何もしない
+    wasm_logger::init(wasm_logger::Config::new(level));
+}
+
+/// `log`クレートの出力先を初期化
+///
+/// ブラウザではコンソールに、それ以外では標準エラー出力に書き出します。
+/// すでに初期化されている場合は何もしません。
+///
+/// # 引数
+///
+/// * `level` - 出力する最も詳細なレベル
+#[cfg(not(all(feature = "web", target_arch = "wasm32")))]
+pub fn init_logger(level: log::Level) {
+    if log::set_logger(&STDERR_LOGGER).is_ok() {
+        log::set_max_level(level.to_level_filter());
+    }
+}
+
+/// 標準エラー出力に書き出すロガー
+#[cfg(not(all(feature = "web", target_arch = "wasm32")))]
+struct StderrLogger;
+
+#[cfg(not(all(feature = "web", target_arch = "wasm32")))]
+static STDERR_LOGGER: StderrLogger = StderrLogger;
+
+#[cfg(not(all(feature = "web", target_arch = "wasm32")))]
+impl log::Log for StderrLogger {
+    fn enabled(&self, metadata: &log::Metadata) -> bool {
+        metadata.level() <= log::max_level()
+    }
+
+    fn log(&self, record: &log::Record) {
+        if self.enabled(record.metadata()) {
+            eprintln!("[{} {}] {}", record.level(), record.target(), record.args());
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clocks() {
+        let started = now_ms();
+        assert!(now_ms() >= started);
+        // 2020年以降のUNIX時刻
+        assert!(unix_time_ms() > 1_577_836_800_000.0);
+    }
+}
@@ -47,6 +47,15 @@ impl RenderLayer {
         }
     }
 
+    /// 複数のエンティティをまとめて削除
+    ///
+    /// `World::despawned_entities`で得た削除済みエンティティの掃除に使用します。
+    pub fn remove_entities<I: IntoIterator<Item = u32>>(&mut self, entity_ids: I) {
+        for entity_id in entity_ids {
+            self.remove_entity(entity_id);
+        }
+    }
+
     /// エンティティが存在するか確認
     pub fn contains_entity(&self, entity_id: u32) -> bool {
         self.entities.contains(&entity_id)
@@ -116,6 +125,17 @@ mod tests {
         assert!(layer.entities.is_empty());
     }
 
+    #[test]
+    fn test_remove_entities() {
+        let mut layer = RenderLayer::new("cursor".to_string(), 10);
+        layer.add_entity(1);
+        layer.add_entity(2);
+        layer.add_entity(3);
+
+        layer.remove_entities(vec![1, 3, 4]);
+        assert_eq!(layer.get_entities(), &[2]);
+    }
+
     #[test]
     fn test_entity_list() {
         let mut layer = RenderLayer::new("background".to_string(), 0);
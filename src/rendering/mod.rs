@@ -27,8 +27,8 @@ pub fn init_rendering_system(world: &mut crate::ecs::World, canvas_id: &str) ->
     // レンダリングリソースをワールドに追加
     world.insert_resource(renderer);
     
-    // TODO: 必要に応じてレンダリングシステムを登録
-    // world.register_system(RenderingSystem::new());
+    // 描画と、削除されたエンティティのレイヤーからの除去を毎フレーム行う
+    world.register_system(crate::game::systems::RenderingSystem)?;
     
     Ok(())
 }
@@ -116,6 +116,20 @@ impl Renderer {
         self.layers.iter_mut().find(|l| l.name == name)
     }
 
+    /// 削除されたエンティティをすべてのレイヤーから取り除く
+    ///
+    /// `World::despawned_entities`で得たエンティティを渡します。
+    /// `RenderingSystem`が毎フレーム呼び出します。
+    pub fn remove_despawned_entities<I: IntoIterator<Item = crate::ecs::Entity>>(&mut self, despawned: I) {
+        let despawned: Vec<u32> = despawned.into_iter()
+            .map(|entity| entity.index())
+            .collect();
+        
+        for layer in self.layers.iter_mut() {
+            layer.remove_entities(despawned.iter().copied());
+        }
+    }
+
     /// カメラを更新
     pub fn update_camera(&mut self, delta_time: Duration) {
         self.camera.update(delta_time);
@@ -288,6 +302,34 @@ mod tests {
         document.body().unwrap().remove_child(&canvas).unwrap();
     }
 
+    #[wasm_bindgen_test]
+    fn test_despawned_entity_leaves_layer() {
+        // テスト用のキャンバスを作成
+        let document = web_sys::window().unwrap().document().unwrap();
+        let canvas = document.create_element("canvas").unwrap();
+        canvas.set_id("despawn_test_canvas");
+        document.body().unwrap().append_child(&canvas).unwrap();
+
+        let mut world = crate::ecs::World::new();
+        init_rendering_system(&mut world, "despawn_test_canvas").unwrap();
+        let kept = world.create_entity();
+        let despawned = world.create_entity();
+        let mut layer = RenderLayer::new("sprites".to_string(), 0);
+        layer.add_entity(kept.index());
+        layer.add_entity(despawned.index());
+        world.get_resource_mut::<Renderer>().unwrap().add_layer(layer);
+
+        // 次のフレームの描画で、削除されたエンティティだけがレイヤーから外れる
+        world.destroy_entity(despawned);
+        world.update(0.016);
+        let layer = world.get_resource_mut::<Renderer>().unwrap().get_layer("sprites").unwrap();
+        assert!(layer.contains_entity(kept.index()));
+        assert!(!layer.contains_entity(despawned.index()));
+
+        // クリーンアップ
+        document.body().unwrap().remove_child(&canvas).unwrap();
+    }
+
     #[test]
     fn test_animation_integration() {
         let mut renderer = Renderer::new("test_canvas").unwrap();
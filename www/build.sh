@@ -59,8 +59,8 @@ fi
 
 echo -e "${BLUE}📦 Wasmパッケージのビルド中...${NC}"
 
-# wasm-packを使用してWebAssemblyパッケージをビルド - バージョンを指定
-wasm-pack build --target web --out-dir www/pkg
+# wasm-packを使用してWebAssemblyパッケージをビルド（ブラウザ向けのwebフィーチャーを有効にする）
+wasm-pack build --target web --out-dir www/pkg -- --features web
 
 if [ $? -ne 0 ]; then
     echo -e "${RED}❌ Wasmビルドに失敗しました${NC}"